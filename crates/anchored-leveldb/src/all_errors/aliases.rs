@@ -18,6 +18,15 @@ pub type RwResult<T, FS, Cmp, Codecs> = Result<T, types::RwError<
     <Codecs as CompressionCodecs>::DecompressionError,
 >>;
 
+pub type ConvertFormatResult<T, FS, Cmp, Codecs, NewCodecs> = Result<T, types::ConvertFormatError<
+    <FS as LevelDBFilesystem>::Error,
+    <Cmp as LevelDBComparator>::InvalidKeyError,
+    <Codecs as CompressionCodecs>::CompressionError,
+    <Codecs as CompressionCodecs>::DecompressionError,
+    <NewCodecs as CompressionCodecs>::CompressionError,
+    <NewCodecs as CompressionCodecs>::DecompressionError,
+>>;
+
 
 pub(crate) type RecoveryErrorAlias<FS, Cmp, Codecs> = types::RecoveryError<
     <FS as LevelDBFilesystem>::Error,
@@ -53,3 +62,13 @@ pub(crate) type WriteErrorAlias<FS, Cmp, Codecs> = types::WriteError<
     <Codecs as CompressionCodecs>::CompressionError,
     <Codecs as CompressionCodecs>::DecompressionError,
 >;
+
+pub(crate) type ConvertFormatErrorKindAlias<FS, Cmp, Codecs, NewCodecs> =
+    types::ConvertFormatErrorKind<
+        <FS as LevelDBFilesystem>::Error,
+        <Cmp as LevelDBComparator>::InvalidKeyError,
+        <Codecs as CompressionCodecs>::CompressionError,
+        <Codecs as CompressionCodecs>::DecompressionError,
+        <NewCodecs as CompressionCodecs>::CompressionError,
+        <NewCodecs as CompressionCodecs>::DecompressionError,
+    >;
@@ -1,4 +1,4 @@
-use std::{mem, str};
+use std::{mem, num::NonZeroU16, str};
 use std::{collections::HashSet, error::Error};
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 
@@ -175,6 +175,46 @@ impl<Fs: Display> Display for types::DestroyError<Fs> {
 
 impl<Fs: Error> Error for types::DestroyError<Fs> {}
 
+impl<Fs: Display> Display for types::DetectFormatError<Fs> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        // TODO: fill out error stubs
+        f.debug_struct("DetectFormatError").finish_non_exhaustive()
+    }
+}
+
+impl<Fs: Error> Error for types::DetectFormatError<Fs> {}
+
+impl<Fs, InvalidKey, Compression, Decompression, NewCompression, NewDecompression> Display
+for types::ConvertFormatError<
+    Fs, InvalidKey, Compression, Decompression, NewCompression, NewDecompression,
+>
+where
+    Fs:               Display,
+    InvalidKey:       Display,
+    Compression:      Display,
+    Decompression:    Display,
+    NewCompression:   Display,
+    NewDecompression: Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        // TODO: fill out error stubs
+        f.debug_struct("ConvertFormatError").finish_non_exhaustive()
+    }
+}
+
+impl<Fs, InvalidKey, Compression, Decompression, NewCompression, NewDecompression> Error
+for types::ConvertFormatError<
+    Fs, InvalidKey, Compression, Decompression, NewCompression, NewDecompression,
+>
+where
+    Fs:               Error,
+    InvalidKey:       Error,
+    Compression:      Error,
+    Decompression:    Error,
+    NewCompression:   Error,
+    NewDecompression: Error,
+{}
+
 // ================================================================
 //  Debug utilities
 // ================================================================
@@ -268,8 +308,21 @@ enum OptionsError<'a> {
         chosen:   ByteString<'a>,
         recorded: ByteString<'a>,
     },
+    MismatchedCompressionCodec {
+        compressor_id: CompressorId,
+        chosen:        Option<&'static str>,
+        recorded:      ByteString<'a>,
+    },
     UnsupportedMemtableCompressor(CompressorId),
     UnsupportedTableCompressor(NonZeroLevel, CompressorId),
+    Level0TriggersOutOfOrder {
+        throttle_trigger: NonZeroU16,
+        halt_trigger:     NonZeroU16,
+    },
+    PendingCompactionBytesTriggersOutOfOrder {
+        throttle_trigger: u64,
+        halt_trigger:     u64,
+    },
 }
 
 #[derive(Debug)]
@@ -281,6 +334,10 @@ enum WriteError<'a, Fs, InvalidKey, Compression, Decompression> {
     WritesClosedByCorruptionError,
     OutOfFileNumbers,
     OutOfSequenceNumbers,
+    TransactionConflict,
+    InvalidPut(types::WriteBatchPutError),
+    InvalidDelete(types::WriteBatchDeleteError),
+    InvalidCompactFiles(types::CompactFilesError),
     TableFileUnusable(&'a FileNumber, &'a types::CorruptedTableError<InvalidKey, Decompression>),
     Compression(CompressorId, UncompressedData<'a>, &'a Compression),
     Filesystem(&'a types::FilesystemError<Fs>, &'a FileNumber, &'a types::WriteFsError),
@@ -331,10 +388,26 @@ impl Debug for types::OptionsError {
                     chosen:   ByteString::new(chosen.inner()),
                     recorded: ByteString::new(recorded),
                 },
+            Self::MismatchedCompressionCodec { compressor_id, chosen, recorded }
+                => OptionsError::MismatchedCompressionCodec {
+                    compressor_id: *compressor_id,
+                    chosen:        *chosen,
+                    recorded:      ByteString::new(recorded),
+                },
             Self::UnsupportedMemtableCompressor(id)
                 => OptionsError::UnsupportedMemtableCompressor(*id),
             Self::UnsupportedTableCompressor(level, id)
                 => OptionsError::UnsupportedTableCompressor(*level, *id),
+            Self::Level0TriggersOutOfOrder { throttle_trigger, halt_trigger }
+                => OptionsError::Level0TriggersOutOfOrder {
+                    throttle_trigger: *throttle_trigger,
+                    halt_trigger:     *halt_trigger,
+                },
+            Self::PendingCompactionBytesTriggersOutOfOrder { throttle_trigger, halt_trigger }
+                => OptionsError::PendingCompactionBytesTriggersOutOfOrder {
+                    throttle_trigger: *throttle_trigger,
+                    halt_trigger:     *halt_trigger,
+                },
         };
 
         Debug::fmt(&this, f)
@@ -352,6 +425,10 @@ for types::WriteError<Fs, InvalidKey, Compression, Decompression>
             Self::WritesClosedByCorruptionError => WriteError::WritesClosedByCorruptionError,
             Self::OutOfFileNumbers              => WriteError::OutOfFileNumbers,
             Self::OutOfSequenceNumbers          => WriteError::OutOfSequenceNumbers,
+            Self::TransactionConflict           => WriteError::TransactionConflict,
+            Self::InvalidPut(err)               => WriteError::InvalidPut(*err),
+            Self::InvalidDelete(err)            => WriteError::InvalidDelete(*err),
+            Self::InvalidCompactFiles(err)      => WriteError::InvalidCompactFiles(*err),
             Self::TableFileUnusable(table, err)
                 => WriteError::TableFileUnusable(table, err),
             Self::Compression(id, data, err)
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, path::PathBuf};
+use std::{collections::HashSet, num::NonZeroU16, path::PathBuf};
 use std::io::{Error as IoError, ErrorKind as IoErrorKind};
 
 use crate::pub_traits::{compression::CompressorId, pool::BufferAllocError};
@@ -61,6 +61,44 @@ pub enum DestroyErrorKind<Fs> {
     RemoveFileErrors(Vec<(Fs, RemoveError)>),
 }
 
+#[derive(Debug)]
+pub struct DetectFormatError<Fs> {
+    pub db_directory: PathBuf,
+    pub kind:         DetectFormatErrorKind<Fs>,
+}
+
+#[derive(Debug)]
+pub enum DetectFormatErrorKind<Fs> {
+    Filesystem(FilesystemError<Fs>, OpenFsError),
+    /// The `CURRENT` file of the database does not name a `MANIFEST` file.
+    ///
+    /// # Data
+    /// The full contents of the `CURRENT` file.
+    CorruptedCurrent(Vec<u8>),
+}
+
+#[derive(Debug)]
+pub struct ConvertFormatError<
+    Fs, InvalidKey, Compression, Decompression, NewCompression, NewDecompression,
+> {
+    pub db_directory: PathBuf,
+    pub kind:         ConvertFormatErrorKind<
+        Fs, InvalidKey, Compression, Decompression, NewCompression, NewDecompression,
+    >,
+}
+
+#[derive(Debug)]
+pub enum ConvertFormatErrorKind<
+    Fs, InvalidKey, Compression, Decompression, NewCompression, NewDecompression,
+> {
+    /// An error occurred while opening the database or reading its table files, which use the
+    /// original compression codecs.
+    Source(RecoveryErrorKind<Fs, InvalidKey, Compression, Decompression>),
+    /// An error occurred while writing the converted table files or the new `MANIFEST` file,
+    /// which use the new compression codecs.
+    Target(RecoveryErrorKind<Fs, InvalidKey, NewCompression, NewDecompression>),
+}
+
 // ================================================================
 //  The many error types used by the above errors.
 // ================================================================
@@ -91,6 +129,21 @@ pub enum OptionsError {
         chosen:   ShortSlice<'static>,
         recorded: Vec<u8>,
     },
+    /// The name of a compression codec recorded in the persistent database files does not match
+    /// the name of the chosen set of compression codecs' codec with the same [`CompressorId`],
+    /// or the chosen set of compression codecs does not support that [`CompressorId`].
+    ///
+    /// Reading the database with the wrong compression codecs would make the database appear
+    /// to be corrupted, and writing to it could corrupt it.
+    ///
+    /// # Data
+    /// The compressor ID, the name of the chosen codec with that ID (if any), and the name
+    /// recorded for that ID.
+    MismatchedCompressionCodec {
+        compressor_id: CompressorId,
+        chosen:        Option<&'static str>,
+        recorded:      Vec<u8>,
+    },
     /// The chosen compressor for flushing write buffers (that is, memtables) is not supported by
     /// the chosen set of compression codecs.
     ///
@@ -105,6 +158,17 @@ pub enum OptionsError {
     /// # Data
     /// The level which was being written to, and the compressor ID chosen for that level.
     UnsupportedTableCompressor(NonZeroLevel, CompressorId),
+    /// The chosen level-0 write throttle trigger exceeds the chosen level-0 write halt trigger.
+    Level0TriggersOutOfOrder {
+        throttle_trigger: NonZeroU16,
+        halt_trigger:     NonZeroU16,
+    },
+    /// The chosen pending compaction bytes throttle trigger exceeds the chosen pending
+    /// compaction bytes halt trigger.
+    PendingCompactionBytesTriggersOutOfOrder {
+        throttle_trigger: u64,
+        halt_trigger:     u64,
+    },
 }
 
 /// Errors exclusive to the process of opening a database.
@@ -352,6 +416,26 @@ pub enum WriteError<Fs, InvalidKey, Compression, Decompression> {
     WritesClosedByCorruptionError,
     OutOfFileNumbers,
     OutOfSequenceNumbers,
+    /// A [`Transaction`] could not be committed, since a key that it read was written to after the
+    /// transaction's [`Snapshot`] was acquired.
+    ///
+    /// Unlike most other write errors, this error does not close writes to the database; the
+    /// transaction can simply be retried.
+    ///
+    /// [`Transaction`]: crate::pub_leveldb::Transaction
+    /// [`Snapshot`]: crate::snapshot::Snapshot
+    TransactionConflict,
+    /// The entry to be written could not be added to a write batch.
+    InvalidPut(WriteBatchPutError),
+    /// The deletion to be written could not be added to a write batch.
+    InvalidDelete(WriteBatchDeleteError),
+    /// The files passed to [`DB::compact_files`] could not be compacted into the requested
+    /// level.
+    ///
+    /// Like [`Self::TransactionConflict`], this error does not close writes to the database.
+    ///
+    /// [`DB::compact_files`]: crate::pub_leveldb::DB::compact_files
+    InvalidCompactFiles(CompactFilesError),
     /// A just-written table file is corrupted, and will therefore be discarded.
     ///
     /// Either the filesystem genuinely did fail to save that table file in some way, or the
@@ -916,6 +1000,41 @@ pub enum CorruptedTableError<InvalidKey, Decompression> {
     /// The offset into the metaindex block of the corrupted handle, followed by the type of
     /// corruption.
     CorruptedFilterBlockHandle(TableBlockOffset, BlockHandleCorruption),
+    /// The handle for the compression codecs block listed in the metaindex block is corrupted.
+    ///
+    /// # Data
+    /// The offset into the metaindex block of the corrupted handle, followed by the type of
+    /// corruption.
+    CorruptedCompressionCodecsHandle(TableBlockOffset, BlockHandleCorruption),
+    /// The compression codecs block of the table, which lists the compressor ID and name of
+    /// each codec which the table was written with, was malformed.
+    ///
+    /// # Data
+    /// The handle to the compression codecs block.
+    InvalidCompressionCodecs(BlockHandle),
+    /// The handle for the compression dictionary block listed in the metaindex block is corrupted.
+    ///
+    /// # Data
+    /// The offset into the metaindex block of the corrupted handle, followed by the type of
+    /// corruption.
+    CorruptedCompressionDictionaryHandle(TableBlockOffset, BlockHandleCorruption),
+    /// The compression dictionary block of the table did not begin with the (nonzero) ID of the
+    /// compressor which the dictionary is for.
+    ///
+    /// # Data
+    /// The handle to the compression dictionary block.
+    InvalidCompressionDictionary(BlockHandle),
+    /// The handle for the table properties block listed in the metaindex block is corrupted.
+    ///
+    /// # Data
+    /// The offset into the metaindex block of the corrupted handle, followed by the type of
+    /// corruption.
+    CorruptedTablePropertiesHandle(TableBlockOffset, BlockHandleCorruption),
+    /// The table properties block of the table could not be decoded.
+    ///
+    /// # Data
+    /// The handle to the table properties block.
+    InvalidTableProperties(BlockHandle),
     /// One of the data block handles listed in the index block is corrupted.
     ///
     /// # Data
@@ -1219,6 +1338,35 @@ pub enum WriteBatchPutError {
     ValueTooLong,
 }
 
+/// The reason that a set of files could not be compacted by [`DB::compact_files`].
+///
+/// [`DB::compact_files`]: crate::pub_leveldb::DB::compact_files
+#[derive(Debug, Clone, Copy)]
+pub enum CompactFilesError {
+    /// No input files were provided.
+    NoInputFiles,
+    /// The indicated file is not a table file in the current version of the database.
+    ///
+    /// It may have already been removed by another compaction.
+    FileNotLive(FileNumber),
+    /// Some input file is in a level greater than the output level, or every input file is in
+    /// the output level.
+    ///
+    /// # Data
+    /// The requested output level.
+    OutputLevelTooLow(NonZeroLevel),
+    /// The input files (excluding input files in the output level) are spread across more than
+    /// one level.
+    InputsInMultipleLevels,
+    /// The indicated file was not provided as input, but overlaps the input files in a way
+    /// that requires it to be compacted along with them.
+    ///
+    /// This occurs for level-0 files overlapping the input files, for files in levels between
+    /// the input files and the output level, and for files sharing a user key with the last
+    /// input file of a nonzero level.
+    OverlapsOtherFile(FileNumber),
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum WriteBatchDeleteError {
     /// The write batch already contained the maximum number of entries, [`u32::MAX`].
@@ -1234,6 +1382,10 @@ pub enum WriteBatchDeleteError {
 #[derive(Debug, Clone, Copy)]
 pub struct PushBatchError;
 
+/// A write batch savepoint was rolled back to or popped, but no savepoint had been set.
+#[derive(Debug, Clone, Copy)]
+pub struct NoSavepointError;
+
 #[derive(Debug, Clone, Copy)]
 pub enum PrefixedBytesParseError {
     /// The varint32 prefix of a length-prefixed byte slice was truncated.
@@ -1334,6 +1486,8 @@ pub(crate) enum NewTableReaderError<InvalidKey, Decompression> {
     /// The `file_size` passed to [`TableReader::new`] was less than
     /// [`TableFooter::ENCODED_LENGTH`].
     FileSizeTooShort,
+    /// The compression codecs recorded in the table do not match the chosen codecs.
+    Options(OptionsError),
     TableCorruption(CorruptedTableError<InvalidKey, Decompression>),
     Io(IoError),
 }
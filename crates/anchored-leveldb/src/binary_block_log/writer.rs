@@ -17,17 +17,17 @@ use super::{BINARY_LOG_HEADER_SIZE, slices::Slices};
 /// [`WriteBatch`]: crate::write_batch::WriteBatch
 /// [`VersionEdit`]: crate::version::version_edit::VersionEdit
 pub(crate) struct WriteLogWriter<File> {
-    file:                File,
-    type_checksums:      [u32; PhysicalRecordType::ALL_TYPES.len()],
-    block_size:          BinaryLogBlockSize,
+    file:                      File,
+    type_checksums:            [u32; PhysicalRecordType::ALL_TYPES.len()],
+    block_size:                BinaryLogBlockSize,
     /// The space remaining in the current block of `block_size` bytes.
     ///
     /// This should be in the range `0..=block_size`, where `0` should be incremented back up to
     /// to `block_size`.
-    remaining_space:     usize,
+    remaining_space:           usize,
     /// The number of blocks which have been written in part or in full, such that the total length
     /// of `file` should be `self.block_size * self.cur_block_index - self.remaining_space`.
-    cur_block_index:     NonZeroU64,
+    cur_block_index:           NonZeroU64,
     /// The last offset that was synced with [`Self::sync_log_data`].
     ///
     /// When recovering an existing log file, we pessimistically assume that nothing was synced.
@@ -36,7 +36,11 @@ pub(crate) struct WriteLogWriter<File> {
     /// is based on file descriptor, not file. However, on the off chance that it slightly helps
     /// some obscure edge case... we might as well try, since the performance impact should be
     /// relatively small.
-    offset_of_last_sync: FileOffset,
+    offset_of_last_sync:       FileOffset,
+    /// The end of the last range which was synced with [`Self::sync_incrementally`].
+    ///
+    /// Ranges are only begun to be synced, so this does not affect [`Self::needs_sync`].
+    offset_of_last_range_sync: FileOffset,
 }
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
@@ -50,10 +54,11 @@ impl<File: WritableFile> WriteLogWriter<File> {
             file,
             type_checksums,
             block_size,
-            remaining_space:     block_size.as_usize(),
+            remaining_space:           block_size.as_usize(),
             #[allow(clippy::unwrap_used, reason = "validated at compile time")]
-            cur_block_index:     const { NonZeroU64::new(1).unwrap() },
-            offset_of_last_sync: FileOffset(0),
+            cur_block_index:           const { NonZeroU64::new(1).unwrap() },
+            offset_of_last_sync:       FileOffset(0),
+            offset_of_last_range_sync: FileOffset(0),
         }
     }
 
@@ -93,7 +98,8 @@ impl<File: WritableFile> WriteLogWriter<File> {
             block_size,
             remaining_space,
             cur_block_index,
-            offset_of_last_sync: FileOffset(0),
+            offset_of_last_sync:       FileOffset(0),
+            offset_of_last_range_sync: FileOffset(0),
         }
     }
 
@@ -129,6 +135,24 @@ impl<File: WritableFile> WriteLogWriter<File> {
         self.file.sync_data()
     }
 
+    /// Calls [`WritableFile::sync_data_range`] on the data written since the last sync, if at
+    /// least `bytes_per_sync` such bytes have been written.
+    ///
+    /// Unlike [`Self::sync_log_data`], this does not ensure that the data has reached persistent
+    /// storage.
+    pub fn sync_incrementally(&mut self, bytes_per_sync: NonZeroU64) -> Result<(), IoError> {
+        let file_length = self.file_length();
+        let last_sync = self.offset_of_last_sync.0.max(self.offset_of_last_range_sync.0);
+        let unsynced = file_length.saturating_sub(last_sync);
+
+        if unsynced >= bytes_per_sync.get() {
+            self.offset_of_last_range_sync = FileOffset(file_length);
+            self.file.sync_data_range(last_sync, unsynced)
+        } else {
+            Ok(())
+        }
+    }
+
     /// Returns `true` if the most-recent data might not been synced with [`Self::sync_log_data`].
     #[must_use]
     pub const fn needs_sync(&self) -> bool {
@@ -226,12 +250,13 @@ impl<File: WritableFile> WriteLogWriter<File> {
 impl<File> Debug for WriteLogWriter<File> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         f.debug_struct("WriteLogWriter")
-            .field("file",                &"<File>")
-            .field("type_checksums",      &self.type_checksums)
-            .field("block_size",          &self.block_size)
-            .field("remaining_space",     &self.remaining_space)
-            .field("cur_block_index",     &self.cur_block_index)
-            .field("offset_of_last_sync", &self.offset_of_last_sync)
+            .field("file",                      &"<File>")
+            .field("type_checksums",            &self.type_checksums)
+            .field("block_size",                &self.block_size)
+            .field("remaining_space",           &self.remaining_space)
+            .field("cur_block_index",           &self.cur_block_index)
+            .field("offset_of_last_sync",       &self.offset_of_last_sync)
+            .field("offset_of_last_range_sync", &self.offset_of_last_range_sync)
             .finish()
     }
 }
@@ -501,6 +501,29 @@ macro_rules! codec_list_impl {
             type CompressionError = $cerr<$($generics)*>;
             type DecompressionError = $derr<$($generics)*>;
 
+            #[inline]
+            fn codec_names(&self) -> ::std::vec::Vec<(
+                $crate::db_options::CompressorId,
+                &'static ::core::primitive::str,
+            )> {
+                #[allow(unused_mut, reason = "the list of codecs may be empty")]
+                let mut __names = ::std::vec::Vec::new();
+                $(
+                    // The IDs are documented to be nonzero; a zero ID could never be used anyway.
+                    if let ::std::option::Option::Some(__id)
+                        = ::core::num::NonZeroU8::new($codec_id)
+                    {
+                        __names.push((
+                            $crate::db_options::CompressorId(__id),
+                            <$codec_ty as $crate::db_options::CompressionCodec>::name(
+                                {let $__index = self; &$indexed},
+                            ),
+                        ));
+                    }
+                )*
+                __names
+            }
+
             #[inline]
             fn init_encoders(&self) -> Self::Encoders {
                 $encoders(
@@ -582,6 +605,93 @@ macro_rules! codec_list_impl {
                     ),
                 }
             }
+
+            #[inline]
+            fn train_dictionary(
+                &self,
+                __id:                 $crate::db_options::CompressorId,
+                __samples:            &[::core::primitive::u8],
+                __sample_lens:        &[::core::primitive::usize],
+                __max_dictionary_len: ::core::primitive::usize,
+            ) -> Option<::std::vec::Vec<::core::primitive::u8>> {
+                match __id.0.get() {
+                    $(__codec_id @ $codec_id => {
+                        <$codec_ty as $crate::db_options::CompressionCodec>::train_dictionary(
+                            {let $__index = self; &$indexed},
+                            __samples,
+                            __sample_lens,
+                            __max_dictionary_len,
+                        )
+                    })*
+                    _ => ::std::option::Option::None,
+                }
+            }
+
+            #[inline]
+            fn encode_with_dictionary<Pool: $crate::db_options::BufferPool>(
+                __encoders:         &mut Self::Encoders,
+                __dictionary:       &[::core::primitive::u8],
+                __src:              &[::core::primitive::u8],
+                __id:               $crate::db_options::CompressorId,
+                __compression_goal: ::core::primitive::usize,
+                __pool:             &Pool,
+                __existing_buf:     &mut Option<<Pool as $crate::db_options::BufferPool>::PooledBuffer>,
+            ) -> Result<
+                <Pool as $crate::db_options::BufferPool>::PooledBuffer,
+                $crate::db_options::CodecsCompressionError<Self::CompressionError>
+            > {
+                match __id.0.get() {
+                    $(__codec_id @ $codec_id => {
+                        <$codec_ty as $crate::db_options::CompressionCodec>::encode_with_dictionary(
+                            {let $__index = __encoders; &mut $indexed},
+                            __dictionary,
+                            __src,
+                            __compression_goal,
+                            __pool,
+                            __existing_buf,
+                        ).map_err(|__error| {
+                            $crate::db_options::CodecsCompressionError::from(
+                                __error.map_custom($cerr::$codec_variant),
+                            )
+                        })
+                    })*
+                    _ => ::std::result::Result::Err(
+                        $crate::db_options::CodecsCompressionError::Unsupported,
+                    ),
+                }
+            }
+
+            #[inline]
+            fn decode_with_dictionary<Pool: $crate::db_options::BufferPool>(
+                __decoders:     &mut Self::Decoders,
+                __dictionary:   &[::core::primitive::u8],
+                __src:          &[::core::primitive::u8],
+                __id:           $crate::db_options::CompressorId,
+                __pool:         &Pool,
+                __existing_buf: &mut Option<<Pool as $crate::db_options::BufferPool>::PooledBuffer>,
+            ) -> Result<
+                <Pool as $crate::db_options::BufferPool>::PooledBuffer,
+                $crate::db_options::CodecsDecompressionError<Self::DecompressionError>
+            > {
+                match __id.0.get() {
+                    $(__codec_id @ $codec_id => {
+                        <$codec_ty as $crate::db_options::CompressionCodec>::decode_with_dictionary(
+                            {let $__index = __decoders; &mut $indexed},
+                            __dictionary,
+                            __src,
+                            __pool,
+                            __existing_buf,
+                        ).map_err(|__error| {
+                            $crate::db_options::CodecsDecompressionError::from(
+                                __error.map_custom($derr::$codec_variant),
+                            )
+                        })
+                    })*
+                    _ => ::std::result::Result::Err(
+                        $crate::db_options::CodecsDecompressionError::Unsupported,
+                    ),
+                }
+            }
         }
     };
 }
@@ -646,4 +756,19 @@ mod test {
             enum AllQuux;
         }
     }
+
+    #[cfg(feature = "lz4-compression")]
+    mod lz4_test {
+        use crate::db_options::{Lz4Codec, NoCompressionCodec};
+
+        codec_list! {
+            codecs[(None, NoCompressionCodec), (Lz4, Lz4Codec)];
+
+            struct Lz4Foo;
+            struct Lz4Bar;
+            struct Lz4Baz;
+            enum Lz4Qux;
+            enum Lz4Quux;
+        }
+    }
 }
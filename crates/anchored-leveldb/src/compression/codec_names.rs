@@ -0,0 +1,135 @@
+use std::num::NonZeroU8;
+
+use crate::{
+    all_errors::types::OptionsError,
+    pub_traits::compression::CompressorId,
+    pub_typed_bytes::{ReadPrefixedBytes as _, ShortSlice},
+    utils::WriteVarint as _,
+};
+
+
+/// Encode a list of compressor IDs and codec names, in the format recorded in the
+/// `compression_codecs` meta blocks of SSTable files.
+///
+/// Each entry is encoded as the compressor ID byte followed by the length-prefixed name.
+pub(crate) fn encode_codec_names<'a, I>(output: &mut Vec<u8>, codec_names: I)
+where
+    I: IntoIterator<Item = (CompressorId, ShortSlice<'a>)>,
+{
+    for (compressor_id, name) in codec_names {
+        output.push(compressor_id.0.get());
+        output.write_varint32(u32::from(name.len()));
+        output.extend(name.inner());
+    }
+}
+
+/// Decode a list of compressor IDs and codec names encoded by [`encode_codec_names`].
+///
+/// Returns `None` if the list is malformed.
+#[must_use]
+pub(crate) fn decode_codec_names(mut input: &[u8]) -> Option<Vec<(CompressorId, &[u8])>> {
+    let mut codec_names = Vec::new();
+
+    while let Some((&compressor_id, rest)) = input.split_first() {
+        let compressor_id = CompressorId(NonZeroU8::new(compressor_id)?);
+        input = rest;
+        let name = input.read_prefixed_bytes().ok()?.unprefixed_inner();
+
+        codec_names.push((compressor_id, name.inner()));
+    }
+
+    Some(codec_names)
+}
+
+/// Check that the recorded name of each compressor ID matches the name of the chosen codec with
+/// the same compressor ID.
+///
+/// If the chosen compression codecs do not report any names, nothing is checked.
+pub(crate) fn check_codec_names(
+    chosen:   &[(CompressorId, &'static str)],
+    recorded: &[(CompressorId, &[u8])],
+) -> Result<(), OptionsError> {
+    if chosen.is_empty() {
+        return Ok(());
+    }
+
+    for &(compressor_id, recorded_name) in recorded {
+        let chosen_name = chosen
+            .iter()
+            .find(|&&(chosen_id, _)| chosen_id == compressor_id)
+            .map(|&(_, chosen_name)| chosen_name);
+
+        if chosen_name.is_none_or(|chosen_name| chosen_name.as_bytes() != recorded_name) {
+            return Err(OptionsError::MismatchedCompressionCodec {
+                compressor_id,
+                chosen:        chosen_name,
+                recorded:      recorded_name.to_owned(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+#[allow(
+    clippy::missing_assert_message,
+    clippy::unwrap_used,
+    reason = "these are tests",
+)]
+mod tests {
+    use super::*;
+
+
+    fn id(compressor_id: u8) -> CompressorId {
+        CompressorId(NonZeroU8::new(compressor_id).unwrap())
+    }
+
+    #[test]
+    fn codec_names_round_trip() {
+        let names = [(id(1), b"snappy".as_slice()), (id(4), b"zstd".as_slice())];
+
+        let mut encoded = Vec::new();
+        encode_codec_names(
+            &mut encoded,
+            names.iter().map(|&(compressor_id, name)| {
+                (compressor_id, ShortSlice::new(name).unwrap())
+            }),
+        );
+
+        assert_eq!(decode_codec_names(&encoded).unwrap(), names);
+    }
+
+    #[test]
+    fn zero_compressor_id_is_malformed() {
+        assert!(decode_codec_names(&[0, 1, b'a']).is_none());
+    }
+
+    #[test]
+    fn matching_codec_names() {
+        let chosen = [(id(1), "snappy"), (id(4), "zstd")];
+
+        assert!(check_codec_names(&chosen, &[(id(4), b"zstd".as_slice())]).is_ok());
+        assert!(check_codec_names(&chosen, &[]).is_ok());
+        // Codecs which do not report names are not checked.
+        assert!(check_codec_names(&[], &[(id(2), b"zlib".as_slice())]).is_ok());
+    }
+
+    #[test]
+    fn mismatched_codec_names() {
+        let chosen = [(id(1), "snappy"), (id(4), "zstd")];
+
+        let renamed = check_codec_names(&chosen, &[(id(4), b"lz4".as_slice())]);
+        assert!(matches!(
+            renamed,
+            Err(OptionsError::MismatchedCompressionCodec { chosen: Some("zstd"), .. }),
+        ));
+
+        let unsupported = check_codec_names(&chosen, &[(id(2), b"zlib".as_slice())]);
+        assert!(matches!(
+            unsupported,
+            Err(OptionsError::MismatchedCompressionCodec { chosen: None, .. }),
+        ));
+    }
+}
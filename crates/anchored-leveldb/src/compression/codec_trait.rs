@@ -10,6 +10,14 @@ pub trait CompressionCodec {
     type CompressionError;
     type DecompressionError;
 
+    /// A stable name identifying the format of this codec's compressed data.
+    ///
+    /// The names of a database's codecs are recorded in its persistent files, so that the
+    /// database is not opened with incompatible codecs. Codecs whose compressed data is not
+    /// interchangeable must have distinct names, and a codec's name must never change.
+    #[must_use]
+    fn name(&self) -> &'static str;
+
     #[must_use]
     fn init_encoder(&self) -> Self::Encoder;
 
@@ -30,6 +38,53 @@ pub trait CompressionCodec {
         pool:         &Pool,
         existing_buf: &mut Option<Pool::PooledBuffer>,
     ) -> Result<Pool::PooledBuffer, CodecDecompressionError<Self::DecompressionError>>;
+
+    /// Train a compression dictionary of at most `max_dictionary_len` bytes on the given samples.
+    ///
+    /// The samples are concatenated in `samples`, and `sample_lens` holds the length of each
+    /// sample.
+    ///
+    /// Returns `None` if this codec does not support dictionaries, or if a dictionary could not
+    /// be trained (for instance, because there were too few samples). By default, `None` is
+    /// always returned.
+    #[must_use]
+    fn train_dictionary(
+        &self,
+        _samples:            &[u8],
+        _sample_lens:        &[usize],
+        _max_dictionary_len: usize,
+    ) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Equivalent to [`CompressionCodec::encode`], except that the given `dictionary`, which was
+    /// returned by [`CompressionCodec::train_dictionary`], is used.
+    ///
+    /// By default, the dictionary is ignored.
+    fn encode_with_dictionary<Pool: BufferPool>(
+        encoder:          &mut Self::Encoder,
+        _dictionary:      &[u8],
+        src:              &[u8],
+        compression_goal: usize,
+        pool:             &Pool,
+        existing_buf:     &mut Option<Pool::PooledBuffer>,
+    ) -> Result<Pool::PooledBuffer, CodecCompressionError<Self::CompressionError>> {
+        Self::encode(encoder, src, compression_goal, pool, existing_buf)
+    }
+
+    /// Equivalent to [`CompressionCodec::decode`], except that the given `dictionary`, which
+    /// should be the dictionary that `src` was encoded with, is used.
+    ///
+    /// By default, the dictionary is ignored.
+    fn decode_with_dictionary<Pool: BufferPool>(
+        decoder:      &mut Self::Decoder,
+        _dictionary:  &[u8],
+        src:          &[u8],
+        pool:         &Pool,
+        existing_buf: &mut Option<Pool::PooledBuffer>,
+    ) -> Result<Pool::PooledBuffer, CodecDecompressionError<Self::DecompressionError>> {
+        Self::decode(decoder, src, pool, existing_buf)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
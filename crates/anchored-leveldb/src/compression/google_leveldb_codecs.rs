@@ -36,6 +36,17 @@ impl CompressionCodecs for GoogleLevelDBCodecs {
     type CompressionError   = SnappyOrZstdCompressionError;
     type DecompressionError = SnappyOrZstdDecompressionError;
 
+    fn codec_names(&self) -> Vec<(CompressorId, &'static str)> {
+        let zstd_codec = ZstdCodec {
+            compression_level: self.zstd_compression_level,
+        };
+
+        vec![
+            (SNAPPY_COMPRESSION, SnappyCodec.name()),
+            (ZSTD_COMPRESSION,   zstd_codec.name()),
+        ]
+    }
+
     fn init_encoders(&self) -> Self::Encoders {
         let zstd_codec = ZstdCodec {
             compression_level: self.zstd_compression_level,
@@ -96,6 +107,67 @@ impl CompressionCodecs for GoogleLevelDBCodecs {
             _ => Err(CodecsDecompressionError::Unsupported),
         }
     }
+
+    fn train_dictionary(
+        &self,
+        id:                 CompressorId,
+        samples:            &[u8],
+        sample_lens:        &[usize],
+        max_dictionary_len: usize,
+    ) -> Option<Vec<u8>> {
+        // Snappy does not support dictionaries.
+        if id == ZSTD_COMPRESSION {
+            let zstd_codec = ZstdCodec {
+                compression_level: self.zstd_compression_level,
+            };
+
+            zstd_codec.train_dictionary(samples, sample_lens, max_dictionary_len)
+        } else {
+            None
+        }
+    }
+
+    fn encode_with_dictionary<Pool: BufferPool>(
+        encoders:         &mut Self::Encoders,
+        dictionary:       &[u8],
+        src:              &[u8],
+        id:               CompressorId,
+        compression_goal: usize,
+        pool:             &Pool,
+        existing_buf:     &mut Option<Pool::PooledBuffer>,
+    ) -> Result<Pool::PooledBuffer, CodecsCompressionError<Self::CompressionError>> {
+        if id == ZSTD_COMPRESSION {
+            ZstdCodec::encode_with_dictionary(
+                &mut encoders.1,
+                dictionary,
+                src,
+                compression_goal,
+                pool,
+                existing_buf,
+            )
+                .map_err(|err| err.map_custom(SnappyOrZstdCompressionError::Zstd))
+                .map_err(Into::into)
+        } else {
+            Self::encode(encoders, src, id, compression_goal, pool, existing_buf)
+        }
+    }
+
+    fn decode_with_dictionary<Pool: BufferPool>(
+        decoders:     &mut Self::Decoders,
+        dictionary:   &[u8],
+        src:          &[u8],
+        id:           CompressorId,
+        pool:         &Pool,
+        existing_buf: &mut Option<Pool::PooledBuffer>,
+    ) -> Result<Pool::PooledBuffer, CodecsDecompressionError<Self::DecompressionError>> {
+        if id == ZSTD_COMPRESSION {
+            ZstdCodec::decode_with_dictionary(&mut decoders.1, dictionary, src, pool, existing_buf)
+                .map_err(|err| err.map_custom(SnappyOrZstdDecompressionError::Zstd))
+                .map_err(Into::into)
+        } else {
+            Self::decode(decoders, src, id, pool, existing_buf)
+        }
+    }
 }
 
 impl Default for GoogleLevelDBCodecs {
@@ -0,0 +1,238 @@
+use lz4_flex::block::{compress_into, decompress_into, get_maximum_output_size};
+
+use crate::utils::get_buffer;
+use crate::pub_traits::pool::{BufferPool, ByteBuffer as _};
+use super::codec_trait::{CodecCompressionError, CodecDecompressionError, CompressionCodec};
+
+
+pub use lz4_flex::block::{
+    CompressError as Lz4CompressError, DecompressError as Lz4DecompressError,
+};
+
+
+/// The length of the little-endian `u32` prefix which stores the uncompressed length of the data.
+const LEN_PREFIX: usize = size_of::<u32>();
+
+/// Each byte of an LZ4 block decompresses to at most 255 bytes, so a recorded uncompressed length
+/// above `MAX_EXPANSION` times the compressed length must be corrupt. Checking this bounds the
+/// buffer allocated for decompression.
+const MAX_EXPANSION: usize = 255;
+
+
+/// LZ4 block compression via [`lz4_flex`].
+///
+/// Each compressed block is prefixed with its uncompressed length as a little-endian `u32`
+/// (the same format as [`lz4_flex::block::compress_prepend_size`]), since the LZ4 block format
+/// does not record it.
+#[derive(Debug, Clone, Copy)]
+pub struct Lz4Codec;
+
+impl CompressionCodec for Lz4Codec {
+    type Encoder = Self;
+    type Decoder = Self;
+    type CompressionError   = Lz4CompressionError;
+    type DecompressionError = Lz4DecompressionError;
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        "lz4"
+    }
+
+    #[inline]
+    fn init_encoder(&self) -> Self::Encoder {
+        Self
+    }
+
+    #[expect(clippy::panic_in_result_fn, reason = "the `assert!` could only fail due to a bug")]
+    fn encode<Pool: BufferPool>(
+        _encoder:         &mut Self::Encoder,
+        src:              &[u8],
+        compression_goal: usize,
+        pool:             &Pool,
+        existing_buf:     &mut Option<Pool::PooledBuffer>,
+    ) -> Result<Pool::PooledBuffer, CodecCompressionError<Self::CompressionError>> {
+        let src_len = u32::try_from(src.len())
+            .map_err(|_| CodecCompressionError::Custom(Lz4CompressionError::SourceTooLong))?;
+
+        if compression_goal <= LEN_PREFIX {
+            return Err(CodecCompressionError::Incompressible);
+        }
+
+        // Like `snap`, `lz4_flex` only uses its fast path if the output buffer has the maximum
+        // possible length. That is only slightly larger than `src.len()`, though.
+        let dst_len = LEN_PREFIX + get_maximum_output_size(src.len());
+
+        let mut buf = get_buffer(pool, existing_buf, dst_len)?;
+
+        let (len_prefix, compressed) = buf.as_mut_slice().split_at_mut(LEN_PREFIX);
+        len_prefix.copy_from_slice(&src_len.to_le_bytes());
+
+        match compress_into(src, compressed) {
+            Ok(written_bytes) => {
+                let written_bytes = LEN_PREFIX + written_bytes;
+
+                if written_bytes > compression_goal {
+                    *existing_buf = Some(buf);
+                    return Err(CodecCompressionError::Incompressible);
+                }
+
+                assert!(written_bytes <= buf.len(), "`lz4_flex::block` shouldn't be buggy");
+                // Note: `written_bytes` should be at most `buf.len()`, so this should not panic.
+                buf.set_len(written_bytes);
+                Ok(buf)
+            }
+            Err(err) => {
+                *existing_buf = Some(buf);
+                Err(CodecCompressionError::Custom(Lz4CompressionError::Lz4(err)))
+            }
+        }
+    }
+
+    #[inline]
+    fn init_decoder(&self) -> Self::Decoder {
+        Self
+    }
+
+    fn decode<Pool: BufferPool>(
+        _decoder:     &mut Self::Decoder,
+        src:          &[u8],
+        pool:         &Pool,
+        existing_buf: &mut Option<Pool::PooledBuffer>,
+    ) -> Result<Pool::PooledBuffer, CodecDecompressionError<Self::DecompressionError>> {
+        let Some((len_prefix, compressed)) = src.split_first_chunk::<LEN_PREFIX>() else {
+            return Err(CodecDecompressionError::Custom(Lz4DecompressionError::MissingLength));
+        };
+        let dst_len = usize::try_from(u32::from_le_bytes(*len_prefix))
+            .ok()
+            .filter(|&dst_len| dst_len <= compressed.len().saturating_mul(MAX_EXPANSION))
+            .ok_or(CodecDecompressionError::Custom(Lz4DecompressionError::TooLong))?;
+
+        let mut buf = get_buffer(pool, existing_buf, dst_len)?;
+
+        match decompress_into(compressed, buf.as_mut_slice()) {
+            Ok(written_bytes) if written_bytes == dst_len => Ok(buf),
+            Ok(_) => {
+                *existing_buf = Some(buf);
+                Err(CodecDecompressionError::Custom(Lz4DecompressionError::LengthMismatch))
+            }
+            Err(err) => {
+                *existing_buf = Some(buf);
+                Err(CodecDecompressionError::Custom(Lz4DecompressionError::Lz4(err)))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Lz4CompressionError {
+    /// LZ4-compressed blocks record their uncompressed length as a `u32`, so the source data must
+    /// be shorter than 4 GiB.
+    SourceTooLong,
+    Lz4(Lz4CompressError),
+}
+
+#[derive(Debug)]
+pub enum Lz4DecompressionError {
+    /// The compressed data was too short to contain its uncompressed length.
+    MissingLength,
+    /// The recorded uncompressed length could not fit in a `usize`, or was longer than the
+    /// compressed data could possibly decompress to.
+    TooLong,
+    /// The data decompressed to a different length than its recorded uncompressed length.
+    LengthMismatch,
+    Lz4(Lz4DecompressError),
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::missing_assert_message,
+    clippy::panic,
+    clippy::unwrap_used,
+    reason = "these are tests",
+)]
+mod tests {
+    use oorandom::Rand64;
+
+    use crate::pub_traits::pool::BadPool;
+    use super::*;
+
+
+    fn encode(src: &[u8], compression_goal: usize) -> Result<Vec<u8>, String> {
+        let pool = BadPool::new();
+        Lz4Codec::encode(&mut Lz4Codec, src, compression_goal, &pool, &mut None)
+            .map(|buf| buf.as_slice().to_vec())
+            .map_err(|err| format!("{err:?}"))
+    }
+
+    fn decode(src: &[u8]) -> Result<Vec<u8>, String> {
+        let pool = BadPool::new();
+        Lz4Codec::decode(&mut Lz4Codec, src, &pool, &mut None)
+            .map(|buf| buf.as_slice().to_vec())
+            .map_err(|err| format!("{err:?}"))
+    }
+
+    fn random_bytes(len: usize, seed: u128) -> Vec<u8> {
+        let mut rng = Rand64::new(seed);
+        (0..len).flat_map(|_| rng.rand_u64().to_le_bytes()).take(len).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let repetitive = b"leveldb leveldb leveldb leveldb ".repeat(64);
+        let mixed = [random_bytes(300, 1), b"a".repeat(500), random_bytes(300, 1)].concat();
+
+        for src in [&b""[..], b"x", b"short value", repetitive.as_slice(), mixed.as_slice()] {
+            let compressed = encode(src, usize::MAX).unwrap();
+            assert_eq!(decode(&compressed).unwrap(), src);
+        }
+
+        // Repetitive data should actually be compressed.
+        let compressed = encode(&repetitive, repetitive.len()).unwrap();
+        assert!(compressed.len() < repetitive.len() / 4);
+    }
+
+    #[test]
+    fn incompressible_input() {
+        let src = random_bytes(4096, 2);
+
+        // Random data does not compress, so a goal of at most the input's length is not met.
+        let err = encode(&src, src.len()).unwrap_err();
+        assert!(err.contains("Incompressible"), "{err}");
+
+        // A goal that cannot even fit the length prefix is never met.
+        let err = encode(b"", LEN_PREFIX).unwrap_err();
+        assert!(err.contains("Incompressible"), "{err}");
+
+        // Without a tight goal, incompressible data still round-trips.
+        let compressed = encode(&src, usize::MAX).unwrap();
+        assert_eq!(decode(&compressed).unwrap(), src);
+    }
+
+    #[test]
+    fn corrupted_input() {
+        let src = b"leveldb leveldb leveldb leveldb ".repeat(8);
+        let mut compressed = encode(&src, usize::MAX).unwrap();
+
+        assert!(decode(&compressed[..LEN_PREFIX - 1]).unwrap_err().contains("MissingLength"));
+
+        // Claim a longer uncompressed length than the actual data has.
+        let wrong_len = u32::try_from(src.len() + 1).unwrap();
+        compressed[..LEN_PREFIX].copy_from_slice(&wrong_len.to_le_bytes());
+        assert!(decode(&compressed).is_err());
+    }
+
+    #[test]
+    fn implausible_length_is_rejected() {
+        // The most compressible data is still within the bound on the uncompressed length.
+        let src = b"a".repeat(1 << 20_u8);
+        let compressed = encode(&src, usize::MAX).unwrap();
+        assert_eq!(decode(&compressed).unwrap(), src);
+
+        // A few bytes cannot decompress to 4 GiB, so nothing that large should be allocated.
+        let mut compressed = encode(b"short value", usize::MAX).unwrap();
+        compressed[..LEN_PREFIX].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(decode(&compressed).unwrap_err().contains("TooLong"));
+    }
+}
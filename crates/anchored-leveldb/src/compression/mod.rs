@@ -1,5 +1,6 @@
 mod codec_trait;
 mod codec_list;
+mod codec_names;
 
 #[cfg(feature = "google-leveldb-compression")]
 mod google_leveldb_codecs;
@@ -7,6 +8,8 @@ mod google_leveldb_codecs;
 mod mojang_leveldb_codecs;
 
 mod no_compression_impl;
+#[cfg(feature = "lz4-compression")]
+mod lz4_impl;
 #[cfg(feature = "snappy-compression")]
 mod raw_snap_impl;
 #[cfg(feature = "zstd-compression")]
@@ -25,9 +28,16 @@ pub use self::mojang_leveldb_codecs::{
     MojangLevelDBCodecs, MojangLevelDBCompressors, MojangLevelDBDecompressors,
 };
 
+pub(crate) use self::codec_names::{check_codec_names, decode_codec_names, encode_codec_names};
+
 pub use self::no_compression_impl::NoCompressionCodec;
 pub use self::codec_trait::{CodecCompressionError, CodecDecompressionError, CompressionCodec};
 
+// `Lz4CompressError` and `Lz4DecompressError` are public reexports from `lz4_flex`.
+#[cfg(feature = "lz4-compression")]
+pub use self::lz4_impl::{
+    Lz4Codec, Lz4CompressError, Lz4CompressionError, Lz4DecompressError, Lz4DecompressionError,
+};
 // `SnappyError` is a public reexport from `snap`.
 #[cfg(feature = "snappy-compression")]
 pub use self::raw_snap_impl::{SnappyCodec, SnappyDecoder, SnappyEncoder, SnappyError};
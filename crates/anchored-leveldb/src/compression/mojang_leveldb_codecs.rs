@@ -24,6 +24,13 @@ impl CompressionCodecs for MojangLevelDBCodecs {
     type CompressionError = ZlibDeflateError;
     type DecompressionError = ZlibInflateError;
 
+    fn codec_names(&self) -> Vec<(CompressorId, &'static str)> {
+        vec![
+            (ZLIB_COMPRESSION,     ZlibCodec::with_default_compression(true).name()),
+            (RAW_ZLIB_COMPRESSION, ZlibCodec::with_default_compression(false).name()),
+        ]
+    }
+
     fn init_encoders(&self) -> Self::Encoders {
         MojangLevelDBCompressors {
             with_header:    None,
@@ -16,6 +16,11 @@ impl CompressionCodec for NoCompressionCodec {
     type CompressionError   = Infallible;
     type DecompressionError = Infallible;
 
+    #[inline]
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
     #[inline]
     fn init_encoder(&self) -> Self::Encoder {
         Self
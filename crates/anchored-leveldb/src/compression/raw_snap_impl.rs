@@ -18,6 +18,11 @@ impl CompressionCodec for SnappyCodec {
     type CompressionError   = SnappyError;
     type DecompressionError = SnappyError;
 
+    #[inline]
+    fn name(&self) -> &'static str {
+        "snappy"
+    }
+
     fn init_encoder(&self) -> Self::Encoder {
         SnappyEncoder(Encoder::new())
     }
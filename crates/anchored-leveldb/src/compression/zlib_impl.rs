@@ -46,6 +46,11 @@ impl CompressionCodec for ZlibCodec {
     type CompressionError   = ZlibDeflateError;
     type DecompressionError = ZlibInflateError;
 
+    #[inline]
+    fn name(&self) -> &'static str {
+        if self.zlib_header { "zlib" } else { "raw-zlib" }
+    }
+
     fn init_encoder(&self) -> Self::Encoder {
         let compression_level = i32::from(self.compression_level);
 
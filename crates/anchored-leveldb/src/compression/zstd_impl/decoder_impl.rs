@@ -1,4 +1,4 @@
-use zstd_safe::{ContentSizeError, DCtx, get_frame_content_size};
+use zstd_safe::{ContentSizeError, DCtx, DDict, get_frame_content_size};
 
 use crate::utils::get_buffer;
 use crate::pub_traits::pool::{BufferPool, ByteBuffer as _};
@@ -6,17 +6,33 @@ use super::super::codec_trait::CodecDecompressionError;
 use super::{ZstdDecompressionError, ZstdErrorCode};
 
 
-pub(super) struct ZstdDecoderImpl(DCtx<'static>);
+/// The maximum number of digested dictionaries kept by each decoder.
+///
+/// Each table has its own dictionary, and reads commonly alternate between the tables of
+/// different levels, so a decoder should not need to redigest a dictionary for every block.
+const MAX_CACHED_DICTIONARIES: usize = 16;
+
+pub(super) struct ZstdDecoderImpl {
+    dctx:         DCtx<'static>,
+    /// Recently-used dictionaries and their digested forms, with the most recently used first.
+    dictionaries: Vec<(Vec<u8>, DDict<'static>)>,
+}
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
 impl ZstdDecoderImpl {
     #[must_use]
     pub fn new() -> Self {
-        Self(DCtx::create())
+        Self {
+            dctx:         DCtx::create(),
+            dictionaries: Vec::new(),
+        }
     }
 
+    /// Decompress `src` with the given dictionary. An empty `dictionary` indicates that no
+    /// dictionary should be used.
     pub fn decode<Pool: BufferPool>(
         &mut self,
+        dictionary:   &[u8],
         src:          &[u8],
         pool:         &Pool,
         existing_buf: &mut Option<Pool::PooledBuffer>,
@@ -40,9 +56,21 @@ impl ZstdDecoderImpl {
             return Err(CodecDecompressionError::BufferAlloc);
         };
 
+        let ddict = if dictionary.is_empty() {
+            None
+        } else {
+            Some(Self::digested_dictionary(&mut self.dictionaries, dictionary)?)
+        };
+
         let mut buf = get_buffer(pool, existing_buf, decompressed_size)?;
 
-        match self.0.decompress(buf.as_mut_slice(), src) {
+        let decompress_result = if let Some(ddict) = ddict {
+            self.dctx.decompress_using_ddict(buf.as_mut_slice(), src, ddict)
+        } else {
+            self.dctx.decompress(buf.as_mut_slice(), src)
+        };
+
+        match decompress_result {
             Ok(bytes_written) => {
                 // Should not panic, since assuming Zstd is not buggy,
                 // `bytes_written <= buf.as_mut_slice().len() <= buf.capacity()`.
@@ -58,4 +86,37 @@ impl ZstdDecoderImpl {
             }
         }
     }
+
+    /// Get the digested form of a nonempty `dictionary` from the cached `dictionaries`, digesting
+    /// it if it is not cached.
+    ///
+    /// Digesting a dictionary is fairly expensive, so the digested forms of the most recently
+    /// used dictionaries are kept.
+    fn digested_dictionary<'d>(
+        dictionaries: &'d mut Vec<(Vec<u8>, DDict<'static>)>,
+        dictionary:   &[u8],
+    ) -> Result<&'d DDict<'static>, CodecDecompressionError<ZstdDecompressionError>> {
+        let cached_idx = dictionaries
+            .iter()
+            .position(|(cached, _)| cached.as_slice() == dictionary);
+
+        match cached_idx {
+            Some(0) => {}
+            Some(cached_idx) => {
+                let entry = dictionaries.remove(cached_idx);
+                dictionaries.insert(0, entry);
+            }
+            None => {
+                // Digesting a dictionary only fails if allocation fails.
+                let ddict = DDict::try_create(dictionary)
+                    .ok_or(CodecDecompressionError::BufferAlloc)?;
+
+                dictionaries.truncate(MAX_CACHED_DICTIONARIES.saturating_sub(1));
+                dictionaries.insert(0, (dictionary.to_vec(), ddict));
+            }
+        }
+
+        #[expect(clippy::indexing_slicing, reason = "the dictionary is now the first entry")]
+        Ok(&dictionaries[0].1)
+    }
 }
@@ -5,7 +5,8 @@ use std::ffi::{c_int, c_ulonglong, c_void};
 
 use zstd_safe::{max_c_level, min_c_level};
 use zstd_safe::zstd_sys::{
-    ZSTD_CCtx, ZSTD_CCtx_setCParams, ZSTD_compress2, ZSTD_createCCtx, ZSTD_getCParams, ZSTD_isError,
+    ZSTD_CCtx, ZSTD_CCtx_loadDictionary, ZSTD_CCtx_setCParams, ZSTD_compress2, ZSTD_createCCtx,
+    ZSTD_getCParams, ZSTD_isError,
 };
 
 use crate::pub_traits::pool::ByteBuffer;
@@ -16,6 +17,8 @@ use super::{DST_FULL, DST_TOO_SMALL, ZstdCompressionError, ZstdErrorCode};
 pub(super) struct ZstdEncoderImpl {
     raw_cctx:                  NonNull<ZSTD_CCtx>,
     clamped_compression_level: c_int,
+    /// The dictionary currently loaded into `raw_cctx`, or empty if there is none.
+    dictionary:                Vec<u8>,
 }
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
@@ -42,20 +45,50 @@ impl ZstdEncoderImpl {
         Self {
             raw_cctx,
             clamped_compression_level,
+            dictionary: Vec::new(),
         }
     }
 
+    /// Compress `src` into `dst` with the given dictionary. An empty `dictionary` indicates that
+    /// no dictionary should be used.
     pub fn encode<Buffer: ByteBuffer>(
         &mut self,
-        src: &[u8],
-        dst: &mut Buffer,
+        dictionary: &[u8],
+        src:        &[u8],
+        dst:        &mut Buffer,
     ) -> Result<(), CodecCompressionError<ZstdCompressionError>> {
+        // Loading a dictionary is fairly expensive, so avoid reloading the same dictionary.
+        if self.dictionary != dictionary {
+            // SAFETY: `self.raw_cctx.as_ptr()` is a valid Zstd compression context that we have
+            // exclusive access to, and it is sound to read offsets `0..dictionary.len()` of
+            // `dictionary.as_ptr()` as initialized bytes. Zstd copies the dictionary, so it need
+            // not outlive this call. Note that loading an empty dictionary unloads the previous
+            // dictionary.
+            let result = unsafe {
+                ZSTD_CCtx_loadDictionary(
+                    self.raw_cctx.as_ptr(),
+                    dictionary.as_ptr().cast(),
+                    dictionary.len(),
+                )
+            };
+
+            // SAFETY: "Just FFI", quoting `zstd-safe`'s internal `is_error` function.
+            if unsafe { ZSTD_isError(result) != 0 } {
+                return Err(CodecCompressionError::Custom(ZstdCompressionError::ErrorCode(
+                    ZstdErrorCode(result),
+                )));
+            }
+
+            self.dictionary.clear();
+            self.dictionary.extend(dictionary);
+        }
+
         let estimated_src_size = c_ulonglong::try_from(src.len()).unwrap_or(c_ulonglong::MAX);
 
         // SAFETY: Seems that Zstd places no preconditions on this function. This is just `unsafe`
         // because it's FFI that `zstd_sys` didn't bother to mark safe.
         let compression_params = unsafe {
-            ZSTD_getCParams(self.clamped_compression_level, estimated_src_size, 0)
+            ZSTD_getCParams(self.clamped_compression_level, estimated_src_size, dictionary.len())
         };
 
         // SAFETY: `self.raw_cctx.as_ptr()` is a valid Zstd compression context that we have
@@ -110,7 +143,7 @@ impl ZstdEncoderImpl {
             // Since `ZSTD_compress2` was successful, its return value is the number of bytes
             // written (which is at most `dst_len`, which should equal `dst.len()` if the `Buffer`
             // implementation is sane).
-            let bytes_written = result;
+            let bytes_written = compress_result;
             dst.set_len(bytes_written);
             Ok(())
         }
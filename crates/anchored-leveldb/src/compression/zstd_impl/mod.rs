@@ -10,7 +10,7 @@ mod decoder_impl;
 
 use std::fmt::{Debug, Formatter, Result as FmtResult};
 
-use zstd_safe::{CLEVEL_DEFAULT, zstd_sys::ZSTD_ErrorCode};
+use zstd_safe::{CLEVEL_DEFAULT, train_from_buffer, zstd_sys::ZSTD_ErrorCode};
 
 use crate::utils::get_buffer;
 use crate::pub_traits::pool::{BufferPool, ByteBuffer as _};
@@ -37,8 +37,9 @@ const DST_FULL: usize = ZSTD_ErrorCode::ZSTD_error_noForwardProgress_destFull as
 
 /// Zstandard compression via bindings.
 ///
-/// No dictionary is used, and compressed data is required to indicate the uncompressed size in
-/// its header.
+/// No dictionary is used unless one is provided through
+/// [`CompressionCodec::encode_with_dictionary`] or [`CompressionCodec::decode_with_dictionary`],
+/// and compressed data is required to indicate the uncompressed size in its header.
 #[derive(Debug, Clone, Copy)]
 pub struct ZstdCodec {
     /// The level of compression to perform when encoding/compressing data. Has no effect on
@@ -61,6 +62,11 @@ impl CompressionCodec for ZstdCodec {
     type CompressionError   = ZstdCompressionError;
     type DecompressionError = ZstdDecompressionError;
 
+    #[inline]
+    fn name(&self) -> &'static str {
+        "zstd"
+    }
+
     fn init_encoder(&self) -> Self::Encoder {
        ZstdEncoder(ZstdEncoderImpl::new(self.compression_level))
     }
@@ -76,7 +82,7 @@ impl CompressionCodec for ZstdCodec {
         // Should not panic, since `buf.capacity()` should be at least `compression_goal`.
         buf.set_len(compression_goal);
 
-        if let Err(err) = encoder.0.encode(src, &mut buf) {
+        if let Err(err) = encoder.0.encode(&[], src, &mut buf) {
             *existing_buf = Some(buf);
             Err(err)
         } else {
@@ -94,7 +100,56 @@ impl CompressionCodec for ZstdCodec {
         pool:         &Pool,
         existing_buf: &mut Option<Pool::PooledBuffer>,
     ) -> Result<Pool::PooledBuffer, CodecDecompressionError<Self::DecompressionError>> {
-        decoder.0.decode(src, pool, existing_buf)
+        decoder.0.decode(&[], src, pool, existing_buf)
+    }
+
+    fn train_dictionary(
+        &self,
+        samples:            &[u8],
+        sample_lens:        &[usize],
+        max_dictionary_len: usize,
+    ) -> Option<Vec<u8>> {
+        let mut dictionary = Vec::with_capacity(max_dictionary_len);
+
+        // Training fails if, for instance, there are too few samples.
+        train_from_buffer(&mut dictionary, samples, sample_lens).ok()?;
+
+        // An empty dictionary would be indistinguishable from using no dictionary.
+        if dictionary.is_empty() {
+            None
+        } else {
+            Some(dictionary)
+        }
+    }
+
+    fn encode_with_dictionary<Pool: BufferPool>(
+        encoder:          &mut Self::Encoder,
+        dictionary:       &[u8],
+        src:              &[u8],
+        compression_goal: usize,
+        pool:             &Pool,
+        existing_buf:     &mut Option<Pool::PooledBuffer>,
+    ) -> Result<Pool::PooledBuffer, CodecCompressionError<Self::CompressionError>> {
+        let mut buf = get_buffer(pool, existing_buf, compression_goal)?;
+        // Should not panic, since `buf.capacity()` should be at least `compression_goal`.
+        buf.set_len(compression_goal);
+
+        if let Err(err) = encoder.0.encode(dictionary, src, &mut buf) {
+            *existing_buf = Some(buf);
+            Err(err)
+        } else {
+            Ok(buf)
+        }
+    }
+
+    fn decode_with_dictionary<Pool: BufferPool>(
+        decoder:      &mut Self::Decoder,
+        dictionary:   &[u8],
+        src:          &[u8],
+        pool:         &Pool,
+        existing_buf: &mut Option<Pool::PooledBuffer>,
+    ) -> Result<Pool::PooledBuffer, CodecDecompressionError<Self::DecompressionError>> {
+        decoder.0.decode(dictionary, src, pool, existing_buf)
     }
 }
 
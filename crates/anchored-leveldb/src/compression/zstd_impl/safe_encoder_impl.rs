@@ -5,7 +5,11 @@ use super::super::codec_trait::CodecCompressionError;
 use super::{DST_FULL, DST_TOO_SMALL, ZstdCompressionError, ZstdErrorCode};
 
 
-pub(super) struct ZstdEncoderImpl(CCtx<'static>);
+pub(super) struct ZstdEncoderImpl {
+    cctx:       CCtx<'static>,
+    /// The dictionary currently loaded into `cctx`, or empty if there is none.
+    dictionary: Vec<u8>,
+}
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
 impl ZstdEncoderImpl {
@@ -30,15 +34,33 @@ impl ZstdEncoderImpl {
             panic!("`ZSTD_CCtx` creation failed: {}", get_error_name(code));
         }
 
-        Self(cctx)
+        Self {
+            cctx,
+            dictionary: Vec::new(),
+        }
     }
 
+    /// Compress `src` into `dst` with the given dictionary. An empty `dictionary` indicates that
+    /// no dictionary should be used.
     pub fn encode<Buffer: ByteBuffer>(
         &mut self,
-        src: &[u8],
-        dst: &mut Buffer,
+        dictionary: &[u8],
+        src:        &[u8],
+        dst:        &mut Buffer,
     ) -> Result<(), CodecCompressionError<ZstdCompressionError>> {
-        match self.0.compress2(dst.as_mut_slice(), src) {
+        // Loading a dictionary is fairly expensive, so avoid reloading the same dictionary.
+        if self.dictionary != dictionary {
+            // Note that loading an empty dictionary unloads the previous dictionary.
+            if let Err(code) = self.cctx.load_dictionary(dictionary) {
+                return Err(CodecCompressionError::Custom(ZstdCompressionError::ErrorCode(
+                    ZstdErrorCode(code),
+                )));
+            }
+            self.dictionary.clear();
+            self.dictionary.extend(dictionary);
+        }
+
+        match self.cctx.compress2(dst.as_mut_slice(), src) {
             Ok(bytes_written) => {
                 dst.set_len(bytes_written);
                 Ok(())
@@ -1,6 +1,7 @@
 #![expect(unsafe_code, reason = "perform unsafe lifetime transmutes on a covariant type")]
 
-use crate::{binary_block_log::Slices, pub_typed_bytes::FlushWrites};
+use crate::{options::InternalWriteOptions, write_batch::BorrowedWriteBatch};
+use crate::pub_typed_bytes::{FlushWrites, SequenceNumber};
 
 
 /// # Safety
@@ -38,6 +39,44 @@ unsafe impl AdHocCovariantFamily for VaryingWriteCommand {
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum WriteCommand<'a> {
-    Write(Slices<'a>),
+    Write(BorrowedWriteBatch<'a>, InternalWriteOptions),
+    /// A write batch which should be applied only if none of the keys in the read set have been
+    /// written to at a sequence number greater than the sequence number recorded for that key.
+    ///
+    /// A `Transaction` command is never merged into a different writer's batch, since it might
+    /// fail to apply.
+    Transaction {
+        batch:      BorrowedWriteBatch<'a>,
+        read_set:   &'a [(Vec<u8>, SequenceNumber)],
+        write_opts: InternalWriteOptions,
+    },
+    /// A write batch which should be applied only if the `condition` holds for the database's
+    /// current state.
+    ///
+    /// Like a `Transaction` command, a `Conditional` command is never merged into a different
+    /// writer's batch; it is evaluated only once every write queued ahead of it has been applied,
+    /// so the writes to each key are applied in the order they were queued.
+    Conditional {
+        batch:      BorrowedWriteBatch<'a>,
+        condition:  WriteCondition<'a>,
+        write_opts: InternalWriteOptions,
+    },
     Flush(FlushWrites),
 }
+
+/// A condition on the current value of a key.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WriteCondition<'a> {
+    pub key:      &'a [u8],
+    /// The condition holds if the key's current value is equal to `expected`, where `None`
+    /// indicates that the key should be absent (or deleted).
+    pub expected: Option<&'a [u8]>,
+}
+
+impl WriteCondition<'_> {
+    /// Determine whether the condition holds, given the current value of the key.
+    #[must_use]
+    pub(crate) fn holds(self, current_value: Option<&[u8]>) -> bool {
+        self.expected == current_value
+    }
+}
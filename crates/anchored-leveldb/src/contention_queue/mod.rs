@@ -3,6 +3,10 @@ mod task;
 mod ad_hoc_variance_family_trait;
 
 pub(crate) use self::{
-    ad_hoc_variance_family_trait::{AdHocCovariantFamily, VaryingWriteCommand, WriteCommand},
-    queue::{ContentionQueue, PanicOptions, ProcessResult, ProcessTask, QueueHandle},
+    ad_hoc_variance_family_trait::{
+        AdHocCovariantFamily, VaryingWriteCommand, WriteCommand, WriteCondition,
+    },
+    queue::{
+        ContentionQueue, PanicOptions, ProcessResult, ProcessTask, QueueHandle, SpinOptions,
+    },
 };
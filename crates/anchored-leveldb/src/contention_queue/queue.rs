@@ -6,6 +6,7 @@ use std::{
     fmt::{Debug, Formatter, Result as FmtResult},
     panic::{AssertUnwindSafe, catch_unwind, RefUnwindSafe, resume_unwind, UnwindSafe},
     sync::{atomic::{AtomicUsize, Ordering}, Mutex, MutexGuard, PoisonError},
+    time::Duration,
 };
 
 use crate::utils::{NotShared, UnsafeMutexCell, UnwrapPoison as _};
@@ -81,6 +82,8 @@ pub(crate) struct ContentionQueue<'upper, FrontState, Value: AdHocCovariantFamil
     mutex_address:    AtomicUsize,
     /// There is no safety invariant on this field.
     options:          PanicOptions,
+    /// There is no safety invariant on this field.
+    spin_options:     SpinOptions,
     /// # Safety invariant
     /// Can only be accessed by a given invocation of `self.process_unchecked` if the invocation
     /// acquired exclusive permissions over `front_exclusive` by either:
@@ -155,7 +158,11 @@ impl<FS, V: AdHocCovariantFamily> ContentionQueue<'_, FS, V> {
             unwrap_mutex_poison: true,
             unwrap_queue_poison: true,
         };
-        Self::new_with_options(front_state, default_options)
+        let default_spin_options = SpinOptions {
+            max_spins: 0,
+            max_yield: Duration::ZERO,
+        };
+        Self::new_with_options(front_state, default_options, default_spin_options)
     }
 
     /// Construct a new [`ContentionQueue`].
@@ -165,11 +172,16 @@ impl<FS, V: AdHocCovariantFamily> ContentionQueue<'_, FS, V> {
     /// front of the queue can access it.
     #[inline]
     #[must_use]
-    pub const fn new_with_options(front_state: FS, options: PanicOptions) -> Self {
+    pub const fn new_with_options(
+        front_state:  FS,
+        options:      PanicOptions,
+        spin_options: SpinOptions,
+    ) -> Self {
         Self {
             // Safety invariant: initialized to zero.
             mutex_address:   AtomicUsize::new(0),
             options,
+            spin_options,
             front_exclusive: UnsafeMutexCell::new(FrontExclusive {
                 front_state,
             }),
@@ -348,6 +360,7 @@ impl<'upper, FS, V: AdHocCovariantFamily> ContentionQueue<'upper, FS, V> {
     unsafe fn try_wait_until_at_front<'m, 't, 'v, M>(
         &self,
         abort_on_drop: AbortIfNotAtFront,
+        mutex:         &'m Mutex<M>,
         mut guard:     MutexGuard<'m, M>,
         task_state:    &'t TaskState,
         value:         V::Varying<'v>,
@@ -388,8 +401,9 @@ impl<'upper, FS, V: AdHocCovariantFamily> ContentionQueue<'upper, FS, V> {
         // synchronized across threads by a lock.
         // This also fulfills the safety invariant of `mutex_exclusive` that extends to tasks pushed
         // or popped into/from the queue.
+        // Additionally, as asserted by the caller, `guard` is a guard of `mutex`.
         let (returned_guard, processing_panicked) = unsafe {
-            task_state.wait_until_at_front(guard)
+            task_state.wait_until_at_front(mutex, guard, self.spin_options)
         };
         // Robustness guarantee of `wait_until_at_front` implies that `returned_guard` is a guard
         // of the same mutex as `guard` was (namely, of `mutex`).
@@ -524,7 +538,7 @@ impl<'upper, FS, V: AdHocCovariantFamily> ContentionQueue<'upper, FS, V> {
             // `self.assert_mutex_good(mutex)` successfully returned, and no references to
             // the contents of `self.mutex_exclusive` exist when this call is made.
             let (returned_guard, result) = unsafe {
-                self.try_wait_until_at_front(abort_on_drop, guard, &task_state, value)
+                self.try_wait_until_at_front(abort_on_drop, mutex, guard, &task_state, value)
             };
 
             // Robustness guarantee of this function: we do not release exclusive permissions
@@ -1314,6 +1328,21 @@ where
     }
 }
 
+/// Options for how a task waits to reach the front of a [`ContentionQueue`].
+///
+/// A waiting task first spins, then yields to other threads, and finally sleeps until it is
+/// woken. Spinning and yielding avoid the latency of sleeping and waking when other tasks are
+/// processed quickly.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SpinOptions {
+    /// The number of times that a waiting task checks whether it has reached the front of the
+    /// queue before it starts yielding.
+    pub max_spins: u32,
+    /// How long a waiting task yields to other threads, checking whether it has reached the
+    /// front of the queue, before it sleeps.
+    pub max_yield: Duration,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct PanicOptions {
     /// If a thread panics while holding a mutex, other threads are informed of that panic
@@ -1,16 +1,16 @@
 #![expect(unsafe_code, reason = "assert that a generic type is covariant over a lifetime")]
 
-use std::marker::PhantomData;
+use std::{hint, thread, marker::PhantomData, time::Instant};
 use std::{
     fmt::{Debug, Formatter, Result as FmtResult},
     mem::{MaybeUninit, transmute},
-    sync::{Condvar, MutexGuard, PoisonError},
+    sync::{Condvar, Mutex, MutexGuard, PoisonError, atomic::{AtomicU8, Ordering}},
 };
 
 use variance_family::UpperBound;
 
-use crate::utils::UnsafeMutexCell;
 use super::{ad_hoc_variance_family_trait::AdHocCovariantFamily, queue::ProcessingPanicked};
+use super::queue::SpinOptions;
 
 
 const FRONT_BIT: u8 = 0b_01;
@@ -49,7 +49,9 @@ const PANIC_BIT: u8 = 0b_10;
 #[derive(Debug)]
 pub(super) struct TaskState {
     condvar: Condvar,
-    state:   UnsafeMutexCell<u8>,
+    /// Only written while the associated `Mutex` is held, but may be read without holding it
+    /// (while spinning).
+    state:   AtomicU8,
 }
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
@@ -59,40 +61,71 @@ impl TaskState {
     pub const fn new() -> Self {
         Self {
             condvar: Condvar::new(),
-            state:   UnsafeMutexCell::new(0),
+            state:   AtomicU8::new(0),
         }
     }
 
-    /// Returns the input guard, in addition to whether the task panicked while something else
+    /// Returns a guard of `mutex`, in addition to whether the task panicked while something else
     /// processed it (if it was processed by something else).
     ///
+    /// Before sleeping until woken, the lock is released while spinning and yielding as
+    /// permitted by `spin_options`.
+    ///
     /// Note that this function unwraps poison in order to avoid panicking. However, it does
     /// not clear poison.
     ///
     /// # Robust guarantee
-    /// The returned guard is a guard of the same mutex as the given guard.
+    /// The returned guard is a guard of `mutex`.
     ///
     /// # Safety
-    /// All concurrent calls to `self`'s unsafe methods must be synchronized across threads by the
-    /// `Mutex` associated with `guard`.
+    /// `guard` must be a guard of `mutex`. All concurrent calls to `self`'s unsafe methods must be
+    /// synchronized across threads by `mutex`.
     pub unsafe fn wait_until_at_front<'m, M>(
         &self,
-        mut guard: MutexGuard<'m, M>,
+        mutex:        &'m Mutex<M>,
+        mut guard:    MutexGuard<'m, M>,
+        spin_options: SpinOptions,
     ) -> (MutexGuard<'m, M>, ProcessingPanicked) {
-        // Correctness of robust guarantee: holds by correctness of `std::sync::Condvar`.
-        loop {
-            guard = self.condvar.wait(guard).unwrap_or_else(PoisonError::into_inner);
+        if spin_options.max_spins > 0 || !spin_options.max_yield.is_zero() {
+            drop(guard);
+            self.spin_until_at_front(spin_options);
+            guard = mutex.lock().unwrap_or_else(PoisonError::into_inner);
+        }
 
-            // SAFETY: We only access `self.state`'s contents within `self`'s unsafe methods,
-            // so the caller asserts that we are the only function trying to access `self.state`'s
-            // contents (since all such method calls are synchronized by a `Mutex` we hold
-            // (as asserted by the caller), and we do not leak references to `self.state`'s
-            // contents outside the `unsafe` methods of `self`).
-            // Therefore, we can exclusively borrow `self.state`'s contents.
-            let state = unsafe { *self.state.get_mut() };
+        // Correctness of robust guarantee: `guard` is either the given guard of `mutex` or was
+        // acquired from `mutex` above, and otherwise holds by correctness of `std::sync::Condvar`.
+        loop {
+            // `self.state` is only written while `mutex` is held, and we hold `mutex`, so a
+            // `Relaxed` load observes the latest value.
+            let state = self.state.load(Ordering::Relaxed);
             if state & FRONT_BIT != 0 {
                 break (guard, ProcessingPanicked(state & PANIC_BIT != 0));
             }
+
+            // Note that the task cannot be woken while we hold `mutex`, so the wakeup cannot be
+            // missed.
+            guard = self.condvar.wait(guard).unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// Spin, and then yield, until the task is at the front of the queue or `spin_options` says
+    /// to stop. The result must be confirmed while holding the lock.
+    fn spin_until_at_front(&self, spin_options: SpinOptions) {
+        let at_front = || self.state.load(Ordering::Relaxed) & FRONT_BIT != 0;
+
+        for _ in 0..spin_options.max_spins {
+            if at_front() {
+                return;
+            }
+            hint::spin_loop();
+        }
+
+        let yield_start = Instant::now();
+        while yield_start.elapsed() < spin_options.max_yield {
+            if at_front() {
+                return;
+            }
+            thread::yield_now();
         }
     }
 
@@ -101,9 +134,7 @@ impl TaskState {
     /// lock.
     /// (The lock must be held when calling this method.)
     pub unsafe fn wake_front_task(&self) {
-        // SAFETY: Same as in `self.wait`.
-        let state = unsafe { self.state.get_mut() };
-        *state |= FRONT_BIT;
+        self.state.fetch_or(FRONT_BIT, Ordering::Relaxed);
 
         self.condvar.notify_one();
     }
@@ -113,9 +144,7 @@ impl TaskState {
     /// lock.
     /// (The lock must be held when calling this method.)
     pub unsafe fn wake_front_task_panicking(&self) {
-        // SAFETY: Same as in `self.wait`.
-        let state = unsafe { self.state.get_mut() };
-        *state |= PANIC_BIT | FRONT_BIT;
+        self.state.fetch_or(PANIC_BIT | FRONT_BIT, Ordering::Relaxed);
 
         self.condvar.notify_one();
     }
@@ -1,13 +1,97 @@
 use std::num::NonZeroU64;
-use std::sync::{Arc, atomic::{AtomicU32, Ordering}};
+use std::time::{Duration, Instant, SystemTime};
+use std::sync::{Arc, OnceLock, atomic::{AtomicU32, Ordering}};
 
 use crate::options::pub_options::SeekCompactionOptions;
 use crate::{
     pub_typed_bytes::{EntryType, FileNumber, FileSize, MinU32Usize, NonZeroLevel, SequenceNumber},
     typed_bytes::{InternalKey, InternalKeyTag, UserKey},
+    utils::{ReadVarint as _, WriteVarint as _},
 };
 
 
+/// Statistics about the entries of a table file, recorded in the table file's
+/// `table_properties` meta block when the file is created.
+///
+/// Table files written by other LevelDB implementations (or by older versions of this crate)
+/// have no recorded properties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TableProperties {
+    pub num_entries:   u64,
+    /// The number of entries in the table file which are deletions (tombstones).
+    pub num_deletions: u64,
+    /// The smallest sequence number of any entry in the table file.
+    pub smallest_seq:  SequenceNumber,
+    /// The largest sequence number of any entry in the table file.
+    pub largest_seq:   SequenceNumber,
+    /// When the table file was written, in seconds since the Unix epoch, or `0` if unknown.
+    pub creation_time: u64,
+}
+
+#[expect(unreachable_pub, reason = "control visibility at type definition")]
+impl TableProperties {
+    /// Encode the properties as the contents of a `table_properties` meta block: each field
+    /// as a varint64, in declaration order.
+    pub fn encode_to(self, output: &mut Vec<u8>) {
+        output.write_varint64(self.num_entries);
+        output.write_varint64(self.num_deletions);
+        output.write_varint64(self.smallest_seq.inner());
+        output.write_varint64(self.largest_seq.inner());
+        output.write_varint64(self.creation_time);
+    }
+
+    /// Decode properties encoded by [`Self::encode_to`].
+    ///
+    /// Any bytes following the known fields are ignored, so that fields can be appended in the
+    /// future. Returns `None` if the input is malformed.
+    #[must_use]
+    pub fn decode_from(mut input: &[u8]) -> Option<Self> {
+        let num_entries = input.read_varint64().ok()?.0;
+        let num_deletions = input.read_varint64().ok()?.0;
+        let smallest_seq = SequenceNumber::new(input.read_varint64().ok()?.0)?;
+        let largest_seq = SequenceNumber::new(input.read_varint64().ok()?.0)?;
+        let creation_time = input.read_varint64().ok()?.0;
+
+        Some(Self {
+            num_entries,
+            num_deletions,
+            smallest_seq,
+            largest_seq,
+            creation_time,
+        })
+    }
+
+    /// The current time, in seconds since the Unix epoch, as recorded in
+    /// [`Self::creation_time`].
+    ///
+    /// Returns `0` (meaning unknown) if the system clock is set before the Unix epoch.
+    #[must_use]
+    pub fn current_time() -> u64 {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_secs())
+    }
+
+    /// The fraction of the table's entries which are deletions.
+    ///
+    /// Returns `None` if the table has no entries.
+    #[must_use]
+    pub fn deletion_ratio(self) -> Option<f64> {
+        #![expect(
+            clippy::as_conversions,
+            clippy::cast_precision_loss,
+            clippy::float_arithmetic,
+            reason = "precision is not critical for a heuristic",
+        )]
+
+        if self.num_entries == 0 {
+            None
+        } else {
+            Some((self.num_deletions as f64) / (self.num_entries as f64))
+        }
+    }
+}
+
 /// Metadata for a table file.
 #[derive(Debug)]
 pub(crate) struct FileMetadata {
@@ -31,6 +115,12 @@ pub(crate) struct FileMetadata {
     smallest_entry_type:   EntryType,
     largest_seq:           SequenceNumber,
     largest_entry_type:    EntryType,
+    /// Set when the table file is written, or loaded from the table file's meta block
+    /// when the table is opened.
+    properties:            OnceLock<TableProperties>,
+    /// When the table file was written, or when the database was opened if the file already
+    /// existed. Only used if the file's properties do not record its creation time.
+    tracked_since:         Instant,
 }
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
@@ -41,6 +131,7 @@ impl FileMetadata {
         file_size:    FileSize,
         smallest_key: InternalKey<'_>,
         largest_key:  InternalKey<'_>,
+        properties:   Option<TableProperties>,
         opts:         SeekCompactionOptions,
     ) -> Self {
         let smallest_user_key_len = smallest_key.0.len();
@@ -63,6 +154,8 @@ impl FileMetadata {
             smallest_entry_type:   smallest_key.1.entry_type(),
             largest_seq:           largest_key.1.sequence_number(),
             largest_entry_type:    largest_key.1.entry_type(),
+            properties:            properties.map(OnceLock::from).unwrap_or_default(),
+            tracked_since:         Instant::now(),
         }
     }
 
@@ -113,6 +206,13 @@ impl FileMetadata {
         );
     }
 
+    /// The number of seeks remaining before a seek compaction of this file is requested.
+    #[must_use]
+    pub fn remaining_seeks(&self) -> u32 {
+        // As in `record_seek`, the exact value doesn't particularly matter.
+        self.remaining_seeks.load(Ordering::Relaxed)
+    }
+
     #[must_use]
     pub const fn file_number(&self) -> FileNumber {
         self.file_number
@@ -123,6 +223,33 @@ impl FileMetadata {
         self.file_size
     }
 
+    #[must_use]
+    pub fn properties(&self) -> Option<TableProperties> {
+        self.properties.get().copied()
+    }
+
+    /// Record the properties read from the table file, if they were not already known.
+    pub fn set_properties(&self, properties: TableProperties) {
+        let _already_set = self.properties.set(properties);
+    }
+
+    /// The time since the table file was written.
+    ///
+    /// If the file's properties are not known or do not record its creation time, the time
+    /// since the database was opened is used instead (if the file already existed by then).
+    #[must_use]
+    pub fn age(&self) -> Duration {
+        let creation_time = self.properties().map_or(0, |properties| properties.creation_time);
+
+        if creation_time == 0 {
+            self.tracked_since.elapsed()
+        } else {
+            // If the system clock went backwards, consider the file to be brand new.
+            let age = TableProperties::current_time().saturating_sub(creation_time);
+            Duration::from_secs(age)
+        }
+    }
+
     #[must_use]
     pub fn total_file_size(files: &[Arc<Self>]) -> u64 {
         files.iter().fold(0, |sum, file| sum.saturating_add(file.file_size().0))
@@ -247,3 +374,57 @@ impl StartSeekCompaction {
         }
     }
 }
+
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, clippy::unwrap_used, reason = "these are tests")]
+mod tests {
+    use super::*;
+
+
+    fn properties() -> TableProperties {
+        TableProperties {
+            num_entries:   300,
+            num_deletions: 7,
+            smallest_seq:  SequenceNumber::new(5).unwrap(),
+            largest_seq:   SequenceNumber::new(1 << 40_u8).unwrap(),
+            creation_time: 1_790_000_000,
+        }
+    }
+
+    #[test]
+    fn table_properties_round_trip() {
+        let mut encoded = Vec::new();
+        properties().encode_to(&mut encoded);
+
+        assert_eq!(TableProperties::decode_from(&encoded), Some(properties()));
+    }
+
+    #[test]
+    fn table_properties_ignore_appended_fields() {
+        let mut encoded = Vec::new();
+        properties().encode_to(&mut encoded);
+        encoded.extend([1, 2, 3]);
+
+        assert_eq!(TableProperties::decode_from(&encoded), Some(properties()));
+    }
+
+    #[test]
+    fn table_properties_without_creation_time() {
+        let mut encoded = Vec::new();
+        properties().encode_to(&mut encoded);
+        // Remove the varint-encoded creation time (five bytes long).
+        encoded.truncate(encoded.len() - 5);
+
+        assert_eq!(TableProperties::decode_from(&encoded), None);
+    }
+
+    #[test]
+    fn truncated_table_properties() {
+        let mut encoded = Vec::new();
+        properties().encode_to(&mut encoded);
+        encoded.pop();
+
+        assert_eq!(TableProperties::decode_from(&encoded), None);
+    }
+}
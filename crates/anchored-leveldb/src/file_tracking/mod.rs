@@ -3,6 +3,6 @@ mod sorted_files;
 
 
 pub(crate) use self::{
-    file_metadata::{FileMetadata, SeeksRemaining, StartSeekCompaction},
+    file_metadata::{FileMetadata, SeeksRemaining, StartSeekCompaction, TableProperties},
     sorted_files::{OwnedSortedFiles, SortedFiles},
 };
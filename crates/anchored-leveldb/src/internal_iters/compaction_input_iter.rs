@@ -78,8 +78,7 @@ where
                         Self::read_opts(db_state),
                         decoders,
                         manifest_number,
-                        file.file_number(),
-                        file.file_size(),
+                        file,
                     )?;
 
                     let sstable_iter = TableIter::new(&sstable);
@@ -92,6 +91,11 @@ where
             }
         }
 
+        for &(_, middle_files) in &start_compaction.middle_inputs {
+            let iter = DisjointCompactionInputIter::new(middle_files, manifest_number);
+            iters.push(CompactionIterToMerge::Multiple(iter));
+        }
+
         let parent_iter = DisjointCompactionInputIter::new(
             start_compaction.parent_inputs,
             manifest_number,
@@ -357,8 +361,7 @@ impl<'a, File, Policy, Pool: BufferPool> DisjointCompactionInputIter<'a, File, P
                 CompactionInputs::read_opts(db_state),
                 decoders,
                 self.manifest_number,
-                next_file.file_number(),
-                next_file.file_size(),
+                next_file,
             )?;
 
             self.sstable_iter.set(&sstable);
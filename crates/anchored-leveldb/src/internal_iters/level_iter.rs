@@ -168,8 +168,7 @@ macro_rules! set_table {
                 $self.read_opts,
                 $self.decoders,
                 $self.iter.manifest_number,
-                $sstable.file_number(),
-                $sstable.file_size(),
+                $sstable,
             )?;
             $self.iter.sstable_iter.set(&sstable);
             $self.iter.sstable.insert(sstable)
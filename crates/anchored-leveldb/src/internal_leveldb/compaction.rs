@@ -17,14 +17,15 @@ use crate::{
 use crate::{
     all_errors::{
         aliases::{RwErrorAlias, RwErrorKindAlias},
-        types::{RwErrorKind, WriteError},
+        types::{OptionsError, RwErrorKind, WriteError},
     },
+    options::{DynamicOptions, InternalCompactionOptions},
     pub_traits::{
         cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
         compression::CompressionCodecs,
         pool::BufferPool,
     },
-    pub_typed_bytes::{CloseStatus, FileNumber, FlushWrites, NonZeroLevel},
+    pub_typed_bytes::{CloseStatus, FileNumber, FlushWrites, Level, NonZeroLevel},
     typed_bytes::{
         ContinueBackgroundCompaction, InternalKey, InternalKeyTag, OwnedInternalKey, UserKey,
     },
@@ -118,7 +119,7 @@ where
     ///
     /// `is_done` should return `true` only if the waited-for compaction has successfully completed.
     /// (It is permitted to spuriously return `true`.)
-    fn wait_for_some_compaction<'a, F>(
+    pub(super) fn wait_for_some_compaction<'a, F>(
         &'a self,
         mut mut_state: MutexGuard<'a, SharedMutableState<FS, Cmp, Policy, Codecs, Pool>>,
         mut is_done:   F,
@@ -146,7 +147,7 @@ where
     }
 
     /// When this function returns, there is no ongoing uninterrupted memtable compaction.
-    fn wait_for_memtable_compaction<'a>(
+    pub(super) fn wait_for_memtable_compaction<'a>(
         &'a self,
         mut_state: MutexGuard<'a, SharedMutableState<FS, Cmp, Policy, Codecs, Pool>>,
     ) -> MutexGuard<'a, SharedMutableState<FS, Cmp, Policy, Codecs, Pool>> {
@@ -202,6 +203,17 @@ where
         self.wait_for_some_compaction(mut_state, |_state| false)
     }
 
+    /// The compaction options, except that the size compaction targets which can be dynamically
+    /// changed are taken from the dynamic options.
+    #[must_use]
+    fn compaction_opts(&self) -> InternalCompactionOptions {
+        let dynamic = self.mut_opts.dynamic.read();
+        let mut compaction_opts = self.opts.compaction;
+        compaction_opts.size_compactions.max_level0_files = dynamic.max_level0_files;
+        compaction_opts.size_compactions.max_level_sizes = dynamic.max_level_sizes;
+        compaction_opts
+    }
+
     #[must_use]
     const fn should_start_any_compaction(
         &self,
@@ -253,6 +265,28 @@ where
         }
     }
 
+    /// Validate and apply new dynamic options.
+    ///
+    /// Since the size compaction targets may have changed, the compactions desired by the
+    /// current version are recomputed, and writers which are halted are woken to check the new
+    /// write throttling triggers.
+    pub fn set_dynamic_options(
+        &self,
+        decoders: &mut Codecs::Decoders,
+        options:  DynamicOptions,
+    ) -> Result<(), OptionsError> {
+        options.validate(&self.opts.codec_names)?;
+        self.mut_opts.dynamic.set_all(options);
+
+        let mut mut_state = self.lock_mutable_state();
+        mut_state.version_set.refresh_compactions(&self.compaction_opts());
+        let mut_state = self.maybe_start_compaction(mut_state, decoders);
+        drop(mut_state);
+
+        self.compaction_finished.notify_all();
+        Ok(())
+    }
+
     /// Maybe start a new compaction. This function performs all necessary checks.
     pub fn maybe_start_compaction<'a>(
         &'a self,
@@ -469,13 +503,54 @@ where
                 level,
                 lower,
                 upper,
+                None,
             );
         }
 
         Ok(mut_state)
     }
 
+    /// Compact exactly the indicated table files (along with any overlapping files in
+    /// `output_level`) into `output_level`.
+    #[expect(clippy::type_complexity, reason = "the number of generics is unavoidable")]
+    pub fn files_compaction<'a>(
+        &'a self,
+        decoders:     &mut Codecs::Decoders,
+        file_numbers: &[FileNumber],
+        output_level: NonZeroLevel,
+    ) -> Result<
+        MutexGuard<'a, SharedMutableState<FS, Cmp, Policy, Codecs, Pool>>,
+        RwErrorAlias<FS, Cmp, Codecs>,
+    > {
+        // Wait for any other manual compaction first, since it may remove the indicated files.
+        let mut mut_state = self.lock_mutable_state();
+        mut_state = self.wait_for_any_manual_compaction(mut_state);
+
+        if let Err(compact_files_err) = StartCompaction::new_files_compaction(
+            &self.opts,
+            mut_state.version_set.current(),
+            file_numbers,
+            output_level,
+        ) {
+            return Err(self.rw_error(RwErrorKind::Write(
+                WriteError::InvalidCompactFiles(compact_files_err),
+            )));
+        }
+
+        Ok(self.manual_compaction(
+            mut_state,
+            decoders,
+            output_level,
+            None,
+            None,
+            Some(file_numbers),
+        ))
+    }
+
     /// Start a manual compaction (if more compactions can be started).
+    ///
+    /// If `files` is `Some`, exactly those table files are compacted into `dst_level`, and the
+    /// bounds are ignored.
     fn manual_compaction<'a>(
         &'a self,
         mut mut_state: MutexGuard<'a, SharedMutableState<FS, Cmp, Policy, Codecs, Pool>>,
@@ -483,6 +558,7 @@ where
         dst_level:     NonZeroLevel,
         lower_bound:   Option<InternalKey<'_>>,
         upper_bound:   Option<InternalKey<'_>>,
+        files:         Option<&[FileNumber]>,
     ) -> MutexGuard<'a, SharedMutableState<FS, Cmp, Policy, Codecs, Pool>> {
         // Wait for there to be no ongoing manual compaction, and then set the new manual
         // compaction (even if there is a different ongoing compaction).
@@ -499,6 +575,7 @@ where
 
         OwnedInternalKey::set_optional(&mut manual_compaction.lower_bound, lower_bound);
         OwnedInternalKey::set_optional(&mut manual_compaction.upper_bound, upper_bound);
+        manual_compaction.files = files.map(<[FileNumber]>::to_vec);
         manual_compaction.level = Some(dst_level);
 
         let counter = mut_state.compaction_state.manual_compaction_counter.wrapping_add(1);
@@ -576,6 +653,20 @@ where
             let version = mut_state.version_set.cloned_current_version();
             compaction_edit.clear();
 
+            let fifo_deletions = mut_state.version_set.current()
+                .fifo_deletions(&self.opts.compaction);
+            if !fifo_deletions.is_empty() {
+                compaction_edit.deleted_files.extend(
+                    fifo_deletions.into_iter().map(|file_number| (Level::ZERO, file_number)),
+                );
+
+                // Correctness: We are the only compactor, so there is no risk of contention
+                // causing `self.apply_log_install(..)` to panic.
+                mut_state = self.apply_log_install(mut_state, &mut compaction_edit);
+                mut_state = self.garbage_collect_files(mut_state);
+                continue;
+            }
+
             let Some(compaction) = self.choose_compaction(
                 &mut mut_state,
                 &version,
@@ -593,7 +684,7 @@ where
             if !compaction.is_manual {
                 if let Some(sole_input) = compaction.trivial_move() {
                     compaction_edit.deleted_files.insert((
-                        compaction.parent_level.prev_level(),
+                        compaction.base_level,
                         sole_input.file_number(),
                     ));
                     compaction_edit.added_files.push((
@@ -715,9 +806,11 @@ where
         }
 
         // Since we're compacting a memtable / write-ahead log, we can discard
-        // all previous write-ahead logs.
+        // all previous write-ahead logs. The memtable was switched out when writes moved to
+        // the current write-ahead log, and no other memtable can be switched out until this one
+        // has been flushed.
         edit.prev_log_number = Some(FileNumber(0));
-        edit.log_number      = Some(mut_state.version_set.current_log_number());
+        edit.log_number      = Some(mut_state.write_log_number);
 
         // Correctness: the caller is warned not to contend this.
         mut_state = self.apply_log_install(mut_state, &mut edit);
@@ -750,7 +843,27 @@ where
         let (size_compaction, seek_compaction) = version_set.current()
             .compactions(&self.opts.compaction);
 
-        if let Some(manual_level) = manual_compaction.level {
+        if let (Some(manual_level), Some(files)) = (
+            manual_compaction.level,
+            &mut manual_compaction.files,
+        ) {
+            // The files are compacted all at once, so the manual compaction is finished the next
+            // time that a compaction is chosen. The files are revalidated, since the current
+            // version may have changed since the manual compaction was set.
+            let compaction = if files.is_empty() {
+                None
+            } else {
+                StartCompaction::new_files_compaction(&self.opts, version, files, manual_level)
+                    .ok()
+            };
+            files.clear();
+
+            if compaction.is_some() {
+                return compaction;
+            }
+            manual_compaction.level = None;
+            manual_compaction.files = None;
+        } else if let Some(manual_level) = manual_compaction.level {
             if let Some(compaction) = StartCompaction::new_manual_compaction(
                 &self.opts,
                 version_set,
@@ -776,13 +889,25 @@ where
             }
         }
 
-        if let Some(parent_level) = size_compaction {
+        if let Some(tiered_compaction) = version_set.current()
+            .tiered_compaction(&self.opts.compaction)
+        {
+            if let Some(compaction) = StartCompaction::new_tiered_compaction(
+                &self.opts,
+                version,
+                tiered_compaction,
+            ) {
+                return Some(compaction);
+            }
+        }
+
+        if let Some(size_compaction) = size_compaction {
             if let Some(compaction) = StartCompaction::new_size_compaction(
                 &self.opts,
                 version_set,
                 compaction_edit,
                 version,
-                parent_level,
+                size_compaction,
             ) {
                 return Some(compaction);
             }
@@ -798,6 +923,20 @@ where
             ));
         }
 
+        // Deletion-triggered and periodic compactions of a single file are set up in the same
+        // way as seek compactions.
+        if let Some(start_file_compaction) = version_set.current()
+            .file_compaction(&self.opts.compaction)
+        {
+            return Some(StartCompaction::new_seek_compaction(
+                &self.opts,
+                version_set,
+                compaction_edit,
+                version,
+                start_file_compaction,
+            ));
+        }
+
         None
     }
 
@@ -851,6 +990,15 @@ where
                     }
                 };
 
+                if self.opts.compaction.rate_limit_reads {
+                    // Approximate the bytes read with the entry's uncompressed size, including
+                    // its 8-byte tag.
+                    let key_len = u64::from(u32::from(next_input.0.0.len()));
+                    let value_len = u64::from(u32::from(next_input.1.0.len()));
+                    let entry_len = key_len.saturating_add(value_len).saturating_add(8);
+                    self.mut_opts.rate_limiter.request(entry_len);
+                }
+
                 if self.compactor_should_lock.load(Ordering::Relaxed) {
                     if let Some(relocked) = self.nontrivial_compaction_interruption(
                         encoders,
@@ -863,7 +1011,11 @@ where
                 }
 
                 #[expect(clippy::collapsible_if, reason = "incomplete")]
-                if compaction.should_stop_before(&self.opts.cmp, next_input.0) {
+                if compaction.should_stop_before(
+                    &self.opts.cmp,
+                    self.opts.compaction_partitioner.as_deref(),
+                    next_input.0,
+                ) {
                     if table_builder.active() {
                         // table_builder.finish(
                         //     opts,
@@ -1003,7 +1155,7 @@ where
             mut_state = self.lock_mutable_state();
         };
 
-        mut_state.version_set.install(install_token, self.opts.compaction.size_compactions);
+        mut_state.version_set.install(install_token, &self.compaction_opts());
         mut_state
     }
 
@@ -1047,7 +1199,7 @@ where
             match parsed_name {
                 LevelDBFileName::Log { file_number } => {
                     if file_number == mut_state.version_set.prev_log_number()
-                        && file_number >= mut_state.version_set.current_log_number()
+                        || file_number >= mut_state.version_set.current_log_number()
                     {
                         // Keep this write-ahead log
                         continue;
@@ -21,8 +21,10 @@ use anchored_vfs::{
 use crate::{
     internal_logger::InternalLogger,
     memtable::UniqueMemtable,
+    rate_limiter::RateLimiter,
     snapshot::SnapshotList,
-    table_file::TableFileBuilder,
+    sstable::ParallelCompression,
+    table_file::{TableFileBuilder, read_sstable},
     typed_bytes::ContinueReadingLogs,
 };
 use crate::{
@@ -35,12 +37,12 @@ use crate::{
         },
     },
     binary_block_log::{BinaryBlockLogReaderBuffers, LogRecordResult, Slices, WriteLogWriter},
-    contention_queue::{ContentionQueue, PanicOptions},
+    contention_queue::{ContentionQueue, PanicOptions, SpinOptions},
     database_files::{LevelDBFileName, set_current},
     options::{
         AtomicDynamicOptions, DynamicOptions, InternalCompactionOptions, InternallyMutableOptions,
-        InternalOpenOptions, InternalOptions,
-        pub_options::{ClampOptions, OpenOptions},
+        InternalOpenOptions, InternalOptions, InternalReadOptions, usable_compression_dictionary,
+        pub_options::{CacheUsage, ClampOptions, CompactionStyle, OpenOptions},
     },
     pub_traits::{
         cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
@@ -50,8 +52,8 @@ use crate::{
         pool::BufferPool,
     },
     pub_typed_bytes::{
-        BinaryLogBlockSize, CloseStatus, FileNumber, FileOffset, FileSize, LogicalRecordOffset,
-        SequenceNumber, ShortSlice,
+        BinaryLogBlockSize, CloseStatus, FileNumber, FileOffset, FileSize, Level,
+        LogicalRecordOffset, SequenceNumber, ShortSlice,
     },
     table_caches::{BlockCache, TableCache},
     table_format::{InternalComparator, InternalFilterPolicy},
@@ -60,7 +62,7 @@ use crate::{
 };
 use super::state::{
     BackgroundCompactor, CompactionState, ForegroundCompactor, FrontWriterState, InternalDBState,
-    ManualCompaction, PerHandleState, SharedMutableState,
+    ManualCompaction, PerHandleState, SharedMutableState, WriteStallCounters,
 };
 
 
@@ -114,7 +116,7 @@ where
     FS:     LevelDBFilesystem,
     Cmp:    LevelDBComparator + FastMirroredClone,
     Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone + Send,
-    Codecs: CompressionCodecs,
+    Codecs: CompressionCodecs + 'static,
     Pool:   BufferPool,
     // TODO: Loosen `Send + Sync` requirements
     Self:               Send + Sync + 'static,
@@ -151,7 +153,14 @@ where
             }),
         };
 
-        let mut builder = DBBuilder::new(options, begin_open);
+        // Compressing on other threads requires `Codecs::Encoders: Send`, which `DBBuilder`
+        // does not know about.
+        let parallel_compression = options.compression.compression_threads
+            .and_then(|num_threads| {
+                ParallelCompression::new(options.format.compression_codecs(), num_threads)
+            });
+
+        let mut builder = DBBuilder::new(options, begin_open, parallel_compression);
 
         let recovered = match builder.recover() {
             Ok(recovered) => recovered,
@@ -184,7 +193,7 @@ where
     /// - Execute `clamp_options`.
     /// - Optionally create a `LOG` file.
     #[expect(clippy::type_complexity, reason = "only complex because of generics, but very flat")]
-    fn begin_open(
+    pub(super) fn begin_open(
         options: &mut OpenOptions<FS, Cmp, Policy, Codecs, Pool>,
     ) -> Result<BeginOpen<FS::Lockfile, FS::WriteFile>, RecoveryErrorKindAlias<FS, Cmp, Codecs>> {
         let lock_path = LevelDBFileName::Lockfile.file_path(&options.database_directory);
@@ -572,6 +581,7 @@ where
                 level:       None,
                 lower_bound: None,
                 upper_bound: None,
+                files:       None,
             },
             manual_compaction_counter: 0,
         };
@@ -583,6 +593,7 @@ where
             non_compactor_arc_refcounts:  1,
             write_status:                 Ok(()),
             close_status:                 CloseStatus::Open,
+            write_log_number:             version_set.current_log_number(),
             version_set,
             current_memtable,
             iter_read_sample_seed:        0,
@@ -598,6 +609,10 @@ where
                 unwrap_mutex_poison: opts.unwrap_poison,
                 unwrap_queue_poison: opts.unwrap_poison,
             },
+            SpinOptions {
+                max_spins: opts.write_throttling.write_queue_spins,
+                max_yield: opts.write_throttling.write_queue_max_yield,
+            },
         );
 
         let this = Arc::new(Self {
@@ -610,6 +625,7 @@ where
             background_compactor:  background,
             contention_queue,
             snapshot_list:         SnapshotList::new(),
+            write_stalls:          WriteStallCounters::default(),
         });
 
         let per_handle = PerHandleState {
@@ -629,30 +645,30 @@ where
 }
 
 // TODO: implement Debug
-struct DBBuilder<FS, Cmp, Policy, Codecs, Pool>
+pub(super) struct DBBuilder<FS, Cmp, Policy, Codecs, Pool>
 where
     FS:     LevelDBFilesystem,
     Cmp:    LevelDBComparator,
     Codecs: CompressionCodecs,
     Pool:   BufferPool,
 {
-    opts:                    InternalOptions<Cmp, Policy, Codecs>,
-    mut_opts:                InternallyMutableOptions<FS, Policy, Pool>,
-    open_corruption_handler: Box<dyn OpenCorruptionHandler<Cmp::InvalidKeyError> + Send + Sync>,
-    open_opts:               InternalOpenOptions,
-    lockfile:                FS::Lockfile,
-    current_path:            PathBuf,
+    pub opts:                    InternalOptions<Cmp, Policy, Codecs>,
+    pub mut_opts:                InternallyMutableOptions<FS, Policy, Pool>,
+    pub open_corruption_handler: Box<dyn OpenCorruptionHandler<Cmp::InvalidKeyError> + Send + Sync>,
+    pub open_opts:               InternalOpenOptions,
+    pub lockfile:                FS::Lockfile,
+    pub current_path:            PathBuf,
     /// Only guaranteed to be accurate on successful recovery.
-    manifest_file_number:    FileNumber,
-    memtable:                UniqueMemtable<Cmp>,
+    pub manifest_file_number:    FileNumber,
+    pub memtable:                UniqueMemtable<Cmp>,
     /// This is filled only on the last cal to `self.recover_log_file(..)`, if ever.
     ///
     /// Also, `self.memtable` is only ever written to inside `self.recover_log_file(..)`, and
     /// it is reset if and only if the log is *not* reused.
-    reused_log:              Option<ReusedLog<FS::WriteFile>>,
-    table_builder:           TableFileBuilder<FS::WriteFile, Policy, Pool>,
-    encoders:                Codecs::Encoders,
-    decoders:                Codecs::Decoders,
+    pub reused_log:              Option<ReusedLog<FS::WriteFile>>,
+    pub table_builder:           TableFileBuilder<FS::WriteFile, Policy, Pool>,
+    pub encoders:                Codecs::Encoders,
+    pub decoders:                Codecs::Decoders,
 }
 
 impl<FS, Cmp, Policy, Codecs, Pool> DBBuilder<FS, Cmp, Policy, Codecs, Pool>
@@ -667,9 +683,10 @@ where
     /// recovery of an existing database.
     ///
     /// `clamp_options` should already have been executed.
-    pub(self) fn new(
-        options:    OpenOptions<FS, Cmp, Policy, Codecs, Pool>,
-        begin_open: BeginOpen<FS::Lockfile, FS::WriteFile>,
+    pub(super) fn new(
+        options:              OpenOptions<FS, Cmp, Policy, Codecs, Pool>,
+        begin_open:           BeginOpen<FS::Lockfile, FS::WriteFile>,
+        parallel_compression: Option<ParallelCompression<Codecs>>,
     ) -> Self {
         let BeginOpen {
             lockfile,
@@ -677,21 +694,49 @@ where
             current: current_path,
         } = begin_open;
 
+        // FIFO compactions only ever delete level-0 files, so memtables must not be flushed to
+        // other levels. With dynamic level sizes, the levels before the base level should be
+        // empty, and memtables should not be flushed past the base level; flushing to level 0
+        // is simplest.
+        let max_level_for_memtable_flush = match options.compaction.style {
+            CompactionStyle::Fifo(_) => Level::ZERO,
+            CompactionStyle::Leveled if options.size_compaction.dynamic_level_sizes
+                => Level::ZERO,
+            CompactionStyle::Leveled | CompactionStyle::Tiered(_)
+                => options.compaction.max_level_for_memtable_flush,
+        };
+
         let compaction = InternalCompactionOptions {
-            max_level_for_memtable_flush: options.compaction.max_level_for_memtable_flush,
+            max_level_for_memtable_flush,
             max_compaction_inputs:        options.compaction.max_compaction_inputs,
             max_grandparent_overlap:      options.compaction.max_grandparent_overlap,
             size_compactions:             options.size_compaction,
             seek_compactions:             options.seek_compaction,
+            style:                        options.compaction.style,
+            max_deletion_ratio:           options.compaction.max_deletion_ratio,
+            periodic_compaction_age:      options.compaction.periodic_compaction_age,
+            rate_limit_reads:             options.compaction.rate_limit_reads,
         };
 
         let (cmp, codecs, binary_log_block_size) = options.format.into_pieces();
+        let codec_names = codecs.codec_names();
+        let compression_dictionary = options.sstable.compression_dictionary
+            .map(|mut dictionary_opts| {
+                dictionary_opts.max_sample_bytes = dictionary_opts.max_sample_bytes
+                    .min(1 << 30_u8);
+                dictionary_opts
+            });
+        let compression_dictionary = usable_compression_dictionary(
+            compression_dictionary,
+            &codec_names,
+        );
         let opts = InternalOptions {
             db_directory:               options.database_directory,
             cmp:                        InternalComparator(cmp),
             policy:                     options.filter.filter_policy.map(InternalFilterPolicy),
             filter_chunk_size_log2:     options.filter.filter_chunk_size_log2,
             codecs,
+            codec_names,
             binary_log_block_size,
             verify_data_checksums:      options.consistency.verify_data_checksums,
             verify_index_checksums:     options.consistency.verify_index_checksums,
@@ -699,19 +744,35 @@ where
             web_scale:                  options.consistency.web_scale,
             max_memtable_size:          options.memtable.max_memtable_size,
             max_write_log_file_size:    options.memtable.max_write_log_file_size,
+            write_log_bytes_per_sync:   options.memtable.write_log_bytes_per_sync,
             max_sstable_sizes:          options.sstable.max_sstable_sizes,
+            sstable_bytes_per_sync:     options.sstable.bytes_per_sync,
+            compression_dictionary,
+            adaptive_compression:       options.compression.adaptive,
+            parallel_compression,
             compaction,
+            compaction_partitioner:     options.compaction.partitioner,
             write_throttling:           options.write_throttling,
             iter_buffer_capacity_limit: options.buffer_pool.iter_buffer_capacity_limit,
         };
 
+        let throttling = options.write_throttling;
         let dynamic = AtomicDynamicOptions::new(DynamicOptions {
-            memtable_compressor:            options.compression.memtable_compressor,
-            table_compressors:              options.compression.table_compressors,
-            memtable_compression_goal:      options.compression.memtable_compression_goal,
-            table_compression_goals:        options.compression.table_compression_goals,
-            sstable_block_size:             options.sstable.sstable_block_size,
-            sstable_block_restart_interval: options.sstable.block_restart_interval,
+            memtable_compressor:                       options.compression.memtable_compressor,
+            table_compressors:                         options.compression.table_compressors,
+            memtable_compression_goal:
+                options.compression.memtable_compression_goal,
+            table_compression_goals:                   options.compression.table_compression_goals,
+            sstable_block_size:                        options.sstable.sstable_block_size,
+            sstable_block_restart_interval:            options.sstable.block_restart_interval,
+            level0_write_throttle_trigger:             throttling.level0_write_throttle_trigger,
+            level0_write_halt_trigger:                 throttling.level0_write_halt_trigger,
+            pending_compaction_bytes_throttle_trigger:
+                throttling.pending_compaction_bytes_throttle_trigger,
+            pending_compaction_bytes_halt_trigger:
+                throttling.pending_compaction_bytes_halt_trigger,
+            max_level0_files:                          options.size_compaction.max_level0_files,
+            max_level_sizes:                           options.size_compaction.max_level_sizes,
         });
 
         let block_cache = BlockCache::new(
@@ -727,6 +788,10 @@ where
             buffer_pool: options.buffer_pool.buffer_pool,
             block_cache,
             table_cache,
+            rate_limiter: RateLimiter::new(
+                options.compaction.rate_limit,
+                options.consistency.unwrap_poison,
+            ),
         };
 
         let open_corruption_handler = options.consistency.open_corruption_handler;
@@ -850,7 +915,7 @@ where
     /// present and determine which `.log` files to recover.
     ///
     /// Returns an unsorted list of all `.log` files which should be recovered.
-    fn enumerate_files(
+    pub(super) fn enumerate_files(
         &self,
         vset_builder: &mut VersionSetBuilder<FS::WriteFile, false>,
     ) -> Result<
@@ -1159,11 +1224,55 @@ where
             log_number,
         )?;
 
+        // The table properties of the recovered table files are loaded as the files are read.
+        if !self.opts.codec_names.is_empty() {
+            self.check_newest_table(&version_set)?;
+        }
+
         Ok(RecoveredDB {
             version_set,
             current_write_log: log,
         })
     }
+
+    /// Open the most recently written table file in the current version (if any), so that
+    /// a mismatch between the chosen compression codecs and the codec names recorded in
+    /// table files is reported when the database is opened.
+    ///
+    /// Other table files are likewise checked whenever they are opened.
+    fn check_newest_table(
+        &mut self,
+        version_set: &VersionSet<FS::WriteFile>,
+    ) -> Result<(), RecoveryErrorKindAlias<FS, Cmp, Codecs>> {
+        let newest_table = Level::ALL_LEVELS
+            .into_iter()
+            .flat_map(move |level| version_set.current().level_files(level).inner())
+            .max_by_key(|file| file.file_number());
+
+        let Some(newest_table) = newest_table else {
+            return Ok(());
+        };
+
+        let read_opts = InternalReadOptions {
+            verify_data_checksums:  self.opts.verify_data_checksums,
+            verify_index_checksums: self.opts.verify_index_checksums,
+            // `read_sstable` only uses the table cache, so this setting is irrelevant.
+            block_cache_usage:      CacheUsage::ReadAndFill,
+            // The opened tables are likely to be read again soon.
+            table_cache_usage:      CacheUsage::ReadAndFill,
+        };
+
+        let _table = read_sstable::<FS, Cmp, Policy, Codecs, Pool>(
+            &self.opts,
+            &self.mut_opts,
+            read_opts,
+            &mut self.decoders,
+            version_set.manifest_file_number(),
+            newest_table,
+        ).map_err(RwErrorKind::into_recovery_err)?;
+
+        Ok(())
+    }
 }
 
 fn parse_write_batch(
@@ -1205,7 +1314,7 @@ fn parse_write_batch(
 
 /// Returned by [`InternalDBState::begin_open`].
 #[derive(Debug)]
-struct BeginOpen<Lockfile, WriteFile> {
+pub(super) struct BeginOpen<Lockfile, WriteFile> {
     lockfile: Lockfile,
     logger:   InternalLogger<WriteFile>,
     current:  PathBuf,
@@ -1221,7 +1330,7 @@ struct DefinitelyExistsOrNot<Lockfile> {
 }
 
 /// A reused write-ahead `.log` file.
-struct ReusedLog<File> {
+pub(super) struct ReusedLog<File> {
     log:        WriteLogWriter<File>,
     log_number: FileNumber,
 }
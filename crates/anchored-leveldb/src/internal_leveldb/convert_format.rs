@@ -0,0 +1,522 @@
+use std::path::Path;
+
+use clone_behavior::FastMirroredClone;
+
+use anchored_vfs::LevelDBFilesystem;
+
+use crate::{
+    database_files::LevelDBFileName,
+    file_tracking::FileMetadata,
+    pub_leveldb::ConvertFormatProgress,
+    sstable::{ParallelCompression, TableIter},
+    table_file::{TableFileBuilder, read_sstable},
+    typed_bytes::EncodedInternalKey,
+};
+use crate::{
+    all_errors::{
+        aliases::{
+            ConvertFormatErrorKindAlias, ConvertFormatResult, RecoveryErrorKindAlias,
+            RwErrorKindAlias,
+        },
+        types::{
+            AddTableEntryError, ConvertFormatError, ConvertFormatErrorKind, CorruptionError,
+            FinishError, RecoveryErrorKind,
+        },
+    },
+    options::{
+        DynamicOptions, InternallyMutableOptions, InternalOptions, InternalReadOptions,
+        pub_options::{CacheUsage, CompressionOptions, OpenOptions},
+    },
+    pub_traits::{
+        cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
+        compression::CompressionCodecs,
+        error_handler::OpenCorruptionHandler,
+        pool::BufferPool,
+    },
+    pub_typed_bytes::{FileNumber, Level},
+    version::{BeginVersionSetRecovery, VersionSetBuilder},
+};
+use super::{construct::DBBuilder, state::InternalDBState};
+
+
+#[expect(unreachable_pub, reason = "control visibility at type definition")]
+impl<FS, Cmp, Policy, Codecs, Pool> InternalDBState<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator + FastMirroredClone,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone + Send,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+    // TODO: Loosen `Send + Sync` requirements
+    Self:               Send + Sync + 'static,
+    FS::WriteFile:      Send,
+    Codecs::Encoders:   Send,
+    Codecs::Decoders:   Send,
+    Pool::PooledBuffer: Send,
+{
+    /// Rewrite every table file of an existing database with `new_codecs`, and write a new
+    /// `MANIFEST` file which refers to the rewritten table files.
+    ///
+    /// The database is never opened for reading or writing; the `MANIFEST` file is recovered,
+    /// but `.log` files are left to be recovered by the next database invocation.
+    ///
+    /// See [`convert_db_format`] for more.
+    ///
+    /// [`convert_db_format`]: crate::pub_leveldb::convert_db_format
+    pub fn convert_format<NewCodecs, Progress>(
+        mut options:     OpenOptions<FS, Cmp, Policy, Codecs, Pool>,
+        new_codecs:      NewCodecs,
+        new_compression: CompressionOptions,
+        progress:        Progress,
+    ) -> ConvertFormatResult<(), FS, Cmp, Codecs, NewCodecs>
+    where
+        NewCodecs:           CompressionCodecs + 'static,
+        NewCodecs::Encoders: Send,
+        Progress:            FnMut(ConvertFormatProgress),
+    {
+        // Only an existing database can be converted.
+        options.create_if_missing = false;
+        options.error_if_exists = false;
+
+        let begin_open = match Self::begin_open(&mut options) {
+            Ok(begin_open) => begin_open,
+            Err(kind)       => return Err(ConvertFormatError {
+                db_directory: options.database_directory,
+                kind:         ConvertFormatErrorKind::Source(kind),
+            }),
+        };
+
+        // The old table files are only read, so they do not need `ParallelCompression`.
+        let builder = DBBuilder::new(options, begin_open, None);
+        let db_directory = builder.opts.db_directory.clone();
+        let new_parallel_compression = new_compression.compression_threads
+            .and_then(|num_threads| ParallelCompression::new(&new_codecs, num_threads));
+
+        builder
+            .convert_tables(
+                new_codecs,
+                new_parallel_compression,
+                new_compression,
+                progress,
+            )
+            .map_err(|kind| ConvertFormatError { db_directory, kind })
+    }
+}
+
+impl<FS, Cmp, Policy, Codecs, Pool> DBBuilder<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator + FastMirroredClone,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Recover the `MANIFEST` file, convert each live table file to `new_codecs`, and then
+    /// switch the database to the converted table files by writing a new `MANIFEST` file.
+    ///
+    /// Compressing on other threads requires `NewCodecs::Encoders: Send`, which `DBBuilder`
+    /// does not know about, so `new_parallel_compression` is provided separately.
+    fn convert_tables<NewCodecs, Progress>(
+        mut self,
+        new_codecs:               NewCodecs,
+        new_parallel_compression: Option<ParallelCompression<NewCodecs>>,
+        new_compression:          CompressionOptions,
+        mut progress:             Progress,
+    ) -> Result<(), ConvertFormatErrorKindAlias<FS, Cmp, Codecs, NewCodecs>>
+    where
+        NewCodecs: CompressionCodecs,
+        Progress:  FnMut(ConvertFormatProgress),
+    {
+        let mut vset_builder = match self.recover_manifest() {
+            Ok(vset_builder) => vset_builder,
+            Err(kind) => return Err(ConvertFormatErrorKind::Source(merge_handler_error(
+                kind,
+                self.open_corruption_handler,
+                self.manifest_file_number,
+            ))),
+        };
+
+        let verify_new_version = match self.open_corruption_handler.finished_all_logs() {
+            Ok(finished) => finished.verify_new_version,
+            Err(FinishError {}) => return Err(ConvertFormatErrorKind::Source(merge_handler_error(
+                RecoveryErrorKind::Corruption(CorruptionError::HandlerReportedError),
+                self.open_corruption_handler,
+                self.manifest_file_number,
+            ))),
+        };
+
+        let Self { opts, mut_opts, manifest_file_number, lockfile, decoders, .. } = self;
+
+        let mut new_opts = opts.with_codecs(new_codecs, new_parallel_compression);
+        new_opts.adaptive_compression = new_compression.adaptive;
+
+        // Reading table files does not depend on the dynamic options, so the same
+        // `InternallyMutableOptions` can be used to read the old table files and write new ones.
+        mut_opts.dynamic.set_all(DynamicOptions {
+            memtable_compressor:       new_compression.memtable_compressor,
+            table_compressors:         new_compression.table_compressors,
+            memtable_compression_goal: new_compression.memtable_compression_goal,
+            table_compression_goals:   new_compression.table_compression_goals,
+            ..mut_opts.dynamic.read()
+        });
+
+        let mut converter = TableConverter {
+            opts:            &opts,
+            new_opts:        &new_opts,
+            mut_opts:        &mut_opts,
+            manifest_number: manifest_file_number,
+            decoders,
+            new_encoders:    new_opts.codecs.init_encoders(),
+            new_decoders:    new_opts.codecs.init_decoders(),
+            table_builder:   TableFileBuilder::new(&new_opts),
+            table_number:    None,
+            first_key:       Vec::new(),
+            last_key:        Vec::new(),
+        };
+
+        let mut old_tables = vset_builder.live_table_files();
+        // Allocate the new file numbers in the same order as the old file numbers.
+        old_tables.sort_unstable_by_key(|(_, old_table)| old_table.file_number());
+
+        let mut report = ConvertFormatProgress {
+            converted_tables: 0,
+            total_tables:     old_tables.len(),
+            bytes_read:       0,
+            bytes_written:    0,
+        };
+        let mut replaced_tables = Vec::with_capacity(old_tables.len());
+        let mut old_table_numbers = Vec::with_capacity(old_tables.len());
+        let mut new_table_numbers = Vec::new();
+
+        for (level, old_table) in old_tables {
+            let mut new_tables = Vec::new();
+            let converted = converter.convert_table(
+                &mut vset_builder,
+                level,
+                &old_table,
+                &mut new_tables,
+            );
+            new_table_numbers.extend(new_tables.iter().map(FileMetadata::file_number));
+
+            if let Err(kind) = converted {
+                remove_tables(&mut_opts.filesystem, &opts.db_directory, new_table_numbers);
+                return Err(kind);
+            }
+
+            report.converted_tables += 1;
+            report.bytes_read += old_table.file_size().0;
+            report.bytes_written += new_tables.iter()
+                .map(|new_table| new_table.file_size().0)
+                .sum::<u64>();
+            progress(report);
+
+            old_table_numbers.push(old_table.file_number());
+            replaced_tables.push((level, old_table.file_number(), new_tables));
+        }
+
+        let version_set = match vset_builder.finish_replacing_tables(
+            &new_opts,
+            &mut_opts,
+            verify_new_version,
+            replaced_tables,
+        ) {
+            Ok(version_set) => version_set,
+            Err(kind) => {
+                remove_tables(&mut_opts.filesystem, &opts.db_directory, new_table_numbers);
+                return Err(ConvertFormatErrorKind::Target(kind));
+            }
+        };
+        drop(version_set);
+
+        // The new `MANIFEST` is in place, so the old table files and old `MANIFEST` are no
+        // longer needed.
+        remove_tables(&mut_opts.filesystem, &opts.db_directory, old_table_numbers);
+        let old_manifest_path = LevelDBFileName::Manifest { file_number: manifest_file_number }
+            .file_path(&opts.db_directory);
+        let _err = mut_opts.filesystem.remove_file(&old_manifest_path);
+
+        drop(lockfile);
+        Ok(())
+    }
+
+    /// Recover the current `MANIFEST` file, and make sure that all expected table files are
+    /// present.
+    ///
+    /// Existing `.log` files are not recovered, though their file numbers are marked as used.
+    ///
+    /// On success, `self.manifest_number` is set to the `MANIFEST` file's file number.
+    fn recover_manifest(&mut self) -> Result<
+        VersionSetBuilder<FS::WriteFile, true>,
+        RecoveryErrorKindAlias<FS, Cmp, Codecs>,
+    > {
+        let BeginVersionSetRecovery {
+            builder: mut vset_builder,
+            ..
+        } = VersionSetBuilder::begin_recovery(
+            &self.opts,
+            &self.mut_opts,
+            &mut *self.open_corruption_handler,
+            self.open_opts,
+            &self.current_path,
+            &mut self.manifest_file_number,
+        )?;
+
+        // The returned `.log` files are left for the next database invocation to recover.
+        self.enumerate_files(&mut vset_builder)?;
+
+        Ok(vset_builder.finish_listing_old_logs())
+    }
+}
+
+/// The state used to rewrite old table files with new compression codecs.
+struct TableConverter<'a, FS, Cmp, Policy, Codecs, NewCodecs, Pool>
+where
+    FS:        LevelDBFilesystem,
+    Codecs:    CompressionCodecs,
+    NewCodecs: CompressionCodecs,
+    Pool:      BufferPool,
+{
+    opts:            &'a InternalOptions<Cmp, Policy, Codecs>,
+    new_opts:        &'a InternalOptions<Cmp, Policy, NewCodecs>,
+    mut_opts:        &'a InternallyMutableOptions<FS, Policy, Pool>,
+    manifest_number: FileNumber,
+    decoders:        Codecs::Decoders,
+    new_encoders:    NewCodecs::Encoders,
+    new_decoders:    NewCodecs::Decoders,
+    table_builder:   TableFileBuilder<FS::WriteFile, Policy, Pool>,
+    /// The file number of the new table file currently being written, if any. This is set
+    /// before the table file is created, and cleared once the table file is finished.
+    table_number:    Option<FileNumber>,
+    /// The first key added to the new table file currently being written.
+    first_key:       Vec<u8>,
+    /// The last key added to the new table file currently being written.
+    last_key:        Vec<u8>,
+}
+
+impl<FS, Cmp, Policy, Codecs, NewCodecs, Pool>
+    TableConverter<'_, FS, Cmp, Policy, Codecs, NewCodecs, Pool>
+where
+    FS:        LevelDBFilesystem,
+    Cmp:       LevelDBComparator,
+    Policy:    FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs:    CompressionCodecs,
+    NewCodecs: CompressionCodecs,
+    Pool:      BufferPool,
+{
+    /// Copy every entry of the old table file into new table files, placed into the same level.
+    ///
+    /// Usually, one new table file is enough; the entries are split into more table files
+    /// **only** when absolutely necessary (for the sake of not overfilling a table's index block).
+    ///
+    /// The new table files are pushed to `new_tables` as they are finished. On error, the table
+    /// file being written (if any) is deleted, but the caller is responsible for deleting the
+    /// table files in `new_tables`.
+    fn convert_table(
+        &mut self,
+        vset_builder: &mut VersionSetBuilder<FS::WriteFile, true>,
+        level:        Level,
+        old_table:    &FileMetadata,
+        new_tables:   &mut Vec<FileMetadata>,
+    ) -> Result<(), ConvertFormatErrorKindAlias<FS, Cmp, Codecs, NewCodecs>> {
+        let converted = self.try_convert_table(vset_builder, level, old_table, new_tables);
+        if converted.is_err() {
+            self.abandon_table();
+        }
+        converted
+    }
+
+    /// The implementation of [`Self::convert_table`], which may leave behind a partially written
+    /// table file on error.
+    fn try_convert_table(
+        &mut self,
+        vset_builder: &mut VersionSetBuilder<FS::WriteFile, true>,
+        level:        Level,
+        old_table:    &FileMetadata,
+        new_tables:   &mut Vec<FileMetadata>,
+    ) -> Result<(), ConvertFormatErrorKindAlias<FS, Cmp, Codecs, NewCodecs>> {
+        let read_opts = InternalReadOptions {
+            verify_data_checksums:  true,
+            verify_index_checksums: true,
+            // Each old table file is read exactly once.
+            block_cache_usage:      CacheUsage::Ignore,
+            table_cache_usage:      CacheUsage::Ignore,
+        };
+
+        let old_reader = read_sstable(
+            self.opts,
+            self.mut_opts,
+            read_opts,
+            &mut self.decoders,
+            self.manifest_number,
+            old_table,
+        ).map_err(source_error::<FS, Cmp, Codecs, NewCodecs>)?;
+        let mut old_iter = TableIter::new(&old_reader);
+
+        // Level-0 table files are written with the memtable compressor.
+        let nonzero_level = level.try_as_nonzero_level();
+
+        loop {
+            let entry = match old_iter.next(
+                &old_reader,
+                self.opts,
+                self.mut_opts,
+                read_opts,
+                &mut self.decoders,
+            ) {
+                Ok(Some(entry)) => entry,
+                Ok(None)        => break,
+                Err(err)        => return Err(source_error::<FS, Cmp, Codecs, NewCodecs>(err)),
+            };
+
+            // Each entry is added to a table at most twice, since adding an entry to an empty
+            // table cannot fail with `AddEntryError`.
+            loop {
+                if !self.table_builder.active() {
+                    let table_file_number = vset_builder
+                        .new_table_file_number()
+                        .map_err(|err| ConvertFormatErrorKind::Target(err.into_recovery_err()))?;
+                    self.table_number = Some(table_file_number);
+
+                    self.table_builder
+                        .start(self.new_opts, self.mut_opts, table_file_number, nonzero_level)
+                        .map_err(|err| ConvertFormatErrorKind::Target(
+                            RecoveryErrorKind::Write(err),
+                        ))?;
+
+                    self.first_key.clear();
+                    self.first_key.extend(entry.0.inner());
+                }
+
+                // Correctness: the old table file is sorted solely by internal key, and does not
+                // have any entries with duplicate keys.
+                match self.table_builder.add_entry(
+                    self.new_opts,
+                    self.mut_opts,
+                    &mut self.new_encoders,
+                    entry.0,
+                    entry.1,
+                ) {
+                    Ok(()) => break,
+                    Err(AddTableEntryError::AddEntryError) => {
+                        new_tables.push(self.finish_table()?);
+                    }
+                    Err(AddTableEntryError::Write(err)) => {
+                        return Err(target_error::<FS, Cmp, Codecs, NewCodecs>(err));
+                    }
+                }
+            }
+
+            self.last_key.clear();
+            self.last_key.extend(entry.0.inner());
+        }
+
+        // If the old table file was somehow empty, no new table file is needed.
+        if self.table_builder.active() {
+            new_tables.push(self.finish_table()?);
+        }
+
+        Ok(())
+    }
+
+    /// Finish the active table file, whose first and last keys are `self.first_key` and
+    /// `self.last_key`.
+    fn finish_table(
+        &mut self,
+    ) -> Result<FileMetadata, ConvertFormatErrorKindAlias<FS, Cmp, Codecs, NewCodecs>> {
+        // Correctness: the keys were copied from `EncodedInternalKey`s.
+        let smallest_key = EncodedInternalKey::new_unchecked(&self.first_key).as_internal_key();
+        let largest_key = EncodedInternalKey::new_unchecked(&self.last_key).as_internal_key();
+
+        let new_table = self.table_builder.finish(
+            self.new_opts,
+            self.mut_opts,
+            &mut self.new_encoders,
+            &mut self.new_decoders,
+            self.manifest_number,
+            smallest_key,
+            largest_key,
+        ).map_err(target_error::<FS, Cmp, Codecs, NewCodecs>)?;
+
+        // The caller is now responsible for the finished table file.
+        self.table_number = None;
+        Ok(new_table)
+    }
+
+    /// Delete the table file currently being written (if any), ignoring any error.
+    ///
+    /// The table file is deleted even if the table builder is no longer active, such as when
+    /// the table file was written but could not be read back.
+    fn abandon_table(&mut self) {
+        #[expect(
+            let_underscore_drop,
+            clippy::let_underscore_must_use,
+            reason = "ignore any error which occurs while handling the root error",
+        )]
+        let _: Result<_, _> = self.table_builder.deactivate(self.new_opts, self.mut_opts);
+
+        if let Some(table_number) = self.table_number.take() {
+            // This is redundant if `deactivate` deleted the table file.
+            remove_tables(&self.mut_opts.filesystem, &self.new_opts.db_directory, [table_number]);
+        }
+    }
+}
+
+/// Merge any error reported by the `OpenCorruptionHandler` into `kind`.
+fn merge_handler_error<FS, Cmp, Codecs>(
+    mut kind:             RecoveryErrorKindAlias<FS, Cmp, Codecs>,
+    handler:              Box<dyn OpenCorruptionHandler<Cmp::InvalidKeyError> + Send + Sync>,
+    manifest_file_number: FileNumber,
+) -> RecoveryErrorKindAlias<FS, Cmp, Codecs>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator,
+    Codecs: CompressionCodecs,
+{
+    if let Some(other_err) = handler.get_error() {
+        kind.merge_worst_error(RecoveryErrorKind::Corruption(
+            other_err.into_corruption_error(manifest_file_number),
+        ));
+    }
+    kind
+}
+
+/// Delete the indicated table files, ignoring any errors. No worries if that fails; any leftover
+/// files will eventually be garbage-collected.
+fn remove_tables<FS, I>(filesystem: &FS, db_directory: &Path, file_numbers: I)
+where
+    FS: LevelDBFilesystem,
+    I:  IntoIterator<Item = FileNumber>,
+{
+    for file_number in file_numbers {
+        let table_path = LevelDBFileName::Table { file_number }.file_path(db_directory);
+        if filesystem.remove_file(&table_path).is_err() {
+            let sst_path = LevelDBFileName::TableLegacyExtension { file_number }
+                .file_path(db_directory);
+            let _err = filesystem.remove_file(&sst_path);
+        }
+    }
+}
+
+fn source_error<FS, Cmp, Codecs, NewCodecs>(
+    err: RwErrorKindAlias<FS, Cmp, Codecs>,
+) -> ConvertFormatErrorKindAlias<FS, Cmp, Codecs, NewCodecs>
+where
+    FS:        LevelDBFilesystem,
+    Cmp:       LevelDBComparator,
+    Codecs:    CompressionCodecs,
+    NewCodecs: CompressionCodecs,
+{
+    ConvertFormatErrorKind::Source(err.into_recovery_err())
+}
+
+fn target_error<FS, Cmp, Codecs, NewCodecs>(
+    err: RwErrorKindAlias<FS, Cmp, NewCodecs>,
+) -> ConvertFormatErrorKindAlias<FS, Cmp, Codecs, NewCodecs>
+where
+    FS:        LevelDBFilesystem,
+    Cmp:       LevelDBComparator,
+    Codecs:    CompressionCodecs,
+    NewCodecs: CompressionCodecs,
+{
+    ConvertFormatErrorKind::Target(err.into_recovery_err())
+}
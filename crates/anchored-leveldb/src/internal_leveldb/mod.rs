@@ -7,6 +7,7 @@ mod compaction;
 mod put_delete_get;
 mod other_read_write;
 mod debug_and_stats;
+mod convert_format;
 mod utils;
 
 
@@ -1,3 +1,34 @@
+use anchored_vfs::LevelDBFilesystem;
+
+use crate::snapshot::{Snapshot, SnapshotList};
+use crate::pub_traits::{
+    cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
+    compression::CompressionCodecs,
+    pool::BufferPool,
+};
+use super::state::InternalDBState;
+
+
+#[expect(unreachable_pub, reason = "control visibility at type definition")]
+impl<FS, Cmp, Policy, Codecs, Pool> InternalDBState<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>>,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Get a [`Snapshot`] of the database's current state.
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot {
+        let mut_state = self.lock_mutable_state();
+        // The snapshot is added while holding the database mutex, so that no compaction can
+        // observe the snapshot list before the new snapshot is added but after `last_sequence`
+        // is read.
+        SnapshotList::get_snapshot(&self.snapshot_list, mut_state.version_set.last_sequence())
+    }
+}
+
 // iter
 // iter_with
 // snapshot
@@ -1,19 +1,92 @@
-use anchored_vfs::LevelDBFilesystem;
+use std::{mem, thread};
+use std::{
+    sync::{Arc, atomic::Ordering},
+    time::{Duration, Instant},
+};
+
+use clone_behavior::FastMirroredClone;
+
+use anchored_vfs::{CreateParentDir, LevelDBFilesystem, SyncParentDir};
 
 use crate::{
-    all_errors::aliases::RwResult,
-    pub_typed_bytes::FlushWrites,
+    binary_block_log::{Slices, WriteLogWriter},
+    database_files::LevelDBFileName,
+    file_tracking::StartSeekCompaction,
+    memtable::MemtableReader,
+    options::{
+        InternalReadOptions,
+        pub_options::{CacheUsage, CompactionStyle, StallCurve},
+    },
+    version::{CurrentVersion, Version},
+    write_batch::ChainedWriteBatches,
 };
 use crate::{
-    // contention_queue::{ProcessTask, QueueHandle, VaryingWriteCommand, WriteCommand},
+    all_errors::{
+        aliases::{RwErrorKindAlias, RwResult},
+        types::{
+            FilesystemError, OutOfFileNumbers, OutOfSequenceNumbers, PushBatchError, ReadError,
+            RwErrorKind, WriteError, WriteFsError,
+        },
+    },
+    contention_queue::{
+        ProcessResult, ProcessTask, QueueHandle, VaryingWriteCommand, WriteCommand,
+        WriteCondition,
+    },
     pub_traits::{
         cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
         compression::CompressionCodecs,
         pool::BufferPool,
     },
+    pub_typed_bytes::{CloseStatus, EntryType, FileNumber, FlushWrites, Level, SequenceNumber},
+    typed_bytes::{CmpSequenceTag, InternalEntry, LookupKey, UserKey},
 };
-use super::state::InternalDBState;
+use super::state::{FrontWriterState, InternalDBState, SharedMutableState};
+
+
+/// The length of the header which precedes the entries of a write batch persisted to a
+/// write-ahead log: an 8-byte sequence number followed by a 4-byte entry count.
+const WRITE_BATCH_HEADER_LEN: usize = 12;
 
+/// Once a group of merged write batches reaches this size, no further batches are merged in.
+const MAX_WRITE_GROUP_SIZE: usize = 1 << 20;
+/// If the front writer's batch is at most this size, the merged group is capped at the front
+/// batch's size plus this amount, so that small writes are not slowed down too much by
+/// merging.
+const SMALL_WRITE_SIZE: usize = 128 << 10;
+
+/// How a write should be slowed down because compactions are falling behind.
+#[derive(Debug, Clone, Copy)]
+enum WriteStall {
+    None,
+    Delay(Duration),
+    /// Wait until compactions bring the database back below the halt triggers.
+    Halt,
+}
+
+/// The newest entry for a user key, as of some sequence number.
+#[derive(Debug, Clone)]
+pub(crate) struct GetEntry {
+    pub sequence_number: SequenceNumber,
+    /// `None` if the entry is a deletion tombstone.
+    pub value:           Option<Vec<u8>>,
+}
+
+impl GetEntry {
+    #[must_use]
+    fn new(entry: InternalEntry<'_>) -> Self {
+        let tag = entry.0.1;
+
+        let value = match tag.entry_type() {
+            EntryType::Deletion => None,
+            EntryType::Value    => Some(entry.not_deleted_user_value().inner().to_owned()),
+        };
+
+        Self {
+            sequence_number: tag.sequence_number(),
+            value,
+        }
+    }
+}
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
 impl<FS, Cmp, Policy, Codecs, Pool> InternalDBState<FS, Cmp, Policy, Codecs, Pool>
@@ -28,11 +101,275 @@ where
         todo!()
     }
 
-    // fn process(&self) {
+    /// Record a write error in `write_status`, closing writes.
+    ///
+    /// The worst error seen so far is returned, and replaced in `write_status` with a
+    /// `WritesClosedBy*` error, in the same manner as `take_write_status`.
+    fn write_err(
+        &self,
+        mut_state: &mut SharedMutableState<FS, Cmp, Policy, Codecs, Pool>,
+        error:     RwErrorKindAlias<FS, Cmp, Codecs>,
+    ) -> RwErrorKindAlias<FS, Cmp, Codecs> {
+        let mut worst_error = match mem::replace(&mut mut_state.write_status, Ok(())) {
+            Ok(())              => error,
+            Err(mut prev_error) => {
+                prev_error.merge_worst_error(error);
+                prev_error
+            }
+        };
+
+        let returned_error = worst_error.replace_with_writes_closed();
+        mut_state.write_status = Err(worst_error);
+        self.set_compactor_should_lock(mut_state);
+
+        // Wake everything up. Due to the error, whatever the threads are waiting for might
+        // never happen.
+        if let Some(background_compactor) = &self.background_compactor {
+            background_compactor.start_compaction.notify_one();
+        }
+        self.compaction_finished.notify_all();
+        self.resume_compactions.notify_all();
 
-    // }
+        returned_error
+    }
+
+    /// Determine how writes should currently be slowed down, based on the number of level-0
+    /// files and the estimated number of bytes which compactions still need to rewrite.
+    #[must_use]
+    fn write_stall(&self, current: &CurrentVersion) -> WriteStall {
+        #![expect(
+            clippy::as_conversions,
+            clippy::cast_precision_loss,
+            clippy::float_arithmetic,
+            reason = "precision is not critical for a heuristic",
+        )]
+
+        let throttling = &self.opts.write_throttling;
+        let dynamic = self.mut_opts.dynamic.read();
+
+        // FIFO compactions keep every table file in level 0 and delete level-0 files without
+        // rewriting them, so the number of level-0 files does not indicate that compactions are
+        // falling behind.
+        let fifo = matches!(self.opts.compaction.style, CompactionStyle::Fifo(_));
+        let num_l0_files = current.level_files(Level::ZERO).inner().len();
+        let l0_throttle_trigger = usize::from(dynamic.level0_write_throttle_trigger.get());
+        let l0_halt_trigger = usize::from(dynamic.level0_write_halt_trigger.get());
+
+        if !fifo && num_l0_files >= l0_halt_trigger {
+            return WriteStall::Halt;
+        }
+
+        let mut throttled = false;
+        let mut pressure = 0_f64;
+
+        if !fifo && num_l0_files >= l0_throttle_trigger {
+            // Note that `l0_throttle_trigger <= num_l0_files < l0_halt_trigger`, so neither
+            // subtraction underflows, and the pressure is in `(0, 1)`.
+            throttled = true;
+            pressure = (num_l0_files + 1 - l0_throttle_trigger) as f64
+                / (l0_halt_trigger + 1 - l0_throttle_trigger) as f64;
+        }
+
+        if matches!(self.opts.compaction.style, CompactionStyle::Leveled) {
+            let pending_bytes = current.pending_compaction_bytes();
+            let halt_trigger = dynamic.pending_compaction_bytes_halt_trigger;
+
+            if halt_trigger.is_some_and(|halt_trigger| pending_bytes >= halt_trigger) {
+                return WriteStall::Halt;
+            }
+
+            if let Some(throttle_trigger) = dynamic.pending_compaction_bytes_throttle_trigger {
+                if pending_bytes >= throttle_trigger {
+                    // Note that `throttle_trigger <= pending_bytes < halt_trigger`.
+                    let bytes_pressure = halt_trigger.map_or(1_f64, |halt_trigger| {
+                        (pending_bytes - throttle_trigger) as f64
+                            / (halt_trigger - throttle_trigger) as f64
+                    });
+
+                    throttled = true;
+                    pressure = pressure.max(bytes_pressure);
+                }
+            }
+        }
+
+        if !throttled {
+            return WriteStall::None;
+        }
+
+        let factor = match throttling.stall_curve {
+            StallCurve::Step      => 1_f64,
+            StallCurve::Linear    => pressure,
+            StallCurve::Quadratic => pressure * pressure,
+        };
+
+        WriteStall::Delay(throttling.throttle_sleep_duration.mul_f64(factor.clamp(0_f64, 1_f64)))
+    }
+}
+
+#[expect(unreachable_pub, reason = "control visibility at type definition")]
+impl<FS, Cmp, Policy, Codecs, Pool> InternalDBState<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator + FastMirroredClone,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Submit a write command to the contention queue, and wait for it to be processed.
+    ///
+    /// Returns `true` if the write was applied, and `false` if the condition of a
+    /// [`WriteCommand::Conditional`] command did not hold.
+    ///
+    /// If the command was merged into a different writer's group of writes, the outcome of the
+    /// write is learned from `write_status`.
+    pub fn write_command(
+        &self,
+        decoders: &mut Codecs::Decoders,
+        command:  WriteCommand<'_>,
+    ) -> RwResult<bool, FS, Cmp, Codecs> {
+        let task = ProcessWrites {
+            db_state: self,
+            decoders,
+        };
+
+        let result = match self.contention_queue.process(&self.mutable_state, command, task) {
+            ProcessResult::Processed(result) => result,
+            ProcessResult::ProcessedElsewhere => {
+                // Only unconditional writes are merged into other writers' groups.
+                let mut mut_state = self.lock_mutable_state();
+                return self.take_write_status(&mut mut_state, false).map(|()| true);
+            }
+            // TODO: should this close writes? It is unknown whether or not the write (or any
+            // other write in its group) made it to the write-ahead log.
+            ProcessResult::ProcessingPanicked => {
+                Err(RwErrorKind::Write(WriteError::WritesClosedByError))
+            }
+        };
+
+        result.map_err(|kind| self.rw_error(kind))
+    }
+}
+
+#[expect(unreachable_pub, reason = "control visibility at type definition")]
+impl<FS, Cmp, Policy, Codecs, Pool> InternalDBState<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Get the newest entry for `user_key` whose sequence number is at most `sequence_number`,
+    /// or at most the database's last sequence number if `sequence_number` is `None`.
+    ///
+    /// Deletion tombstones are returned, rather than being treated as a missing entry.
+    pub fn get_entry(
+        &self,
+        decoders:        &mut Codecs::Decoders,
+        read_opts:       InternalReadOptions,
+        record_seeks:    bool,
+        sequence_number: Option<SequenceNumber>,
+        user_key:        UserKey<'_>,
+    ) -> Result<Option<GetEntry>, RwErrorKindAlias<FS, Cmp, Codecs>> {
+        let mut mut_state = self.lock_mutable_state();
+
+        #[expect(clippy::or_fun_call, reason = "`.last_sequence()` is cheap")]
+        let sequence_number = sequence_number.unwrap_or(mut_state.version_set.last_sequence());
+        let lookup_key = LookupKey(
+            user_key,
+            CmpSequenceTag::new(sequence_number)
+                .ok_or(RwErrorKind::Read(ReadError::OutOfSequenceNumbers))?,
+        );
+
+        let memtable = mut_state.current_memtable.fast_mirrored_clone();
+        let imm = mut_state.compaction_state.memtable_under_compaction
+            .as_ref()
+            .map(FastMirroredClone::fast_mirrored_clone);
+        let version = mut_state.version_set.cloned_current_version();
+        let manifest_number = mut_state.version_set.manifest_file_number();
+
+        mut_state.lockfile_refcount += 1;
+        drop(mut_state);
+
+        // User reads take priority over rate-limited compaction I/O.
+        let foreground = self.mut_opts.rate_limiter.foreground_operation();
+        let get_result = self.get_unlocked(
+            decoders,
+            read_opts,
+            &memtable,
+            imm.as_ref(),
+            &version,
+            manifest_number,
+            lookup_key,
+        );
+
+        drop(foreground);
+
+        let mut mut_state = self.lock_mutable_state();
+        mut_state.lockfile_refcount -= 1;
+
+        let (entry, start_seek_compaction) = get_result?;
+
+        if record_seeks && self.opts.compaction.seek_compactions.seek_autocompactions {
+            if let Some(start_seek_compaction) = start_seek_compaction {
+                let needs_compaction = mut_state.version_set
+                    .needs_seek_compaction(&version, start_seek_compaction);
+
+                if needs_compaction.needs_seek_compaction {
+                    let _drop = self.maybe_start_compaction(mut_state, decoders);
+                }
+            }
+        }
+
+        Ok(entry)
+    }
+
+    /// Look up `lookup_key` in the memtables and then the table files of `version`.
+    ///
+    /// The mutex of the database state need not be held, but a `lockfile_refcount` should be.
+    #[expect(clippy::too_many_arguments, reason = "internal helper for `get`-like lookups")]
+    #[expect(clippy::type_complexity, reason = "the individual types have clear semantic meaning")]
+    fn get_unlocked(
+        &self,
+        decoders:        &mut Codecs::Decoders,
+        read_opts:       InternalReadOptions,
+        memtable:        &MemtableReader<Cmp>,
+        imm:             Option<&MemtableReader<Cmp>>,
+        version:         &Arc<Version>,
+        manifest_number: FileNumber,
+        lookup_key:      LookupKey<'_>,
+    ) -> Result<(Option<GetEntry>, Option<StartSeekCompaction>), RwErrorKindAlias<FS, Cmp, Codecs>>
+    {
+        if let Some(entry) = memtable.get(lookup_key) {
+            return Ok((Some(GetEntry::new(entry.as_internal_entry())), None));
+        }
+
+        if let Some(entry) = imm.and_then(|imm| imm.get(lookup_key)) {
+            return Ok((Some(GetEntry::new(entry.as_internal_entry())), None));
+        }
+
+        let (table_entry, start_seek_compaction) = version.get(
+            &self.opts,
+            &self.mut_opts,
+            read_opts,
+            decoders,
+            manifest_number,
+            lookup_key,
+        )?;
+
+        let entry = table_entry.map(|table_entry| {
+            let (key, value) = table_entry.entry();
+            GetEntry::new(InternalEntry(key.as_internal_key(), value))
+        });
+
+        Ok((entry, start_seek_compaction))
+    }
 }
 
+/// The [`ProcessTask`] run by the front writer of the database's contention queue.
+///
+/// Following `Write` commands in the queue are merged into the front writer's batch, up to a
+/// size limit.
 struct ProcessWrites<'a, FS, Cmp, Policy, Codecs, Pool>
 where
     FS:     LevelDBFilesystem,
@@ -42,6 +379,455 @@ where
     Pool:   BufferPool,
 {
     db_state: &'a InternalDBState<FS, Cmp, Policy, Codecs, Pool>,
+    decoders: &'a mut Codecs::Decoders,
+}
+
+/// What must be checked by the front writer before applying a write command's batch.
+#[derive(Debug, Clone, Copy)]
+enum Precondition<'a> {
+    ReadSet(&'a [(Vec<u8>, SequenceNumber)]),
+    Condition(WriteCondition<'a>),
+}
+
+impl<FS, Cmp, Policy, Codecs, Pool> ProcessWrites<'_, FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator + FastMirroredClone,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Look up the current entry of each of the `keys` (with `None` indicating that there is no
+    /// entry for that key), and return `true` as soon as `rejects` returns `true` for some key's
+    /// data and current entry.
+    ///
+    /// Since this is only called by the front writer, no writes can occur concurrently.
+    fn any_current_entry<'k, T, I, F>(
+        &mut self,
+        queue_handle: &mut QueueHandle<
+            '_, '_, 'static,
+            SharedMutableState<FS, Cmp, Policy, Codecs, Pool>,
+            VaryingWriteCommand,
+        >,
+        keys:         I,
+        mut rejects:  F,
+    ) -> Result<bool, RwErrorKindAlias<FS, Cmp, Codecs>>
+    where
+        I: IntoIterator<Item = (&'k [u8], T)>,
+        F: FnMut(T, Option<GetEntry>) -> bool,
+    {
+        let db_state = self.db_state;
+        let decoders = &mut *self.decoders;
+
+        let mut_state = queue_handle.mutex_state_mut();
+
+        let lookup_tag = CmpSequenceTag::new(mut_state.version_set.last_sequence())
+            .ok_or(RwErrorKind::Read(ReadError::OutOfSequenceNumbers))?;
+        let memtable = mut_state.current_memtable.fast_mirrored_clone();
+        let imm = mut_state.compaction_state.memtable_under_compaction
+            .as_ref()
+            .map(FastMirroredClone::fast_mirrored_clone);
+        let version = mut_state.version_set.cloned_current_version();
+        let manifest_number = mut_state.version_set.manifest_file_number();
+
+        mut_state.lockfile_refcount += 1;
+
+        let read_opts = InternalReadOptions {
+            verify_data_checksums:  db_state.opts.verify_data_checksums,
+            verify_index_checksums: db_state.opts.verify_index_checksums,
+            block_cache_usage:      CacheUsage::ReadAndFill,
+            table_cache_usage:      CacheUsage::ReadAndFill,
+        };
+
+        // Note that seeks performed by the front writer are not recorded.
+        let lookup_result = queue_handle.unlocked(|| {
+            for (key, data) in keys {
+                // Keys which are too long cannot be in the database.
+                let entry = if let Some(user_key) = UserKey::new(key) {
+                    db_state.get_unlocked(
+                        decoders,
+                        read_opts,
+                        &memtable,
+                        imm.as_ref(),
+                        &version,
+                        manifest_number,
+                        LookupKey(user_key, lookup_tag),
+                    )?.0
+                } else {
+                    None
+                };
+
+                if rejects(data, entry) {
+                    return Ok(true);
+                }
+            }
+
+            Ok(false)
+        });
+
+        queue_handle.mutex_state_mut().lockfile_refcount -= 1;
+
+        lookup_result
+    }
+
+    /// Delay or halt the write if compactions are falling behind, as configured by the
+    /// database's `WriteThrottlingOptions`.
+    ///
+    /// Delays and halts are performed without holding the database mutex, and are recorded in
+    /// the database's write stall counters.
+    fn throttle(
+        &self,
+        queue_handle: &mut QueueHandle<
+            '_, '_, 'static,
+            SharedMutableState<FS, Cmp, Policy, Codecs, Pool>,
+            VaryingWriteCommand,
+        >,
+    ) {
+        let db_state = self.db_state;
+
+        match db_state.write_stall(queue_handle.mutex_state().version_set.current()) {
+            WriteStall::None => {}
+            WriteStall::Delay(delay) => {
+                queue_handle.unlocked(|| thread::sleep(delay));
+
+                let counters = &db_state.write_stalls;
+                let delay_nanos = u64::try_from(delay.as_nanos()).unwrap_or(u64::MAX);
+                counters.delayed_writes.fetch_add(1, Ordering::Relaxed);
+                counters.delay_nanos.fetch_add(delay_nanos, Ordering::Relaxed);
+            }
+            WriteStall::Halt => {
+                let halt_start = Instant::now();
+
+                // If there is no ongoing compaction, the write proceeds anyway, rather than
+                // waiting for a compaction which might never occur.
+                queue_handle.unlocked(|| {
+                    let mut_state = db_state.wait_for_some_compaction(
+                        db_state.lock_mutable_state(),
+                        |state| {
+                            let stall = db_state.write_stall(state.version_set.current());
+                            !matches!(stall, WriteStall::Halt)
+                        },
+                    );
+                    drop(mut_state);
+                });
+
+                self.record_halt(halt_start);
+            }
+        }
+    }
+
+    /// Record a write which was halted since `halt_start` in the database's write stall
+    /// counters.
+    fn record_halt(&self, halt_start: Instant) {
+        let counters = &self.db_state.write_stalls;
+        let halt_nanos = u64::try_from(halt_start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        counters.halted_writes.fetch_add(1, Ordering::Relaxed);
+        counters.halt_nanos.fetch_add(halt_nanos, Ordering::Relaxed);
+    }
+
+    /// Ensure that the memtable has room for a write, after delaying or halting the write with
+    /// [`Self::throttle`].
+    ///
+    /// Once the memtable is full, it is switched out for a new memtable and write-ahead log, and
+    /// a compaction is started to flush the full memtable. If the previous full memtable has not
+    /// yet been flushed, the write is halted until it has been.
+    fn make_room(
+        &mut self,
+        front_state:  &mut FrontWriterState<FS::WriteFile, Cmp>,
+        queue_handle: &mut QueueHandle<
+            '_, '_, 'static,
+            SharedMutableState<FS, Cmp, Policy, Codecs, Pool>,
+            VaryingWriteCommand,
+        >,
+    ) -> Result<(), RwErrorKindAlias<FS, Cmp, Codecs>> {
+        let db_state = self.db_state;
+
+        self.throttle(queue_handle);
+
+        loop {
+            if front_state.memtable_writer.allocated_bytes() < db_state.opts.max_memtable_size {
+                return Ok(());
+            }
+
+            let mut_state = queue_handle.mutex_state();
+            if mut_state.compaction_state.memtable_under_compaction.is_none() {
+                return self.switch_memtable(front_state, queue_handle);
+            }
+
+            // Wait for the previous full memtable to be flushed. If compactions are not
+            // progressing, the database's `close_status` or `write_status` is checked below.
+            let halt_start = Instant::now();
+            let decoders = &mut *self.decoders;
+            queue_handle.unlocked(|| {
+                let mut_state = db_state.maybe_start_compaction(
+                    db_state.lock_mutable_state(),
+                    decoders,
+                );
+                let mut_state = db_state.wait_for_memtable_compaction(mut_state);
+                drop(mut_state);
+            });
+            self.record_halt(halt_start);
+
+            let mut_state = queue_handle.mutex_state_mut();
+            if !matches!(mut_state.close_status, CloseStatus::Open) {
+                return Err(RwErrorKind::Write(WriteError::ManuallyClosed));
+            }
+            if let Err(err) = &mut mut_state.write_status {
+                return Err(err.replace_with_writes_closed());
+            }
+        }
+    }
+
+    /// Switch out the full memtable for a new memtable and write-ahead log, and start a
+    /// compaction to flush the full memtable.
+    ///
+    /// There must not be a memtable under compaction.
+    fn switch_memtable(
+        &mut self,
+        front_state:  &mut FrontWriterState<FS::WriteFile, Cmp>,
+        queue_handle: &mut QueueHandle<
+            '_, '_, 'static,
+            SharedMutableState<FS, Cmp, Policy, Codecs, Pool>,
+            VaryingWriteCommand,
+        >,
+    ) -> Result<(), RwErrorKindAlias<FS, Cmp, Codecs>> {
+        let db_state = self.db_state;
+
+        let new_log_number = queue_handle.mutex_state_mut().version_set.new_file_number()
+            .map_err(|OutOfFileNumbers {}| RwErrorKind::Write(WriteError::OutOfFileNumbers))?;
+        let new_log_path = LevelDBFileName::Log { file_number: new_log_number }
+            .file_path(&db_state.opts.db_directory);
+
+        // Since the new log's file number is at least the `current_log_number` of the version
+        // set, the new log is not garbage collected while the mutex is unlocked. If it cannot be
+        // opened, the current memtable and log remain in use, so writes need not be closed.
+        let new_log_file = queue_handle
+            .unlocked(|| {
+                db_state.mut_opts.filesystem
+                    .open_writable(&new_log_path, CreateParentDir::False, SyncParentDir::False)
+            })
+            .map_err(|fs_err| RwErrorKind::Write(WriteError::Filesystem(
+                FilesystemError::FsError(fs_err),
+                new_log_number,
+                WriteFsError::OpenWritableLog,
+            )))?;
+
+        // Every record of the previous log was flushed by `WriteLogWriter::add_record`, so the
+        // previous log can simply be dropped.
+        front_state.current_write_log = WriteLogWriter::new_empty(
+            new_log_file,
+            db_state.opts.binary_log_block_size,
+        );
+        let full_memtable = front_state.memtable_writer.take();
+
+        // The `current_log_number` of the version set is only advanced once the full memtable
+        // has been flushed, so that the previous log is still recovered if the database is
+        // reopened before then.
+        let mut_state = queue_handle.mutex_state_mut();
+        mut_state.write_log_number = new_log_number;
+        mut_state.current_memtable = front_state.memtable_writer.reader();
+        mut_state.compaction_state.memtable_under_compaction = Some(full_memtable);
+        db_state.set_compactor_should_lock(mut_state);
+
+        let decoders = &mut *self.decoders;
+        queue_handle.unlocked(|| {
+            let mut_state = db_state.maybe_start_compaction(
+                db_state.lock_mutable_state(),
+                decoders,
+            );
+            drop(mut_state);
+        });
+
+        Ok(())
+    }
+}
+
+impl<'v, FS, Cmp, Policy, Codecs, Pool> ProcessTask<
+    'v,
+    'static,
+    SharedMutableState<FS, Cmp, Policy, Codecs, Pool>,
+    FrontWriterState<FS::WriteFile, Cmp>,
+    VaryingWriteCommand,
+    Result<bool, RwErrorKindAlias<FS, Cmp, Codecs>>,
+> for ProcessWrites<'_, FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator + FastMirroredClone,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    #[expect(clippy::too_many_lines, reason = "the steps of a write are clearest in one place")]
+    fn process<'q>(
+        mut self,
+        value:            WriteCommand<'v>,
+        front_state:      &'q mut FrontWriterState<FS::WriteFile, Cmp>,
+        mut queue_handle: QueueHandle<
+            'q, '_, 'static,
+            SharedMutableState<FS, Cmp, Policy, Codecs, Pool>,
+            VaryingWriteCommand,
+        >,
+    ) -> Result<bool, RwErrorKindAlias<FS, Cmp, Codecs>> {
+        let (first_batch, precondition, write_opts) = match value {
+            WriteCommand::Write(batch, write_opts) => (batch, None, write_opts),
+            WriteCommand::Transaction { batch, read_set, write_opts } => {
+                (batch, Some(Precondition::ReadSet(read_set)), write_opts)
+            }
+            WriteCommand::Conditional { batch, condition, write_opts } => {
+                (batch, Some(Precondition::Condition(condition)), write_opts)
+            }
+            WriteCommand::Flush(_flush_writes) => {
+                // Every write is added to the write-ahead log before the front writer finishes,
+                // so syncing the log suffices.
+                // TODO: support `FlushWrites::ToTableFile`.
+                let log = &mut front_state.current_write_log;
+                if !log.needs_sync() {
+                    return Ok(true);
+                }
+
+                let log_number = queue_handle.mutex_state().write_log_number;
+                let sync_result = queue_handle.unlocked(|| log.sync_log_data());
+
+                return sync_result.map(|()| true).map_err(|io_err| {
+                    let error = RwErrorKind::Write(WriteError::Filesystem(
+                        FilesystemError::Io(io_err),
+                        log_number,
+                        WriteFsError::SyncLog,
+                    ));
+                    self.db_state.write_err(queue_handle.mutex_state_mut(), error)
+                });
+            }
+        };
+
+        {
+            let mut_state = queue_handle.mutex_state_mut();
+
+            if !matches!(mut_state.close_status, CloseStatus::Open) {
+                return Err(RwErrorKind::Write(WriteError::ManuallyClosed));
+            }
+            if let Err(err) = &mut mut_state.write_status {
+                return Err(err.replace_with_writes_closed());
+            }
+        }
+
+        self.make_room(front_state, &mut queue_handle)?;
+
+        match precondition {
+            None => {}
+            Some(Precondition::ReadSet(read_set)) => {
+                let conflict = self.any_current_entry(
+                    &mut queue_handle,
+                    read_set.iter().map(|(key, read_sequence)| (key.as_slice(), *read_sequence)),
+                    |read_sequence, entry| {
+                        entry.is_some_and(|entry| entry.sequence_number > read_sequence)
+                    },
+                )?;
+
+                if conflict {
+                    return Err(RwErrorKind::Write(WriteError::TransactionConflict));
+                }
+            }
+            Some(Precondition::Condition(condition)) => {
+                let failed = self.any_current_entry(
+                    &mut queue_handle,
+                    [(condition.key, ())],
+                    |(), entry| !condition.holds(entry.and_then(|entry| entry.value).as_deref()),
+                )?;
+
+                if failed {
+                    return Ok(false);
+                }
+            }
+        }
+
+        let mut batches = ChainedWriteBatches::new();
+        batches
+            .push_batch(first_batch)
+            .map_err(|PushBatchError {}| RwErrorKind::Write(WriteError::OutOfSequenceNumbers))?;
+
+        let first_size = first_batch.entries().len();
+        let max_group_size = if first_size <= SMALL_WRITE_SIZE {
+            first_size + SMALL_WRITE_SIZE
+        } else {
+            MAX_WRITE_GROUP_SIZE
+        };
+        let mut group_size = first_size;
+
+        // Merge in following writes. Transactions and conditional writes are never merged, since
+        // they might not apply.
+        while let Some(&WriteCommand::Write(next_batch, next_opts)) = queue_handle.peek() {
+            if next_opts.sync && !write_opts.sync {
+                // Don't let a synchronous write be handled by a non-synchronous write.
+                break;
+            }
+
+            let new_group_size = group_size.saturating_add(next_batch.entries().len());
+            if new_group_size > max_group_size {
+                break;
+            }
+
+            if batches.push_batch(next_batch).is_err() {
+                break;
+            }
+            group_size = new_group_size;
+
+            let _popped = queue_handle.pop();
+        }
+
+        let mut_state = queue_handle.mutex_state_mut();
+        let prev_sequence = mut_state.version_set.last_sequence();
+        let log_number = mut_state.write_log_number;
+
+        let first_sequence = prev_sequence.checked_add(1)
+            .map_err(|OutOfSequenceNumbers {}| {
+                RwErrorKind::Write(WriteError::OutOfSequenceNumbers)
+            })?;
+        let (batch_iter, last_sequence) = batches.try_get_iter(prev_sequence)
+            .map_err(|OutOfSequenceNumbers {}| {
+                RwErrorKind::Write(WriteError::OutOfSequenceNumbers)
+            })?;
+
+        let mut header = Vec::with_capacity(WRITE_BATCH_HEADER_LEN);
+        header.extend(first_sequence.inner().to_le_bytes());
+        header.extend(batches.num_entries().to_le_bytes());
+        let record = Slices::new(&header, batches.batches());
+
+        let rate_limiter = &self.db_state.mut_opts.rate_limiter;
+        let log_bytes_per_sync = self.db_state.opts.write_log_bytes_per_sync;
+        let write_result = queue_handle.unlocked(|| {
+            // Synced writes take priority over rate-limited compaction I/O.
+            let _foreground = write_opts.sync.then(|| rate_limiter.foreground_operation());
+            let log = &mut front_state.current_write_log;
+
+            log.add_record(record).map_err(|io_err| (io_err, WriteFsError::WriteLog))?;
+            if write_opts.sync {
+                log.sync_log_data().map_err(|io_err| (io_err, WriteFsError::SyncLog))?;
+            } else if let Some(bytes_per_sync) = log_bytes_per_sync {
+                log.sync_incrementally(bytes_per_sync)
+                    .map_err(|io_err| (io_err, WriteFsError::SyncLog))?;
+            }
+
+            front_state.memtable_writer.insert_write_batches(batch_iter);
+            Ok(())
+        });
+
+        let mut_state = queue_handle.mutex_state_mut();
+
+        match write_result {
+            Ok(()) => {
+                mut_state.version_set.set_last_sequence(last_sequence);
+                Ok(true)
+            }
+            Err((io_err, write_fs_err)) => {
+                let error = RwErrorKind::Write(WriteError::Filesystem(
+                    FilesystemError::Io(io_err),
+                    log_number,
+                    write_fs_err,
+                ));
+                Err(self.db_state.write_err(mut_state, error))
+            }
+        }
+    }
 }
 
 
@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 use std::{
     fmt::{Debug, Formatter, Result as FmtResult},
-    sync::{Arc, atomic::AtomicBool, Condvar, Mutex},
+    sync::{Arc, atomic::{AtomicBool, AtomicU64}, Condvar, Mutex},
 };
 
 use anchored_vfs::LevelDBFilesystem;
@@ -58,6 +58,7 @@ where
         VaryingWriteCommand,
     >,
     pub snapshot_list:         Arc<Mutex<SnapshotList>>,
+    pub write_stalls:          WriteStallCounters,
 }
 
 impl<FS, Cmp, Policy, Codecs, Pool> Debug for InternalDBState<FS, Cmp, Policy, Codecs, Pool>
@@ -88,10 +89,22 @@ where
             .field("background_compactor",  &self.background_compactor)
             .field("contention_queue",      &self.contention_queue)
             .field("snapshot_list",         &self.snapshot_list)
+            .field("write_stalls",          &self.write_stalls)
             .finish()
     }
 }
 
+/// Counts the writes which were delayed or halted by write throttling, and for how long.
+///
+/// Durations are measured in nanoseconds.
+#[derive(Debug, Default)]
+pub(crate) struct WriteStallCounters {
+    pub delayed_writes: AtomicU64,
+    pub delay_nanos:    AtomicU64,
+    pub halted_writes:  AtomicU64,
+    pub halt_nanos:     AtomicU64,
+}
+
 #[derive(Debug)]
 pub(crate) struct PerHandleState<Decoders> {
     pub decoders:     Decoders,
@@ -138,6 +151,11 @@ where
     pub close_status:                 CloseStatus,
     pub write_status:                 Result<(), RwErrorKindAlias<FS, Cmp, Codecs>>,
     pub version_set:                  VersionSet<FS::WriteFile>,
+    /// The file number of the write-ahead log which writes are currently appended to.
+    ///
+    /// After the memtable is switched out, this is greater than the `current_log_number` of
+    /// `version_set` until the memtable under compaction has been flushed.
+    pub write_log_number:             FileNumber,
     pub current_memtable:             MemtableReader<Cmp>,
     pub iter_read_sample_seed:        u64,
     /// # Correctness
@@ -172,6 +190,7 @@ where
             .field("write_status",                 &self.write_status)
             .field("close_status",                 &self.close_status)
             .field("version_set",                  &self.version_set)
+            .field("write_log_number",             &self.write_log_number)
             .field("current_memtable",             &self.current_memtable)
             .field("iter_read_sample_seed",        &self.iter_read_sample_seed)
             .field("foreground_compactor",         &self.foreground_compactor)
@@ -269,4 +288,10 @@ pub(crate) struct ManualCompaction {
     pub level:       Option<NonZeroLevel>,
     pub lower_bound: Option<OwnedInternalKey>,
     pub upper_bound: Option<OwnedInternalKey>,
+    /// If `Some`, exactly these table files are compacted into `level`, and the bounds are
+    /// unused.
+    ///
+    /// The list is emptied once the compaction is chosen; an empty list indicates that the
+    /// manual compaction is finished.
+    pub files:       Option<Vec<FileNumber>>,
 }
@@ -4,7 +4,11 @@ use anchored_vfs::LevelDBFilesystem;
 
 use crate::utils::UnwrapPoison as _;
 use crate::{
-    all_errors::{aliases::RwResult, types::RwError},
+    all_errors::{
+        aliases::{RwErrorAlias, RwErrorKindAlias, RwResult},
+        types::RwError,
+    },
+    options::{InternalReadOptions, pub_options::ReadOptions},
     pub_traits::{
         cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
         compression::CompressionCodecs,
@@ -48,4 +52,29 @@ where
             Ok(())
         }
     }
+
+    #[inline]
+    #[must_use]
+    pub fn rw_error(
+        &self,
+        kind: RwErrorKindAlias<FS, Cmp, Codecs>,
+    ) -> RwErrorAlias<FS, Cmp, Codecs> {
+        RwError {
+            db_directory: self.opts.db_directory.clone(),
+            kind,
+        }
+    }
+
+    /// Resolve the settings in `read_opts` which defer to database-wide defaults.
+    #[must_use]
+    pub fn internal_read_opts(&self, read_opts: &ReadOptions) -> InternalReadOptions {
+        InternalReadOptions {
+            verify_data_checksums:  read_opts.verify_data_checksums
+                .unwrap_or(self.opts.verify_data_checksums),
+            verify_index_checksums: read_opts.verify_index_checksums
+                .unwrap_or(self.opts.verify_index_checksums),
+            block_cache_usage:      read_opts.block_cache_usage,
+            table_cache_usage:      read_opts.table_cache_usage,
+        }
+    }
 }
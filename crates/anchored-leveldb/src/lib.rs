@@ -112,6 +112,8 @@ mod file_tracking;
 mod version;
 
 mod read_sampling;
+/// A token-bucket rate limiter for the I/O performed by compactions.
+mod rate_limiter;
 
 mod internal_leveldb;
 mod internal_iters;
@@ -143,17 +145,21 @@ pub mod db_options {
         compression::{
             CodecCompressionError, CodecDecompressionError, CompressionCodec, NoCompressionCodec,
         },
+        options::dynamic_options::DynamicOptions,
         options::pub_options::{
-            BufferPoolOptions, CacheOptions, CacheUsage, ClampOptions, CompactionOptions,
-            CompressionOptions, ConsistencyOptions, FilterOptions, FormatSettings, LoggerOptions,
+            AdaptiveCompressionOptions, BufferPoolOptions, CacheOptions, CacheUsage, ClampOptions,
+            CompactionOptions, CompactionStyle, CompressionDictionaryOptions, CompressionOptions,
+            ConsistencyOptions, FifoCompactionOptions, FilterOptions, FormatSettings, LoggerOptions,
             ManifestOptions, MemtableOptions, OpenOptions, ReadOptions, SSTableOptions,
-            SeekCompactionOptions, SizeCompactionOptions, WebScale, WriteOptions,
-            WriteThrottlingOptions,
+            SeekCompactionOptions, SizeCompactionOptions, StallCurve, TieredCompactionOptions,
+            WebScale, WriteOptions, WriteThrottlingOptions,
         },
         pub_traits::{
             cmp_and_policy::{
-                AllEqual, BloomPolicy, BloomPolicyOverflow, BytewiseComparator, BytewiseEquality,
-                CoarserThan, EquivalenceRelation, FilterPolicy, LevelDBComparator, NoFilterPolicy,
+                AllEqual, BinaryFusePolicy, BinaryFusePolicyError, BlockedBloomPolicy, BloomPolicy,
+                BloomPolicyOverflow, BytewiseComparator, BytewiseEquality, CoarserThan,
+                EquivalenceRelation, FilterPolicy, FuseFingerprintSize, LevelDBComparator,
+                NoFilterPolicy,
             },
             compression::{
                 CodecsCompressionError, CodecsDecompressionError, CompressionCodecs, CompressorId,
@@ -164,8 +170,10 @@ pub mod db_options {
                 LogControlFlow, ManifestControlFlow, OpenCorruptionHandler,
             },
             logger::{Logger, TracingLogger},
+            partitioner::{CompactionPartitioner, PrefixPartitioner},
             pool::{BufferAllocError, BufferPool, ByteBuffer, BadPool, BadPoolBuf},
         },
+        pub_leveldb::{ConvertFormatProgress, DetectedCodecs, SuggestedFormat, UnknownFormat},
         pub_typed_bytes::{
             BinaryLogBlockSize, FileSize, Level, NUM_LEVELS, NUM_LEVELS_USIZE, NUM_MIDDLE_LEVELS,
             NUM_MIDDLE_LEVELS_USIZE, NUM_NONZERO_LEVELS, NUM_NONZERO_LEVELS_USIZE, ShortSlice,
@@ -182,6 +190,11 @@ pub mod db_options {
         MojangLevelDBCodecs, MojangLevelDBCompressors, MojangLevelDBDecompressors,
     };
 
+    // `Lz4CompressError` and `Lz4DecompressError` are public reexports from `lz4_flex`.
+    #[cfg(feature = "lz4-compression")]
+    pub use crate::compression::{
+        Lz4Codec, Lz4CompressError, Lz4CompressionError, Lz4DecompressError, Lz4DecompressionError,
+    };
     // `SnappyError` is a public reexport from `snap`.
     #[cfg(feature = "snappy-compression")]
     pub use crate::compression::{SnappyCodec, SnappyDecoder, SnappyEncoder, SnappyError};
@@ -206,23 +219,29 @@ pub mod db_interface {
             Close, CloseStatus, FlushWrites, OwnedTableEntry, PrefixedBytes, TableEntry,
         },
         write_batch::{
-            BorrowedWriteBatch, ChainedWriteBatches, WriteBatch, WriteBatchIter, WriteEntry,
+            BorrowedWriteBatch, ChainedWriteBatches, IndexedWriteBatch, IndexedWriteBatchIter,
+            WriteBatch, WriteBatchIter, WriteEntry,
+        },
+        pub_leveldb::{
+            DBIter, IndexedBatchDBIter, LiveFilesMetadata, LiveTableFileMetadata, Transaction,
+            WriteStallStatistics,
         },
-        pub_leveldb::DBIter,
     };
 }
 
 pub mod errors {
-    pub use crate::all_errors::aliases::{RecoveryResult, RwResult};
+    pub use crate::all_errors::aliases::{ConvertFormatResult, RecoveryResult, RwResult};
     pub use crate::all_errors::types::{
-        BinaryBlockLogCorruptionError, BlockHandleCorruption, CompressedBlockError,
-        CorruptedBlockError, CorruptedFilterBlockError, CorruptedLogError, CorruptedManifestError,
-        CorruptedTableError, CorruptedVersionError, CorruptionError, DestroyError, DestroyErrorKind,
-        FilesystemError, FinishError, HandlerError, InitEmptyDatabaseError, InvalidInternalKey,
-        OpenError, OpenFsError, OptionsError, PrefixedBytesParseError, PushBatchError, ReadError,
-        ReadFsError, RecoveryError, RecoveryErrorKind, RemoveError, RwError, RwErrorKind,
-        SetCurrentError, VersionEditDecodeError, WriteBatchDecodeError, WriteBatchDeleteError,
-        WriteBatchPutError, WriteBatchValidationError, WriteError, WriteFsError,
+        BinaryBlockLogCorruptionError, BlockHandleCorruption, CompactFilesError,
+        CompressedBlockError, ConvertFormatError, ConvertFormatErrorKind, CorruptedBlockError,
+        CorruptedFilterBlockError, CorruptedLogError, CorruptedManifestError, CorruptedTableError,
+        CorruptedVersionError, CorruptionError, DestroyError, DestroyErrorKind, DetectFormatError,
+        DetectFormatErrorKind, FilesystemError, FinishError, HandlerError, InitEmptyDatabaseError,
+        InvalidInternalKey, NoSavepointError, OpenError, OpenFsError, OptionsError,
+        PrefixedBytesParseError, PushBatchError, ReadError, ReadFsError, RecoveryError,
+        RecoveryErrorKind, RemoveError, RwError, RwErrorKind, SetCurrentError,
+        VersionEditDecodeError, WriteBatchDecodeError, WriteBatchDeleteError, WriteBatchPutError,
+        WriteBatchValidationError, WriteError, WriteFsError,
     };
 
     // These types are not exposed except via error types.
@@ -238,5 +257,7 @@ pub use self::{
     db_options::{BloomPolicy, BytewiseComparator, FilterPolicy, LevelDBComparator, OpenOptions},
     errors::{RecoveryError, RwError},
     // These are only exported at the root
-    pub_leveldb::{DB, DBState, irreversibly_destroy_entire_db},
+    pub_leveldb::{
+        DB, DBState, convert_db_format, detect_format, irreversibly_destroy_entire_db,
+    },
 };
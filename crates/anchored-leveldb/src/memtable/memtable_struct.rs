@@ -99,6 +99,12 @@ impl<Cmp: LevelDBComparator> UniqueMemtable<Cmp> {
         MemtableIter::new(self.skiplist.iter())
     }
 
+    #[inline]
+    #[must_use]
+    pub const fn cmp(&self) -> &InternalComparator<Cmp> {
+        self.skiplist.cmp()
+    }
+
     #[must_use]
     pub fn into_memtable(self, unwrap_poison: bool, pool_size: NonZeroU8) -> Memtable<Cmp> {
         let pool = MemtablePool::new(unwrap_poison, NonZeroUsize::from(pool_size), self.prng);
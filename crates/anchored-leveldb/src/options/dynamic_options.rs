@@ -1,20 +1,138 @@
 use std::{
-    num::{NonZeroU32, NonZeroU8},
-    sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering},
+    num::{NonZeroU16, NonZeroU32, NonZeroU8},
+    sync::atomic::{AtomicU16, AtomicU32, AtomicU64, AtomicUsize, Ordering},
 };
 
-use crate::{pub_traits::compression::CompressorId, pub_typed_bytes::NUM_NONZERO_LEVELS_USIZE};
+use crate::{all_errors::types::OptionsError, pub_traits::compression::CompressorId};
+use crate::pub_typed_bytes::{NonZeroLevel, NUM_MIDDLE_LEVELS_USIZE, NUM_NONZERO_LEVELS_USIZE};
 
 
-/// Options which can be changed while the database is running.
+/// Options which can be changed while the database is running, with
+/// [`DB::set_dynamic_options`].
+///
+/// The initial values are taken from the corresponding fields of [`CompressionOptions`],
+/// [`SSTableOptions`], [`WriteThrottlingOptions`], and [`SizeCompactionOptions`]; see those
+/// options for more information. New values apply to table files which are started after the
+/// change, to writes which are submitted after the change, and to the choice of which size
+/// compactions to perform.
+///
+/// [`DB::set_dynamic_options`]: crate::DB::set_dynamic_options
+/// [`CompressionOptions`]: crate::db_options::CompressionOptions
+/// [`SSTableOptions`]: crate::db_options::SSTableOptions
+/// [`WriteThrottlingOptions`]: crate::db_options::WriteThrottlingOptions
+/// [`SizeCompactionOptions`]: crate::db_options::SizeCompactionOptions
 #[derive(Debug, Clone, Copy)]
-pub(crate) struct DynamicOptions {
-    pub memtable_compressor:            Option<CompressorId>,
-    pub table_compressors:              [Option<CompressorId>; NUM_NONZERO_LEVELS_USIZE.get()],
-    pub memtable_compression_goal:      u8,
-    pub table_compression_goals:        [u8; NUM_NONZERO_LEVELS_USIZE.get()],
-    pub sstable_block_size:             usize,
-    pub sstable_block_restart_interval: NonZeroU32,
+pub struct DynamicOptions {
+    /// The compressor used for table files produced by memtable flushes.
+    ///
+    /// Corresponds to the [`CompressionOptions`] field of the same name.
+    ///
+    /// [`CompressionOptions`]: crate::db_options::CompressionOptions
+    pub memtable_compressor:                       Option<CompressorId>,
+    /// The compressor used for table files produced by compactions in each nonzero level.
+    ///
+    /// Corresponds to the [`CompressionOptions`] field of the same name.
+    ///
+    /// [`CompressionOptions`]: crate::db_options::CompressionOptions
+    pub table_compressors:
+        [Option<CompressorId>; NUM_NONZERO_LEVELS_USIZE.get()],
+    /// Corresponds to the [`CompressionOptions`] field of the same name.
+    ///
+    /// [`CompressionOptions`]: crate::db_options::CompressionOptions
+    pub memtable_compression_goal:                 u8,
+    /// Corresponds to the [`CompressionOptions`] field of the same name.
+    ///
+    /// [`CompressionOptions`]: crate::db_options::CompressionOptions
+    pub table_compression_goals:                   [u8; NUM_NONZERO_LEVELS_USIZE.get()],
+    /// Corresponds to the [`SSTableOptions`] field of the same name.
+    ///
+    /// [`SSTableOptions`]: crate::db_options::SSTableOptions
+    pub sstable_block_size:                        usize,
+    /// Corresponds to the `block_restart_interval` field of [`SSTableOptions`].
+    ///
+    /// [`SSTableOptions`]: crate::db_options::SSTableOptions
+    pub sstable_block_restart_interval:            NonZeroU32,
+    /// Corresponds to the [`WriteThrottlingOptions`] field of the same name.
+    ///
+    /// Must not exceed `level0_write_halt_trigger`.
+    ///
+    /// [`WriteThrottlingOptions`]: crate::db_options::WriteThrottlingOptions
+    pub level0_write_throttle_trigger:             NonZeroU16,
+    /// Corresponds to the [`WriteThrottlingOptions`] field of the same name.
+    ///
+    /// [`WriteThrottlingOptions`]: crate::db_options::WriteThrottlingOptions
+    pub level0_write_halt_trigger:                 NonZeroU16,
+    /// Corresponds to the [`WriteThrottlingOptions`] field of the same name.
+    ///
+    /// Must not exceed `pending_compaction_bytes_halt_trigger`, if both are `Some`.
+    /// `Some(u64::MAX)` is treated as `None`.
+    ///
+    /// [`WriteThrottlingOptions`]: crate::db_options::WriteThrottlingOptions
+    pub pending_compaction_bytes_throttle_trigger: Option<u64>,
+    /// Corresponds to the [`WriteThrottlingOptions`] field of the same name.
+    ///
+    /// `Some(u64::MAX)` is treated as `None`.
+    ///
+    /// [`WriteThrottlingOptions`]: crate::db_options::WriteThrottlingOptions
+    pub pending_compaction_bytes_halt_trigger:     Option<u64>,
+    /// Corresponds to the [`SizeCompactionOptions`] field of the same name.
+    ///
+    /// [`SizeCompactionOptions`]: crate::db_options::SizeCompactionOptions
+    pub max_level0_files:                          NonZeroU16,
+    /// Corresponds to the [`SizeCompactionOptions`] field of the same name.
+    ///
+    /// [`SizeCompactionOptions`]: crate::db_options::SizeCompactionOptions
+    pub max_level_sizes:                           [u64; NUM_MIDDLE_LEVELS_USIZE.get()],
+}
+
+impl DynamicOptions {
+    /// Check that the chosen compressors are supported by the chosen codecs (if the codecs
+    /// report their names), and that no throttle trigger exceeds the corresponding halt trigger.
+    pub(crate) fn validate(
+        &self,
+        codec_names: &[(CompressorId, &'static str)],
+    ) -> Result<(), OptionsError> {
+        let is_supported = |compressor: CompressorId| {
+            codec_names.is_empty() || codec_names.iter().any(|&(id, _)| id == compressor)
+        };
+
+        if let Some(compressor) = self.memtable_compressor {
+            if !is_supported(compressor) {
+                return Err(OptionsError::UnsupportedMemtableCompressor(compressor));
+            }
+        }
+
+        for (level, compressor) in NonZeroLevel::NONZERO_LEVELS.into_iter()
+            .zip(self.table_compressors)
+        {
+            if let Some(compressor) = compressor {
+                if !is_supported(compressor) {
+                    return Err(OptionsError::UnsupportedTableCompressor(level, compressor));
+                }
+            }
+        }
+
+        if self.level0_write_throttle_trigger > self.level0_write_halt_trigger {
+            return Err(OptionsError::Level0TriggersOutOfOrder {
+                throttle_trigger: self.level0_write_throttle_trigger,
+                halt_trigger:     self.level0_write_halt_trigger,
+            });
+        }
+
+        if let (Some(throttle_trigger), Some(halt_trigger)) = (
+            self.pending_compaction_bytes_throttle_trigger,
+            self.pending_compaction_bytes_halt_trigger,
+        ) {
+            if throttle_trigger > halt_trigger {
+                return Err(OptionsError::PendingCompactionBytesTriggersOutOfOrder {
+                    throttle_trigger,
+                    halt_trigger,
+                });
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -23,6 +141,14 @@ pub(crate) struct AtomicDynamicOptions {
     compression_goals: AtomicU64,
     block_size:        AtomicUsize,
     restart_interval:  AtomicU32,
+    /// The level-0 throttle trigger in the low 16 bits, and the halt trigger in the high 16 bits.
+    level0_triggers:   AtomicU32,
+    /// `u64::MAX` indicates `None`.
+    pending_throttle:  AtomicU64,
+    /// `u64::MAX` indicates `None`.
+    pending_halt:      AtomicU64,
+    max_level0_files:  AtomicU16,
+    max_level_sizes:   [AtomicU64; NUM_MIDDLE_LEVELS_USIZE.get()],
 }
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
@@ -49,6 +175,13 @@ impl AtomicDynamicOptions {
         u64::from_le_bytes(goals)
     }
 
+    #[must_use]
+    fn u32_level0_triggers(frozen: DynamicOptions) -> u32 {
+        let throttle_trigger = u32::from(frozen.level0_write_throttle_trigger.get());
+        let halt_trigger = u32::from(frozen.level0_write_halt_trigger.get());
+        throttle_trigger | (halt_trigger << 16_u8)
+    }
+
     #[must_use]
     fn compressor_to_u8(compressor: Option<CompressorId>) -> u8 {
         compressor.map_or(0, |id| id.0.get())
@@ -59,6 +192,20 @@ impl AtomicDynamicOptions {
         NonZeroU8::new(compressor).map(CompressorId)
     }
 
+    #[must_use]
+    fn trigger_to_u64(trigger: Option<u64>) -> u64 {
+        trigger.unwrap_or(u64::MAX)
+    }
+
+    #[must_use]
+    const fn u64_to_trigger(trigger: u64) -> Option<u64> {
+        if trigger == u64::MAX {
+            None
+        } else {
+            Some(trigger)
+        }
+    }
+
     #[inline]
     #[must_use]
     pub fn new(frozen: DynamicOptions) -> Self {
@@ -67,22 +214,40 @@ impl AtomicDynamicOptions {
             compression_goals: AtomicU64::new(Self::u64_compression_goals(frozen)),
             block_size:        AtomicUsize::new(frozen.sstable_block_size),
             restart_interval:  AtomicU32::new(frozen.sstable_block_restart_interval.get()),
+            level0_triggers:   AtomicU32::new(Self::u32_level0_triggers(frozen)),
+            pending_throttle:  AtomicU64::new(Self::trigger_to_u64(
+                frozen.pending_compaction_bytes_throttle_trigger,
+            )),
+            pending_halt:      AtomicU64::new(Self::trigger_to_u64(
+                frozen.pending_compaction_bytes_halt_trigger,
+            )),
+            max_level0_files:  AtomicU16::new(frozen.max_level0_files.get()),
+            max_level_sizes:   frozen.max_level_sizes.map(AtomicU64::new),
         }
     }
 
+    /// Each option is set separately, so a concurrent [`Self::read`] may observe a mix of old
+    /// and new values.
     pub fn set_all(&self, frozen: DynamicOptions) {
-        let mut compressors = [None; 8];
-        compressors[0] = frozen.memtable_compressor;
-        #[expect(clippy::expect_used, reason = "cannot panic")]
-        {
-            *compressors.last_chunk_mut::<6>().expect("`6 <= 8`") = frozen.table_compressors;
-        };
-        let compressors = u64::from_le_bytes(compressors.map(Self::compressor_to_u8));
+        let compressors = Self::u64_compressors(frozen);
+        let compression_goals = Self::u64_compression_goals(frozen);
         let restart_interval = frozen.sstable_block_restart_interval.get();
+        let level0_triggers = Self::u32_level0_triggers(frozen);
+        let pending_throttle = frozen.pending_compaction_bytes_throttle_trigger;
+        let pending_throttle = Self::trigger_to_u64(pending_throttle);
+        let pending_halt = Self::trigger_to_u64(frozen.pending_compaction_bytes_halt_trigger);
 
         self.compressors.store(compressors, Ordering::Relaxed);
+        self.compression_goals.store(compression_goals, Ordering::Relaxed);
         self.block_size.store(frozen.sstable_block_size, Ordering::Relaxed);
         self.restart_interval.store(restart_interval, Ordering::Relaxed);
+        self.level0_triggers.store(level0_triggers, Ordering::Relaxed);
+        self.pending_throttle.store(pending_throttle, Ordering::Relaxed);
+        self.pending_halt.store(pending_halt, Ordering::Relaxed);
+        self.max_level0_files.store(frozen.max_level0_files.get(), Ordering::Relaxed);
+        for (atomic_size, max_size) in self.max_level_sizes.iter().zip(frozen.max_level_sizes) {
+            atomic_size.store(max_size, Ordering::Relaxed);
+        }
     }
 
     pub fn set_memtable_compressor(&self, memtable_compressor: Option<CompressorId>) {
@@ -164,6 +329,13 @@ impl AtomicDynamicOptions {
         let compression_goals = u64::to_le_bytes(self.compression_goals.load(Ordering::Relaxed));
         let sstable_block_size = self.block_size.load(Ordering::Relaxed);
         let restart_interval = self.restart_interval.load(Ordering::Relaxed);
+        let level0_triggers = self.level0_triggers.load(Ordering::Relaxed);
+        let pending_throttle = self.pending_throttle.load(Ordering::Relaxed);
+        let pending_halt = self.pending_halt.load(Ordering::Relaxed);
+        let max_level0_files = self.max_level0_files.load(Ordering::Relaxed);
+        let max_level_sizes = self.max_level_sizes
+            .each_ref()
+            .map(|max_size| max_size.load(Ordering::Relaxed));
 
         let compressors = compressors.map(Self::u8_to_compressor);
 
@@ -179,6 +351,25 @@ impl AtomicDynamicOptions {
         let sstable_block_restart_interval = NonZeroU32::new(restart_interval)
             .expect("`DynamicOptions.restart_interval` is only ever set to `NonZeroU32` values");
 
+        #[expect(
+            clippy::as_conversions,
+            clippy::cast_possible_truncation,
+            reason = "each trigger is stored in 16 bits",
+        )]
+        let (throttle_trigger, halt_trigger) = (
+            level0_triggers as u16,
+            (level0_triggers >> 16_u8) as u16,
+        );
+        #[expect(clippy::expect_used, reason = "cannot panic")]
+        let level0_write_throttle_trigger = NonZeroU16::new(throttle_trigger)
+            .expect("the level-0 throttle trigger is only ever set to `NonZeroU16` values");
+        #[expect(clippy::expect_used, reason = "cannot panic")]
+        let level0_write_halt_trigger = NonZeroU16::new(halt_trigger)
+            .expect("the level-0 halt trigger is only ever set to `NonZeroU16` values");
+        #[expect(clippy::expect_used, reason = "cannot panic")]
+        let max_level0_files = NonZeroU16::new(max_level0_files)
+            .expect("`DynamicOptions.max_level0_files` is only ever set to `NonZeroU16` values");
+
         DynamicOptions {
             memtable_compressor,
             table_compressors,
@@ -186,6 +377,12 @@ impl AtomicDynamicOptions {
             table_compression_goals,
             sstable_block_size,
             sstable_block_restart_interval,
+            level0_write_throttle_trigger,
+            level0_write_halt_trigger,
+            pending_compaction_bytes_throttle_trigger: Self::u64_to_trigger(pending_throttle),
+            pending_compaction_bytes_halt_trigger:     Self::u64_to_trigger(pending_halt),
+            max_level0_files,
+            max_level_sizes,
         }
     }
 }
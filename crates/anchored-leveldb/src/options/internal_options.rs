@@ -1,9 +1,19 @@
-use std::{num::NonZeroU8, path::PathBuf};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::num::{NonZeroU8, NonZeroU64};
 use std::fmt::{Debug, Formatter, Result as FmtResult};
 
+use clone_behavior::FastMirroredClone;
+
 use anchored_vfs::LevelDBFilesystem;
 
-use crate::{internal_logger::InternalLogger, pub_traits::pool::BufferPool};
+use crate::{
+    internal_logger::InternalLogger, rate_limiter::RateLimiter, sstable::ParallelCompression,
+};
+use crate::pub_traits::{
+    compression::{CompressionCodecs, CompressorId},
+    partitioner::CompactionPartitioner,
+    pool::BufferPool,
+};
 use crate::{
     pub_typed_bytes::{
         BinaryLogBlockSize, FileSize, Level, NUM_MIDDLE_LEVELS_USIZE, NUM_NONZERO_LEVELS_USIZE,
@@ -13,7 +23,8 @@ use crate::{
 };
 use super::dynamic_options::AtomicDynamicOptions;
 use super::pub_options::{
-    CacheUsage, SeekCompactionOptions, SizeCompactionOptions, WebScale, WriteThrottlingOptions,
+    AdaptiveCompressionOptions, CacheUsage, CompactionStyle, CompressionDictionaryOptions,
+    SeekCompactionOptions, SizeCompactionOptions, WebScale, WriteThrottlingOptions,
 };
 
 
@@ -25,6 +36,8 @@ pub(crate) struct InternalOptions<Cmp, Policy, Codecs> {
     pub policy:                     Option<InternalFilterPolicy<Policy>>,
     pub filter_chunk_size_log2:     u8,
     pub codecs:                     Codecs,
+    /// The result of `codecs.codec_names()`.
+    pub codec_names:                Vec<(CompressorId, &'static str)>,
     pub binary_log_block_size:      BinaryLogBlockSize,
     pub verify_data_checksums:      bool,
     pub verify_index_checksums:     bool,
@@ -32,12 +45,80 @@ pub(crate) struct InternalOptions<Cmp, Policy, Codecs> {
     pub web_scale:                  WebScale,
     pub max_memtable_size:          usize,
     pub max_write_log_file_size:    FileSize,
+    pub write_log_bytes_per_sync:   Option<NonZeroU64>,
     pub max_sstable_sizes:          [FileSize; NUM_NONZERO_LEVELS_USIZE.get()],
+    pub sstable_bytes_per_sync:     Option<NonZeroU64>,
+    pub compression_dictionary:     Option<CompressionDictionaryOptions>,
+    pub adaptive_compression:       Option<AdaptiveCompressionOptions>,
+    pub parallel_compression:       Option<ParallelCompression<Codecs>>,
     pub compaction:                 InternalCompactionOptions,
+    pub compaction_partitioner:     Option<Arc<dyn CompactionPartitioner + Send + Sync>>,
     pub write_throttling:           WriteThrottlingOptions,
     pub iter_buffer_capacity_limit: usize,
 }
 
+#[expect(unreachable_pub, reason = "control visibility at type definition")]
+impl<Cmp, Policy, Codecs> InternalOptions<Cmp, Policy, Codecs>
+where
+    Cmp:    FastMirroredClone,
+    Policy: FastMirroredClone,
+{
+    /// Copy these options, except that `codecs` (and `parallel_compression`) are replaced.
+    #[must_use]
+    pub fn with_codecs<NewCodecs: CompressionCodecs>(
+        &self,
+        codecs:               NewCodecs,
+        parallel_compression: Option<ParallelCompression<NewCodecs>>,
+    ) -> InternalOptions<Cmp, Policy, NewCodecs> {
+        let codec_names = codecs.codec_names();
+        let compression_dictionary = usable_compression_dictionary(
+            self.compression_dictionary,
+            &codec_names,
+        );
+        InternalOptions {
+            db_directory:               self.db_directory.clone(),
+            cmp:                        self.cmp.fast_mirrored_clone(),
+            policy:                     self.policy.as_ref().map(|policy| {
+                policy.fast_mirrored_clone()
+            }),
+            filter_chunk_size_log2:     self.filter_chunk_size_log2,
+            codecs,
+            codec_names,
+            binary_log_block_size:      self.binary_log_block_size,
+            verify_data_checksums:      self.verify_data_checksums,
+            verify_index_checksums:     self.verify_index_checksums,
+            unwrap_poison:              self.unwrap_poison,
+            web_scale:                  self.web_scale,
+            max_memtable_size:          self.max_memtable_size,
+            max_write_log_file_size:    self.max_write_log_file_size,
+            write_log_bytes_per_sync:   self.write_log_bytes_per_sync,
+            max_sstable_sizes:          self.max_sstable_sizes,
+            sstable_bytes_per_sync:     self.sstable_bytes_per_sync,
+            compression_dictionary,
+            adaptive_compression:       self.adaptive_compression,
+            parallel_compression,
+            compaction:                 self.compaction,
+            compaction_partitioner:     self.compaction_partitioner.clone(),
+            write_throttling:           self.write_throttling,
+            iter_buffer_capacity_limit: self.iter_buffer_capacity_limit,
+        }
+    }
+}
+
+/// Returns `None` if any of the chosen codecs uses the compressor ID which data blocks compressed
+/// with a dictionary are tagged with, since those blocks could then be mistaken for blocks
+/// compressed without the dictionary.
+#[must_use]
+pub(crate) fn usable_compression_dictionary(
+    dictionary_opts: Option<CompressionDictionaryOptions>,
+    codec_names:     &[(CompressorId, &'static str)],
+) -> Option<CompressionDictionaryOptions> {
+    dictionary_opts.filter(|dictionary_opts| {
+        let dictionary_id = dictionary_opts.dictionary_compressor_id;
+        codec_names.iter().all(|&(compressor_id, _)| compressor_id != dictionary_id)
+    })
+}
+
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct InternalCompactionOptions {
     pub max_level_for_memtable_flush: Level,
@@ -45,16 +126,21 @@ pub(crate) struct InternalCompactionOptions {
     pub max_grandparent_overlap:      [u64; NUM_MIDDLE_LEVELS_USIZE.get()],
     pub size_compactions:             SizeCompactionOptions,
     pub seek_compactions:             SeekCompactionOptions,
+    pub style:                        CompactionStyle,
+    pub max_deletion_ratio:           Option<f64>,
+    pub periodic_compaction_age:      Option<Duration>,
+    pub rate_limit_reads:             bool,
 }
 
 pub(crate) struct InternallyMutableOptions<FS: LevelDBFilesystem, Policy, Pool: BufferPool> {
     // TODO: abbreviate to `fs` since this is common?
-    pub filesystem:  FS,
-    pub dynamic:     AtomicDynamicOptions,
-    pub logger:      InternalLogger<FS::WriteFile>,
-    pub buffer_pool: Pool,
-    pub block_cache: BlockCache<Pool>,
-    pub table_cache: TableCache<FS::RandomAccessFile, Policy, Pool>,
+    pub filesystem:   FS,
+    pub dynamic:      AtomicDynamicOptions,
+    pub logger:       InternalLogger<FS::WriteFile>,
+    pub buffer_pool:  Pool,
+    pub block_cache:  BlockCache<Pool>,
+    pub table_cache:  TableCache<FS::RandomAccessFile, Policy, Pool>,
+    pub rate_limiter: RateLimiter,
 }
 
 impl<FS, Policy, Pool> Debug for InternallyMutableOptions<FS, Policy, Pool>
@@ -64,12 +150,13 @@ where
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         f.debug_struct("InternallyMutableOptions")
-            .field("filesystem",  &self.filesystem)
-            .field("dynamic",     &self.dynamic)
-            .field("logger",      &self.logger)
-            .field("buffer_pool", &self.buffer_pool)
-            .field("block_cache", &self.block_cache)
-            .field("table_cache", &self.table_cache)
+            .field("filesystem",   &self.filesystem)
+            .field("dynamic",      &self.dynamic)
+            .field("logger",       &self.logger)
+            .field("buffer_pool",  &self.buffer_pool)
+            .field("block_cache",  &self.block_cache)
+            .field("table_cache",  &self.table_cache)
+            .field("rate_limiter", &self.rate_limiter)
             .finish()
     }
 }
@@ -1,5 +1,5 @@
 pub(crate) mod pub_options;
-mod dynamic_options;
+pub(crate) mod dynamic_options;
 mod internal_options;
 
 
@@ -7,6 +7,6 @@ pub(crate) use self::{
     dynamic_options::{AtomicDynamicOptions, DynamicOptions},
     internal_options::{
         InternalCompactionOptions, InternalOpenOptions, InternalOptions, InternalReadOptions,
-        InternalWriteOptions, InternallyMutableOptions,
+        InternalWriteOptions, InternallyMutableOptions, usable_compression_dictionary,
     },
 };
@@ -1,8 +1,8 @@
 use std::array;
-use std::{path::PathBuf, time::Duration};
+use std::{path::PathBuf, sync::Arc, time::Duration};
 use std::{
     fmt::{Debug, Formatter, Result as FmtResult},
-    num::{NonZeroU8, NonZeroU16, NonZeroU32, NonZeroUsize},
+    num::{NonZeroU8, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroUsize},
 };
 
 // TODO: create own enum.
@@ -12,9 +12,13 @@ use crate::snapshot::Snapshot;
 use crate::{
     pub_traits::{
         compression::CompressorId,
-        cmp_and_policy::{BloomPolicy, LevelDBComparator},
+        cmp_and_policy::{
+            BinaryFusePolicy, BlockedBloomPolicy, BloomPolicy, FuseFingerprintSize,
+            LevelDBComparator,
+        },
         error_handler::{DefaultOpenHandler, DefaultOpenHandlerOptions, OpenCorruptionHandler},
         logger::Logger,
+        partitioner::CompactionPartitioner,
     },
     pub_typed_bytes::{
         BinaryLogBlockSize, FileSize, Level, NUM_MIDDLE_LEVELS_USIZE, NUM_NONZERO_LEVELS_USIZE,
@@ -112,10 +116,20 @@ impl Default for ReadOptions {
 #[derive(Debug)]
 pub struct WriteOptions {
     // TODO: Some `ReadOptions` might need to be included here.
+    /// Defaults to `false`.
     pub sync: bool,
     // TODO: error handler (with per-db default)
 }
 
+impl Default for WriteOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            sync: false,
+        }
+    }
+}
+
 /// Persistent database settings that readers and writers of a LevelDB database **must** agree on
 /// for correctness rather than solely performance.
 #[derive(Debug, Clone, Copy)]
@@ -170,9 +184,19 @@ impl<Cmp, Codecs> FormatSettings<Cmp, Codecs> {
     /// If you attempt to read the database with an incorrect choice of comparator, an error
     /// will be returned that indicates the name of the correct comparator.
     ///
-    /// No metadata about the choice of compression codecs is stored, so making an incorrect
-    /// choice of compression codecs may corrupt the database, or it make the database appear
-    /// to be corrupted even if it could be read with the correct choice of compression codecs.
+    /// If the chosen compression codecs report their names (see
+    /// [`CompressionCodecs::codec_names`]), the names are recorded in a `compression_codecs` meta
+    /// block of each table file written by this crate, and an error is likewise returned if the
+    /// recorded name of a compressor ID does not match the chosen codecs. The most recently
+    /// written table file is checked when the database is opened, and every other table file
+    /// is checked when it is first read. Other LevelDB implementations ignore that meta block.
+    ///
+    /// Otherwise, or for table files written by a LevelDB implementation which does not record
+    /// codec names, making an incorrect choice of compression codecs may corrupt the database,
+    /// or it make the database appear to be corrupted even if it could be read with the correct
+    /// choice of compression codecs.
+    ///
+    /// [`CompressionCodecs::codec_names`]: crate::db_options::CompressionCodecs::codec_names
     #[inline]
     #[must_use]
     pub const fn from_cmp_and_unchecked_compression_codecs(
@@ -264,6 +288,35 @@ pub struct CompressionOptions {
     /// Defaults to `32` (for a minimum of 12.5% compression, or no compression). Not clamped.
     /// This option can be dynamically changed while the database is running.
     pub table_compression_goals:        [u8; NUM_NONZERO_LEVELS_USIZE.get()],
+    /// If set, each data block of an SSTable file may be compressed with a different compressor,
+    /// chosen based on how well each candidate compressor performs on that table's data.
+    ///
+    /// Adaptive compression is not used for SSTable files whose compressor (as set by
+    /// [`memtable_compressor`] or [`table_compressors`]) is `None`.
+    ///
+    /// See [`AdaptiveCompressionOptions`].
+    ///
+    /// Defaults to `None`.
+    ///
+    /// [`memtable_compressor`]: CompressionOptions::memtable_compressor
+    /// [`table_compressors`]: CompressionOptions::table_compressors
+    pub adaptive:                       Option<AdaptiveCompressionOptions>,
+    /// If set, the data blocks of each SSTable file are compressed in batches by this many
+    /// worker threads, each with its own encoders. Otherwise, data blocks are compressed on the
+    /// thread writing the SSTable file.
+    ///
+    /// The worker threads are spawned when the database is opened and live until it's closed,
+    /// and are shared by every SSTable file being written. Each batch is compressed while the
+    /// previous batch is written to its SSTable file.
+    ///
+    /// Blocks are still written to the SSTable file in order, so the resulting file is the same
+    /// as though it were compressed by a single thread. Tables which use [`adaptive`]
+    /// compression are compressed by a single thread.
+    ///
+    /// Defaults to `None`.
+    ///
+    /// [`adaptive`]: CompressionOptions::adaptive
+    pub compression_threads:            Option<NonZeroUsize>,
 }
 
 impl CompressionOptions {
@@ -289,6 +342,8 @@ impl CompressionOptions {
             table_compressors:         [Some(compressor); LEN],
             memtable_compression_goal: Self::DEFAULT_COMPRESSION_GOAL,
             table_compression_goals:   [Self::DEFAULT_COMPRESSION_GOAL; LEN],
+            adaptive:                  None,
+            compression_threads:       None,
         }
     }
 
@@ -343,6 +398,81 @@ impl CompressionOptions {
         self.table_compression_goals = compression_goals;
         self
     }
+
+    /// Adaptively choose between several compressors for each data block of an SSTable.
+    ///
+    /// See [`AdaptiveCompressionOptions`].
+    #[inline]
+    #[must_use]
+    pub const fn with_adaptive(mut self, adaptive: Option<AdaptiveCompressionOptions>) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+
+    /// Compress the data blocks of each SSTable file on this many worker threads.
+    #[inline]
+    #[must_use]
+    pub const fn with_compression_threads(mut self, threads: Option<NonZeroUsize>) -> Self {
+        self.compression_threads = threads;
+        self
+    }
+}
+
+/// Options for adaptively choosing which compressor to use for each data block of an SSTable.
+///
+/// The table's usual compressor and each of the [`candidates`] are all tried on a sample of the
+/// table's data blocks, and the time taken and the number of bytes saved by each compressor are
+/// tracked. Each sampled block is compressed with whichever compressor performed best on that
+/// block, and each other block is compressed with whichever compressor has performed best on the
+/// table's samples so far. Additionally, if no compressor is worth its CPU time, blocks are
+/// stored uncompressed.
+///
+/// Regardless of which compressor is chosen, blocks which cannot meet the table's compression
+/// goal are stored uncompressed.
+///
+/// The compressor used for each block is recorded in that block, so reading tables requires no
+/// additional configuration, aside from the compression codecs supporting each candidate.
+///
+/// [`candidates`]: AdaptiveCompressionOptions::candidates
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveCompressionOptions {
+    /// Compressors to try in addition to the usual compressor of each SSTable file. `None`
+    /// entries are ignored.
+    ///
+    /// If any of these compressors are not supported by the chosen set of compression codecs,
+    /// then writing SSTable files will fail.
+    ///
+    /// No default.
+    pub candidates:                  [Option<CompressorId>; 4],
+    /// One out of every `sample_interval` data blocks of each table, starting with the first,
+    /// is compressed with every compressor.
+    ///
+    /// Defaults to `16`.
+    pub sample_interval:             NonZeroU32,
+    /// How many bytes of compression savings are worth one microsecond of compression time.
+    ///
+    /// Larger values favor faster compressors, and `0` chooses whichever compressor results in
+    /// the smallest output.
+    ///
+    /// Defaults to `32`. Not clamped.
+    pub saved_bytes_per_microsecond: u32,
+}
+
+impl AdaptiveCompressionOptions {
+    /// Try the given candidates in addition to the usual compressor of each SSTable file, and
+    /// use default values for other fields.
+    #[inline]
+    #[must_use]
+    pub const fn from_candidates(candidates: [Option<CompressorId>; 4]) -> Self {
+        #[allow(clippy::unwrap_used, reason = "validated at compile time")]
+        let sixteen = const { NonZeroU32::new(16).unwrap() };
+
+        Self {
+            candidates,
+            sample_interval:             sixteen,
+            saved_bytes_per_microsecond: 32,
+        }
+    }
 }
 
 // TODO: add link to `get` below.
@@ -358,11 +488,13 @@ pub struct FilterOptions<Policy> {
     /// The [`FilterPolicy`] to use for the database. Filters improve the performance of
     /// random-access reads, such as calls to `get`.
     ///
-    /// When reading a database, any filters for other filter policies are ignored.
+    /// When reading a database, any filters for other filter policies are ignored, except for
+    /// filters of the policies named by [`FilterPolicy::compatible_names`].
     ///
     /// LevelDB provides a default Bloom filter implementation.
     ///
     /// [`FilterPolicy`]: crate::pub_traits::cmp_and_policy::FilterPolicy
+    /// [`FilterPolicy::compatible_names`]: crate::db_options::FilterPolicy::compatible_names
     pub filter_policy:          Option<Policy>,
     /// One filter is generated per `1 << filter_chunk_size_log2` bytes of key data.
     ///
@@ -413,6 +545,38 @@ impl FilterOptions<BloomPolicy> {
     }
 }
 
+impl FilterOptions<BlockedBloomPolicy> {
+    #[inline]
+    #[must_use]
+    pub const fn default_blocked_bloom_policy() -> Self {
+        Self::from_filter_policy(Some(BlockedBloomPolicy::new(
+            BlockedBloomPolicy::DEFAULT_BITS_PER_KEY,
+        )))
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn blocked_bloom_policy_with_bits(bits_per_key: u8) -> Self {
+        Self::from_filter_policy(Some(BlockedBloomPolicy::new(bits_per_key)))
+    }
+}
+
+impl FilterOptions<BinaryFusePolicy> {
+    /// The [`filter_chunk_size_log2`] used by [`Self::binary_fuse_policy`]. One filter is
+    /// generated per 16 MiB of a table file, so that a table file usually has a single filter
+    /// which covers enough keys for binary fuse filters to be smaller than Bloom filters.
+    ///
+    /// [`filter_chunk_size_log2`]: FilterOptions::filter_chunk_size_log2
+    pub const BINARY_FUSE_FILTER_CHUNK_SIZE_LOG2: u8 = 24;
+
+    #[inline]
+    #[must_use]
+    pub const fn binary_fuse_policy(fingerprint_size: FuseFingerprintSize) -> Self {
+        Self::from_filter_policy(Some(BinaryFusePolicy::new(fingerprint_size)))
+            .with_filter_chunk_size_log2(Self::BINARY_FUSE_FILTER_CHUNK_SIZE_LOG2)
+    }
+}
+
 impl<Policy: Default> Default for FilterOptions<Policy> {
     #[inline]
     fn default() -> Self {
@@ -651,6 +815,17 @@ pub struct MemtableOptions {
     ///
     /// Defaults to 4.
     pub memtable_pool_size:        NonZeroU8,
+    /// If set, the write-ahead log is incrementally synced to persistent storage each time
+    /// approximately this many more bytes have been written to it without being synced by
+    /// a synchronous write.
+    ///
+    /// This bounds the amount of unsynced data that a later synchronous write (or the operating
+    /// system) must flush at once. As with [`SSTableOptions::bytes_per_sync`], only the
+    /// newly-written range is synced where the filesystem supports it. Unsynchronous writes are
+    /// still not durable until the log is fully synced.
+    ///
+    /// Defaults to `None`. Not clamped.
+    pub write_log_bytes_per_sync:  Option<NonZeroU64>,
 }
 
 impl Default for MemtableOptions {
@@ -664,6 +839,7 @@ impl Default for MemtableOptions {
             max_write_log_file_size:   FileSize(8 << 20_u8),
             max_reused_write_log_size: FileSize(8 << 20_u8),
             memtable_pool_size:        four,
+            write_log_bytes_per_sync:  None,
         }
     }
 }
@@ -716,8 +892,26 @@ pub struct SSTableOptions {
     /// Defaults to 16. Not clamped. This option can be dynamically changed while the database is
     /// running. (The dynamic values are not clamped.)
     pub block_restart_interval: NonZeroU32,
-    // TODO: Could add a `sst_bytes_per_sync`, like what RocksDB has. However, it'd be best to
-    // get benchmarks to motivate such a setting and its default value.
+    /// While an SSTable is being written, its data is incrementally synced to persistent storage
+    /// each time approximately this many more bytes have been written, so that the final sync
+    /// performed once the table is complete does not cause a long I/O stall.
+    ///
+    /// Where the filesystem supports it (see [`WritableFile::sync_data_range`]), only the
+    /// newly-written range is synced, and the sync need not wait for the data to reach
+    /// persistent storage.
+    ///
+    /// Defaults to `None`, which performs only the final sync. Values around 1 MiB are
+    /// reasonable. Not clamped.
+    ///
+    /// [`WritableFile::sync_data_range`]: anchored_vfs::WritableFile::sync_data_range
+    pub bytes_per_sync:         Option<NonZeroU64>,
+    /// If set, a compression dictionary is trained for each SSTable file and used to compress
+    /// every data block of that table.
+    ///
+    /// See [`CompressionDictionaryOptions`].
+    ///
+    /// Defaults to `None`.
+    pub compression_dictionary: Option<CompressionDictionaryOptions>,
 }
 
 impl Default for SSTableOptions {
@@ -731,13 +925,74 @@ impl Default for SSTableOptions {
             max_sstable_sizes:      [FileSize(1 << 20_u8); LEN],
             sstable_block_size:     4 << 10_u8,
             block_restart_interval: sixteen,
+            bytes_per_sync:         None,
+            compression_dictionary: None,
+        }
+    }
+}
+
+/// Options for training a compression dictionary for each SSTable file.
+///
+/// When many small and similar entries are compressed one data block at a time, most of what they
+/// have in common is repeated in every block. A dictionary trained on samples of the whole table
+/// lets each block refer to that common data instead. The dictionary is stored in the table file,
+/// and tables without a dictionary remain readable.
+///
+/// Only codecs which support dictionaries (such as Zstd) make use of them; see
+/// [`CompressionCodec::train_dictionary`]. Dictionaries are not used by tables whose compressor
+/// is `None`, and if training fails, the table is written without a dictionary.
+///
+/// Data blocks compressed with a dictionary are tagged with [`dictionary_compressor_id`] instead
+/// of the ID of their compressor, so that other LevelDB implementations report them as using an
+/// unsupported compressor rather than misreading them.
+///
+/// [`CompressionCodec::train_dictionary`]: crate::db_options::CompressionCodec::train_dictionary
+/// [`dictionary_compressor_id`]: CompressionDictionaryOptions::dictionary_compressor_id
+#[derive(Debug, Clone, Copy)]
+pub struct CompressionDictionaryOptions {
+    /// The maximum length of the dictionary trained for each table.
+    ///
+    /// Defaults to 16 KiB. Not clamped.
+    pub max_dictionary_size:      usize,
+    /// Approximately how many bytes of uncompressed data blocks are used as samples to train the
+    /// dictionary of each table.
+    ///
+    /// The sampled blocks are buffered in memory until the dictionary is trained, after which
+    /// they and all later blocks are written to the table file.
+    ///
+    /// Defaults to 1 MiB. Clamped to at most 1 GiB.
+    pub max_sample_bytes:         usize,
+    /// The compressor ID recorded in the trailer of each data block compressed with a
+    /// dictionary. The compressor the dictionary was trained for is recorded alongside the
+    /// dictionary.
+    ///
+    /// This ID must not be used by any codec of the chosen set of compression codecs. If the
+    /// codecs report their names (see [`CompressionCodecs::codec_names`]) and one of them uses
+    /// this ID, dictionaries are not used.
+    ///
+    /// Defaults to 255.
+    ///
+    /// [`CompressionCodecs::codec_names`]: crate::db_options::CompressionCodecs::codec_names
+    pub dictionary_compressor_id: CompressorId,
+}
+
+impl Default for CompressionDictionaryOptions {
+    #[inline]
+    fn default() -> Self {
+        #[allow(clippy::unwrap_used, reason = "validated at compile time")]
+        let max_id = const { NonZeroU8::new(u8::MAX).unwrap() };
+
+        Self {
+            max_dictionary_size:      16 << 10_u8,
+            max_sample_bytes:         1 << 20_u8,
+            dictionary_compressor_id: CompressorId(max_id),
         }
     }
 }
 
 /// Options for configuring compactions (other than options specific to size or seek compactions),
 /// which improve read performance and reduce the memory consumed by the database.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct CompactionOptions {
     /// Whether compactions should be performed in a background thread (rather than on foreground
     /// user threads as necessary).
@@ -756,6 +1011,11 @@ pub struct CompactionOptions {
     /// The set of input files to a compaction may be expanded if the expanded compaction would not
     /// exceed this limit. The `i`-th limit corresponds to level `i+1`.
     ///
+    /// With [`CompactionStyle::Tiered`], this instead limits how much of a sorted run is merged
+    /// into the next-older run by a single compaction; at least one file is always merged. It
+    /// also limits how many sorted runs are merged at once when the space amplification limit
+    /// is exceeded.
+    ///
     /// Defaults to 25 MiB.
     pub max_compaction_inputs:        [u64; NUM_NONZERO_LEVELS_USIZE.get()],
     /// An approximate limit (in bytes) on the amount of level-`n+1` data that a single output file
@@ -766,6 +1026,62 @@ pub struct CompactionOptions {
     ///
     /// Defaults to 10 MiB.
     pub max_grandparent_overlap:      [u64; NUM_MIDDLE_LEVELS_USIZE.get()],
+    /// The strategy used to choose automatic compactions based on the sizes of levels.
+    ///
+    /// Defaults to [`CompactionStyle::Leveled`].
+    pub style:                        CompactionStyle,
+    /// If set, a table file in which at least this fraction of entries are deletions is
+    /// compacted into the next level, even if no level is too large. This reclaims the space
+    /// used by deleted entries in key ranges which are rarely written to.
+    ///
+    /// Files in the last level are never compacted this way. Ignored by
+    /// [`CompactionStyle::Fifo`].
+    ///
+    /// The number of deletions is recorded in a `table_properties` meta block of each table file
+    /// written by this crate, regardless of this setting. Other LevelDB implementations ignore
+    /// that block, and table files written by them are never compacted this way. Since the
+    /// `MANIFEST` does not record the number of deletions, a table file which already existed
+    /// when the database was opened is not compacted this way until it has been read.
+    ///
+    /// Defaults to `None`.
+    pub max_deletion_ratio:           Option<f64>,
+    /// If set, a table file older than this is compacted into the next level, even if no level
+    /// is too large, so that old data is eventually rewritten.
+    ///
+    /// Files in the last level are never compacted this way. Ignored by
+    /// [`CompactionStyle::Fifo`].
+    ///
+    /// The time at which each table file was written is recorded in its `table_properties` meta
+    /// block (see [`max_deletion_ratio`]). Until a table file which already existed when the
+    /// database was opened has been read, and for table files written by other LevelDB
+    /// implementations, the file is considered to have been written when the database was opened.
+    ///
+    /// Defaults to `None`.
+    ///
+    /// [`max_deletion_ratio`]: CompactionOptions::max_deletion_ratio
+    pub periodic_compaction_age:      Option<Duration>,
+    /// If set, chooses additional user-key boundaries at which compactions begin a new output
+    /// table file, so that table files can be aligned with logical divisions of the key space.
+    ///
+    /// Defaults to `None`.
+    pub partitioner:                  Option<Arc<dyn CompactionPartitioner + Send + Sync>>,
+    /// If set, limits the rate (in bytes per second) at which compactions and memtable flushes
+    /// write table files. While user reads or synced writes are in progress, rate-limited
+    /// compaction I/O briefly yields to them.
+    ///
+    /// Can be changed while the database is running with [`DB::set_compaction_rate_limit`].
+    ///
+    /// Defaults to `None`.
+    ///
+    /// [`DB::set_compaction_rate_limit`]: crate::DB::set_compaction_rate_limit
+    pub rate_limit:                   Option<NonZeroU64>,
+    /// Whether the bytes read by compactions also count against [`rate_limit`]. The bytes read
+    /// are approximated by the uncompressed size of the entries read.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`rate_limit`]: CompactionOptions::rate_limit
+    pub rate_limit_reads:             bool,
 }
 
 impl Default for CompactionOptions {
@@ -782,6 +1098,150 @@ impl Default for CompactionOptions {
             max_level_for_memtable_flush: level_2,
             max_compaction_inputs:        [25 << 20_u8; LEN1],
             max_grandparent_overlap:      [10 << 20_u8; LEN2],
+            style:                        CompactionStyle::Leveled,
+            max_deletion_ratio:           None,
+            periodic_compaction_age:      None,
+            partitioner:                  None,
+            rate_limit:                   None,
+            rate_limit_reads:             false,
+        }
+    }
+}
+
+/// The strategy used to choose automatic compactions based on the sizes of levels.
+///
+/// Every style produces databases in the same format, so a database may be reopened with a
+/// different style (or by other LevelDB implementations).
+#[derive(Debug, Clone, Copy)]
+pub enum CompactionStyle {
+    /// LevelDB's strategy, in which each level is limited in size by [`SizeCompactionOptions`],
+    /// and data is compacted from a level into the next level once the level is too large.
+    ///
+    /// This minimizes read and space amplification at the cost of greater write amplification.
+    Leveled,
+    /// A strategy in which sorted runs of similar size are merged together.
+    ///
+    /// This reduces write amplification at the cost of greater read and space amplification.
+    /// See [`TieredCompactionOptions`] for more information.
+    Tiered(TieredCompactionOptions),
+    /// A strategy for databases used as bounded caches, in which files are never merged, and the
+    /// oldest data is deleted once the database is too large.
+    ///
+    /// See [`FifoCompactionOptions`] for more information.
+    Fifo(FifoCompactionOptions),
+}
+
+/// Options for [`CompactionStyle::Tiered`] compactions.
+///
+/// # Sorted runs
+/// Each level-0 table file is a sorted run, as is each nonempty nonzero level. Sorted runs are
+/// ordered from newest to oldest: level-0 files, then level 1, then level 2, and so on.
+///
+/// Once there are at least [`sorted_run_trigger`] sorted runs, a tiered compaction is chosen
+/// in the following order of preference:
+/// - If the total size of every sorted run except the oldest is large enough compared to the
+///   oldest run (see [`max_space_amplification`]), then every sorted run is merged into the
+///   oldest run. Runs are taken from oldest to newest for as long as their total size stays
+///   within [`max_compaction_inputs`], but the two oldest runs are always merged.
+/// - Otherwise, the newest run (treating all of level 0 as one run) whose next-older run is
+///   similar in size or smaller (see [`size_ratio`]) is merged into that next-older run.
+/// - Otherwise, the newest run is merged into its next-older run, except that level-0 files
+///   are instead moved together into a new sorted run if there is an empty level in which to
+///   place the run.
+///
+/// A single tiered compaction merges files of the newer run, in key order, only until the
+/// total size of its inputs would exceed [`max_compaction_inputs`]; the rest of the run is
+/// merged by later compactions.
+///
+/// If [`autocompact_level_zero`] or [`autocompact_nonzero_levels`] is disabled, tiered
+/// compactions out of level 0 or out of nonzero levels, respectively, are disabled.
+///
+/// [`max_compaction_inputs`]: CompactionOptions::max_compaction_inputs
+/// [`sorted_run_trigger`]: TieredCompactionOptions::sorted_run_trigger
+/// [`max_space_amplification`]: TieredCompactionOptions::max_space_amplification
+/// [`size_ratio`]: TieredCompactionOptions::size_ratio
+/// [`autocompact_level_zero`]: SizeCompactionOptions::autocompact_level_zero
+/// [`autocompact_nonzero_levels`]: SizeCompactionOptions::autocompact_nonzero_levels
+#[derive(Debug, Clone, Copy)]
+pub struct TieredCompactionOptions {
+    /// The number of sorted runs at which a tiered compaction is performed.
+    ///
+    /// This should be less than [`level0_write_throttle_trigger`], since most sorted runs are
+    /// level-0 files.
+    ///
+    /// Defaults to `4`.
+    ///
+    /// [`level0_write_throttle_trigger`]: WriteThrottlingOptions::level0_write_throttle_trigger
+    pub sorted_run_trigger:      NonZeroU16,
+    /// A run is considered similar in size to the next-newer run if it is at most this
+    /// percentage larger than the next-newer run.
+    ///
+    /// Defaults to `1` (percent).
+    pub size_ratio:              u32,
+    /// The maximum size of every sorted run except the oldest, as a percentage of the size of
+    /// the oldest sorted run, before every sorted run is merged into the oldest run.
+    ///
+    /// Defaults to `200` (percent).
+    pub max_space_amplification: u32,
+}
+
+impl Default for TieredCompactionOptions {
+    #[inline]
+    fn default() -> Self {
+        #[allow(clippy::unwrap_used, reason = "validated at compile time")]
+        let four = const { NonZeroU16::new(4).unwrap() };
+
+        Self {
+            sorted_run_trigger:      four,
+            size_ratio:              1,
+            max_space_amplification: 200,
+        }
+    }
+}
+
+/// Options for [`CompactionStyle::Fifo`] compactions.
+///
+/// Memtables are always flushed to level 0, and level-0 files are never merged. Instead, the
+/// oldest level-0 files are deleted (which deletes every entry in them) once the total size of
+/// level-0 files exceeds [`max_table_files_size`], or once they are older than
+/// [`max_file_age`].
+///
+/// Files in nonzero levels (for instance, from when the database used a different compaction
+/// style) are neither counted nor deleted, and seek compactions are not performed.
+///
+/// Since every table file is in level 0 and deleting level-0 files requires no rewriting, the
+/// level-0 triggers of [`WriteThrottlingOptions`] are ignored, and writes are never delayed or
+/// halted because of the number of level-0 files.
+///
+/// If [`autocompact_level_zero`] is disabled, FIFO deletions are disabled.
+///
+/// [`max_table_files_size`]: FifoCompactionOptions::max_table_files_size
+/// [`max_file_age`]: FifoCompactionOptions::max_file_age
+/// [`autocompact_level_zero`]: SizeCompactionOptions::autocompact_level_zero
+#[derive(Debug, Clone, Copy)]
+pub struct FifoCompactionOptions {
+    /// The maximum total size (in bytes) of level-0 table files.
+    ///
+    /// Defaults to 1 GiB.
+    pub max_table_files_size: u64,
+    /// If `Some`, level-0 files older than this are deleted, even if the size budget has not
+    /// been reached.
+    ///
+    /// The time at which each table file was written is recorded in the file. Until a table file
+    /// which already existed when the database was opened has been read, and for table files
+    /// written by other LevelDB implementations, the file's age is measured from when the
+    /// database was opened.
+    ///
+    /// Defaults to `None`.
+    pub max_file_age:         Option<Duration>,
+}
+
+impl Default for FifoCompactionOptions {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            max_table_files_size: 1 << 30_u8,
+            max_file_age:         None,
         }
     }
 }
@@ -792,13 +1252,44 @@ pub struct SizeCompactionOptions {
     pub autocompact_level_zero:     bool,
     /// Defaults to `true`.
     pub autocompact_nonzero_levels: bool,
-    /// Defaults to `4`.
+    /// Defaults to `4`. This option can be dynamically changed while the database is running.
     pub max_level0_files:           NonZeroU16,
     /// Defaults to 10 MiB for level 1, increasing by a factor of 10 for each higher level.
+    /// This option can be dynamically changed while the database is running.
+    ///
+    /// If [`dynamic_level_sizes`] is enabled, only the level-1 limit is used.
+    ///
+    /// [`dynamic_level_sizes`]: SizeCompactionOptions::dynamic_level_sizes
     pub max_level_sizes:            [u64; NUM_MIDDLE_LEVELS_USIZE.get()],
+    /// If `true`, the size limits of levels are computed backwards from the size of the
+    /// bottommost nonempty level, instead of being taken from [`max_level_sizes`].
+    ///
+    /// The bottommost nonempty level has no size limit. The level above it is limited to its
+    /// size divided by [`level_size_multiplier`], and each higher level is limited to the next
+    /// level's limit divided by [`level_size_multiplier`]. The highest level whose limit is at
+    /// least the level-1 limit in [`max_level_sizes`] is the base level, into which level-0
+    /// files are compacted; any levels between level 0 and the base level are kept empty.
+    ///
+    /// Memtables are always flushed to level 0 when this is enabled.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`max_level_sizes`]: SizeCompactionOptions::max_level_sizes
+    /// [`level_size_multiplier`]: SizeCompactionOptions::level_size_multiplier
+    pub dynamic_level_sizes:        bool,
+    /// The ratio between the size limits of adjacent levels, if [`dynamic_level_sizes`]
+    /// is enabled.
+    ///
+    /// Defaults to `10`.
+    ///
+    /// [`dynamic_level_sizes`]: SizeCompactionOptions::dynamic_level_sizes
+    pub level_size_multiplier:      NonZeroU16,
 }
 
 impl SizeCompactionOptions {
+    #[allow(clippy::unwrap_used, reason = "validated at compile time")]
+    const DEFAULT_LEVEL_SIZE_MULTIPLIER: NonZeroU16 = NonZeroU16::new(10).unwrap();
+
     #[inline]
     #[must_use]
     fn default_level_limits() -> (NonZeroU16, [u64; NUM_MIDDLE_LEVELS_USIZE.get()]) {
@@ -822,7 +1313,9 @@ impl SizeCompactionOptions {
             autocompact_level_zero:     true,
             autocompact_nonzero_levels: true,
             max_level0_files,
-            max_level_sizes
+            max_level_sizes,
+            dynamic_level_sizes:        false,
+            level_size_multiplier:      Self::DEFAULT_LEVEL_SIZE_MULTIPLIER,
         }
     }
 
@@ -834,7 +1327,9 @@ impl SizeCompactionOptions {
             autocompact_level_zero:     false,
             autocompact_nonzero_levels: false,
             max_level0_files,
-            max_level_sizes
+            max_level_sizes,
+            dynamic_level_sizes:        false,
+            level_size_multiplier:      Self::DEFAULT_LEVEL_SIZE_MULTIPLIER,
         }
     }
 }
@@ -856,6 +1351,8 @@ impl Default for SizeCompactionOptions {
             autocompact_nonzero_levels: true,
             max_level0_files:           four,
             max_level_sizes,
+            dynamic_level_sizes:        false,
+            level_size_multiplier:      Self::DEFAULT_LEVEL_SIZE_MULTIPLIER,
         }
     }
 }
@@ -963,16 +1460,92 @@ impl Default for SeekCompactionOptions {
     }
 }
 
+/// How the delay applied to writes grows as the database approaches a write halt.
+///
+/// See [`WriteThrottlingOptions`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StallCurve {
+    /// Once a throttle trigger is reached, each write is delayed by the full
+    /// [`throttle_sleep_duration`], as in Google's LevelDB.
+    ///
+    /// [`throttle_sleep_duration`]: WriteThrottlingOptions::throttle_sleep_duration
+    Step,
+    /// The delay grows in proportion to how far the database is past a throttle trigger,
+    /// relative to the corresponding halt trigger.
+    Linear,
+    /// The delay grows with the square of how far the database is past a throttle trigger,
+    /// relative to the corresponding halt trigger, so that writes are only slightly slowed until
+    /// the halt trigger is near.
+    Quadratic,
+}
+
+/// Options for slowing down writes when compactions are falling behind, and for how writers
+/// wait for each other.
+///
+/// Writes are throttled based on the number of level-0 table files and on the estimated number
+/// of bytes which compactions still need to rewrite. Past a throttle trigger, each write (or
+/// group of merged writes) is delayed by up to [`throttle_sleep_duration`], as determined by
+/// [`stall_curve`]. Past a halt trigger, writes wait for ongoing compactions to bring the
+/// database back below the halt trigger.
+///
+/// The time spent on delays and halts is reported by [`DB::write_stall_statistics`].
+///
+/// [`throttle_sleep_duration`]: WriteThrottlingOptions::throttle_sleep_duration
+/// [`stall_curve`]: WriteThrottlingOptions::stall_curve
+/// [`DB::write_stall_statistics`]: crate::DB::write_stall_statistics
 #[derive(Debug, Clone, Copy)]
 pub struct WriteThrottlingOptions {
-    /// Defaults to 8.
-    pub level0_write_throttle_trigger: NonZeroU16,
-    /// Defaults to 1 second.
-    pub throttle_sleep_duration:       Duration,
-    /// Defaults to 12.
-    pub level0_write_halt_trigger:     NonZeroU16,
-    // TODO: write_thread_adaptive_yield - RocksDB has this option to try to improve the
-    // performance of what I'd call the "contention queue".
+    /// Once level 0 has at least this many table files, writes are delayed.
+    ///
+    /// Defaults to 8. This option can be dynamically changed while the database is running.
+    /// Ignored by [`CompactionStyle::Fifo`].
+    pub level0_write_throttle_trigger:             NonZeroU16,
+    /// The longest delay applied to a single write (or group of merged writes) while writes
+    /// are throttled.
+    ///
+    /// Defaults to 1 millisecond.
+    pub throttle_sleep_duration:                   Duration,
+    /// Once level 0 has at least this many table files, writes are halted until compactions
+    /// reduce the number of level-0 files.
+    ///
+    /// Defaults to 12. This option can be dynamically changed while the database is running.
+    /// Ignored by [`CompactionStyle::Fifo`].
+    pub level0_write_halt_trigger:                 NonZeroU16,
+    /// Once compactions are estimated to need to rewrite at least this many bytes in order to
+    /// bring every level within its size limit, writes are delayed.
+    ///
+    /// The estimate is only computed for [`CompactionStyle::Leveled`] compactions.
+    ///
+    /// Defaults to 64 GiB. If `None`, pending compaction bytes do not delay writes. This option
+    /// can be dynamically changed while the database is running.
+    pub pending_compaction_bytes_throttle_trigger: Option<u64>,
+    /// Once compactions are estimated to need to rewrite at least this many bytes, writes are
+    /// halted until compactions catch up.
+    ///
+    /// Defaults to 256 GiB. If `None`, pending compaction bytes do not halt writes. This option
+    /// can be dynamically changed while the database is running.
+    pub pending_compaction_bytes_halt_trigger:     Option<u64>,
+    /// How the delay applied to writes grows between the throttle triggers and the halt
+    /// triggers.
+    ///
+    /// Defaults to [`StallCurve::Linear`].
+    pub stall_curve:                               StallCurve,
+    /// A writer which is waiting for another thread's write to finish first spins this many
+    /// times, checking whether it may proceed, before yielding or sleeping.
+    ///
+    /// Defaults to 200.
+    pub write_queue_spins:                         u32,
+    /// After spinning, a waiting writer repeatedly yields to other threads for up to this long
+    /// before going to sleep until it is woken.
+    ///
+    /// Spinning and yielding avoid the cost of sleeping and waking when writes are short, at
+    /// the cost of some CPU time. Set this and [`write_queue_spins`] to zero to always sleep
+    /// immediately.
+    ///
+    /// Defaults to 100 microseconds.
+    ///
+    /// [`write_queue_spins`]: WriteThrottlingOptions::write_queue_spins
+    pub write_queue_max_yield:                     Duration,
 }
 
 impl Default for WriteThrottlingOptions {
@@ -984,9 +1557,14 @@ impl Default for WriteThrottlingOptions {
         let twelve = const { NonZeroU16::new(12).unwrap() };
 
         Self {
-            level0_write_throttle_trigger: eight,
-            throttle_sleep_duration:       Duration::from_secs(1),
-            level0_write_halt_trigger:     twelve,
+            level0_write_throttle_trigger:             eight,
+            throttle_sleep_duration:                   Duration::from_millis(1),
+            level0_write_halt_trigger:                 twelve,
+            pending_compaction_bytes_throttle_trigger: Some(64 << 30_u8),
+            pending_compaction_bytes_halt_trigger:     Some(256 << 30_u8),
+            stall_curve:                               StallCurve::Linear,
+            write_queue_spins:                         200,
+            write_queue_max_yield:                     Duration::from_micros(100),
         }
     }
 }
@@ -0,0 +1,138 @@
+use std::num::NonZeroU64;
+
+use clone_behavior::FastMirroredClone;
+
+use anchored_vfs::LevelDBFilesystem;
+
+use crate::{
+    all_errors::{aliases::RwResult, types::OptionsError},
+    options::DynamicOptions,
+    pub_typed_bytes::{FileNumber, NonZeroLevel},
+};
+use crate::pub_traits::{
+    cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
+    compression::CompressionCodecs,
+    pool::BufferPool,
+};
+use super::structs::DB;
+
+
+impl<FS, Cmp, Policy, Codecs, Pool> DB<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Compact exactly the indicated table files into `output_level`, and wait for the compaction
+    /// to finish.
+    ///
+    /// Every input file not in `output_level` must be in the same level, and any files in
+    /// `output_level` which overlap the inputs are compacted along with them.
+    ///
+    /// If new compactions are prohibited (for instance, because the database is being closed or
+    /// a write error occurred), the compaction may silently not be performed.
+    ///
+    /// # Errors
+    /// Returns a [`WriteError::InvalidCompactFiles`] error if some input file is not live in the
+    /// current version of the database, or if the output level is not legal for the inputs
+    /// (including when files which were not provided would need to be compacted as well).
+    ///
+    /// [`WriteError::InvalidCompactFiles`]: crate::errors::WriteError::InvalidCompactFiles
+    pub fn compact_files(
+        &mut self,
+        files:        &[FileNumber],
+        output_level: NonZeroLevel,
+    ) -> RwResult<(), FS, Cmp, Codecs> {
+        let (db_state, per_handle) = self.inner();
+
+        let mut_state = db_state.files_compaction(&mut per_handle.decoders, files, output_level)?;
+        drop(mut_state);
+        Ok(())
+    }
+
+    /// Change the rate limit (in bytes per second) on the I/O performed by compactions and
+    /// memtable flushes. `None` removes the limit.
+    ///
+    /// See [`CompactionOptions::rate_limit`].
+    ///
+    /// [`CompactionOptions::rate_limit`]: crate::db_options::CompactionOptions::rate_limit
+    pub fn set_compaction_rate_limit(&self, bytes_per_second: Option<NonZeroU64>) {
+        self.db_state().mut_opts.rate_limiter.set_bytes_per_second(bytes_per_second);
+    }
+
+    /// Get the current values of the options which can be changed while the database is
+    /// running.
+    #[must_use]
+    pub fn dynamic_options(&self) -> DynamicOptions {
+        self.db_state().mut_opts.dynamic.read()
+    }
+
+    /// Change the options which can be changed while the database is running.
+    ///
+    /// The new compression and block options apply to table files started after this call,
+    /// and the new write throttling triggers apply to subsequent writes. Which size compactions
+    /// are needed is recomputed with the new size compaction targets, and a compaction may be
+    /// started as a result.
+    ///
+    /// # Errors
+    /// Returns an error, without changing any options, if a chosen compressor is not supported
+    /// by the database's compression codecs (checked only if the codecs report their names; see
+    /// [`CompressionCodecs::codec_names`]), or if a write throttle trigger exceeds the
+    /// corresponding write halt trigger.
+    pub fn set_dynamic_options(&mut self, options: DynamicOptions) -> Result<(), OptionsError> {
+        let (db_state, per_handle) = self.inner();
+        db_state.set_dynamic_options(&mut per_handle.decoders, options)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, clippy::unwrap_used, reason = "these are tests")]
+mod tests {
+    use crate::{
+        options::pub_options::{ReadOptions, WriteOptions},
+        pub_typed_bytes::{Close, CloseStatus},
+    };
+    use super::super::test_db::{TestDB, TestDir};
+
+
+    fn put(db: &mut TestDB, key: &[u8], value: &[u8]) {
+        let mut batch = db.indexed_write_batch();
+        batch.put(key, value).unwrap();
+        db.write_indexed_batch(&batch, &WriteOptions::default()).unwrap();
+    }
+
+    fn get(db: &mut TestDB, key: &[u8]) -> Option<Vec<u8>> {
+        let empty = db.indexed_write_batch();
+        db.get_with_batch(&empty, key, &ReadOptions::default()).unwrap()
+    }
+
+    fn close(db: TestDB) {
+        let (status, result) = db.close(Close::AfterCompaction);
+        result.unwrap();
+        assert!(matches!(status, CloseStatus::Closed));
+    }
+
+    /// Opening a database garbage collects its files, which must not remove the write-ahead log
+    /// that the opened database writes to.
+    #[test]
+    fn garbage_collection_keeps_current_log() {
+        let dir = TestDir::new("garbage_collection_keeps_current_log");
+
+        let mut db = dir.open();
+        assert!(db.live_files_metadata().log_path.exists());
+        put(&mut db, b"first", b"value");
+        close(db);
+
+        let mut db = dir.open();
+        assert!(db.live_files_metadata().log_path.exists());
+        put(&mut db, b"second", b"value");
+        close(db);
+
+        let mut db = dir.open();
+        assert_eq!(get(&mut db, b"first").as_deref(), Some(b"value".as_slice()));
+        assert_eq!(get(&mut db, b"second").as_deref(), Some(b"value".as_slice()));
+        close(db);
+    }
+}
@@ -0,0 +1,76 @@
+use clone_behavior::FastMirroredClone;
+
+use anchored_vfs::LevelDBFilesystem;
+
+use crate::{
+    all_errors::aliases::ConvertFormatResult,
+    internal_leveldb::InternalDBState,
+    options::pub_options::{CompressionOptions, OpenOptions},
+};
+use crate::pub_traits::{
+    cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
+    compression::CompressionCodecs,
+    pool::BufferPool,
+};
+
+
+/// Convert the table files of a closed LevelDB database to use different compression codecs,
+/// for instance to convert a database from Mojang's LevelDB format (with zlib compression) to
+/// Google's LevelDB format (with Snappy or Zstandard compression).
+///
+/// The database is opened with `options` (which must use the database's current compression
+/// codecs), and every table file is rewritten with `new_codecs`, using the compressors chosen by
+/// `new_compression`. Every entry's key, sequence number, and level is preserved. A new
+/// `MANIFEST` file referring to the rewritten table files is then written; the database only
+/// switches to the converted table files once the new `MANIFEST` file is in place, so if an error
+/// occurs, the database is left unchanged.
+///
+/// `.log` files are left untouched, since they are not compressed. The memtable is not recovered,
+/// and `.log` files are instead recovered the next time that the database is opened.
+///
+/// `progress` is called after each table file is converted.
+///
+/// The `create_if_missing` and `error_if_exists` settings of `options` are ignored, and treated
+/// as `false`. Afterwards, the database should be opened with `new_codecs`.
+pub fn convert_db_format<FS, Cmp, Policy, Codecs, Pool, NewCodecs, Progress>(
+    options:         OpenOptions<FS, Cmp, Policy, Codecs, Pool>,
+    new_codecs:      NewCodecs,
+    new_compression: CompressionOptions,
+    progress:        Progress,
+) -> ConvertFormatResult<(), FS, Cmp, Codecs, NewCodecs>
+where
+    // TODO: Loosen `Send + Sync` requirements
+    FS:                         LevelDBFilesystem + Send + Sync + 'static,
+    FS::RandomAccessFile:       Send + Sync,
+    FS::WriteFile:              Send + Sync,
+    FS::Lockfile:               Send,
+    FS::Error:                  Send,
+    Cmp:                        LevelDBComparator + FastMirroredClone + Send + Sync + 'static,
+    Cmp::InvalidKeyError:       Send,
+    Policy:                     FilterPolicy + FastMirroredClone + Send + Sync + 'static,
+    Policy::Eq:                 CoarserThan<Cmp::Eq>,
+    Codecs:                     CompressionCodecs + Send + Sync + 'static,
+    Codecs::Encoders:           Send,
+    Codecs::Decoders:           Send,
+    Codecs::CompressionError:   Send,
+    Codecs::DecompressionError: Send,
+    Pool:                       BufferPool<PooledBuffer: Send + Sync> + Send + Sync + 'static,
+    NewCodecs:                  CompressionCodecs + 'static,
+    NewCodecs::Encoders:        Send,
+    Progress:                   FnMut(ConvertFormatProgress),
+{
+    InternalDBState::convert_format(options, new_codecs, new_compression, progress)
+}
+
+/// The progress of [`convert_db_format`], reported after each table file is converted.
+#[derive(Debug, Clone, Copy)]
+pub struct ConvertFormatProgress {
+    /// The number of table files which have been converted so far.
+    pub converted_tables: usize,
+    /// The total number of table files which need to be converted.
+    pub total_tables:     usize,
+    /// The total size of the table files which have been converted so far.
+    pub bytes_read:       u64,
+    /// The total size of the new table files which have been written so far.
+    pub bytes_written:    u64,
+}
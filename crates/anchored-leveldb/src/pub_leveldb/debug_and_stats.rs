@@ -1,15 +1,157 @@
+use std::{path::PathBuf, sync::atomic::Ordering, time::Duration};
 use std::fmt::{Debug, Formatter, Result as FmtResult};
 
 use anchored_vfs::LevelDBFilesystem;
 
-use crate::pub_traits::{
-    cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
-    compression::CompressionCodecs,
-    pool::BufferPool,
+use crate::database_files::LevelDBFileName;
+use crate::{
+    pub_traits::{
+        cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
+        compression::CompressionCodecs,
+        pool::BufferPool,
+    },
+    pub_typed_bytes::{FileNumber, FileSize, Level, SequenceNumber},
 };
 use super::structs::{DB, DBState};
 
 
+/// Metadata about the live files of a database, as returned by [`DB::live_files_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveFilesMetadata {
+    /// The table files of the current version of the database, sorted by level.
+    ///
+    /// The files of each nonzero level are sorted by key range. Level-0 files are in no
+    /// particular order.
+    pub table_files:     Vec<LiveTableFileMetadata>,
+    /// The file number of the write-ahead log currently being written to.
+    ///
+    /// While a memtable is being compacted, the older write-ahead log backing that memtable is
+    /// also still needed; it is not listed.
+    pub log_number:      FileNumber,
+    /// The path of the `.log` file indicated by `log_number`.
+    pub log_path:        PathBuf,
+    /// The file number of the current `MANIFEST` file.
+    pub manifest_number: FileNumber,
+    /// The path of the `MANIFEST` file indicated by `manifest_number`.
+    pub manifest_path:   PathBuf,
+}
+
+/// Metadata about a live table file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveTableFileMetadata {
+    pub level:             Level,
+    pub file_number:       FileNumber,
+    /// The path of the table file, which has either a `.ldb` or legacy `.sst` extension.
+    pub path:              PathBuf,
+    pub file_size:         FileSize,
+    pub smallest_user_key: Vec<u8>,
+    pub largest_user_key:  Vec<u8>,
+    /// The smallest and largest sequence numbers of entries in the table file.
+    ///
+    /// `None` if unknown, since they are only recorded for table files written by this crate.
+    pub sequence_range:    Option<(SequenceNumber, SequenceNumber)>,
+    /// The number of remaining reads which may seek through this file before a seek compaction
+    /// of the file is triggered.
+    pub remaining_seeks:   u32,
+}
+
+/// Cumulative statistics about writes slowed down by write throttling, as returned by
+/// [`DB::write_stall_statistics`].
+///
+/// See [`WriteThrottlingOptions`].
+///
+/// [`WriteThrottlingOptions`]: crate::db_options::WriteThrottlingOptions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WriteStallStatistics {
+    /// The number of writes which were delayed because compactions were falling behind.
+    pub delayed_writes: u64,
+    /// The total time spent sleeping by delayed writes.
+    pub total_delay:    Duration,
+    /// The number of writes which were halted until compactions caught up.
+    pub halted_writes:  u64,
+    /// The total time spent waiting by halted writes.
+    pub total_halt:     Duration,
+}
+
+impl<FS, Cmp, Policy, Codecs, Pool> DB<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>>,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Get metadata about every live table file of the current version of the database, along
+    /// with the current write-ahead log and `MANIFEST` file.
+    ///
+    /// The returned metadata is a snapshot; compactions may add or remove files at any time.
+    #[must_use]
+    pub fn live_files_metadata(&self) -> LiveFilesMetadata {
+        let db_state = self.db_state();
+
+        let mut_state = db_state.lock_mutable_state();
+        let version = mut_state.version_set.cloned_current_version();
+        let log_number = mut_state.write_log_number;
+        let manifest_number = mut_state.version_set.manifest_file_number();
+        drop(mut_state);
+
+        let db_directory = &db_state.opts.db_directory;
+        let filesystem = &db_state.mut_opts.filesystem;
+
+        let mut table_files = Vec::new();
+        for level in Level::ALL_LEVELS {
+            for file in version.level_files(level).inner() {
+                let file_number = file.file_number();
+
+                let mut path = LevelDBFileName::Table { file_number }.file_path(db_directory);
+                // Only fall back to the legacy extension if the usual path definitely does not
+                // exist.
+                if matches!(filesystem.file_exists(&path), Ok(false)) {
+                    path = LevelDBFileName::TableLegacyExtension { file_number }
+                        .file_path(db_directory);
+                }
+
+                table_files.push(LiveTableFileMetadata {
+                    level,
+                    file_number,
+                    path,
+                    file_size:         file.file_size(),
+                    smallest_user_key: file.smallest_user_key().inner().to_vec(),
+                    largest_user_key:  file.largest_user_key().inner().to_vec(),
+                    sequence_range:    file.properties()
+                        .map(|properties| (properties.smallest_seq, properties.largest_seq)),
+                    remaining_seeks:   file.remaining_seeks(),
+                });
+            }
+        }
+
+        LiveFilesMetadata {
+            table_files,
+            log_number,
+            log_path:        LevelDBFileName::Log { file_number: log_number }
+                .file_path(db_directory),
+            manifest_number,
+            manifest_path:   LevelDBFileName::Manifest { file_number: manifest_number }
+                .file_path(db_directory),
+        }
+    }
+
+    /// Get cumulative statistics about writes which were delayed or halted since the database
+    /// was opened.
+    #[must_use]
+    pub fn write_stall_statistics(&self) -> WriteStallStatistics {
+        let counters = &self.db_state().write_stalls;
+
+        WriteStallStatistics {
+            delayed_writes: counters.delayed_writes.load(Ordering::Relaxed),
+            total_delay:    Duration::from_nanos(counters.delay_nanos.load(Ordering::Relaxed)),
+            halted_writes:  counters.halted_writes.load(Ordering::Relaxed),
+            total_halt:     Duration::from_nanos(counters.halt_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+
 impl<FS, Cmp, Policy, Codecs, Pool> Debug for DB<FS, Cmp, Policy, Codecs, Pool>
 where
     FS:     Debug + LevelDBFilesystem<
@@ -0,0 +1,363 @@
+use std::str;
+use std::{cmp::Reverse, convert::Infallible, io::Read as _, num::NonZeroU8, path::Path};
+
+use anchored_vfs::{IntoChildFileIterator as _, LevelDBFilesystem};
+
+#[cfg(feature = "google-leveldb-compression")]
+use crate::compression::GoogleLevelDBCodecs;
+#[cfg(feature = "mojang-leveldb-compression")]
+use crate::compression::MojangLevelDBCodecs;
+#[cfg(any(feature = "google-leveldb-compression", feature = "mojang-leveldb-compression"))]
+use crate::options::pub_options::FormatSettings;
+use crate::{
+    all_errors::types::{DetectFormatError, DetectFormatErrorKind, FilesystemError, OpenFsError},
+    binary_block_log::{BinaryBlockLogReaderBuffers, ManifestRecordResult},
+    database_files::LevelDBFileName,
+    options::pub_options::SeekCompactionOptions,
+    pub_traits::{
+        cmp_and_policy::{BytewiseComparator, LevelDBComparator as _},
+        compression::CompressorId,
+        error_handler::{DefaultOpenHandler, DefaultOpenHandlerOptions},
+    },
+    pub_typed_bytes::{BinaryLogBlockSize, FileSize},
+    sstable::{SAMPLED_PREFIX_LEN, sample_table_blocks},
+    version::VersionEdit,
+};
+
+
+/// The maximum number of table files whose blocks are sampled by [`detect_format`].
+const MAX_SAMPLED_TABLES: usize = 8;
+/// The maximum number of data blocks sampled from each table file by [`detect_format`].
+const MAX_SAMPLED_DATA_BLOCKS_PER_TABLE: usize = 4;
+
+/// The magic number at the start of every Zstd frame.
+const ZSTD_FRAME_MAGIC: [u8; SAMPLED_PREFIX_LEN] = [0x28, 0xB5, 0x2F, 0xFD];
+
+
+/// Guess the persistent format of an existing LevelDB database.
+///
+/// The comparator name recorded in the database's `MANIFEST` is read. Then, the compressor IDs in
+/// the trailers of a few blocks from the most recent table files are sampled.
+///
+/// Google's leveldb uses compressor IDs `1` and `2` for Snappy and Zstd, while Mojang's fork uses
+/// compressor IDs `2` and `4` for zlib with and without a zlib header. Blocks with compressor ID
+/// `2` are told apart by whether they begin with a Zstd frame or a zlib header.
+///
+/// If no compressed blocks are found, any format using the bytewise comparator can read the
+/// database, and Google's format is preferred over Mojang's.
+///
+/// This function neither modifies the database nor acquires its lockfile. The returned format is
+/// only a heuristic guess, and should be double-checked (for instance, by opening the database
+/// and reading from it) if the source of the database is not trusted.
+pub fn detect_format<FS: LevelDBFilesystem>(
+    filesystem:   &FS,
+    db_directory: &Path,
+) -> Result<SuggestedFormat, DetectFormatError<FS::Error>> {
+    let detect_format_err = |kind| DetectFormatError {
+        db_directory: db_directory.to_owned(),
+        kind,
+    };
+
+    let mut evidence = CodecEvidence::default();
+
+    let comparator_name = read_manifest(filesystem, db_directory).map_err(detect_format_err)?;
+    sample_tables(filesystem, db_directory, &mut evidence)
+        .map_err(detect_format_err)?;
+
+    Ok(evidence.suggest(comparator_name))
+}
+
+/// The format settings which a LevelDB database most likely uses, as guessed by
+/// [`detect_format`].
+#[derive(Debug, Clone)]
+pub enum SuggestedFormat {
+    /// The database appears to have been written by Google's leveldb.
+    #[cfg(feature = "google-leveldb-compression")]
+    GoogleLevelDB(FormatSettings<BytewiseComparator, GoogleLevelDBCodecs>),
+    /// The database appears to have been written by Mojang's fork of LevelDB.
+    #[cfg(feature = "mojang-leveldb-compression")]
+    MojangLevelDB(FormatSettings<BytewiseComparator, MojangLevelDBCodecs>),
+    /// No format could be suggested.
+    Unknown(UnknownFormat),
+}
+
+/// The reason that [`detect_format`] could not suggest a format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnknownFormat {
+    /// The database's `MANIFEST` records a comparator other than LevelDB's default bytewise
+    /// comparator.
+    ///
+    /// # Data
+    /// The recorded comparator name.
+    UnknownComparator(Vec<u8>),
+    /// Compressor IDs used by neither Google's leveldb nor Mojang's fork were found in block
+    /// trailers.
+    ///
+    /// # Data
+    /// The unfamiliar compressor IDs, in the order they were found.
+    UnknownCompressorIds(Vec<CompressorId>),
+    /// Evidence of both Google's and Mojang's compression codecs was found.
+    ConflictingCodecs,
+    /// The database's codecs were detected, but the crate feature providing them is not enabled.
+    CodecsNotEnabled(DetectedCodecs),
+}
+
+/// The compression codecs which [`detect_format`] found evidence of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectedCodecs {
+    /// The codecs of Google's leveldb, provided by the `google-leveldb-compression` feature.
+    GoogleLevelDB,
+    /// The codecs of Mojang's fork of LevelDB, provided by the `mojang-leveldb-compression`
+    /// feature.
+    MojangLevelDB,
+    /// No compressed blocks were found.
+    Uncompressed,
+}
+
+/// Read the comparator name recorded in the database's `MANIFEST`.
+///
+/// Corruption in the `MANIFEST` ends the search early rather than causing an error.
+fn read_manifest<FS: LevelDBFilesystem>(
+    filesystem:   &FS,
+    db_directory: &Path,
+) -> Result<Option<Vec<u8>>, DetectFormatErrorKind<FS::Error>> {
+    let current_path = LevelDBFileName::Current.file_path(db_directory);
+    let mut current = filesystem.open_sequential(&current_path)
+        .map_err(|fs_err| DetectFormatErrorKind::Filesystem(
+            FilesystemError::FsError(fs_err),
+            OpenFsError::OpenCurrent,
+        ))?;
+
+    let mut full_manifest_name = Vec::new();
+    current.read_to_end(&mut full_manifest_name)
+        .map_err(|io_err| DetectFormatErrorKind::Filesystem(
+            FilesystemError::Io(io_err),
+            OpenFsError::ReadCurrent,
+        ))?;
+
+    drop(current);
+
+    let manifest_file_number = str::from_utf8(full_manifest_name.trim_ascii_end()).ok()
+        .and_then(LevelDBFileName::parse)
+        .and_then(|file_name| {
+            if let LevelDBFileName::Manifest { file_number } = file_name {
+                Some(file_number)
+            } else {
+                None
+            }
+        });
+    let Some(manifest_file_number) = manifest_file_number else {
+        return Err(DetectFormatErrorKind::CorruptedCurrent(full_manifest_name));
+    };
+
+    let manifest_path = LevelDBFileName::Manifest { file_number: manifest_file_number }
+        .file_path(db_directory);
+    let manifest_file_size = filesystem.size_of_file(&manifest_path)
+        .map(FileSize)
+        .map_err(|fs_err| DetectFormatErrorKind::Filesystem(
+            FilesystemError::FsError(fs_err),
+            OpenFsError::SizeOfManifest(manifest_file_number),
+        ))?;
+    let manifest_file = filesystem.open_sequential(&manifest_path)
+        .map_err(|fs_err| DetectFormatErrorKind::Filesystem(
+            FilesystemError::FsError(fs_err),
+            OpenFsError::OpenManifest(manifest_file_number),
+        ))?;
+
+    let mut log_buffers = BinaryBlockLogReaderBuffers::new(BinaryLogBlockSize::DEFAULT);
+    let mut manifest_reader = log_buffers.read_manifest(manifest_file, manifest_file_size)
+        .map_err(|io_err| DetectFormatErrorKind::Filesystem(
+            FilesystemError::Io(io_err),
+            OpenFsError::ReadManifest(manifest_file_number),
+        ))?;
+    let mut handler = DefaultOpenHandler::<Infallible>::new(
+        DefaultOpenHandlerOptions::never_reuse_files(),
+    );
+
+    let mut comparator_name = None;
+
+    loop {
+        let record = match manifest_reader.read_record(&mut handler) {
+            ManifestRecordResult::Some(record) => record,
+            ManifestRecordResult::EndOfFile | ManifestRecordResult::HandlerReportedError => break,
+            ManifestRecordResult::ReadError(io_err) => return Err(
+                DetectFormatErrorKind::Filesystem(
+                    FilesystemError::Io(io_err),
+                    OpenFsError::ReadManifest(manifest_file_number),
+                ),
+            ),
+        };
+
+        let mut edit_input = record.data;
+        let edit_result = VersionEdit::decode_from(
+            &mut edit_input,
+            SeekCompactionOptions::default(),
+            |_| Ok::<(), Infallible>(()),
+        );
+        let Ok(edit) = edit_result else { continue };
+
+        if let Some(recorded_cmp_name) = edit.comparator_name {
+            comparator_name = Some(recorded_cmp_name.into_owned());
+        }
+    }
+
+    Ok(comparator_name)
+}
+
+/// Sample the blocks of the most recent table files in the database directory.
+///
+/// Table files which cannot be opened or read are skipped.
+fn sample_tables<FS: LevelDBFilesystem>(
+    filesystem:   &FS,
+    db_directory: &Path,
+    evidence:     &mut CodecEvidence,
+) -> Result<(), DetectFormatErrorKind<FS::Error>> {
+    let read_dir_err = |fs_err: FS::Error| DetectFormatErrorKind::Filesystem(
+        FilesystemError::FsError(fs_err),
+        OpenFsError::ReadDatabaseDirectory,
+    );
+
+    let db_files = filesystem.child_files(db_directory).map_err(read_dir_err)?;
+    let mut table_files = Vec::new();
+
+    for file in db_files.child_files() {
+        let (file_name, file_size) = file.map_err(|fs_err| read_dir_err(fs_err.into()))?;
+
+        // All of LevelDB's files have ASCII names, so files with non-UTF-8 names can be ignored.
+        let Some(utf8_file_name) = file_name.as_os_str().to_str() else { continue };
+
+        if let Some(
+            LevelDBFileName::Table { file_number }
+            | LevelDBFileName::TableLegacyExtension { file_number },
+        ) = LevelDBFileName::parse(utf8_file_name) {
+            table_files.push((file_number, file_name, FileSize(file_size)));
+        }
+    }
+
+    // More recent table files are more likely to reflect how the database is currently written.
+    table_files.sort_unstable_by_key(|&(file_number, _, _)| Reverse(file_number));
+
+    for (_, file_name, file_size) in table_files.into_iter().take(MAX_SAMPLED_TABLES) {
+        let Ok(table_file) = filesystem.open_random_access(&db_directory.join(file_name)) else {
+            continue;
+        };
+
+        sample_table_blocks(
+            &table_file,
+            file_size,
+            MAX_SAMPLED_DATA_BLOCKS_PER_TABLE,
+            |compressor_id, block_prefix| evidence.add_block(compressor_id, block_prefix),
+        );
+    }
+
+    Ok(())
+}
+
+#[derive(Default, Debug)]
+struct CodecEvidence {
+    google_leveldb: bool,
+    mojang_leveldb: bool,
+    unknown_ids:    Vec<CompressorId>,
+}
+
+impl CodecEvidence {
+    fn add_block(&mut self, compressor_id: u8, block_prefix: &[u8]) {
+        let Some(compressor_id) = NonZeroU8::new(compressor_id) else {
+            // The block is not compressed.
+            return;
+        };
+
+        match compressor_id.get() {
+            1 => self.google_leveldb = true,
+            2 => {
+                if block_prefix == ZSTD_FRAME_MAGIC.as_slice() {
+                    self.google_leveldb = true;
+                } else if is_zlib_header(block_prefix) {
+                    self.mojang_leveldb = true;
+                } else {
+                    // Inconclusive; the block might be corrupted.
+                }
+            }
+            4 => self.mojang_leveldb = true,
+            _ => self.add_unknown_id(CompressorId(compressor_id)),
+        }
+    }
+
+    fn add_unknown_id(&mut self, compressor_id: CompressorId) {
+        if !self.unknown_ids.contains(&compressor_id) {
+            self.unknown_ids.push(compressor_id);
+        }
+    }
+
+    fn suggest(self, comparator_name: Option<Vec<u8>>) -> SuggestedFormat {
+        let bytewise_name = BytewiseComparator.name().inner();
+        if let Some(cmp_name) = comparator_name.filter(|cmp_name| cmp_name != bytewise_name) {
+            return SuggestedFormat::Unknown(UnknownFormat::UnknownComparator(cmp_name));
+        }
+
+        if !self.unknown_ids.is_empty() {
+            return SuggestedFormat::Unknown(UnknownFormat::UnknownCompressorIds(self.unknown_ids));
+        }
+
+        match (self.google_leveldb, self.mojang_leveldb) {
+            (true,  true)  => SuggestedFormat::Unknown(UnknownFormat::ConflictingCodecs),
+            (true,  false) => SuggestedFormat::google_leveldb(),
+            (false, true)  => SuggestedFormat::mojang_leveldb(),
+            (false, false) => SuggestedFormat::uncompressed(),
+        }
+    }
+}
+
+impl SuggestedFormat {
+    #[cfg(feature = "google-leveldb-compression")]
+    const fn google_leveldb() -> Self {
+        Self::GoogleLevelDB(FormatSettings::google_leveldb_format())
+    }
+
+    #[cfg(not(feature = "google-leveldb-compression"))]
+    const fn google_leveldb() -> Self {
+        Self::Unknown(UnknownFormat::CodecsNotEnabled(DetectedCodecs::GoogleLevelDB))
+    }
+
+    #[cfg(feature = "mojang-leveldb-compression")]
+    const fn mojang_leveldb() -> Self {
+        Self::MojangLevelDB(FormatSettings::mojang_leveldb_format())
+    }
+
+    #[cfg(not(feature = "mojang-leveldb-compression"))]
+    const fn mojang_leveldb() -> Self {
+        Self::Unknown(UnknownFormat::CodecsNotEnabled(DetectedCodecs::MojangLevelDB))
+    }
+
+    #[cfg(feature = "google-leveldb-compression")]
+    const fn uncompressed() -> Self {
+        Self::google_leveldb()
+    }
+
+    #[cfg(all(
+        not(feature = "google-leveldb-compression"),
+        feature = "mojang-leveldb-compression",
+    ))]
+    const fn uncompressed() -> Self {
+        Self::mojang_leveldb()
+    }
+
+    #[cfg(not(any(
+        feature = "google-leveldb-compression",
+        feature = "mojang-leveldb-compression",
+    )))]
+    const fn uncompressed() -> Self {
+        Self::Unknown(UnknownFormat::CodecsNotEnabled(DetectedCodecs::Uncompressed))
+    }
+}
+
+/// Check whether `block_prefix` begins with a valid zlib header using the Deflate method.
+fn is_zlib_header(block_prefix: &[u8]) -> bool {
+    if let &[cmf, flg, ..] = block_prefix {
+        let is_deflate = cmf & 0x0F == 8 && cmf >> 4 <= 7;
+        let check_bits_valid = ((u16::from(cmf) << 8_u8) | u16::from(flg)) % 31 == 0;
+        is_deflate && check_bits_valid
+    } else {
+        false
+    }
+}
@@ -0,0 +1,576 @@
+use std::cmp::Ordering;
+
+use clone_behavior::FastMirroredClone;
+
+use anchored_vfs::LevelDBFilesystem;
+
+use crate::{
+    contention_queue::WriteCommand,
+    options::{InternalWriteOptions, pub_options::{ReadOptions, WriteOptions}},
+    pub_typed_bytes::{ShortSlice, TableEntry},
+    snapshot::Snapshot,
+    typed_bytes::UserKey,
+    write_batch::{IndexedWriteBatch, IndexedWriteBatchIter, WriteEntry},
+};
+use crate::{
+    all_errors::{
+        aliases::RwResult,
+        types::{ReadError, RwErrorKind},
+    },
+    pub_traits::{
+        cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
+        compression::CompressionCodecs,
+        pool::BufferPool,
+    },
+};
+use super::{other_read_write::DBIter, structs::DB};
+
+
+impl<FS, Cmp, Policy, Codecs, Pool> DB<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator + FastMirroredClone,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Create an empty [`IndexedWriteBatch`] whose keys are sorted by this database's comparator.
+    #[must_use]
+    pub fn indexed_write_batch(&self) -> IndexedWriteBatch<Cmp> {
+        IndexedWriteBatch::new(self.db_state().opts.cmp.0.fast_mirrored_clone())
+    }
+
+    /// Get a circular lending iterator over the entries of the database, as modified by the
+    /// pending writes and deletions in `batch`, in sorted order.
+    ///
+    /// Default [`ReadOptions`] are used.
+    ///
+    /// See [`DB::iter`] for more information. Note that `batch` must have been created with the
+    /// same comparator as this database; otherwise, the iterator's order is unspecified.
+    pub fn iter_with_batch<'b>(
+        self,
+        batch: &'b IndexedWriteBatch<Cmp>,
+    ) -> RwResult<IndexedBatchDBIter<'b, FS, Cmp, Policy, Codecs, Pool>, FS, Cmp, Codecs> {
+        self.iter_with_batch_and_opts(batch, &ReadOptions::default())
+    }
+
+    /// Get a circular lending iterator over the entries of the database, as modified by the
+    /// pending writes and deletions in `batch`, in sorted order.
+    ///
+    /// See [`DB::iter`] for more information. Note that `batch` must have been created with the
+    /// same comparator as this database; otherwise, the iterator's order is unspecified.
+    pub fn iter_with_batch_and_opts<'b>(
+        self,
+        batch:     &'b IndexedWriteBatch<Cmp>,
+        read_opts: &ReadOptions,
+    ) -> RwResult<IndexedBatchDBIter<'b, FS, Cmp, Policy, Codecs, Pool>, FS, Cmp, Codecs> {
+        Ok(IndexedBatchDBIter {
+            db_iter:    self.iter_with(read_opts)?,
+            batch,
+            batch_iter: batch.iter(),
+            current:    Current::Invalid,
+            direction:  Direction::Forwards,
+        })
+    }
+}
+
+impl<FS, Cmp, Policy, Codecs, Pool> DB<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator + FastMirroredClone,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Get the value of `key`, as modified by the pending writes and deletions in `batch`.
+    ///
+    /// If `batch` contains `key`, the newest entry for `key` in `batch` is used, and the database
+    /// is not read. Otherwise, the database is read as of the `snapshot` in `read_opts` (if any).
+    pub fn get_with_batch(
+        &mut self,
+        batch:     &IndexedWriteBatch<Cmp>,
+        key:       &[u8],
+        read_opts: &ReadOptions,
+    ) -> RwResult<Option<Vec<u8>>, FS, Cmp, Codecs> {
+        if let Some(batch_value) = batch.get(key) {
+            return Ok(batch_value.map(<[u8]>::to_owned));
+        }
+
+        let (db_state, per_handle) = self.inner();
+        let user_key = UserKey::new(key)
+            .ok_or_else(|| db_state.rw_error(RwErrorKind::Read(ReadError::KeyTooLong)))?;
+
+        let entry = db_state
+            .get_entry(
+                &mut per_handle.decoders,
+                db_state.internal_read_opts(read_opts),
+                read_opts.record_seeks,
+                read_opts.snapshot.as_ref().map(Snapshot::sequence_number),
+                user_key,
+            )
+            .map_err(|kind| db_state.rw_error(kind))?;
+
+        Ok(entry.and_then(|entry| entry.value))
+    }
+
+    /// Atomically apply the writes and deletions in `batch` to the database, through the normal
+    /// write path.
+    pub fn write_indexed_batch(
+        &mut self,
+        batch:      &IndexedWriteBatch<Cmp>,
+        write_opts: &WriteOptions,
+    ) -> RwResult<(), FS, Cmp, Codecs> {
+        let (db_state, per_handle) = self.inner();
+
+        let _applied: bool = db_state.write_command(
+            &mut per_handle.decoders,
+            WriteCommand::Write(
+                batch.borrow(),
+                InternalWriteOptions {
+                    sync: write_opts.sync,
+                },
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// The source of an [`IndexedBatchDBIter`]'s current entry.
+#[derive(Debug, Clone, Copy)]
+enum Current {
+    Invalid,
+    Db,
+    /// The current entry of the batch iterator, which is not a deletion.
+    ///
+    /// If `shadows_db`, then the database iterator is at an entry with the same key, which
+    /// the batch entry overrides.
+    Batch { shadows_db: bool },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Forwards,
+    Backwards,
+}
+
+/// A circular lending iterator over the entries of a database, as modified by the pending writes
+/// and deletions in an [`IndexedWriteBatch`].
+///
+/// Where the batch and the database both have an entry for a key, the batch's entry takes
+/// precedence; keys deleted by the batch are skipped.
+///
+/// See [`DBIter`] for more information.
+#[expect(missing_debug_implementations, reason = "not a priority. TODO: debug impl")]
+pub struct IndexedBatchDBIter<'b, FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>>,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    db_iter:    DBIter<FS, Cmp, Policy, Codecs, Pool>,
+    batch:      &'b IndexedWriteBatch<Cmp>,
+    batch_iter: IndexedWriteBatchIter<'b, Cmp>,
+    current:    Current,
+    /// If `Forwards`, then neither `db_iter` nor `batch_iter` is before the iterator's current
+    /// position. If `Backwards`, then neither is after it.
+    direction:  Direction,
+}
+
+impl<FS, Cmp, Policy, Codecs, Pool> IndexedBatchDBIter<'_, FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>>,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    #[inline]
+    #[must_use]
+    pub fn into_db(self) -> DB<FS, Cmp, Policy, Codecs, Pool> {
+        self.db_iter.into_db()
+    }
+
+    /// Determine whether the iterator is currently at any entry.
+    ///
+    /// [`current()`] will be `Some` if and only if the iterator is valid.
+    ///
+    /// [`current()`]: IndexedBatchDBIter::current
+    #[inline]
+    #[must_use]
+    pub const fn valid(&self) -> bool {
+        !matches!(self.current, Current::Invalid)
+    }
+
+    /// Return the `(key, value)` entry at the iterator's current position.
+    #[must_use]
+    pub fn current(&self) -> Option<TableEntry<'_>> {
+        match self.current {
+            Current::Invalid      => None,
+            Current::Db           => self.db_iter.current(),
+            Current::Batch { .. } => {
+                let entry = self.batch_iter.current()?;
+                // Keys and values in a `WriteBatch` are validated to not be too long.
+                Some(TableEntry::new(
+                    ShortSlice::new(entry.key())?,
+                    ShortSlice::new(entry.value()?)?,
+                ))
+            }
+        }
+    }
+
+    /// Reset the iterator to its initial position.
+    ///
+    /// The iterator becomes `!valid()`, and is conceptually one position before the first entry
+    /// and one position after the last entry (if there are any entries).
+    pub fn reset(&mut self) {
+        self.db_iter.reset();
+        self.batch_iter.reset();
+        self.current = Current::Invalid;
+        self.direction = Direction::Forwards;
+    }
+}
+
+impl<FS, Cmp, Policy, Codecs, Pool> IndexedBatchDBIter<'_, FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Fallibly return the next `(key, value)` entry (if any).
+    ///
+    /// Returns `None` if the iterator was at the last entry.
+    #[expect(clippy::should_implement_trait, reason = "this is a *lending* iterator")]
+    pub fn next(&mut self) -> RwResult<Option<TableEntry<'_>>, FS, Cmp, Codecs> {
+        if matches!(self.current, Current::Invalid) {
+            self.seek_to_first()?;
+            return Ok(self.current());
+        }
+
+        if matches!(self.direction, Direction::Backwards) {
+            self.switch_to_forwards()?;
+        }
+
+        match self.current {
+            Current::Invalid => {}
+            Current::Db => {
+                self.db_iter.next()?;
+            }
+            Current::Batch { shadows_db } => {
+                if shadows_db {
+                    self.db_iter.next()?;
+                }
+                self.batch_iter.next();
+            }
+        }
+
+        self.settle()?;
+        Ok(self.current())
+    }
+
+    /// Fallibly return the previous `(key, value)` entry (if any).
+    ///
+    /// Returns `None` if the iterator was at the first entry.
+    ///
+    /// # Speed Warning
+    /// Backwards iteration is noticeably slower than forwards iteration.
+    pub fn prev(&mut self) -> RwResult<Option<TableEntry<'_>>, FS, Cmp, Codecs> {
+        if matches!(self.current, Current::Invalid) {
+            self.seek_to_last()?;
+            return Ok(self.current());
+        }
+
+        if matches!(self.direction, Direction::Forwards) {
+            self.switch_to_backwards()?;
+        }
+
+        match self.current {
+            Current::Invalid => {}
+            Current::Db => {
+                self.db_iter.prev()?;
+            }
+            Current::Batch { shadows_db } => {
+                if shadows_db {
+                    self.db_iter.prev()?;
+                }
+                self.batch_iter.prev();
+            }
+        }
+
+        self.settle()?;
+        Ok(self.current())
+    }
+
+    /// Move the iterator to the first entry whose key is greater than or equal to the provided
+    /// `lower_bound`.
+    ///
+    /// If there is no such key, the iterator becomes `!valid()`.
+    pub fn seek(&mut self, lower_bound: &[u8]) -> RwResult<(), FS, Cmp, Codecs> {
+        self.db_iter.seek(lower_bound)?;
+        self.batch_iter.seek(user_key(lower_bound));
+        self.direction = Direction::Forwards;
+        self.settle()
+    }
+
+    /// Move the iterator to the last entry whose key is strictly less than the provided
+    /// `strict_upper_bound`.
+    ///
+    /// If there is no such key, the iterator becomes `!valid()`.
+    ///
+    /// # Speed Warning
+    /// Backwards iteration is noticeably slower than forwards iteration.
+    pub fn seek_before(&mut self, strict_upper_bound: &[u8]) -> RwResult<(), FS, Cmp, Codecs> {
+        self.db_iter.seek_before(strict_upper_bound)?;
+        self.batch_iter.seek_before(user_key(strict_upper_bound));
+        self.direction = Direction::Backwards;
+        self.settle()
+    }
+
+    /// Move the iterator to the first entry in the sorted order.
+    ///
+    /// If there are no entries, the iterator becomes `!valid()`.
+    pub fn seek_to_first(&mut self) -> RwResult<(), FS, Cmp, Codecs> {
+        self.db_iter.seek_to_first()?;
+        self.batch_iter.seek_to_first();
+        self.direction = Direction::Forwards;
+        self.settle()
+    }
+
+    /// Move the iterator to the last entry in the sorted order.
+    ///
+    /// If there are no entries, the iterator becomes `!valid()`.
+    ///
+    /// # Speed Warning
+    /// Backwards iteration is noticeably slower than forwards iteration.
+    pub fn seek_to_last(&mut self) -> RwResult<(), FS, Cmp, Codecs> {
+        self.db_iter.seek_to_last()?;
+        self.batch_iter.seek_to_last();
+        self.direction = Direction::Backwards;
+        self.settle()
+    }
+
+    /// For use only in `next`.
+    ///
+    /// Move whichever of the database iterator and batch iterator is behind the current entry
+    /// to the first entry strictly after it.
+    fn switch_to_forwards(&mut self) -> RwResult<(), FS, Cmp, Codecs> {
+        match self.current {
+            Current::Invalid | Current::Batch { shadows_db: true } => {}
+            Current::Db => {
+                // The batch has no entry for the current key, so this moves the batch iterator
+                // strictly after the current key.
+                if let Some(db_entry) = self.db_iter.current() {
+                    self.batch_iter.seek(user_key(db_entry.key_bytes()));
+                }
+            }
+            Current::Batch { shadows_db: false } => {
+                // Likewise, the database has no entry for the current key.
+                if let Some(batch_entry) = self.batch_iter.current() {
+                    self.db_iter.seek(batch_entry.key())?;
+                }
+            }
+        }
+
+        self.direction = Direction::Forwards;
+        Ok(())
+    }
+
+    /// For use only in `prev`.
+    ///
+    /// Move whichever of the database iterator and batch iterator is ahead of the current entry
+    /// to the last entry strictly before it.
+    fn switch_to_backwards(&mut self) -> RwResult<(), FS, Cmp, Codecs> {
+        match self.current {
+            Current::Invalid | Current::Batch { shadows_db: true } => {}
+            Current::Db => {
+                if let Some(db_entry) = self.db_iter.current() {
+                    self.batch_iter.seek_before(user_key(db_entry.key_bytes()));
+                }
+            }
+            Current::Batch { shadows_db: false } => {
+                if let Some(batch_entry) = self.batch_iter.current() {
+                    self.db_iter.seek_before(batch_entry.key())?;
+                }
+            }
+        }
+
+        self.direction = Direction::Backwards;
+        Ok(())
+    }
+
+    /// Given that neither the database iterator nor the batch iterator is behind the iterator's
+    /// intended position (in `self.direction`), move past any entries deleted by the batch,
+    /// and determine which source the current entry comes from.
+    fn settle(&mut self) -> RwResult<(), FS, Cmp, Codecs> {
+        loop {
+            let batch_entry = self.batch_iter.current();
+
+            // `Less` if the database entry comes first in the direction of iteration.
+            let ordering = match (self.db_iter.current(), batch_entry) {
+                (None, None) => {
+                    self.current = Current::Invalid;
+                    return Ok(());
+                }
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(db_entry), Some(batch_entry)) => {
+                    let ordering = self.batch.cmp().cmp(db_entry.key_bytes(), batch_entry.key());
+                    match self.direction {
+                        Direction::Forwards  => ordering,
+                        Direction::Backwards => ordering.reverse(),
+                    }
+                }
+            };
+
+            match (ordering, batch_entry) {
+                (Ordering::Less, _) | (_, None) => {
+                    self.current = Current::Db;
+                    return Ok(());
+                }
+                (_, Some(WriteEntry::Value { .. })) => {
+                    self.current = Current::Batch { shadows_db: ordering.is_eq() };
+                    return Ok(());
+                }
+                (_, Some(WriteEntry::Deletion { .. })) => {
+                    match self.direction {
+                        Direction::Forwards => {
+                            if ordering.is_eq() {
+                                self.db_iter.next()?;
+                            }
+                            self.batch_iter.next();
+                        }
+                        Direction::Backwards => {
+                            if ordering.is_eq() {
+                                self.db_iter.prev()?;
+                            }
+                            self.batch_iter.prev();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Keys of database entries and batch entries are valid [`UserKey`]s, as are keys which
+/// [`DBIter`] has accepted as seek targets.
+#[expect(clippy::expect_used, reason = "only called on keys known to be valid user keys")]
+fn user_key(key: &[u8]) -> UserKey<'_> {
+    UserKey::new(key).expect("database keys, batch keys, and seek targets are not too long")
+}
+
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, clippy::unwrap_used, reason = "these are tests")]
+mod tests {
+    use crate::pub_traits::cmp_and_policy::BytewiseComparator;
+    use super::super::test_db::{TestDB, TestDir};
+    use super::*;
+
+
+    const IN_DB: &[u8] = b"db";
+    const IN_BATCH: &[u8] = b"batch";
+
+    /// Write `a`, `b` and `d` to the database.
+    fn populate(db: &mut TestDB) {
+        let mut batch = db.indexed_write_batch();
+        for key in [b"a", b"b", b"d"] {
+            batch.put(key, IN_DB).unwrap();
+        }
+        db.write_indexed_batch(&batch, &WriteOptions::default()).unwrap();
+    }
+
+    /// Overwrite `b`, add `c`, and delete `d` and the absent `e`.
+    fn pending_edits(db: &TestDB) -> IndexedWriteBatch<BytewiseComparator> {
+        let mut batch = db.indexed_write_batch();
+        batch.put(b"b", IN_BATCH).unwrap();
+        batch.delete(b"d").unwrap();
+        batch.put(b"c", IN_BATCH).unwrap();
+        batch.delete(b"e").unwrap();
+        batch
+    }
+
+    fn key_and_value(entry: Option<TableEntry<'_>>) -> Option<(&[u8], &[u8])> {
+        entry.map(|entry| (entry.key_bytes(), entry.value_bytes()))
+    }
+
+    #[test]
+    fn batch_shadows_db() {
+        let dir = TestDir::new("batch_shadows_db");
+        let mut db = dir.open();
+        populate(&mut db);
+        let batch = pending_edits(&db);
+
+        let read_opts = ReadOptions::default();
+        let mut get = |key: &[u8]| db.get_with_batch(&batch, key, &read_opts).unwrap();
+        assert_eq!(get(b"a").as_deref(), Some(IN_DB));
+        assert_eq!(get(b"b").as_deref(), Some(IN_BATCH));
+        assert_eq!(get(b"c").as_deref(), Some(IN_BATCH));
+        assert_eq!(get(b"d"), None);
+        assert_eq!(get(b"e"), None);
+    }
+
+    #[test]
+    fn merge_order_forwards() {
+        let dir = TestDir::new("merge_order_forwards");
+        let mut db = dir.open();
+        populate(&mut db);
+        let batch = pending_edits(&db);
+
+        let mut iter = db.iter_with_batch(&batch).unwrap();
+        assert_eq!(key_and_value(iter.next().unwrap()), Some((b"a".as_slice(), IN_DB)));
+        assert_eq!(key_and_value(iter.next().unwrap()), Some((b"b".as_slice(), IN_BATCH)));
+        assert_eq!(key_and_value(iter.next().unwrap()), Some((b"c".as_slice(), IN_BATCH)));
+        // `d` is deleted by the batch.
+        assert!(iter.next().unwrap().is_none());
+        assert!(!iter.valid());
+        // The iterator is circular.
+        assert_eq!(key_and_value(iter.next().unwrap()), Some((b"a".as_slice(), IN_DB)));
+    }
+
+    #[test]
+    fn merge_order_backwards() {
+        let dir = TestDir::new("merge_order_backwards");
+        let mut db = dir.open();
+        populate(&mut db);
+        let batch = pending_edits(&db);
+
+        let mut iter = db.iter_with_batch(&batch).unwrap();
+        assert_eq!(key_and_value(iter.prev().unwrap()), Some((b"c".as_slice(), IN_BATCH)));
+        assert_eq!(key_and_value(iter.prev().unwrap()), Some((b"b".as_slice(), IN_BATCH)));
+        assert_eq!(key_and_value(iter.prev().unwrap()), Some((b"a".as_slice(), IN_DB)));
+        assert!(iter.prev().unwrap().is_none());
+        assert!(!iter.valid());
+    }
+
+    #[test]
+    fn merge_order_across_direction_changes() {
+        let dir = TestDir::new("merge_order_across_direction_changes");
+        let mut db = dir.open();
+        populate(&mut db);
+        let batch = pending_edits(&db);
+
+        let mut iter = db.iter_with_batch(&batch).unwrap();
+        iter.seek(b"b").unwrap();
+        assert_eq!(key_and_value(iter.current()), Some((b"b".as_slice(), IN_BATCH)));
+        assert_eq!(key_and_value(iter.prev().unwrap()), Some((b"a".as_slice(), IN_DB)));
+        assert_eq!(key_and_value(iter.next().unwrap()), Some((b"b".as_slice(), IN_BATCH)));
+        assert_eq!(key_and_value(iter.next().unwrap()), Some((b"c".as_slice(), IN_BATCH)));
+        assert_eq!(key_and_value(iter.prev().unwrap()), Some((b"b".as_slice(), IN_BATCH)));
+
+        iter.seek_before(b"e").unwrap();
+        assert_eq!(key_and_value(iter.current()), Some((b"c".as_slice(), IN_BATCH)));
+        assert!(iter.next().unwrap().is_none());
+
+        iter.seek(b"d").unwrap();
+        assert!(!iter.valid());
+        iter.seek_to_last().unwrap();
+        assert_eq!(key_and_value(iter.current()), Some((b"c".as_slice(), IN_BATCH)));
+        iter.seek_to_first().unwrap();
+        assert_eq!(key_and_value(iter.current()), Some((b"a".as_slice(), IN_DB)));
+    }
+}
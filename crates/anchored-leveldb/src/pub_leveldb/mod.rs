@@ -8,14 +8,28 @@ mod destruct;
 mod compaction;
 mod put_delete_get;
 mod other_read_write;
+mod transaction;
+mod indexed_batch;
 mod debug_and_stats;
 
-// A public free function.
+// Public free functions.
+mod convert_format;
 mod destroy;
+mod detect_format;
+
+// A scratch database for tests.
+#[cfg(test)]
+mod test_db;
 
 // later: repair_db
 // later: clone_db, checkpoints
 
 
-pub use self::{destroy::irreversibly_destroy_entire_db, other_read_write::DBIter};
+pub use self::{
+    convert_format::{ConvertFormatProgress, convert_db_format},
+    debug_and_stats::{LiveFilesMetadata, LiveTableFileMetadata, WriteStallStatistics},
+    destroy::irreversibly_destroy_entire_db,
+    detect_format::{DetectedCodecs, SuggestedFormat, UnknownFormat, detect_format},
+    indexed_batch::IndexedBatchDBIter, other_read_write::DBIter, transaction::Transaction,
+};
 pub use self::structs::{DB, DBState};
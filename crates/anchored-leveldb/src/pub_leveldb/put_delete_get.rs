@@ -0,0 +1,265 @@
+use clone_behavior::FastMirroredClone;
+
+use anchored_vfs::LevelDBFilesystem;
+
+use crate::{
+    contention_queue::{WriteCommand, WriteCondition},
+    options::{InternalWriteOptions, pub_options::WriteOptions},
+    write_batch::WriteBatch,
+};
+use crate::{
+    all_errors::{
+        aliases::RwResult,
+        types::{RwErrorKind, WriteError},
+    },
+    pub_traits::{
+        cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
+        compression::CompressionCodecs,
+        pool::BufferPool,
+    },
+};
+use super::structs::DB;
+
+
+impl<FS, Cmp, Policy, Codecs, Pool> DB<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator + FastMirroredClone,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Atomically write `value` to `key` if and only if `key` currently has no value.
+    ///
+    /// Default [`WriteOptions`] are used.
+    ///
+    /// Returns `true` if the write was applied.
+    pub fn put_if_absent(&mut self, key: &[u8], value: &[u8]) -> RwResult<bool, FS, Cmp, Codecs> {
+        self.put_if_absent_with(key, value, &WriteOptions::default())
+    }
+
+    /// Atomically write `value` to `key` if and only if `key` currently has no value.
+    ///
+    /// Returns `true` if the write was applied.
+    pub fn put_if_absent_with(
+        &mut self,
+        key:        &[u8],
+        value:      &[u8],
+        write_opts: &WriteOptions,
+    ) -> RwResult<bool, FS, Cmp, Codecs> {
+        let mut batch = WriteBatch::new();
+        batch.put(key, value).map_err(|err| {
+            self.db_state().rw_error(RwErrorKind::Write(WriteError::InvalidPut(err)))
+        })?;
+
+        self.conditional_write(&batch, WriteCondition { key, expected: None }, write_opts)
+    }
+
+    /// Atomically write `new` to `key` if and only if the current value of `key` is `expected`.
+    ///
+    /// Default [`WriteOptions`] are used.
+    ///
+    /// Returns `true` if the write was applied.
+    pub fn compare_and_swap(
+        &mut self,
+        key:      &[u8],
+        expected: &[u8],
+        new:      &[u8],
+    ) -> RwResult<bool, FS, Cmp, Codecs> {
+        self.compare_and_swap_with(key, expected, new, &WriteOptions::default())
+    }
+
+    /// Atomically write `new` to `key` if and only if the current value of `key` is `expected`.
+    ///
+    /// Returns `true` if the write was applied.
+    pub fn compare_and_swap_with(
+        &mut self,
+        key:        &[u8],
+        expected:   &[u8],
+        new:        &[u8],
+        write_opts: &WriteOptions,
+    ) -> RwResult<bool, FS, Cmp, Codecs> {
+        let mut batch = WriteBatch::new();
+        batch.put(key, new).map_err(|err| {
+            self.db_state().rw_error(RwErrorKind::Write(WriteError::InvalidPut(err)))
+        })?;
+
+        let condition = WriteCondition { key, expected: Some(expected) };
+        self.conditional_write(&batch, condition, write_opts)
+    }
+
+    /// Atomically delete `key` if and only if the current value of `key` is `expected`.
+    ///
+    /// Default [`WriteOptions`] are used.
+    ///
+    /// Returns `true` if the deletion was applied.
+    pub fn delete_if_equals(
+        &mut self,
+        key:      &[u8],
+        expected: &[u8],
+    ) -> RwResult<bool, FS, Cmp, Codecs> {
+        self.delete_if_equals_with(key, expected, &WriteOptions::default())
+    }
+
+    /// Atomically delete `key` if and only if the current value of `key` is `expected`.
+    ///
+    /// Returns `true` if the deletion was applied.
+    pub fn delete_if_equals_with(
+        &mut self,
+        key:        &[u8],
+        expected:   &[u8],
+        write_opts: &WriteOptions,
+    ) -> RwResult<bool, FS, Cmp, Codecs> {
+        let mut batch = WriteBatch::new();
+        batch.delete(key).map_err(|err| {
+            self.db_state().rw_error(RwErrorKind::Write(WriteError::InvalidDelete(err)))
+        })?;
+
+        let condition = WriteCondition { key, expected: Some(expected) };
+        self.conditional_write(&batch, condition, write_opts)
+    }
+
+    /// The condition is evaluated by the front writer of the contention queue, after every write
+    /// queued before this one has been applied.
+    fn conditional_write(
+        &mut self,
+        batch:      &WriteBatch,
+        condition:  WriteCondition<'_>,
+        write_opts: &WriteOptions,
+    ) -> RwResult<bool, FS, Cmp, Codecs> {
+        let (db_state, per_handle) = self.inner();
+
+        db_state.write_command(
+            &mut per_handle.decoders,
+            WriteCommand::Conditional {
+                batch:      batch.borrow(),
+                condition,
+                write_opts: InternalWriteOptions {
+                    sync: write_opts.sync,
+                },
+            },
+        )
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, clippy::unwrap_used, reason = "these are tests")]
+mod tests {
+    use std::thread;
+
+    use crate::options::pub_options::ReadOptions;
+    use super::super::test_db::{TestDB, TestDir};
+    use super::*;
+
+
+    fn get(db: &mut TestDB, key: &[u8]) -> Option<Vec<u8>> {
+        let empty = db.indexed_write_batch();
+        db.get_with_batch(&empty, key, &ReadOptions::default()).unwrap()
+    }
+
+    fn delete(db: &mut TestDB, key: &[u8]) {
+        let mut batch = db.indexed_write_batch();
+        batch.delete(key).unwrap();
+        db.write_indexed_batch(&batch, &WriteOptions::default()).unwrap();
+    }
+
+    #[test]
+    fn put_if_absent() {
+        let dir = TestDir::new("put_if_absent");
+        let mut db = dir.open();
+
+        assert!(db.put_if_absent(b"key", b"first").unwrap());
+        assert!(!db.put_if_absent(b"key", b"second").unwrap());
+        assert_eq!(get(&mut db, b"key").as_deref(), Some(b"first".as_slice()));
+    }
+
+    #[test]
+    fn compare_and_swap() {
+        let dir = TestDir::new("compare_and_swap");
+        let mut db = dir.open();
+        assert!(db.put_if_absent(b"key", b"old").unwrap());
+
+        assert!(!db.compare_and_swap(b"key", b"wrong", b"new").unwrap());
+        assert_eq!(get(&mut db, b"key").as_deref(), Some(b"old".as_slice()));
+
+        assert!(db.compare_and_swap(b"key", b"old", b"new").unwrap());
+        assert_eq!(get(&mut db, b"key").as_deref(), Some(b"new".as_slice()));
+
+        // An absent key has no value to compare against.
+        assert!(!db.compare_and_swap(b"absent", b"", b"new").unwrap());
+        assert_eq!(get(&mut db, b"absent"), None);
+    }
+
+    #[test]
+    fn delete_if_equals() {
+        let dir = TestDir::new("delete_if_equals");
+        let mut db = dir.open();
+        assert!(db.put_if_absent(b"key", b"value").unwrap());
+
+        assert!(!db.delete_if_equals(b"key", b"wrong").unwrap());
+        assert_eq!(get(&mut db, b"key").as_deref(), Some(b"value".as_slice()));
+
+        assert!(db.delete_if_equals(b"key", b"value").unwrap());
+        assert_eq!(get(&mut db, b"key"), None);
+    }
+
+    #[test]
+    fn conditions_on_deleted_key() {
+        let dir = TestDir::new("conditions_on_deleted_key");
+        let mut db = dir.open();
+        assert!(db.put_if_absent(b"key", b"old").unwrap());
+        delete(&mut db, b"key");
+
+        // A deleted key is treated as absent, rather than as having its old value.
+        assert!(!db.compare_and_swap(b"key", b"old", b"new").unwrap());
+        assert!(!db.delete_if_equals(b"key", b"old").unwrap());
+        assert_eq!(get(&mut db, b"key"), None);
+
+        assert!(db.put_if_absent(b"key", b"new").unwrap());
+        assert_eq!(get(&mut db, b"key").as_deref(), Some(b"new".as_slice()));
+    }
+
+    /// Several threads increment a counter with `compare_and_swap` while other threads issue
+    /// unconditional writes, so that conditional writes are queued alongside write groups.
+    /// No increment may be lost.
+    #[test]
+    fn conditions_racing_other_writers() {
+        const THREADS: u32 = 4;
+        const INCREMENTS: u32 = 50;
+
+        let dir = TestDir::new("conditions_racing_other_writers");
+        let mut db = dir.open();
+        assert!(db.put_if_absent(b"counter", &0_u32.to_le_bytes()).unwrap());
+
+        thread::scope(|scope| {
+            for thread_idx in 0..THREADS {
+                let mut incrementer = db.clone();
+                scope.spawn(move || {
+                    for _ in 0..INCREMENTS {
+                        loop {
+                            let old = get(&mut incrementer, b"counter").unwrap();
+                            let count = u32::from_le_bytes(old.as_slice().try_into().unwrap());
+                            let new = (count + 1).to_le_bytes();
+                            if incrementer.compare_and_swap(b"counter", &old, &new).unwrap() {
+                                break;
+                            }
+                        }
+                    }
+                });
+
+                let mut writer = db.clone();
+                scope.spawn(move || {
+                    for write_idx in 0..INCREMENTS {
+                        let mut batch = writer.indexed_write_batch();
+                        batch.put(&thread_idx.to_le_bytes(), &write_idx.to_le_bytes()).unwrap();
+                        writer.write_indexed_batch(&batch, &WriteOptions::default()).unwrap();
+                    }
+                });
+            }
+        });
+
+        let count = get(&mut db, b"counter").unwrap();
+        assert_eq!(count, (THREADS * INCREMENTS).to_le_bytes());
+    }
+}
@@ -0,0 +1,113 @@
+#![allow(clippy::unwrap_used, reason = "these are tests")]
+
+use std::{env, fs, process};
+use std::path::PathBuf;
+
+use tracing::level_filters::LevelFilter;
+
+use anchored_vfs::StandardFS;
+
+use crate::{
+    options::pub_options::{
+        BufferPoolOptions, CacheOptions, ClampOptions, CompactionOptions, CompressionOptions,
+        ConsistencyOptions, FilterOptions, FormatSettings, LoggerOptions, ManifestOptions,
+        MemtableOptions, OpenOptions, SSTableOptions, SeekCompactionOptions,
+        SizeCompactionOptions, WriteThrottlingOptions,
+    },
+    pub_traits::{
+        cmp_and_policy::{BytewiseComparator, NoFilterPolicy},
+        pool::BadPool,
+    },
+    pub_typed_bytes::NUM_NONZERO_LEVELS_USIZE,
+};
+use super::structs::DB;
+use self::empty_codecs::EmptyCodecs;
+
+
+mod empty_codecs {
+    crate::codec_list! {
+        codecs[];
+
+        pub(crate) struct EmptyCodecs;
+        pub(crate) struct EmptyEncoders;
+        pub(crate) struct EmptyDecoders;
+        #[derive(Debug)]
+        pub(crate) enum EmptyCompressionError;
+        #[derive(Debug)]
+        pub(crate) enum EmptyDecompressionError;
+    }
+
+    pub(crate) const fn empty_codecs() -> EmptyCodecs {
+        EmptyCodecs(())
+    }
+}
+
+pub(crate) type TestDB = DB<StandardFS, BytewiseComparator, NoFilterPolicy, EmptyCodecs, BadPool>;
+
+/// A fresh database directory in the system's temporary directory, which is removed when
+/// the `TestDir` is dropped.
+#[derive(Debug)]
+pub(crate) struct TestDir(PathBuf);
+
+impl TestDir {
+    /// `name` should be unique among the tests, since tests may run concurrently.
+    #[must_use]
+    pub(crate) fn new(name: &str) -> Self {
+        let directory = env::temp_dir()
+            .join(format!("anchored-leveldb-test-{}-{name}", process::id()));
+        // The directory might be left over from an earlier run which panicked.
+        let _result: Result<(), _> = fs::remove_dir_all(&directory);
+        Self(directory)
+    }
+
+    /// Open the database in this directory, creating it if it does not yet exist.
+    ///
+    /// Data is left uncompressed, and no filters are used.
+    #[must_use]
+    pub(crate) fn open(&self) -> TestDB {
+        const LEN: usize = NUM_NONZERO_LEVELS_USIZE.get();
+
+        DB::open(OpenOptions {
+            filesystem:         StandardFS,
+            database_directory: self.0.clone(),
+            create_if_missing:  true,
+            error_if_exists:    false,
+            clamp_options:      ClampOptions::BackwardsCompatibilityClamping,
+            format:             FormatSettings::from_cmp_and_unchecked_compression_codecs(
+                BytewiseComparator,
+                empty_codecs::empty_codecs(),
+            ),
+            compression:        CompressionOptions {
+                memtable_compressor:       None,
+                table_compressors:         [None; LEN],
+                memtable_compression_goal: 32,
+                table_compression_goals:   [32; LEN],
+                adaptive:                  None,
+                compression_threads:       None,
+            },
+            filter:             FilterOptions::from_filter_policy(None),
+            consistency:        ConsistencyOptions::default(),
+            logger:             LoggerOptions {
+                log_file_filter: LevelFilter::OFF,
+                logger_filter:   LevelFilter::OFF,
+                custom_logger:   None,
+            },
+            manifest:           ManifestOptions::default(),
+            memtable:           MemtableOptions::default(),
+            sstable:            SSTableOptions::default(),
+            compaction:         CompactionOptions::default(),
+            size_compaction:    SizeCompactionOptions::default(),
+            seek_compaction:    SeekCompactionOptions::default(),
+            write_throttling:   WriteThrottlingOptions::default(),
+            buffer_pool:        BufferPoolOptions::default(),
+            cache:              CacheOptions::default(),
+        })
+        .unwrap()
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _result: Result<(), _> = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,382 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+use clone_behavior::FastMirroredClone;
+
+use anchored_vfs::LevelDBFilesystem;
+
+use crate::{
+    contention_queue::WriteCommand,
+    options::{InternalWriteOptions, pub_options::{ReadOptions, WriteOptions}},
+    pub_typed_bytes::SequenceNumber,
+    snapshot::Snapshot,
+    typed_bytes::UserKey,
+    write_batch::{IndexedWriteBatch, WriteBatch},
+};
+use crate::{
+    all_errors::{
+        aliases::RwResult,
+        types::{ReadError, RwErrorKind, WriteBatchDeleteError, WriteBatchPutError},
+    },
+    pub_traits::{
+        cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
+        compression::CompressionCodecs,
+        pool::BufferPool,
+    },
+};
+use super::structs::DB;
+
+
+impl<FS, Cmp, Policy, Codecs, Pool> DB<FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator + FastMirroredClone,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>>,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Begin an optimistic [`Transaction`], which reads the database as of a [`Snapshot`] taken
+    /// now, and buffers its writes until it is committed.
+    #[must_use]
+    pub fn transaction(&mut self) -> Transaction<'_, FS, Cmp, Policy, Codecs, Pool> {
+        let snapshot = self.db_state().snapshot();
+        let batch = IndexedWriteBatch::new(self.db_state().opts.cmp.0.fast_mirrored_clone());
+
+        Transaction {
+            db:       self,
+            snapshot,
+            batch,
+            read_set: Vec::new(),
+        }
+    }
+}
+
+/// An optimistic transaction, which reads the database as of a [`Snapshot`] and buffers its
+/// writes in an [`IndexedWriteBatch`].
+///
+/// The keys read from the database are recorded. When the transaction is committed, its writes
+/// are applied atomically if and only if none of those keys were written to after the
+/// transaction's [`Snapshot`] was acquired; otherwise, a [`WriteError::TransactionConflict`]
+/// error is returned, and nothing is written.
+///
+/// Reads of keys which the transaction has itself written observe the transaction's writes.
+///
+/// [`WriteError::TransactionConflict`]: crate::errors::WriteError::TransactionConflict
+pub struct Transaction<'a, FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>>,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    db:       &'a mut DB<FS, Cmp, Policy, Codecs, Pool>,
+    snapshot: Snapshot,
+    batch:    IndexedWriteBatch<Cmp>,
+    /// The distinct keys read from the database (rather than from `batch`), each with the
+    /// sequence number of the view of the database it was read from.
+    ///
+    /// Sorted by key according to the database's comparator.
+    read_set: Vec<(Vec<u8>, SequenceNumber)>,
+}
+
+impl<FS, Cmp, Policy, Codecs, Pool> Debug for Transaction<'_, FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     Debug + LevelDBFilesystem<
+        RandomAccessFile: Debug,
+        WriteFile: Debug,
+        Lockfile: Debug,
+        Error: Debug,
+    >,
+    Cmp:    Debug + LevelDBComparator<InvalidKeyError: Debug>,
+    Policy: Debug + FilterPolicy<Eq: CoarserThan<Cmp::Eq>>,
+    Codecs: Debug + CompressionCodecs<
+        Encoders: Debug,
+        Decoders: Debug,
+        CompressionError: Debug,
+        DecompressionError: Debug,
+    >,
+    Pool:   Debug + BufferPool<PooledBuffer: Debug>,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Transaction")
+            .field("db",       &self.db)
+            .field("snapshot", &self.snapshot)
+            .field("batch",    &self.batch)
+            .field("read_set", &self.read_set)
+            .finish()
+    }
+}
+
+impl<FS, Cmp, Policy, Codecs, Pool> Transaction<'_, FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>>,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// The snapshot of the database which this transaction reads from.
+    #[inline]
+    #[must_use]
+    pub const fn snapshot(&self) -> &Snapshot {
+        &self.snapshot
+    }
+
+    /// The writes buffered by this transaction.
+    #[inline]
+    #[must_use]
+    pub const fn write_batch(&self) -> &WriteBatch {
+        self.batch.write_batch()
+    }
+
+    /// Buffer a write of `value` to `key`, to be applied when the transaction is committed.
+    ///
+    /// # Errors
+    /// See [`WriteBatch::put`].
+    #[inline]
+    pub fn put(&mut self, key: &[u8], value: &[u8]) -> Result<(), WriteBatchPutError> {
+        self.batch.put(key, value)
+    }
+
+    /// Buffer a deletion of `key`, to be applied when the transaction is committed.
+    ///
+    /// # Errors
+    /// See [`WriteBatch::delete`].
+    #[inline]
+    pub fn delete(&mut self, key: &[u8]) -> Result<(), WriteBatchDeleteError> {
+        self.batch.delete(key)
+    }
+}
+
+impl<FS, Cmp, Policy, Codecs, Pool> Transaction<'_, FS, Cmp, Policy, Codecs, Pool>
+where
+    FS:     LevelDBFilesystem,
+    Cmp:    LevelDBComparator + FastMirroredClone,
+    Policy: FilterPolicy<Eq: CoarserThan<Cmp::Eq>> + FastMirroredClone,
+    Codecs: CompressionCodecs,
+    Pool:   BufferPool,
+{
+    /// Get the value of `key`, as of this transaction's snapshot and including any writes buffered
+    /// in this transaction.
+    ///
+    /// Default [`ReadOptions`] are used.
+    pub fn get(&mut self, key: &[u8]) -> RwResult<Option<Vec<u8>>, FS, Cmp, Codecs> {
+        self.get_with(key, &ReadOptions::default())
+    }
+
+    /// Get the value of `key`, as of this transaction's snapshot and including any writes buffered
+    /// in this transaction.
+    ///
+    /// The `snapshot` setting of `read_opts` is ignored in favor of the transaction's snapshot.
+    pub fn get_with(
+        &mut self,
+        key:       &[u8],
+        read_opts: &ReadOptions,
+    ) -> RwResult<Option<Vec<u8>>, FS, Cmp, Codecs> {
+        let user_key = UserKey::new(key).ok_or_else(|| {
+            self.db.db_state().rw_error(RwErrorKind::Read(ReadError::KeyTooLong))
+        })?;
+
+        if let Some(buffered) = self.batch.get(key) {
+            return Ok(buffered.map(<[u8]>::to_owned));
+        }
+
+        let (db_state, per_handle) = self.db.inner();
+        let sequence_number = self.snapshot.sequence_number();
+
+        let entry = db_state
+            .get_entry(
+                &mut per_handle.decoders,
+                db_state.internal_read_opts(read_opts),
+                read_opts.record_seeks,
+                Some(sequence_number),
+                user_key,
+            )
+            .map_err(|kind| db_state.rw_error(kind))?;
+
+        // Every key is read as of the same snapshot, so a key only needs to be recorded once.
+        let cmp = self.batch.cmp();
+        if let Err(position) = self.read_set
+            .binary_search_by(|(read_key, _)| cmp.cmp(read_key, key))
+        {
+            self.read_set.insert(position, (key.to_owned(), sequence_number));
+        }
+
+        Ok(entry.and_then(|entry| entry.value))
+    }
+
+    /// Atomically apply the writes buffered in this transaction, if none of the keys it read from
+    /// the database have been written to since the transaction's snapshot was acquired.
+    ///
+    /// # Errors
+    /// If there is a conflict, a [`WriteError::TransactionConflict`] error is returned, and no
+    /// writes are applied. Unlike most write errors, a conflict does not close writes to the
+    /// database, so the transaction can be retried.
+    ///
+    /// [`WriteError::TransactionConflict`]: crate::errors::WriteError::TransactionConflict
+    pub fn commit(self, write_opts: &WriteOptions) -> RwResult<(), FS, Cmp, Codecs> {
+        let (db_state, per_handle) = self.db.inner();
+
+        let _applied: bool = db_state.write_command(
+            &mut per_handle.decoders,
+            WriteCommand::Transaction {
+                batch:      self.batch.borrow(),
+                read_set:   &self.read_set,
+                write_opts: InternalWriteOptions {
+                    sync: write_opts.sync,
+                },
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, clippy::unwrap_used, reason = "these are tests")]
+mod tests {
+    use std::thread;
+
+    use crate::all_errors::types::WriteError;
+    use super::super::test_db::{TestDB, TestDir};
+    use super::*;
+
+
+    fn get(db: &mut TestDB, key: &[u8]) -> Option<Vec<u8>> {
+        db.transaction().get(key).unwrap()
+    }
+
+    fn put(db: &mut TestDB, key: &[u8], value: &[u8]) {
+        let mut batch = db.indexed_write_batch();
+        batch.put(key, value).unwrap();
+        db.write_indexed_batch(&batch, &WriteOptions::default()).unwrap();
+    }
+
+    #[test]
+    fn commit_applies_writes() {
+        let dir = TestDir::new("transaction_commit");
+        let mut db = dir.open();
+        put(&mut db, b"deleted", b"value");
+
+        let mut transaction = db.transaction();
+        transaction.put(b"key", b"value").unwrap();
+        transaction.delete(b"deleted").unwrap();
+        // Nothing is written until the transaction is committed.
+        assert_eq!(get(transaction.db, b"key"), None);
+        transaction.commit(&WriteOptions::default()).unwrap();
+
+        assert_eq!(get(&mut db, b"key").as_deref(), Some(b"value".as_slice()));
+        assert_eq!(get(&mut db, b"deleted"), None);
+    }
+
+    #[test]
+    fn reads_own_writes() {
+        let dir = TestDir::new("transaction_reads_own_writes");
+        let mut db = dir.open();
+        put(&mut db, b"overwritten", b"old");
+        put(&mut db, b"deleted", b"old");
+
+        let mut transaction = db.transaction();
+        transaction.put(b"new", b"value").unwrap();
+        transaction.put(b"overwritten", b"new").unwrap();
+        transaction.delete(b"deleted").unwrap();
+
+        assert_eq!(transaction.get(b"new").unwrap().as_deref(), Some(b"value".as_slice()));
+        assert_eq!(transaction.get(b"overwritten").unwrap().as_deref(), Some(b"new".as_slice()));
+        assert_eq!(transaction.get(b"deleted").unwrap(), None);
+
+        // Keys served by the transaction's own writes are not read from the database.
+        assert!(transaction.read_set.is_empty());
+    }
+
+    #[test]
+    fn conflict_after_snapshot() {
+        let dir = TestDir::new("transaction_conflict");
+        let mut db = dir.open();
+        let mut other_writer = db.clone();
+        put(&mut db, b"read", b"old");
+
+        let mut transaction = db.transaction();
+        assert_eq!(transaction.get(b"read").unwrap().as_deref(), Some(b"old".as_slice()));
+        transaction.put(b"written", b"value").unwrap();
+
+        put(&mut other_writer, b"read", b"new");
+
+        let error = transaction.commit(&WriteOptions::default()).unwrap_err();
+        assert!(matches!(error.kind, RwErrorKind::Write(WriteError::TransactionConflict)));
+        assert_eq!(get(&mut db, b"written"), None);
+
+        // A conflict does not close writes, so the transaction can be retried.
+        let mut transaction = db.transaction();
+        assert_eq!(transaction.get(b"read").unwrap().as_deref(), Some(b"new".as_slice()));
+        transaction.put(b"written", b"value").unwrap();
+        transaction.commit(&WriteOptions::default()).unwrap();
+        assert_eq!(get(&mut db, b"written").as_deref(), Some(b"value".as_slice()));
+    }
+
+    #[test]
+    fn writes_to_unread_keys_do_not_conflict() {
+        let dir = TestDir::new("transaction_no_conflict");
+        let mut db = dir.open();
+        let mut other_writer = db.clone();
+
+        let mut transaction = db.transaction();
+        assert_eq!(transaction.get(b"read").unwrap(), None);
+        transaction.put(b"written", b"value").unwrap();
+
+        put(&mut other_writer, b"unread", b"value");
+        transaction.commit(&WriteOptions::default()).unwrap();
+        assert_eq!(get(&mut db, b"written").as_deref(), Some(b"value".as_slice()));
+    }
+
+    /// Several threads increment a counter in transactions while other threads issue
+    /// unconditional writes. If a transaction were merged into another writer's group, its read
+    /// set would go unchecked, and increments would be lost.
+    #[test]
+    fn transactions_racing_other_writers() {
+        const THREADS: u32 = 4;
+        const INCREMENTS: u32 = 50;
+
+        let dir = TestDir::new("transactions_racing_other_writers");
+        let mut db = dir.open();
+        put(&mut db, b"counter", &0_u32.to_le_bytes());
+
+        thread::scope(|scope| {
+            for thread_idx in 0..THREADS {
+                let mut incrementer = db.clone();
+                scope.spawn(move || {
+                    for _ in 0..INCREMENTS {
+                        loop {
+                            let mut transaction = incrementer.transaction();
+                            let old = transaction.get(b"counter").unwrap().unwrap();
+                            let count = u32::from_le_bytes(old.as_slice().try_into().unwrap());
+                            transaction.put(b"counter", &(count + 1).to_le_bytes()).unwrap();
+
+                            match transaction.commit(&WriteOptions::default()) {
+                                Ok(()) => break,
+                                Err(error) => assert!(matches!(
+                                    error.kind,
+                                    RwErrorKind::Write(WriteError::TransactionConflict),
+                                )),
+                            }
+                        }
+                    }
+                });
+
+                let mut writer = db.clone();
+                scope.spawn(move || {
+                    for write_idx in 0..INCREMENTS {
+                        put(&mut writer, &thread_idx.to_le_bytes(), &write_idx.to_le_bytes());
+                    }
+                });
+            }
+        });
+
+        let count = get(&mut db, b"counter").unwrap();
+        assert_eq!(count, (THREADS * INCREMENTS).to_le_bytes());
+        for thread_idx in 0..THREADS {
+            let last_write = get(&mut db, &thread_idx.to_le_bytes()).unwrap();
+            assert_eq!(last_write, (INCREMENTS - 1).to_le_bytes());
+        }
+    }
+}
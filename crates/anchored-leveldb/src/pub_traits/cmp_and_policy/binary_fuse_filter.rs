@@ -0,0 +1,788 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use clone_behavior::{DeepClone, MirroredClone, Speed};
+
+use super::{blocked_bloom_filter::filter_key_hash, bytewise_implementors::BytewiseEquality};
+use super::traits::FilterPolicy;
+
+
+/// The maximum number of seeds tried when constructing a filter.
+///
+/// Each attempt fails with very low probability, so running out of attempts indicates a bug.
+const MAX_ATTEMPTS: u8 = 100;
+/// Segment lengths are capped at `1 << 18`.
+const MAX_SEGMENT_LENGTH_LOG2: u8 = 18;
+/// The number of bytes after the fingerprints of a filter: the base-2 logarithm of the segment
+/// length, the attempt whose seed was used, and the number of bytes per fingerprint.
+const TRAILER_LEN: usize = 3;
+
+/// The size of the fingerprints stored in a [`BinaryFusePolicy`] filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuseFingerprintSize {
+    /// 8-bit fingerprints, for a false positive rate of about 0.39% (`1 / 256`) at 9 to 10 bits
+    /// per key.
+    Bits8,
+    /// 16-bit fingerprints, for a false positive rate of about 0.0015% (`1 / 65536`) at 18 to 20
+    /// bits per key.
+    Bits16,
+}
+
+impl FuseFingerprintSize {
+    #[inline]
+    #[must_use]
+    const fn num_bytes(self) -> u8 {
+        match self {
+            Self::Bits8  => 1,
+            Self::Bits16 => 2,
+        }
+    }
+}
+
+/// A static, space-efficient [binary fuse filter](https://arxiv.org/abs/2201.01174).
+///
+/// The filter stores a small fingerprint of each key in a table, such that the fingerprints at
+/// three positions determined by a key's hash XOR to the key's fingerprint.
+///
+/// For large key sets, binary fuse filters need about 12.5% more bits per key than the
+/// fingerprint size. With a million keys per filter, that is about 18% (with
+/// [`FuseFingerprintSize::Bits8`]) to 21% (with [`FuseFingerprintSize::Bits16`]) fewer bits
+/// than a [`BloomPolicy`] filter with the same false positive rate. Checking a key reads three
+/// fingerprints.
+///
+/// # Filter size
+/// The space overhead of a binary fuse filter is much higher for small key sets: with around
+/// 1000 keys, a filter uses about 40% more bits per key than the fingerprint size, which is no
+/// smaller than a Bloom filter. Since one filter is generated per `1 << filter_chunk_size_log2`
+/// bytes of a table file, the [`filter_chunk_size_log2`] setting should be raised well above
+/// its default, so that each table file has a single filter covering all of its keys.
+/// [`FilterOptions::binary_fuse_policy`] does so.
+///
+/// The filters of this policy are not readable by Google's LevelDB.
+///
+/// [`BloomPolicy`]: crate::db_options::BloomPolicy
+/// [`filter_chunk_size_log2`]: crate::db_options::FilterOptions::filter_chunk_size_log2
+/// [`FilterOptions::binary_fuse_policy`]: crate::db_options::FilterOptions::binary_fuse_policy
+#[derive(Debug, Clone, Copy)]
+pub struct BinaryFusePolicy {
+    fingerprint_size: FuseFingerprintSize,
+}
+
+impl BinaryFusePolicy {
+    #[inline]
+    #[must_use]
+    pub const fn new(fingerprint_size: FuseFingerprintSize) -> Self {
+        Self { fingerprint_size }
+    }
+
+    /// Generate a filter as in [`FilterPolicy::create_filter`], trying at most `max_attempts`
+    /// seeds.
+    fn create_filter_with_attempts(
+        self,
+        flattened_keys: &[u8],
+        key_offsets:    &[usize],
+        filter:         &mut Vec<u8>,
+        max_attempts:   u8,
+    ) -> Result<(), BinaryFusePolicyError> {
+        let fingerprint_bytes = self.fingerprint_size.num_bytes();
+
+        // ================================
+        //  Hash the keys
+        // ================================
+        let mut key_hashes = Vec::new();
+        key_hashes.try_reserve_exact(key_offsets.len())
+            .map_err(|_ignore| BinaryFusePolicyError::TooManyKeys)?;
+
+        let mut key_offsets_iter = key_offsets.iter().peekable();
+
+        while let Some(&key_offset) = key_offsets_iter.next() {
+            let upper_bound = **key_offsets_iter
+                .peek()
+                .unwrap_or(&&flattened_keys.len());
+
+            #[expect(
+                clippy::indexing_slicing,
+                reason = "for valid `key_offsets`, we know \
+                          `key_offset <= upper_bound <= flattened_key_data.len()`",
+            )]
+            let key = &flattened_keys[key_offset..upper_bound];
+
+            key_hashes.push(filter_key_hash(key));
+        }
+
+        // Construction fails if two keys have the same hash (since mixing in a seed is a
+        // bijection, duplicates cannot be fixed by retrying). Equal keys trivially have the same
+        // hash, and distinct keys with the same hash can share their fingerprints.
+        key_hashes.sort_unstable();
+        key_hashes.dedup();
+
+        if key_hashes.is_empty() {
+            // With no fingerprints, no key matches.
+            filter.try_reserve(TRAILER_LEN)
+                .map_err(|_ignore| BinaryFusePolicyError::TooManyKeys)?;
+            filter.extend([0, 0, fingerprint_bytes]);
+            return Ok(());
+        }
+
+        // ================================
+        //  Peel the keys
+        // ================================
+        let layout = FuseLayout::for_num_keys(key_hashes.len())
+            .ok_or(BinaryFusePolicyError::TooManyKeys)?;
+        let array_len = layout.array_len().ok_or(BinaryFusePolicyError::TooManyKeys)?;
+
+        let mut peeler = Peeler::new(array_len, key_hashes.len())
+            .ok_or(BinaryFusePolicyError::TooManyKeys)?;
+
+        let attempt = (0..max_attempts)
+            .find(|&attempt| peeler.peel(&key_hashes, fuse_seed(attempt), layout))
+            .ok_or(BinaryFusePolicyError::ConstructionFailed)?;
+
+        // ================================
+        //  Add space in the filter buffer
+        // ================================
+        let old_filter_len = filter.len();
+
+        let num_filter_bytes = array_len
+            .checked_mul(usize::from(fingerprint_bytes))
+            .ok_or(BinaryFusePolicyError::TooManyKeys)?;
+        let num_filter_bytes_plus_trailer = num_filter_bytes
+            .checked_add(TRAILER_LEN)
+            .ok_or(BinaryFusePolicyError::TooManyKeys)?;
+
+        filter.try_reserve(num_filter_bytes_plus_trailer)
+            .map_err(|_ignore| BinaryFusePolicyError::TooManyKeys)?;
+
+        // We're careful to not truncate the vec. Note that if the above call succeeded,
+        // `filter.len() + num_filter_bytes + TRAILER_LEN` does not overflow.
+        filter.resize(old_filter_len + num_filter_bytes, 0);
+        // Used by `key_may_match`. Note that we reserved space for the trailer.
+        filter.extend([layout.segment_length_log2, attempt, fingerprint_bytes]);
+
+        #[expect(clippy::indexing_slicing, reason = "we extended the buf, this is in-bounds")]
+        let fingerprints = &mut filter[old_filter_len..old_filter_len + num_filter_bytes];
+
+        // ================================
+        //  Assign fingerprints
+        // ================================
+        // Keys are assigned in the reverse order from which they were peeled. The position at
+        // which a key was peeled is not used by any key peeled after it, so it is not used by any
+        // key assigned before it.
+        for &(mixed_hash, position) in peeler.peeled.iter().rev() {
+            let mut fingerprint = key_fingerprint(mixed_hash, fingerprint_bytes);
+            for other_position in layout.positions(mixed_hash) {
+                // The fingerprint at `position` is still zero.
+                fingerprint ^= get_fingerprint(fingerprints, fingerprint_bytes, other_position);
+            }
+            set_fingerprint(fingerprints, fingerprint_bytes, position, fingerprint);
+        }
+
+        Ok(())
+    }
+}
+
+impl FilterPolicy for BinaryFusePolicy {
+    type Eq          = BytewiseEquality;
+    type FilterError = BinaryFusePolicyError;
+
+    #[inline]
+    fn name(&self) -> &'static [u8] {
+        b"anchored-leveldb.BinaryFuseFilter"
+    }
+
+    #[inline]
+    fn create_filter(
+        &self,
+        flattened_keys: &[u8],
+        key_offsets:    &[usize],
+        filter:         &mut Vec<u8>,
+    ) -> Result<(), Self::FilterError> {
+        self.create_filter_with_attempts(flattened_keys, key_offsets, filter, MAX_ATTEMPTS)
+    }
+
+    fn key_may_match(&self, key: &[u8], filter: &[u8]) -> bool {
+        let [fingerprints @ .., segment_length_log2, attempt, fingerprint_bytes] = filter else {
+            // An empty filter must not match any keys. Any other filter this short is invalid;
+            // default to returning true.
+            return !filter.is_empty();
+        };
+
+        if fingerprints.is_empty() {
+            // There were no keys.
+            return false;
+        }
+
+        let Some(layout) = FuseLayout::from_filter(
+            fingerprints.len(),
+            *segment_length_log2,
+            *fingerprint_bytes,
+        ) else {
+            // This is an invalid binary fuse filter. Default to returning true.
+            return true;
+        };
+
+        let mixed_hash = mix_seed(filter_key_hash(key), fuse_seed(*attempt));
+
+        let mut fingerprint = key_fingerprint(mixed_hash, *fingerprint_bytes);
+        for position in layout.positions(mixed_hash) {
+            fingerprint ^= get_fingerprint(fingerprints, *fingerprint_bytes, position);
+        }
+
+        // If the fingerprints do not cancel out, `key` can't possibly have been in the original
+        // list of keys. Otherwise, this may be a false positive.
+        fingerprint == 0
+    }
+}
+
+impl Default for BinaryFusePolicy {
+    /// The default `BinaryFusePolicy` filter uses 8-bit fingerprints, for a false positive rate
+    /// of about 0.39%.
+    fn default() -> Self {
+        Self::new(FuseFingerprintSize::Bits8)
+    }
+}
+
+impl<S: Speed> MirroredClone<S> for BinaryFusePolicy {
+    #[inline]
+    fn mirrored_clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: Speed> DeepClone<S> for BinaryFusePolicy {
+    #[inline]
+    fn deep_clone(&self) -> Self {
+        *self
+    }
+}
+
+/// The error returned if a binary fuse filter could not be generated.
+#[derive(Debug, Clone, Copy)]
+pub enum BinaryFusePolicyError {
+    /// Returned due to a variety of possible overflows or allocation failures that can happen if
+    /// a filter is generated for an excessively large number of keys.
+    TooManyKeys,
+    /// Returned if no seed allowed the filter to be constructed, which should essentially never
+    /// occur.
+    ConstructionFailed,
+}
+
+impl Display for BinaryFusePolicyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str(match self {
+            Self::TooManyKeys =>
+                "could not generate a binary fuse filter on an excessively large key set",
+            Self::ConstructionFailed =>
+                "could not construct a binary fuse filter with any seed",
+        })
+    }
+}
+
+impl Error for BinaryFusePolicyError {}
+
+impl<S: Speed> MirroredClone<S> for BinaryFusePolicyError {
+    #[inline]
+    fn mirrored_clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: Speed> DeepClone<S> for BinaryFusePolicyError {
+    #[inline]
+    fn deep_clone(&self) -> Self {
+        *self
+    }
+}
+
+/// The arrangement of the fingerprints of a binary fuse filter into segments.
+///
+/// Each key has one position in each of three consecutive segments.
+#[derive(Debug, Clone, Copy)]
+struct FuseLayout {
+    segment_length_log2: u8,
+    /// The number of segments in which a key's first position may lie. There are two more
+    /// segments in total.
+    segment_count:       u64,
+}
+
+impl FuseLayout {
+    /// Choose the layout of a filter for `num_keys` distinct keys, following the parameters
+    /// recommended for 3-wise binary fuse filters.
+    ///
+    /// Returns `None` if any overflow occurs.
+    #[must_use]
+    fn for_num_keys(num_keys: usize) -> Option<Self> {
+        #![expect(
+            clippy::as_conversions,
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            clippy::cast_sign_loss,
+            clippy::float_arithmetic,
+            reason = "the parameters are heuristics, and the results are small and nonnegative",
+        )]
+
+        let num_keys_f64 = num_keys as f64;
+
+        let (segment_length_log2, size_factor) = if num_keys <= 1 {
+            (2, 0_f64)
+        } else {
+            let segment_length_log2 = (num_keys_f64.log(3.33) + 2.25).floor() as u8;
+            let size_factor = (0.875 + 0.25 * 1_000_000_f64.ln() / num_keys_f64.ln()).max(1.125);
+            (segment_length_log2.min(MAX_SEGMENT_LENGTH_LOG2), size_factor)
+        };
+
+        let capacity = (num_keys_f64 * size_factor).round() as u64;
+        let segment_length = 1_u64 << segment_length_log2;
+        let segment_count = capacity.div_ceil(segment_length).saturating_sub(2).max(1);
+
+        let layout = Self {
+            segment_length_log2,
+            segment_count,
+        };
+        layout.array_len()?;
+        Some(layout)
+    }
+
+    /// Recover the layout of a filter from its trailer and the length of its fingerprints.
+    ///
+    /// Returns `None` if the filter is invalid.
+    #[must_use]
+    fn from_filter(
+        fingerprints_len:    usize,
+        segment_length_log2: u8,
+        fingerprint_bytes:   u8,
+    ) -> Option<Self> {
+        if !matches!(fingerprint_bytes, 1 | 2) || segment_length_log2 > MAX_SEGMENT_LENGTH_LOG2 {
+            return None;
+        }
+
+        let fingerprint_bytes = usize::from(fingerprint_bytes);
+        if fingerprints_len % fingerprint_bytes != 0 {
+            return None;
+        }
+        #[expect(clippy::integer_division, reason = "the length is a multiple of the divisor")]
+        let array_len = u64::try_from(fingerprints_len / fingerprint_bytes).ok()?;
+
+        let segment_length = 1_u64 << segment_length_log2;
+        if array_len % segment_length != 0 {
+            return None;
+        }
+        #[expect(clippy::integer_division, reason = "the length is a multiple of the divisor")]
+        let segment_count = (array_len / segment_length).checked_sub(2)?;
+        if segment_count == 0 {
+            return None;
+        }
+
+        Some(Self {
+            segment_length_log2,
+            segment_count,
+        })
+    }
+
+    /// The total number of fingerprints in the filter.
+    #[must_use]
+    fn array_len(self) -> Option<usize> {
+        let array_len = self.segment_count.checked_add(2)?
+            .checked_mul(1 << self.segment_length_log2)?;
+        usize::try_from(array_len).ok()
+    }
+
+    /// The three positions of a key with the given `mixed_hash`, each in a different segment.
+    ///
+    /// Each position is less than the filter's [`array_len`].
+    ///
+    /// [`array_len`]: FuseLayout::array_len
+    #[must_use]
+    fn positions(self, mixed_hash: u64) -> [usize; 3] {
+        let segment_length = 1_u64 << self.segment_length_log2;
+        let segment_mask = segment_length - 1;
+        let segment_count_length = self.segment_count << self.segment_length_log2;
+
+        // Map `mixed_hash` uniformly onto `0..segment_count_length` without division.
+        #[expect(
+            clippy::as_conversions,
+            reason = "the high half of a product of two `u64`s fits in a `u64`",
+        )]
+        let first = ((u128::from(mixed_hash) * u128::from(segment_count_length)) >> 64_u8) as u64;
+        // XORing a position with a value below `segment_length` keeps it in the same segment.
+        let second = (first + segment_length) ^ ((mixed_hash >> 18_u8) & segment_mask);
+        let third = (first + 2 * segment_length) ^ (mixed_hash & segment_mask);
+
+        #[expect(
+            clippy::as_conversions,
+            clippy::cast_possible_truncation,
+            reason = "each position is less than `array_len`, which fits in a `usize`",
+        )]
+        [first as usize, second as usize, third as usize]
+    }
+}
+
+/// The buffers used to peel the keys of a filter, which are reused across attempts.
+struct Peeler {
+    /// The number of fingerprints in the filter.
+    array_len:   usize,
+    /// The number of keys with a position at each index.
+    counts:      Vec<u32>,
+    /// The XOR of the mixed hashes of the keys with a position at each index.
+    xored:       Vec<u64>,
+    /// The indices which are a position of exactly one key that has not been peeled.
+    singletons:  Vec<usize>,
+    /// The mixed hash of each peeled key, and the index at which it was peeled.
+    peeled:      Vec<(u64, usize)>,
+}
+
+impl Peeler {
+    #[must_use]
+    fn new(array_len: usize, num_keys: usize) -> Option<Self> {
+        let mut counts = Vec::new();
+        let mut xored = Vec::new();
+        let mut singletons = Vec::new();
+        let mut peeled = Vec::new();
+
+        counts.try_reserve_exact(array_len).ok()?;
+        xored.try_reserve_exact(array_len).ok()?;
+        singletons.try_reserve_exact(array_len).ok()?;
+        peeled.try_reserve_exact(num_keys).ok()?;
+
+        Some(Self { array_len, counts, xored, singletons, peeled })
+    }
+
+    /// Attempt to repeatedly remove a key whose position at some index is not shared with any
+    /// other remaining key, until every key is removed.
+    ///
+    /// Returns `true` if every key was peeled, in which case `self.peeled` contains every key.
+    #[must_use]
+    fn peel(&mut self, key_hashes: &[u64], seed: u64, layout: FuseLayout) -> bool {
+        #![expect(
+            clippy::indexing_slicing,
+            reason = "every position is less than `array_len`, the length of the buffers",
+        )]
+
+        self.counts.clear();
+        self.xored.clear();
+        self.singletons.clear();
+        self.peeled.clear();
+
+        // `Self::new` reserved space for these elements.
+        self.counts.resize(self.array_len, 0);
+        self.xored.resize(self.array_len, 0);
+
+        for &key_hash in key_hashes {
+            let mixed_hash = mix_seed(key_hash, seed);
+            for position in layout.positions(mixed_hash) {
+                self.counts[position] += 1;
+                self.xored[position] ^= mixed_hash;
+            }
+        }
+
+        self.singletons.extend(
+            self.counts.iter()
+                .enumerate()
+                .filter(|&(_, &count)| count == 1)
+                .map(|(position, _)| position),
+        );
+
+        while let Some(position) = self.singletons.pop() {
+            if self.counts[position] != 1 {
+                // Another key at this position was already peeled.
+                continue;
+            }
+
+            // Since exactly one remaining key has a position here, this is its mixed hash.
+            let mixed_hash = self.xored[position];
+            self.peeled.push((mixed_hash, position));
+
+            for other_position in layout.positions(mixed_hash) {
+                self.counts[other_position] -= 1;
+                self.xored[other_position] ^= mixed_hash;
+                if self.counts[other_position] == 1 {
+                    self.singletons.push(other_position);
+                }
+            }
+        }
+
+        self.peeled.len() == key_hashes.len()
+    }
+}
+
+/// Get the seed used by the given construction attempt.
+#[must_use]
+fn fuse_seed(attempt: u8) -> u64 {
+    // This is a SplitMix64 step.
+    let mut seed = 0x_726b_2b9d_438b_9d4d_u64.wrapping_add(
+        (u64::from(attempt) + 1).wrapping_mul(0x_9e37_79b9_7f4a_7c15),
+    );
+    seed = (seed ^ (seed >> 30_u8)).wrapping_mul(0x_bf58_476d_1ce4_e5b9);
+    seed = (seed ^ (seed >> 27_u8)).wrapping_mul(0x_94d0_49bb_1331_11eb);
+    seed ^ (seed >> 31_u8)
+}
+
+/// Mix a `seed` into a key's hash. This is a bijection for any fixed `seed`.
+#[must_use]
+const fn mix_seed(key_hash: u64, seed: u64) -> u64 {
+    // This is the MurmurHash3 64-bit finalizer.
+    let mut hash = key_hash.wrapping_add(seed);
+    hash = (hash ^ (hash >> 33_u8)).wrapping_mul(0x_ff51_afd7_ed55_8ccd);
+    hash = (hash ^ (hash >> 33_u8)).wrapping_mul(0x_c4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33_u8)
+}
+
+/// The fingerprint of a key with the given `mixed_hash`, truncated to `fingerprint_bytes` bytes.
+#[must_use]
+const fn key_fingerprint(mixed_hash: u64, fingerprint_bytes: u8) -> u16 {
+    #![expect(
+        clippy::as_conversions,
+        clippy::cast_possible_truncation,
+        reason = "intentionally keep the low bits",
+    )]
+
+    let fingerprint = mixed_hash ^ (mixed_hash >> 32_u8);
+    if fingerprint_bytes == 1 {
+        fingerprint as u8 as u16
+    } else {
+        fingerprint as u16
+    }
+}
+
+#[must_use]
+fn get_fingerprint(fingerprints: &[u8], fingerprint_bytes: u8, position: usize) -> u16 {
+    #![expect(
+        clippy::indexing_slicing,
+        reason = "`position` is less than the number of fingerprints",
+    )]
+
+    if fingerprint_bytes == 1 {
+        u16::from(fingerprints[position])
+    } else {
+        let start = position * 2;
+        u16::from_le_bytes([fingerprints[start], fingerprints[start + 1]])
+    }
+}
+
+fn set_fingerprint(fingerprints: &mut [u8], fingerprint_bytes: u8, position: usize, value: u16) {
+    #![expect(
+        clippy::indexing_slicing,
+        reason = "`position` is less than the number of fingerprints",
+    )]
+
+    let [low, high] = value.to_le_bytes();
+    if fingerprint_bytes == 1 {
+        fingerprints[position] = low;
+    } else {
+        let start = position * 2;
+        fingerprints[start] = low;
+        fingerprints[start + 1] = high;
+    }
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::as_conversions,
+    clippy::cast_precision_loss,
+    clippy::float_arithmetic,
+    clippy::indexing_slicing,
+    clippy::missing_assert_message,
+    clippy::unwrap_used,
+    reason = "these are tests",
+)]
+mod tests {
+    use super::super::bloom_filter::BloomPolicy;
+    use super::*;
+
+
+    /// Generate the keys `{prefix}0`, `{prefix}1`, and so on, flattened into one buffer.
+    fn flattened_keys(prefix: &str, num_keys: usize) -> (Vec<u8>, Vec<usize>) {
+        let mut flattened_keys = Vec::new();
+        let mut key_offsets = Vec::new();
+        for key in 0..num_keys {
+            key_offsets.push(flattened_keys.len());
+            flattened_keys.extend(format!("{prefix}{key}").bytes());
+        }
+        (flattened_keys, key_offsets)
+    }
+
+    fn create_filter<Policy: FilterPolicy>(
+        policy:         &Policy,
+        flattened_keys: &[u8],
+        key_offsets:    &[usize],
+    ) -> Vec<u8> {
+        let mut filter = Vec::new();
+        policy.create_filter(flattened_keys, key_offsets, &mut filter).map_err(drop).unwrap();
+        filter
+    }
+
+    /// Returns the false positive rate and the number of bits per key of `policy`'s filter on
+    /// `num_keys` keys.
+    fn measure<Policy: FilterPolicy>(policy: &Policy, num_keys: usize) -> (f64, f64) {
+        let (flattened, offsets) = flattened_keys("present", num_keys);
+        let filter = create_filter(policy, &flattened, &offsets);
+
+        let num_checks = 100_000_u32;
+        let false_positives = (0..num_checks)
+            .filter(|key| policy.key_may_match(format!("absent{key}").as_bytes(), &filter))
+            .count();
+
+        (
+            false_positives as f64 / f64::from(num_checks),
+            (filter.len() * 8) as f64 / num_keys as f64,
+        )
+    }
+
+    const BOTH_SIZES: [FuseFingerprintSize; 2] = [
+        FuseFingerprintSize::Bits8,
+        FuseFingerprintSize::Bits16,
+    ];
+
+    #[test]
+    fn no_false_negatives() {
+        for fingerprint_size in BOTH_SIZES {
+            let policy = BinaryFusePolicy::new(fingerprint_size);
+
+            for num_keys in [1, 2, 3, 10, 100, 1000, 20_000] {
+                let (flattened, offsets) = flattened_keys("key", num_keys);
+                let filter = create_filter(&policy, &flattened, &offsets);
+
+                for key in 0..num_keys {
+                    let key = format!("key{key}");
+                    assert!(policy.key_may_match(key.as_bytes(), &filter));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        let policy = BinaryFusePolicy::default();
+        let num_keys = 1000;
+
+        let (flattened, offsets) = flattened_keys("key", num_keys);
+        let deduped_filter = create_filter(&policy, &flattened, &offsets);
+
+        // Every key twice, and one key many more times.
+        let mut flattened_duplicates = Vec::new();
+        let mut duplicate_offsets = Vec::new();
+        let keys = (0..num_keys).flat_map(|key| [key, key])
+            .chain([7; 100])
+            .map(|key| format!("key{key}"));
+        for key in keys {
+            duplicate_offsets.push(flattened_duplicates.len());
+            flattened_duplicates.extend(key.bytes());
+        }
+
+        let filter = create_filter(&policy, &flattened_duplicates, &duplicate_offsets);
+
+        // Duplicates do not affect the filter.
+        assert_eq!(filter, deduped_filter);
+        for key in 0..num_keys {
+            assert!(policy.key_may_match(format!("key{key}").as_bytes(), &filter));
+        }
+    }
+
+    #[test]
+    fn fingerprint_sizes() {
+        let num_keys = 20_000;
+
+        let (false_positive_rate_8, bits_per_key_8) = measure(
+            &BinaryFusePolicy::new(FuseFingerprintSize::Bits8),
+            num_keys,
+        );
+        let (false_positive_rate_16, bits_per_key_16) = measure(
+            &BinaryFusePolicy::new(FuseFingerprintSize::Bits16),
+            num_keys,
+        );
+
+        // The expected rates are `1 / 256` and `1 / 65536`.
+        assert!(false_positive_rate_8 < 0.006_f64, "{false_positive_rate_8}");
+        assert!(false_positive_rate_16 < 0.000_1_f64, "{false_positive_rate_16}");
+
+        assert!(bits_per_key_8 < 10.5_f64, "{bits_per_key_8}");
+        assert!(bits_per_key_16 < 21_f64, "{bits_per_key_16}");
+        assert!((bits_per_key_16 / bits_per_key_8 - 2_f64).abs() < 0.01_f64);
+    }
+
+    #[test]
+    fn smaller_than_bloom() {
+        let num_keys = 200_000;
+
+        // `BloomPolicy` has about the same false positive rates with these bits per key.
+        for (fingerprint_size, bloom_bits_per_key) in [
+            (FuseFingerprintSize::Bits8, 11),
+            (FuseFingerprintSize::Bits16, 23),
+        ] {
+            let (fuse_rate, fuse_bits) = measure(
+                &BinaryFusePolicy::new(fingerprint_size),
+                num_keys,
+            );
+            let (bloom_rate, bloom_bits) = measure(
+                &BloomPolicy::new(bloom_bits_per_key),
+                num_keys,
+            );
+
+            assert!(
+                fuse_rate <= bloom_rate.mul_add(1.25_f64, 0.000_02_f64),
+                "{fuse_rate} vs {bloom_rate}",
+            );
+            // For a key set of this size, the savings should be over 15%.
+            assert!(fuse_bits < bloom_bits * 0.85_f64, "{fuse_bits} vs {bloom_bits}");
+        }
+    }
+
+    #[test]
+    fn empty_key_set() {
+        for fingerprint_size in BOTH_SIZES {
+            let policy = BinaryFusePolicy::new(fingerprint_size);
+            let filter = create_filter(&policy, &[], &[]);
+
+            assert_eq!(filter.len(), TRAILER_LEN);
+            assert!(!policy.key_may_match(b"", &filter));
+            assert!(!policy.key_may_match(b"key", &filter));
+        }
+
+        assert!(!BinaryFusePolicy::default().key_may_match(b"key", &[]));
+    }
+
+    #[test]
+    fn construction_failure() {
+        let policy = BinaryFusePolicy::default();
+        let (flattened, offsets) = flattened_keys("key", 100);
+
+        let mut filter = vec![1, 2, 3];
+        let result = policy.create_filter_with_attempts(&flattened, &offsets, &mut filter, 0);
+
+        assert!(matches!(result, Err(BinaryFusePolicyError::ConstructionFailed)));
+        // Nothing was written.
+        assert_eq!(filter, [1, 2, 3]);
+    }
+
+    #[test]
+    fn appends_to_existing_filters() {
+        let policy = BinaryFusePolicy::default();
+        let (flattened, offsets) = flattened_keys("key", 100);
+
+        let mut filter = vec![1, 2, 3];
+        policy.create_filter(&flattened, &offsets, &mut filter).unwrap();
+
+        assert_eq!(filter[..3], [1, 2, 3]);
+        assert_eq!(filter[3..], create_filter(&policy, &flattened, &offsets));
+    }
+
+    #[test]
+    fn invalid_filters_match() {
+        let policy = BinaryFusePolicy::default();
+
+        let invalid_filters: [&[u8]; 5] = [
+            // Too short.
+            &[0],
+            &[0, 1],
+            // An unknown fingerprint size.
+            &[0; 12 + TRAILER_LEN],
+            &[[0; 12].as_slice(), [2, 0, 3].as_slice()].concat(),
+            // The number of fingerprints does not match the segment length.
+            &[[0; 13].as_slice(), [2, 0, 1].as_slice()].concat(),
+        ];
+
+        for filter in invalid_filters {
+            assert!(policy.key_may_match(b"key", filter), "{filter:?}");
+        }
+    }
+}
@@ -0,0 +1,464 @@
+use std::iter;
+
+use clone_behavior::{DeepClone, MirroredClone, Speed};
+
+use super::{bytewise_implementors::BytewiseEquality, traits::FilterPolicy};
+use super::bloom_filter::{BLOOM_POLICY_NAME, BloomPolicy, BloomPolicyOverflow};
+
+
+/// The number of bytes in each block of a blocked Bloom filter, which is the size of a cache line
+/// on most platforms.
+const BLOCK_BYTES: usize = 64;
+/// The base-2 logarithm of the number of bits in each block.
+const BLOCK_BITS_LOG2: u32 = 9;
+/// The final byte of every filter generated by [`BlockedBloomPolicy`].
+///
+/// The final byte of a filter generated by [`BloomPolicy`] is its number of hash functions,
+/// which is at most `30`, so the two formats can be told apart.
+const BLOCKED_FILTER_MARKER: u8 = 0xff;
+/// The number of bytes after the blocks of a filter: the number of probes, and the marker.
+const TRAILER_LEN: usize = 2;
+/// Used to derive each probe of a key from the previous probe.
+const PROBE_MULTIPLIER: u32 = 0x_9e37_79b9;
+/// Added to each probe of a key, after multiplying by [`PROBE_MULTIPLIER`], to derive the next
+/// probe. Since it is odd, a probe hash of zero does not probe the same bit repeatedly.
+const PROBE_INCREMENT: u32 = 0x_7f4a_7c15;
+
+/// A cache-local [Bloom filter](https://en.wikipedia.org/wiki/Bloom_filter).
+///
+/// Every bit probed for a key lies in a single 64-byte block (the size of a cache line on most
+/// platforms), so that checking a key usually costs a single cache miss.
+///
+/// Keys are hashed to 64 bits; the high 32 bits choose a block, and the low 32 bits choose the
+/// bits probed within that block. [`BloomPolicy`] instead hashes keys to 32 bits and derives
+/// every probe from that one hash, which correlates the probes and raises its false positive
+/// rate.
+///
+/// The filters of this policy are not readable by Google's LevelDB, and are recorded under a
+/// different name than those of [`BloomPolicy`]. However, this policy can also check the filters
+/// generated by [`BloomPolicy`], so table files written with [`BloomPolicy`] keep the benefit of
+/// their filters after a database switches to this policy. (See
+/// [`FilterPolicy::compatible_names`].)
+#[derive(Debug, Clone, Copy)]
+pub struct BlockedBloomPolicy {
+    bits_per_key: u8,
+    num_probes:   u8,
+}
+
+impl BlockedBloomPolicy {
+    /// Achieve an expected false positive rate just under 1%.
+    pub const DEFAULT_BITS_PER_KEY: u8 = 10;
+
+    /// The number of filter bits to use per key. The default `BlockedBloomPolicy` filter uses
+    /// 10 bits per key to get a false positive rate just under 1%.
+    ///
+    /// Since the probes of a key are confined to one block, the number of probes is somewhat
+    /// lower than for a standard Bloom filter with the same number of bits per key, and is at
+    /// most `24`.
+    #[must_use]
+    pub const fn new(bits_per_key: u8) -> Self {
+        // These values are close to optimal for 512-bit blocks. A standard Bloom filter would
+        // use around `bits_per_key * ln(2)` probes, but the variance in the number of keys per
+        // block makes additional probes less useful.
+        let num_probes = match bits_per_key {
+            0..=2        => 1,
+            3            => 2,
+            4..=5        => 3,
+            6            => 4,
+            7..=8        => 5,
+            9..=10       => 6,
+            11           => 7,
+            12..=14      => 8,
+            15..=16      => 9,
+            17..=18      => 10,
+            19..=22      => 11,
+            23..=25      => 12,
+            // Half of `bits_per_key`.
+            26..=47      => bits_per_key >> 1_u8,
+            48..=u8::MAX => 24,
+        };
+
+        Self {
+            bits_per_key,
+            num_probes,
+        }
+    }
+}
+
+impl FilterPolicy for BlockedBloomPolicy {
+    type Eq          = BytewiseEquality;
+    type FilterError = BloomPolicyOverflow;
+
+    #[inline]
+    fn name(&self) -> &'static [u8] {
+        b"anchored-leveldb.BlockedBloomFilter"
+    }
+
+    #[inline]
+    fn compatible_names(&self) -> &'static [&'static [u8]] {
+        &[BLOOM_POLICY_NAME]
+    }
+
+    fn create_filter(
+        &self,
+        flattened_keys: &[u8],
+        key_offsets:    &[usize],
+        filter:         &mut Vec<u8>,
+    ) -> Result<(), Self::FilterError> {
+        /// Return the number of blocks and bytes (respectively) to use in the filter, excluding
+        /// the trailer.
+        ///
+        /// Returns `None` if any overflow occurs.
+        fn num_blocks_and_bytes(key_offsets_len: usize, bits_per_key: u8) -> Option<(u32, usize)> {
+            let num_blocks = key_offsets_len
+                .checked_mul(usize::from(bits_per_key))?
+                .div_ceil(BLOCK_BYTES * 8)
+                // Enforce a minimum of one block.
+                .max(1);
+            let num_filter_bytes = num_blocks.checked_mul(BLOCK_BYTES)?;
+
+            Some((u32::try_from(num_blocks).ok()?, num_filter_bytes))
+        }
+
+        let (num_blocks, num_filter_bytes) = num_blocks_and_bytes(
+            key_offsets.len(),
+            self.bits_per_key,
+        ).ok_or(BloomPolicyOverflow::TooManyKeys)?;
+
+        // ================================
+        //  Add space in the filter buffer
+        // ================================
+        let old_filter_len = filter.len();
+
+        let num_filter_bytes_plus_trailer = num_filter_bytes
+            .checked_add(TRAILER_LEN)
+            .ok_or(BloomPolicyOverflow::TooManyKeys)?;
+
+        filter.try_reserve(num_filter_bytes_plus_trailer)
+            // Any error here would be caused by a large value of `key_offsets.len()`.
+            .map_err(|_ignore| BloomPolicyOverflow::TooManyKeys)?;
+
+        // We're careful to not truncate the vec. Note that if the above call succeeded,
+        // `filter.len() + num_filter_bytes + TRAILER_LEN` does not overflow.
+        filter.resize(old_filter_len + num_filter_bytes, 0);
+        // Used by `key_may_match`. Note that we reserved space for the trailer.
+        filter.push(self.num_probes);
+        filter.push(BLOCKED_FILTER_MARKER);
+
+        #[expect(clippy::indexing_slicing, reason = "we extended the buf, this is in-bounds")]
+        let filter_bits = &mut filter[old_filter_len..old_filter_len + num_filter_bytes];
+
+        // ================================
+        //  Set filter bits
+        // ================================
+        let mut key_offsets_iter = key_offsets.iter().peekable();
+
+        while let Some(&key_offset) = key_offsets_iter.next() {
+            let upper_bound = **key_offsets_iter
+                .peek()
+                .unwrap_or(&&flattened_keys.len());
+
+            #[expect(
+                clippy::indexing_slicing,
+                reason = "for valid `key_offsets`, we know \
+                          `key_offset <= upper_bound <= flattened_key_data.len()`",
+            )]
+            let key = &flattened_keys[key_offset..upper_bound];
+
+            let (block_start, probe_hash) = block_start_and_probe_hash(key, num_blocks);
+
+            #[expect(
+                clippy::indexing_slicing,
+                reason = "`block_start` is the start of one of the `num_blocks` blocks",
+            )]
+            let block = &mut filter_bits[block_start..block_start + BLOCK_BYTES];
+
+            for bit_to_set in probed_bits(probe_hash, self.num_probes) {
+                #[expect(
+                    clippy::indexing_slicing,
+                    clippy::integer_division,
+                    reason = "bit_to_set < 512, so bit_to_set / 8 < 64 == block.len()",
+                )]
+                {
+                    block[bit_to_set / 8] |= 1 << (bit_to_set % 8);
+                };
+            }
+        }
+
+        Ok(())
+    }
+
+    fn key_may_match(&self, key: &[u8], filter: &[u8]) -> bool {
+        let [filter_bits @ .., num_probes, BLOCKED_FILTER_MARKER] = filter else {
+            // This is not a blocked Bloom filter, so it should be a filter generated by
+            // `BloomPolicy` (which also handles empty or unknown filters).
+            return BloomPolicy::new(BloomPolicy::DEFAULT_BITS_PER_KEY)
+                .key_may_match(key, filter);
+        };
+
+        if filter_bits.is_empty() || filter_bits.len() % BLOCK_BYTES != 0 {
+            // This is an invalid blocked Bloom filter. Default to returning true.
+            return true;
+        }
+        #[expect(clippy::integer_division, reason = "the length is a multiple of the divisor")]
+        let Ok(num_blocks) = u32::try_from(filter_bits.len() / BLOCK_BYTES) else {
+            // Same as above.
+            return true;
+        };
+
+        let (block_start, probe_hash) = block_start_and_probe_hash(key, num_blocks);
+
+        let Some(block) = filter_bits.get(block_start..block_start + BLOCK_BYTES) else {
+            // This can't happen, but if it did, the filter would be invalid.
+            return true;
+        };
+
+        for bit_to_test in probed_bits(probe_hash, *num_probes) {
+            #[expect(
+                clippy::indexing_slicing,
+                clippy::integer_division,
+                reason = "bit_to_test < 512, so bit_to_test / 8 < 64 == block.len()",
+            )]
+            if block[bit_to_test / 8] & (1 << (bit_to_test % 8)) == 0 {
+                // A bit associated with `key` was not set, so it can't possibly have been
+                // in the original list of keys.
+                return false;
+            }
+        }
+
+        // This may be a false positive
+        true
+    }
+}
+
+impl Default for BlockedBloomPolicy {
+    /// The default `BlockedBloomPolicy` filter uses 10 bits per key to get an error rate just
+    /// under 1%.
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_BITS_PER_KEY)
+    }
+}
+
+impl<S: Speed> MirroredClone<S> for BlockedBloomPolicy {
+    #[inline]
+    fn mirrored_clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: Speed> DeepClone<S> for BlockedBloomPolicy {
+    #[inline]
+    fn deep_clone(&self) -> Self {
+        *self
+    }
+}
+
+/// Get the offset of the block (among `num_blocks` blocks) in which `key`'s bits are probed, and
+/// the hash from which the probed bits are derived.
+#[must_use]
+fn block_start_and_probe_hash(key: &[u8], num_blocks: u32) -> (usize, u32) {
+    let hash = filter_key_hash(key);
+
+    #[expect(
+        clippy::as_conversions,
+        clippy::cast_possible_truncation,
+        reason = "intentionally split the hash into two halves",
+    )]
+    let (block_hash, probe_hash) = ((hash >> 32_u8) as u32, hash as u32);
+
+    // Map `block_hash` uniformly onto `0..num_blocks` without division.
+    let block_index = (u64::from(block_hash) * u64::from(num_blocks)) >> 32_u8;
+
+    #[expect(
+        clippy::as_conversions,
+        reason = "`block_index < num_blocks`, and `num_blocks * BLOCK_BYTES` fits in a usize",
+    )]
+    let block_start = block_index as usize * BLOCK_BYTES;
+
+    (block_start, probe_hash)
+}
+
+/// Get the bits probed within a block, each of which is less than `512`.
+fn probed_bits(mut probe_hash: u32, num_probes: u8) -> impl Iterator<Item = usize> {
+    #![expect(clippy::as_conversions, reason = "each bit is less than 512, which fits in a usize")]
+
+    iter::repeat_with(move || {
+        // The top 9 bits of the hash.
+        let bit = probe_hash >> (u32::BITS - BLOCK_BITS_LOG2);
+        probe_hash = probe_hash.wrapping_mul(PROBE_MULTIPLIER).wrapping_add(PROBE_INCREMENT);
+        bit as usize
+    }).take(usize::from(num_probes))
+}
+
+/// Multiply `lhs` and `rhs` to 128 bits, and fold the result into 64 bits.
+#[inline]
+#[must_use]
+fn folded_multiply(lhs: u64, rhs: u64) -> u64 {
+    let product = u128::from(lhs) * u128::from(rhs);
+
+    #[expect(
+        clippy::as_conversions,
+        clippy::cast_possible_truncation,
+        reason = "intentionally split the product into two halves",
+    )]
+    let (low, high) = (product as u64, (product >> 64_u8) as u64);
+
+    low ^ high
+}
+
+/// The 64-bit hash function for blocked Bloom filters and binary fuse filters.
+///
+/// Since the hash determines the format of persisted filters, it must never change.
+#[must_use]
+pub(super) fn filter_key_hash(data: &[u8]) -> u64 {
+    const SEED:         u64 = 0x_2d35_8dcc_aa6c_78a5;
+    const MULTIPLIER_A: u64 = 0x_a076_1d64_78bd_642f;
+    const MULTIPLIER_B: u64 = 0x_e703_7ed1_a0b4_28db;
+
+    #[expect(clippy::as_conversions, reason = "a `usize` fits in a `u64`")]
+    let data_len = data.len() as u64;
+    let mut hash = SEED ^ folded_multiply(data_len ^ MULTIPLIER_A, MULTIPLIER_B);
+
+    let mut data_iter = data.chunks_exact(size_of::<u64>());
+
+    for chunk in &mut data_iter {
+        #[expect(clippy::unwrap_used, reason = "the chunk size means that this always succeeds")]
+        let word = u64::from_le_bytes(chunk.try_into().unwrap());
+
+        hash = folded_multiply(hash ^ word, MULTIPLIER_A);
+    }
+
+    let remainder = data_iter.remainder();
+    if !remainder.is_empty() {
+        let mut word = [0; size_of::<u64>()];
+        #[expect(clippy::indexing_slicing, reason = "the remainder is shorter than a `u64`")]
+        word[..remainder.len()].copy_from_slice(remainder);
+
+        hash = folded_multiply(hash ^ u64::from_le_bytes(word), MULTIPLIER_A);
+    }
+
+    folded_multiply(hash ^ SEED, MULTIPLIER_B)
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::as_conversions,
+    clippy::cast_precision_loss,
+    clippy::float_arithmetic,
+    clippy::missing_assert_message,
+    clippy::unwrap_used,
+    reason = "these are tests",
+)]
+mod tests {
+    use super::*;
+
+
+    /// Generate the keys `{prefix}0`, `{prefix}1`, and so on, flattened into one buffer.
+    fn flattened_keys(prefix: &str, num_keys: usize) -> (Vec<u8>, Vec<usize>) {
+        let mut flattened_keys = Vec::new();
+        let mut key_offsets = Vec::new();
+        for key in 0..num_keys {
+            key_offsets.push(flattened_keys.len());
+            flattened_keys.extend(format!("{prefix}{key}").bytes());
+        }
+        (flattened_keys, key_offsets)
+    }
+
+    fn create_filter<Policy: FilterPolicy>(
+        policy:         &Policy,
+        flattened_keys: &[u8],
+        key_offsets:    &[usize],
+    ) -> Vec<u8> {
+        let mut filter = Vec::new();
+        policy.create_filter(flattened_keys, key_offsets, &mut filter).map_err(drop).unwrap();
+        filter
+    }
+
+    #[test]
+    fn no_false_negatives() {
+        for bits_per_key in [1, 5, 10, 20, 60] {
+            let policy = BlockedBloomPolicy::new(bits_per_key);
+
+            for num_keys in [1, 10, 100, 5000] {
+                let (flattened, offsets) = flattened_keys("key", num_keys);
+                let filter = create_filter(&policy, &flattened, &offsets);
+
+                for key in 0..num_keys {
+                    let key = format!("key{key}");
+                    assert!(policy.key_may_match(key.as_bytes(), &filter));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn false_positive_rate() {
+        let policy = BlockedBloomPolicy::new(10);
+        let num_keys = 10_000;
+
+        let (flattened, offsets) = flattened_keys("present", num_keys);
+        let filter = create_filter(&policy, &flattened, &offsets);
+
+        let false_positives = (0..num_keys)
+            .filter(|key| policy.key_may_match(format!("absent{key}").as_bytes(), &filter))
+            .count();
+
+        // A standard Bloom filter would have a false positive rate under 1%; blocking the filter
+        // costs a little accuracy.
+        let rate = false_positives as f64 / num_keys as f64;
+        assert!(rate < 0.02_f64, "false positive rate of {rate}");
+    }
+
+    #[test]
+    fn zero_probe_hash() {
+        // Every probe must not land on the same bit.
+        let mut bits = probed_bits(0, 6).collect::<Vec<_>>();
+        bits.dedup();
+        assert!(bits.len() > 1, "{bits:?}");
+    }
+
+    #[test]
+    fn reads_bloom_filters() {
+        let bloom_policy = BloomPolicy::new(10);
+        let policy = BlockedBloomPolicy::new(10);
+        let num_keys = 1000;
+
+        let (flattened, offsets) = flattened_keys("key", num_keys);
+        let filter = create_filter(&bloom_policy, &flattened, &offsets);
+
+        for key in 0..num_keys {
+            let key = format!("key{key}");
+            assert!(policy.key_may_match(key.as_bytes(), &filter));
+        }
+
+        // The filter of `BloomPolicy` should actually be used, rather than matching every key.
+        let false_positives = (0..num_keys)
+            .filter(|key| policy.key_may_match(format!("absent{key}").as_bytes(), &filter))
+            .count();
+        assert!(false_positives * 20 < num_keys, "{false_positives} false positives");
+
+        assert_eq!(policy.compatible_names(), &[bloom_policy.name()]);
+    }
+
+    #[test]
+    fn invalid_filters_match() {
+        let policy = BlockedBloomPolicy::default();
+
+        let invalid_filters: [&[u8]; 4] = [
+            // Only a trailer, without any blocks.
+            &[6, BLOCKED_FILTER_MARKER],
+            // A partial block.
+            &[[0; BLOCK_BYTES - 1].as_slice(), [6, BLOCKED_FILTER_MARKER].as_slice()].concat(),
+            // Neither a blocked Bloom filter nor a supported Bloom filter.
+            &[0, 0, 0, 31],
+            &[0, 0, 0, 0xfe],
+        ];
+
+        for filter in invalid_filters {
+            assert!(policy.key_may_match(b"key", filter), "{filter:?}");
+        }
+
+        // An empty filter has no keys.
+        assert!(!policy.key_may_match(b"key", &[]));
+    }
+}
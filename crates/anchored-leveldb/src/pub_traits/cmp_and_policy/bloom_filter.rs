@@ -6,6 +6,9 @@ use clone_behavior::{DeepClone, MirroredClone, Speed};
 use super::{bytewise_implementors::BytewiseEquality, traits::FilterPolicy};
 
 
+/// The name of [`BloomPolicy`], which is the same as Google's LevelDB's builtin Bloom filter.
+pub(super) const BLOOM_POLICY_NAME: &[u8] = b"leveldb.BuiltinBloomFilter2";
+
 /// A LevelDB-compatible [Bloom filter](https://en.wikipedia.org/wiki/Bloom_filter).
 #[derive(Debug, Clone, Copy)]
 pub struct BloomPolicy {
@@ -74,7 +77,7 @@ impl FilterPolicy for BloomPolicy {
 
     #[inline]
     fn name(&self) -> &'static [u8] {
-        b"leveldb.BuiltinBloomFilter2"
+        BLOOM_POLICY_NAME
     }
 
     fn create_filter(
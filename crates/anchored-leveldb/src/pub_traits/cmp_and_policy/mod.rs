@@ -2,9 +2,13 @@ mod traits;
 mod bytewise_implementors;
 mod trivial_implementors;
 mod bloom_filter;
+mod blocked_bloom_filter;
+mod binary_fuse_filter;
 
 
 pub use self::{
+    binary_fuse_filter::{BinaryFusePolicy, BinaryFusePolicyError, FuseFingerprintSize},
+    blocked_bloom_filter::BlockedBloomPolicy,
     bloom_filter::{BloomPolicy, BloomPolicyOverflow},
     bytewise_implementors::{BytewiseComparator, BytewiseEquality},
     traits::{CoarserThan, EquivalenceRelation, FilterPolicy, LevelDBComparator},
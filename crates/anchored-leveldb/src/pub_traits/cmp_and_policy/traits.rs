@@ -146,6 +146,19 @@ pub trait FilterPolicy {
     #[must_use]
     fn name(&self) -> &'static [u8];
 
+    /// The names of other filter policies whose filters can also be checked by
+    /// [`Self::key_may_match`], in order of preference.
+    ///
+    /// When reading a table file which does not have a filter named [`Self::name`], a filter with
+    /// one of these names is used instead (if there is one). This allows the filters of existing
+    /// table files to remain useful after a database switches to a new filter policy.
+    ///
+    /// By default, an empty list is returned.
+    #[must_use]
+    fn compatible_names(&self) -> &'static [&'static [u8]] {
+        &[]
+    }
+
     /// Extends the `filter` buffer with a filter corresponding to the provided flattened keys.
     ///
     /// `flattened_keys` is a slice of all the keys concatenated together.
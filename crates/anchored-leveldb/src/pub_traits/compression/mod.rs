@@ -12,6 +12,20 @@ pub trait CompressionCodecs {
     type CompressionError;
     type DecompressionError;
 
+    /// The [`CompressorId`] and stable name of each supported codec, as in
+    /// [`CompressionCodec::name`].
+    ///
+    /// These are recorded in each table file written with these codecs, and reading a table file
+    /// fails if its recorded name of a compressor ID does not match the name given here.
+    ///
+    /// If empty, nothing is recorded or verified. By default, an empty list is returned.
+    ///
+    /// [`CompressionCodec::name`]: crate::compression::CompressionCodec::name
+    #[must_use]
+    fn codec_names(&self) -> Vec<(CompressorId, &'static str)> {
+        Vec::new()
+    }
+
     #[must_use]
     fn init_encoders(&self) -> Self::Encoders;
 
@@ -34,6 +48,56 @@ pub trait CompressionCodecs {
         pool:         &Pool,
         existing_buf: &mut Option<Pool::PooledBuffer>,
     ) -> Result<Pool::PooledBuffer, CodecsDecompressionError<Self::DecompressionError>>;
+
+    /// Train a compression dictionary of at most `max_dictionary_len` bytes for the codec with
+    /// the given `id`.
+    ///
+    /// The samples are concatenated in `samples`, and `sample_lens` holds the length of each
+    /// sample.
+    ///
+    /// Returns `None` if the codec is unsupported or does not support dictionaries, or if a
+    /// dictionary could not be trained. By default, `None` is always returned.
+    #[must_use]
+    fn train_dictionary(
+        &self,
+        _id:                 CompressorId,
+        _samples:            &[u8],
+        _sample_lens:        &[usize],
+        _max_dictionary_len: usize,
+    ) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Equivalent to [`CompressionCodecs::encode`], except that the given `dictionary`, which was
+    /// returned by [`CompressionCodecs::train_dictionary`] for the same `id`, is used.
+    ///
+    /// By default, the dictionary is ignored.
+    fn encode_with_dictionary<Pool: BufferPool>(
+        encoders:         &mut Self::Encoders,
+        _dictionary:      &[u8],
+        src:              &[u8],
+        id:               CompressorId,
+        compression_goal: usize,
+        pool:             &Pool,
+        existing_buf:     &mut Option<Pool::PooledBuffer>,
+    ) -> Result<Pool::PooledBuffer, CodecsCompressionError<Self::CompressionError>> {
+        Self::encode(encoders, src, id, compression_goal, pool, existing_buf)
+    }
+
+    /// Equivalent to [`CompressionCodecs::decode`], except that the given `dictionary`, which
+    /// should be the dictionary that `src` was encoded with, is used.
+    ///
+    /// By default, the dictionary is ignored.
+    fn decode_with_dictionary<Pool: BufferPool>(
+        decoders:     &mut Self::Decoders,
+        _dictionary:  &[u8],
+        src:          &[u8],
+        id:           CompressorId,
+        pool:         &Pool,
+        existing_buf: &mut Option<Pool::PooledBuffer>,
+    ) -> Result<Pool::PooledBuffer, CodecsDecompressionError<Self::DecompressionError>> {
+        Self::decode(decoders, src, id, pool, existing_buf)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
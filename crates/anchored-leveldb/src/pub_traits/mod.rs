@@ -8,3 +8,5 @@ pub(crate) mod pool;
 pub(crate) mod logger;
 /// `ErrorHandler` trait.
 pub(crate) mod error_handler;
+/// `CompactionPartitioner` trait.
+pub(crate) mod partitioner;
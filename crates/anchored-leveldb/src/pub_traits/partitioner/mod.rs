@@ -0,0 +1,7 @@
+mod partitioner_trait;
+mod prefix_partitioner;
+
+
+pub use self::{
+    partitioner_trait::CompactionPartitioner, prefix_partitioner::PrefixPartitioner,
+};
@@ -0,0 +1,24 @@
+use std::fmt::Debug;
+
+
+/// Chooses user-key boundaries at which a compaction must begin a new output table file.
+///
+/// Aligning table files with logical divisions of the key space (for instance, key prefixes
+/// which identify a shard or region) means that those divisions can be compacted or dropped
+/// without touching table files shared with other divisions.
+///
+/// Output files are still split according to the usual size and grandparent-overlap limits;
+/// a partitioner can only introduce additional boundaries.
+pub trait CompactionPartitioner: Debug {
+    /// Returns `true` if the compaction output file whose last entry has user key `prev_key`
+    /// should be finished before an entry with user key `key` is added, so that `key` begins
+    /// a new output file.
+    ///
+    /// `prev_key` is strictly less than `key` with respect to the database's comparator; this
+    /// method is never called for two entries with the same user key.
+    ///
+    /// For boundaries to be consistent across compactions, the result should depend only on
+    /// the two keys.
+    #[must_use]
+    fn should_partition(&self, prev_key: &[u8], key: &[u8]) -> bool;
+}
@@ -0,0 +1,19 @@
+use super::partitioner_trait::CompactionPartitioner;
+
+
+/// A [`CompactionPartitioner`] which starts a new output file whenever the first `prefix_len`
+/// bytes of consecutive user keys differ.
+///
+/// A user key shorter than `prefix_len` bytes is its own prefix.
+#[derive(Debug, Clone, Copy)]
+pub struct PrefixPartitioner {
+    pub prefix_len: usize,
+}
+
+impl CompactionPartitioner for PrefixPartitioner {
+    #[inline]
+    fn should_partition(&self, prev_key: &[u8], key: &[u8]) -> bool {
+        let prefix = |user_key: &[u8]| user_key.get(..self.prefix_len).unwrap_or(user_key);
+        prefix(prev_key) != prefix(key)
+    }
+}
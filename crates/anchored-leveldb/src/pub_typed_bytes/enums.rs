@@ -7,6 +7,9 @@ use super::simple_newtypes::FileNumber;
 pub enum BlockType {
     Metaindex,
     Filter,
+    CompressionCodecs,
+    CompressionDictionary,
+    TableProperties,
     Index,
     Data,
 }
@@ -164,6 +164,9 @@ impl NonZeroLevel {
     #[allow(clippy::unwrap_used, reason = "validated at compile time")]
     pub(crate) const ONE: Self = Self(NonZeroU8::new(1).unwrap());
 
+    /// The greatest level, level 6.
+    pub(crate) const LAST: Self = Self(NUM_NONZERO_LEVELS);
+
     /// All the nonzero levels in increasing order, from level 1 to level 6.
     #[allow(clippy::unwrap_used, reason = "validated at compile time")]
     pub(crate) const NONZERO_LEVELS: [Self; NUM_NONZERO_LEVELS_USIZE.get()] = [
@@ -312,7 +315,8 @@ impl MiddleLevel {
     #[inline]
     #[must_use]
     pub(crate) const fn next_level(self) -> NonZeroLevel {
-        NonZeroLevel(self.0)
+        // A middle level is strictly less than the greatest level, so this cannot overflow.
+        NonZeroLevel(self.0.saturating_add(1))
     }
 }
 
@@ -341,3 +345,28 @@ impl<T> IndexMiddleLevel<T> for [T; NUM_MIDDLE_LEVELS_USIZE.get()] {
         &mut self[usize::from(level.inner().get() - 1)]
     }
 }
+
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, reason = "these are tests")]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn middle_next_level() {
+        let next_levels = NonZeroLevel::NONZERO_LEVELS.into_iter().skip(1);
+
+        for (middle, next) in MiddleLevel::MIDDLE_LEVELS.into_iter().zip(next_levels) {
+            assert_eq!(middle.next_level(), next);
+            assert_eq!(middle.next_level().prev_level(), middle.as_level());
+        }
+    }
+
+    #[test]
+    fn grandparent_of_last_middle_level() {
+        // Compactions into the last middle level take their grandparents from the last level.
+        let last_middle = MiddleLevel::MIDDLE_LEVELS.last().copied();
+        assert_eq!(last_middle.map(MiddleLevel::next_level), Some(NonZeroLevel::LAST));
+    }
+}
@@ -0,0 +1,107 @@
+use std::{num::NonZeroU64, thread};
+use std::{
+    sync::{Mutex, atomic::{AtomicU64, AtomicUsize, Ordering}},
+    time::{Duration, Instant},
+};
+
+use crate::utils::UnwrapPoison as _;
+
+
+/// How long a rate-limited request sleeps before rechecking whether foreground operations are
+/// still in progress.
+const FOREGROUND_BACKOFF: Duration = Duration::from_millis(1);
+/// The longest that a single rate-limited request yields to foreground operations, so that
+/// a steady stream of reads cannot starve compactions.
+const MAX_FOREGROUND_WAIT: Duration = Duration::from_millis(100);
+/// Unused budget accumulates for at most this long, bounding the size of a burst after an idle
+/// period.
+const MAX_BURST: Duration = Duration::from_secs(1);
+
+/// A token-bucket rate limiter for the bytes written (and optionally read) by compactions and
+/// memtable flushes.
+///
+/// Foreground operations which take priority over compaction I/O (user reads and synced writes)
+/// are registered with [`RateLimiter::foreground_operation`]; while any are in progress,
+/// rate-limited requests briefly yield to them.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    /// The limit in bytes per second, or `0` if there is no limit.
+    bytes_per_second: AtomicU64,
+    /// The time at which the bytes granted so far will have been paid for.
+    next_free:        Mutex<Instant>,
+    foreground_ops:   AtomicUsize,
+    unwrap_poison:    bool,
+}
+
+#[expect(unreachable_pub, reason = "control visibility at type definition")]
+impl RateLimiter {
+    #[must_use]
+    pub fn new(bytes_per_second: Option<NonZeroU64>, unwrap_poison: bool) -> Self {
+        Self {
+            bytes_per_second: AtomicU64::new(bytes_per_second.map_or(0, NonZeroU64::get)),
+            next_free:        Mutex::new(Instant::now()),
+            foreground_ops:   AtomicUsize::new(0),
+            unwrap_poison,
+        }
+    }
+
+    pub fn set_bytes_per_second(&self, bytes_per_second: Option<NonZeroU64>) {
+        self.bytes_per_second.store(bytes_per_second.map_or(0, NonZeroU64::get), Ordering::Relaxed);
+    }
+
+    #[must_use]
+    pub fn bytes_per_second(&self) -> Option<NonZeroU64> {
+        NonZeroU64::new(self.bytes_per_second.load(Ordering::Relaxed))
+    }
+
+    /// Block until `bytes` bytes of compaction I/O may be performed (or have been performed).
+    ///
+    /// Returns immediately if there is no limit.
+    pub fn request(&self, bytes: u64) {
+        let Some(bytes_per_second) = self.bytes_per_second() else { return };
+
+        let yield_start = Instant::now();
+        while self.foreground_ops.load(Ordering::Relaxed) > 0
+            && yield_start.elapsed() < MAX_FOREGROUND_WAIT
+        {
+            thread::sleep(FOREGROUND_BACKOFF);
+        }
+
+        #[expect(clippy::integer_division, reason = "sub-nanosecond precision is unnecessary")]
+        let cost_nanos = u128::from(bytes).saturating_mul(1_000_000_000)
+            / u128::from(bytes_per_second.get());
+        let cost = Duration::from_nanos(u64::try_from(cost_nanos).unwrap_or(u64::MAX));
+
+        let wait = {
+            let mut next_free = self.next_free.lock().unwrap_poison(self.unwrap_poison);
+            let now = Instant::now();
+
+            let start = now.checked_sub(MAX_BURST).map_or(*next_free, |oldest| {
+                (*next_free).max(oldest)
+            });
+            *next_free = start.checked_add(cost).unwrap_or(start);
+            next_free.saturating_duration_since(now)
+        };
+
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+
+    /// Register a foreground operation which should take priority over compaction I/O until
+    /// the returned guard is dropped.
+    #[must_use]
+    pub fn foreground_operation(&self) -> ForegroundOperation<'_> {
+        self.foreground_ops.fetch_add(1, Ordering::Relaxed);
+        ForegroundOperation(self)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct ForegroundOperation<'a>(&'a RateLimiter);
+
+impl Drop for ForegroundOperation<'_> {
+    fn drop(&mut self) {
+        self.0.foreground_ops.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,100 @@
+use anchored_vfs::RandomAccess;
+
+use crate::pub_typed_bytes::{BlockHandle, FileSize};
+use super::block_iter::BlockIter;
+use super::footer::{BLOCK_FOOTER_LEN, TableFooter};
+
+
+/// The number of bytes at the start of each sampled block which are passed to the callback of
+/// [`sample_table_blocks`] (or fewer, if the block is shorter).
+pub(crate) const SAMPLED_PREFIX_LEN: usize = 4;
+
+/// Read the compressor IDs from the trailers of the metaindex block, index block, and up to
+/// `max_data_blocks`-many data blocks of a table file, without decompressing any blocks or
+/// verifying any checksums.
+///
+/// For each block, `on_block` is passed the compressor ID recorded in the block's trailer and the
+/// first (at most) [`SAMPLED_PREFIX_LEN`] bytes of the block's (possibly compressed) contents.
+///
+/// Data blocks can only be found if the index block is uncompressed, since the compression codecs
+/// of the table are not known. Any I/O error or corruption causes sampling to stop early; this
+/// function is only intended to gather evidence about an unknown table, and an incomplete sample
+/// of a corrupted table is not an error.
+pub(crate) fn sample_table_blocks<File, F>(
+    table_file:      &File,
+    file_size:       FileSize,
+    max_data_blocks: usize,
+    mut on_block:    F,
+)
+where
+    File: RandomAccess,
+    F:    FnMut(u8, &[u8]),
+{
+    let Some(footer_offset) = file_size.0
+        .checked_sub(u64::from(TableFooter::ENCODED_LENGTH_U8))
+    else {
+        return;
+    };
+
+    let mut table_footer = [0; TableFooter::ENCODED_LENGTH];
+    if table_file.read_exact_at(footer_offset, &mut table_footer).is_err() {
+        return;
+    }
+    let Ok(table_footer) = TableFooter::decode_from(&table_footer, file_size) else {
+        return;
+    };
+
+    if sample_block(table_file, table_footer.metaindex, &mut on_block).is_none() {
+        return;
+    }
+    // The index block's entries can only be read if it is not compressed.
+    if sample_block(table_file, table_footer.index, &mut on_block) != Some(0) {
+        return;
+    }
+
+    let Ok(index_len) = usize::try_from(table_footer.index.size.0) else { return };
+    let mut index_block = vec![0; index_len];
+    if table_file.read_exact_at(table_footer.index.offset.0, &mut index_block).is_err() {
+        return;
+    }
+    let Ok(mut index_iter) = BlockIter::new(&index_block) else { return };
+
+    for _ in 0..max_data_blocks {
+        let Ok(Some(index_entry)) = index_iter.next(&index_block) else { return };
+        let Ok((data_handle, _)) = BlockHandle::decode(index_entry.value, file_size) else {
+            return;
+        };
+        if sample_block(table_file, data_handle, &mut on_block).is_none() {
+            return;
+        }
+    }
+}
+
+/// Read the compressor ID in the trailer of the indicated block, and pass it to `on_block` along
+/// with the first few bytes of the block.
+///
+/// Returns the compressor ID, or `None` if the block could not be read.
+fn sample_block<File, F>(
+    table_file:   &File,
+    block_handle: BlockHandle,
+    on_block:     &mut F,
+) -> Option<u8>
+where
+    File: RandomAccess,
+    F:    FnMut(u8, &[u8]),
+{
+    let trailer_offset = block_handle.offset.0.checked_add(block_handle.size.0)?;
+    let mut trailer = [0; BLOCK_FOOTER_LEN];
+    table_file.read_exact_at(trailer_offset, &mut trailer).ok()?;
+    let compressor_id = trailer[0];
+
+    let prefix_len = usize::try_from(block_handle.size.0)
+        .map_or(SAMPLED_PREFIX_LEN, |block_size| block_size.min(SAMPLED_PREFIX_LEN));
+    let mut prefix = [0; SAMPLED_PREFIX_LEN];
+    #[expect(clippy::indexing_slicing, reason = "`prefix_len <= SAMPLED_PREFIX_LEN`")]
+    let prefix = &mut prefix[..prefix_len];
+    table_file.read_exact_at(block_handle.offset.0, prefix).ok()?;
+
+    on_block(compressor_id, prefix);
+    Some(compressor_id)
+}
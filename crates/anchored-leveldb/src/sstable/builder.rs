@@ -1,26 +1,36 @@
-use std::num::NonZeroU32;
+use std::{mem, sync::Arc, time::Instant};
+use std::num::{NonZeroU32, NonZeroUsize};
 
 use clone_behavior::FastMirroredClone;
 
 use anchored_vfs::{LevelDBFilesystem, WritableFile};
 
-use crate::utils::ReturnBuffer as _;
+use crate::utils::{ReturnBuffer as _, mask_checksum};
 use crate::{
     all_errors::types::{AddBlockEntryError, AddTableEntryError, WriteTableError},
-    options::{DynamicOptions, InternallyMutableOptions, InternalOptions},
+    compression::encode_codec_names,
+    file_tracking::TableProperties,
+    options::{
+        DynamicOptions, InternallyMutableOptions, InternalOptions,
+        pub_options::{AdaptiveCompressionOptions, CompressionDictionaryOptions},
+    },
     pub_traits::{
         cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
         compression::{CodecsCompressionError, CompressionCodecs, CompressorId},
-        pool::{BufferPool, ByteBuffer},
+        pool::{BufferPool, ByteBuffer as _},
     },
     pub_typed_bytes::{
-        BlockHandle, FileOffset, FileSize, IndexNonZeroLevel as _, NonZeroLevel, ShortSlice,
-        TableBlockSize,
+        BlockHandle, FileOffset, FileSize, IndexNonZeroLevel as _, MinU32Usize, NonZeroLevel,
+        ShortSlice, TableBlockSize,
     },
-    typed_bytes::{EncodedInternalKey, MaybeUserValue},
+    typed_bytes::{EncodedInternalKey, MaybeUserValue, UserKey},
 };
 use super::{block_builder::BlockBuilder, filter_block::FilterBlockBuilder};
-use super::footer::{BLOCK_FOOTER_LEN, FILTER_META_PREFIX, TableFooter};
+use super::parallel_compression::{CompressionJobResult, ParallelCompression, PendingBatch};
+use super::footer::{
+    BLOCK_FOOTER_LEN, COMPRESSION_CODECS_META_KEY, COMPRESSION_DICTIONARY_META_KEY,
+    FILTER_META_PREFIX, TABLE_PROPERTIES_META_KEY, TableFooter,
+};
 
 
 /// A `TableBuilder` is used to create an SSTable from data entries.
@@ -60,8 +70,39 @@ pub(crate) struct TableBuilder<File, Policy, Pool: BufferPool> {
     block_size:       usize,
     compressor:       Option<CompressorId>,
     compression_goal: u8,
+
+    dictionary_opts:  Option<CompressionDictionaryOptions>,
+    /// The compression dictionary of the current table. Empty if the table has no dictionary.
+    ///
+    /// Data blocks compressed with the dictionary are tagged with the `dictionary_compressor_id`
+    /// of `dictionary_opts`.
+    dictionary:       Arc<[u8]>,
+    /// Whether a compression dictionary will be trained on the first data blocks of the
+    /// current table.
+    train_dictionary: bool,
+    /// Whether data blocks of the current table are compressed in batches on worker threads.
+    parallel:         bool,
+    /// The number of compression worker threads, if parallel compression is enabled.
+    parallel_threads: Option<NonZeroUsize>,
+    /// Whether data blocks are currently being held in `buffered` (rather than written to the
+    /// table file) until a compression dictionary is trained on them or until they're
+    /// compressed on worker threads.
+    buffering:        bool,
+    buffered:         BufferedBlocks,
+    /// The previous batch of buffered data blocks, while it's compressed on worker threads.
+    in_flight:        Option<InFlightBatch>,
+    /// Empty buffers to be used for the next batch of buffered data blocks.
+    spare_buffered:   BufferedBlocks,
+
+    adaptive_opts:    Option<AdaptiveCompressionOptions>,
+    /// The state of adaptive compression for the current table, if it's enabled.
+    adaptive:         Option<AdaptiveCompression>,
 }
 
+/// The number of bytes of uncompressed data blocks which are buffered per compression worker
+/// thread before the buffered blocks are compressed in parallel.
+const PARALLEL_BATCH_LEN_PER_THREAD: usize = 256 << 10_u8;
+
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
 impl<File, Policy, Pool> TableBuilder<File, Policy, Pool>
 where
@@ -105,6 +146,19 @@ where
             block_size:       dummy_block_size,
             compressor:       dummy_compressor,
             compression_goal: dummy_compression_goal,
+            dictionary_opts:  opts.compression_dictionary,
+            dictionary:       Arc::default(),
+            train_dictionary: false,
+            parallel:         false,
+            parallel_threads: opts.parallel_compression
+                .as_ref()
+                .map(ParallelCompression::num_threads),
+            buffering:        false,
+            buffered:         BufferedBlocks::default(),
+            in_flight:        None,
+            spare_buffered:   BufferedBlocks::default(),
+            adaptive_opts:    opts.adaptive_compression,
+            adaptive:         None,
         }
     }
 
@@ -143,9 +197,26 @@ where
         self.index_block.reset_with_restart_interval(dynamic_opts.sstable_block_restart_interval);
         self.filter_error = false;
 
+        self.block_size = dynamic_opts.sstable_block_size;
         self.compressor = compressor;
         self.compression_goal = compression_goal;
 
+        self.adaptive = compressor
+            .zip(self.adaptive_opts)
+            .map(|(compressor, adaptive_opts)| AdaptiveCompression::new(compressor, adaptive_opts));
+
+        self.dictionary = Arc::default();
+        self.buffered.clear();
+        // Any batch of the previous table which is still being compressed is abandoned.
+        self.in_flight = None;
+        // A dictionary would go unused if data blocks are not compressed.
+        self.train_dictionary = compressor.is_some() && self.dictionary_opts.is_some();
+        // Adaptive compression depends on the results of previous blocks, so it's sequential.
+        self.parallel = compressor.is_some()
+            && self.parallel_threads.is_some()
+            && self.adaptive.is_none();
+        self.buffering = self.train_dictionary || self.parallel;
+
         if let Some(filter_block) = &mut self.filter_block {
             filter_block.reset();
             // Can be elided. Does nothing. (So we don't have to `expect` or ignore the error.)
@@ -194,13 +265,50 @@ where
         self.num_entries
     }
 
+    /// Get the number of bytes which have been written to the current table file so far.
+    ///
+    /// If the builder is not [active], then the value is unspecified, though a panic will not
+    /// occur.
+    ///
+    /// [active]: TableBuilder::active
+    #[must_use]
+    pub const fn bytes_written(&self) -> u64 {
+        self.offset_in_file.0
+    }
+
+    /// Begin syncing the indicated byte range of the current table file to persistent storage,
+    /// as per [`WritableFile::sync_data_range`].
+    ///
+    /// The range should have already been written; see [`TableBuilder::bytes_written`].
+    ///
+    /// # Panics
+    /// Panics if the builder is not currently [active].
+    ///
+    /// [active]: TableBuilder::active
+    pub fn sync_data_range<Compression>(
+        &mut self,
+        offset: u64,
+        len:    u64,
+    ) -> Result<(), WriteTableError<Compression>> {
+        #[expect(
+            clippy::expect_used,
+            reason = "Panic is declared, and can only occur due to user mistake",
+        )]
+        let table_file = self.table_file.as_mut()
+            .expect("sync_data_range called on an inactive TableBuilder");
+
+        table_file.sync_data_range(offset, len).map_err(WriteTableError::SyncTable)
+    }
+
     /// Estimates the length that the table file currently being built would have if `self.finish()`
     /// were called now.
     ///
     /// This is a rough estimate that does not take into account:
     /// - compression of the current data block,
     /// - compression of the index block,
-    /// - the metaindex block, which contains the name of any filter policy.
+    /// - the metaindex block, which contains the name of any filter policy,
+    /// - compression of any data blocks buffered until a compression dictionary is trained,
+    /// - the compression dictionary block (if any).
     ///
     /// If the builder is not [active], then the value is unspecified, though a panic will not
     /// occur.
@@ -215,6 +323,8 @@ where
         };
 
         let additional_len = self.data_block.finished_length()
+            + self.buffered.blocks.len()
+            + self.in_flight.as_ref().map_or(0, |batch| batch.blocks.len())
             + self.index_block.finished_length()
             + filter_len
             + TableFooter::ENCODED_LENGTH;
@@ -294,6 +404,19 @@ where
             // - following: whatever.
             //
             // We know by assumption that the first insertion does not fail.
+            //
+            // While data blocks are being buffered (or compressed on worker threads), their index
+            // entries are not yet in `self.index_block`. If the buffered index entries are
+            // approaching the limit, we write all the buffered blocks (which is extremely
+            // unlikely to occur), after which `self.index_block` has all the previous entries.
+            if self.buffering && !self.buffered.could_add_following_index_entry(
+                self.in_flight.as_ref().map(|batch| &batch.buffered),
+                &self.index_block,
+                self.data_block.last_key().len(),
+            ) {
+                self.write_all_buffered_blocks(opts, mut_opts, encoders)
+                    .map_err(AddTableEntryError::Write)?;
+            }
             if !self.index_block.could_add_following_entry(
                 self.data_block.last_key().len(),
                 BlockHandle::MAX_ENCODED_LENGTH_MIN_U32_USIZE,
@@ -325,7 +448,10 @@ where
         self.num_entries += 1;
 
         if let Some(filter_block) = &mut self.filter_block {
-            if !self.filter_error {
+            if self.buffering {
+                // The key is added to `filter_block` once the buffered blocks are written.
+                self.buffered.add_filter_key(key.as_internal_key().0);
+            } else if !self.filter_error {
                 // We've called `filter_block.start_block(_)` as appropriate, and the caller
                 // asserts that this key is strictly greater than previously inserted keys.
                 filter_block.add_key(key.as_internal_key().0);
@@ -339,6 +465,8 @@ where
     /// WARNING: if the table file was newly created, then the data of the file's parent directory
     /// would also need to be synced to persistent storage in order to ensure crash resilience.
     ///
+    /// The given `properties` are recorded in the table's `table_properties` meta block.
+    ///
     /// On success, the total number of bytes written to the table file is returned.
     ///
     /// After this method is called, the builder becomes [inactive], and no other
@@ -353,9 +481,10 @@ where
     // This function uses `self.key_scratch` and `self.compression_scratch_buf`.
     pub fn finish<FS, Cmp, Codecs>(
         &mut self,
-        opts:     &InternalOptions<Cmp, Policy, Codecs>,
-        mut_opts: &InternallyMutableOptions<FS, Policy, Pool>,
-        encoders: &mut Codecs::Encoders,
+        opts:       &InternalOptions<Cmp, Policy, Codecs>,
+        mut_opts:   &InternallyMutableOptions<FS, Policy, Pool>,
+        encoders:   &mut Codecs::Encoders,
+        properties: TableProperties,
     ) -> Result<FileSize, WriteTableError<Codecs::CompressionError>>
     where
         FS:         LevelDBFilesystem,
@@ -384,6 +513,7 @@ where
                         encoders,
                         $uncompressed_block,
                         None,
+                        &[],
                     )
                 }
             };
@@ -416,12 +546,78 @@ where
             // There's no next block. We will not write any other blocks to the table being built.
             self.write_data_block(opts, mut_opts, encoders, None)?;
         }
+        if self.buffering {
+            // Either the table has fewer than `max_sample_bytes` of data blocks, or the last
+            // batch of blocks to be compressed in parallel is not full.
+            self.write_all_buffered_blocks(opts, mut_opts, encoders)?;
+        }
 
         // Create metaindex block. We can reuse the data block builder, since this table builder
         // will not be writing any more data blocks until the table is completed.
         // Note that `self.data_block` has already been reset; either it had zero entries,
         // and was thus already in a blank-slate state, or `self.write_data_block(..)`
         // would have called `self.data_block.reset()`.
+        // Note that the metaindex entries must be added in sorted order:
+        // `COMPRESSION_CODECS_META_KEY` is less than `COMPRESSION_DICTIONARY_META_KEY`, which is
+        // less than anything beginning with `FILTER_META_PREFIX`, which is less than
+        // `TABLE_PROPERTIES_META_KEY`.
+        // The metaindex block has at most four short entries, so it's nowhere near too full
+        // to add an entry.
+        macro_rules! add_metaindex_entry {
+            ($key:expr, $encoded_handle:expr) => {
+                if self.data_block.num_entries() == 0 {
+                    self.data_block.add_first_entry($key, $encoded_handle);
+                } else {
+                    #[expect(clippy::expect_used, reason = "see above; cannot panic")]
+                    self.data_block.add_entry($key, $encoded_handle)
+                        .expect("a metaindex block with few entries should not be too full");
+                }
+            };
+        }
+
+        if !opts.codec_names.is_empty() {
+            self.key_scratch.clear();
+            encode_codec_names(
+                &mut self.key_scratch,
+                opts.codec_names.iter().filter_map(|&(compressor_id, name)| {
+                    Some((compressor_id, ShortSlice::new(name.as_bytes())?))
+                }),
+            );
+
+            let codecs_handle = write_block!(&self.key_scratch, NoCompression)?;
+
+            let mut encoded_handle = [0_u8; BlockHandle::MAX_ENCODED_LENGTH];
+            let encoded_handle = codecs_handle.encode_short(&mut encoded_handle);
+
+            #[allow(clippy::unwrap_used, reason = "validated at compile time")]
+            let codecs_key = const { ShortSlice::new(COMPRESSION_CODECS_META_KEY).unwrap() };
+
+            add_metaindex_entry!(codecs_key, encoded_handle);
+        }
+
+        let dictionary = self.compressor
+            .zip(table_dictionary(self.dictionary_opts, &self.dictionary));
+        if let Some((compressor_id, (dictionary_id, dictionary))) = dictionary {
+            // The dictionary block begins with the ID of the compressor it's for, followed by
+            // the ID which data blocks compressed with it are tagged with.
+            self.key_scratch.clear();
+            self.key_scratch.push(compressor_id.0.get());
+            self.key_scratch.push(dictionary_id.0.get());
+            self.key_scratch.extend(dictionary);
+
+            let dictionary_handle = write_block!(&self.key_scratch, NoCompression)?;
+
+            let mut encoded_handle = [0_u8; BlockHandle::MAX_ENCODED_LENGTH];
+            let encoded_handle = dictionary_handle.encode_short(&mut encoded_handle);
+
+            #[allow(clippy::unwrap_used, reason = "validated at compile time")]
+            let dictionary_key = const {
+                ShortSlice::new(COMPRESSION_DICTIONARY_META_KEY).unwrap()
+            };
+
+            add_metaindex_entry!(dictionary_key, encoded_handle);
+        }
+
         'filter: {
             if let Some(filter_block) = &mut self.filter_block {
                 if self.filter_error {
@@ -458,12 +654,23 @@ where
                 let encoded_handle = filter_handle.encode_short(&mut encoded_handle);
 
                 // Reminder: `self.data_block` is currently actually the metaindex block.
-                // This is the first entry added, so it's guaranteed to vacuously be strictly
-                // greater than any previously-inserted entry, and this method does not panic.
-                self.data_block.add_first_entry(prefixed_filter_name, encoded_handle);
+                add_metaindex_entry!(prefixed_filter_name, encoded_handle);
             }
         }
 
+        self.key_scratch.clear();
+        properties.encode_to(&mut self.key_scratch);
+
+        let properties_handle = write_block!(&self.key_scratch, NoCompression)?;
+
+        let mut encoded_handle = [0_u8; BlockHandle::MAX_ENCODED_LENGTH];
+        let encoded_handle = properties_handle.encode_short(&mut encoded_handle);
+
+        #[allow(clippy::unwrap_used, reason = "validated at compile time")]
+        let properties_key = const { ShortSlice::new(TABLE_PROPERTIES_META_KEY).unwrap() };
+
+        add_metaindex_entry!(properties_key, encoded_handle);
+
         // Write the metaindex and index blocks
 
         let metaindex_block = self.data_block.finish_block_contents();
@@ -510,6 +717,9 @@ where
     /// adds a new entry to `self.index_block`, possibly adds to the filter block,
     /// and clears `self.data_block`.
     ///
+    /// If data blocks are being buffered, the data block and its index entry are instead
+    /// buffered, and the buffered blocks are written once enough samples have been collected.
+    ///
     /// # Correctness
     /// This function **must not** be called if `self.data_block` is empty (has zero entries).
     ///
@@ -555,22 +765,51 @@ where
 
         let uncompressed_block = self.data_block.finish_block_contents();
 
+        if self.buffering {
+            self.buffered.push_block(uncompressed_block, index_key.short().inner());
+            self.data_block.reset();
+
+            let flush_threshold = if self.train_dictionary {
+                self.dictionary_opts
+                    .map_or(0, |dictionary_opts| dictionary_opts.max_sample_bytes)
+            } else {
+                self.parallel_threads.map_or(0, |num_threads| {
+                    num_threads.get().saturating_mul(PARALLEL_BATCH_LEN_PER_THREAD)
+                })
+            };
+            if self.buffered.blocks.len() >= flush_threshold {
+                self.write_buffered_blocks(opts, mut_opts, encoders)?;
+            }
+
+            return Ok(());
+        }
+
         #[expect(clippy::expect_used, reason = "panic is documented, and is a caller bug")]
         let table_file = self.table_file
             .as_mut()
             .expect("`add_entry` and `finish` should not be called on an inactive `TableBuilder`");
-        let block_handle = Self::write_block::<Codecs>(
+        let compressed = Self::compress_data_block::<Codecs>(
             // exploded `self`
-            table_file,
-            &mut self.offset_in_file,
+            &mut self.adaptive,
             &mut self.compression_buf,
             self.compression_goal,
+            self.compressor,
+            table_dictionary(self.dictionary_opts, &self.dictionary),
             // Actual args
             &mut_opts.buffer_pool,
             encoders,
             uncompressed_block,
-            self.compressor,
         )?;
+        let block_handle = Self::write_compressed_block(
+            table_file,
+            &mut self.offset_in_file,
+            uncompressed_block,
+            compressed.as_ref().map(|(id, compressed_buf)| (*id, compressed_buf.as_slice())),
+        );
+        if let Some((_, compressed_buf)) = compressed {
+            self.compression_buf.return_buffer(compressed_buf);
+        }
+        let block_handle = block_handle?;
         self.data_block.reset();
 
         let mut encoded_handle = [0_u8; BlockHandle::MAX_ENCODED_LENGTH];
@@ -599,9 +838,287 @@ where
         Ok(())
     }
 
+    /// Write every buffered data block to the table file, including any batch of blocks which is
+    /// being compressed on worker threads.
+    ///
+    /// This function uses `self.compression_scratch_buf`.
+    ///
+    /// # Side Effects
+    /// See [`TableBuilder::write_buffered_blocks`]. Afterwards, every index entry of the table's
+    /// data blocks (so far) is in `self.index_block`.
+    ///
+    /// # Panics
+    /// Panics if the builder is not currently [active].
+    ///
+    /// [active]: TableBuilder::active
+    fn write_all_buffered_blocks<FS, Cmp, Codecs>(
+        &mut self,
+        opts:      &InternalOptions<Cmp, Policy, Codecs>,
+        mut_opts:  &InternallyMutableOptions<FS, Policy, Pool>,
+        encoders:  &mut Codecs::Encoders,
+    ) -> Result<(), WriteTableError<Codecs::CompressionError>>
+    where
+        FS:         LevelDBFilesystem,
+        Codecs:     CompressionCodecs,
+    {
+        self.write_buffered_blocks(opts, mut_opts, encoders)?;
+        if let Some(batch) = self.in_flight.take() {
+            self.write_in_flight_batch::<FS, Codecs>(mut_opts, encoders, batch)?;
+        }
+        Ok(())
+    }
+
+    /// Train a compression dictionary on the buffered data blocks if one has not yet been
+    /// trained for the current table, and write those blocks to the table file (compressed with
+    /// the dictionary, if one was successfully trained).
+    ///
+    /// If parallel compression is enabled for the current table, the blocks are instead sent to
+    /// worker threads to be compressed, and the previous batch of blocks sent to worker threads
+    /// (if any) is written while they are compressed. Blocks are written in order regardless.
+    ///
+    /// This function uses `self.compression_scratch_buf`.
+    ///
+    /// # Side Effects
+    /// Data blocks are not buffered for the rest of the current table, unless they're compressed
+    /// in parallel. Index entries and filter keys of the written blocks are added to
+    /// `self.index_block` and the filter block, as `self.write_data_block` would have done
+    /// without buffering.
+    ///
+    /// # Panics
+    /// Panics if the builder is not currently [active].
+    ///
+    /// [active]: TableBuilder::active
+    fn write_buffered_blocks<FS, Cmp, Codecs>(
+        &mut self,
+        opts:      &InternalOptions<Cmp, Policy, Codecs>,
+        mut_opts:  &InternallyMutableOptions<FS, Policy, Pool>,
+        encoders:  &mut Codecs::Encoders,
+    ) -> Result<(), WriteTableError<Codecs::CompressionError>>
+    where
+        FS:         LevelDBFilesystem,
+        Codecs:     CompressionCodecs,
+    {
+        let mut buffered = mem::replace(&mut self.buffered, mem::take(&mut self.spare_buffered));
+
+        let compressor_and_opts = self.compressor
+            .zip(self.dictionary_opts)
+            .filter(|_| self.train_dictionary);
+        if let Some((compressor_id, dictionary_opts)) = compressor_and_opts {
+            if let Some(dictionary) = opts.codecs.train_dictionary(
+                compressor_id,
+                &buffered.blocks,
+                &buffered.block_lens,
+                dictionary_opts.max_dictionary_size,
+            ) {
+                self.dictionary = Arc::from(dictionary);
+            }
+        }
+        self.train_dictionary = false;
+        self.buffering = self.parallel;
+
+        let parallel_compression = opts.parallel_compression.as_ref().filter(|_| self.parallel);
+        if let (Some(parallel), Some(compressor_id)) = (parallel_compression, self.compressor) {
+            let blocks = Arc::new(mem::take(&mut buffered.blocks));
+            let pending = parallel.compress_batch(
+                Arc::clone(&blocks),
+                &buffered.block_lens,
+                compressor_id,
+                self.compression_goal,
+                &self.dictionary,
+            );
+
+            // Write the previous batch while this one is compressed.
+            let previous = self.in_flight.replace(InFlightBatch { blocks, buffered, pending });
+            if let Some(previous) = previous {
+                self.write_in_flight_batch::<FS, Codecs>(mut_opts, encoders, previous)?;
+            }
+        } else {
+            let blocks = &buffered.blocks;
+            self.write_blocks::<FS, Codecs>(mut_opts, encoders, blocks, &buffered, Vec::new())?;
+
+            // Keep the buffers' capacity around for the next table.
+            buffered.clear();
+            self.spare_buffered = buffered;
+        }
+
+        Ok(())
+    }
+
+    /// Wait for the worker threads to finish compressing the `batch`, and write its blocks to
+    /// the table file.
+    ///
+    /// # Panics
+    /// Panics if the builder is not currently [active].
+    ///
+    /// [active]: TableBuilder::active
+    fn write_in_flight_batch<FS, Codecs>(
+        &mut self,
+        mut_opts:  &InternallyMutableOptions<FS, Policy, Pool>,
+        encoders:  &mut Codecs::Encoders,
+        batch:     InFlightBatch,
+    ) -> Result<(), WriteTableError<Codecs::CompressionError>>
+    where
+        FS:         LevelDBFilesystem,
+        Codecs:     CompressionCodecs,
+    {
+        let InFlightBatch { blocks, mut buffered, pending } = batch;
+        let job_results = pending.wait();
+
+        self.write_blocks::<FS, Codecs>(mut_opts, encoders, &blocks, &buffered, job_results)?;
+
+        // The worker threads have released `blocks`, so its buffer can usually be reused.
+        buffered.blocks = Arc::try_unwrap(blocks).unwrap_or_default();
+        buffered.clear();
+        self.spare_buffered = buffered;
+
+        Ok(())
+    }
+
+    /// Write the data blocks in `blocks` (whose lengths, index keys, and filter keys are in
+    /// `buffered`) to the table file, in order.
+    ///
+    /// Blocks are compressed on this thread, unless their entry in `job_results` indicates that
+    /// a worker thread compressed them (or found them to be incompressible).
+    ///
+    /// This function uses `self.compression_scratch_buf`.
+    ///
+    /// # Panics
+    /// Panics if the builder is not currently [active].
+    ///
+    /// [active]: TableBuilder::active
+    fn write_blocks<FS, Codecs>(
+        &mut self,
+        mut_opts:    &InternallyMutableOptions<FS, Policy, Pool>,
+        encoders:    &mut Codecs::Encoders,
+        blocks:      &[u8],
+        buffered:    &BufferedBlocks,
+        job_results: Vec<CompressionJobResult>,
+    ) -> Result<(), WriteTableError<Codecs::CompressionError>>
+    where
+        FS:         LevelDBFilesystem,
+        Codecs:     CompressionCodecs,
+    {
+        let mut job_results = job_results.into_iter();
+        let dictionary = table_dictionary(self.dictionary_opts, &self.dictionary);
+
+        #[expect(clippy::expect_used, reason = "panic is documented, and is a caller bug")]
+        let table_file = self.table_file
+            .as_mut()
+            .expect("`add_entry` and `finish` should not be called on an inactive `TableBuilder`");
+
+        let mut filter_keys = split_by_lens(&buffered.filter_keys, &buffered.filter_key_lens);
+        let blocks = split_by_lens(blocks, &buffered.block_lens)
+            .zip(split_by_lens(&buffered.index_keys, &buffered.index_key_lens))
+            .zip(&buffered.filter_key_counts);
+
+        for ((uncompressed_block, index_key), &num_filter_keys) in blocks {
+            if let Some(filter_block) = &mut self.filter_block {
+                for filter_key in filter_keys.by_ref().take(num_filter_keys) {
+                    if !self.filter_error {
+                        #[expect(clippy::expect_used, reason = "filter keys were user keys")]
+                        filter_block.add_key(
+                            UserKey::new(filter_key).expect("buffered filter key is a user key"),
+                        );
+                    }
+                }
+            }
+
+            let job_result = job_results.next();
+            let block_handle = match (job_result, self.compressor) {
+                (Some(CompressionJobResult::Compressed(compressed_buf)), Some(compressor_id)) => {
+                    // As in `compress_block`, blocks compressed with a dictionary are tagged
+                    // with the ID given with the dictionary.
+                    let block_compressor_id = dictionary
+                        .map_or(compressor_id, |(dictionary_id, _)| dictionary_id);
+                    Self::write_compressed_block(
+                        table_file,
+                        &mut self.offset_in_file,
+                        uncompressed_block,
+                        Some((block_compressor_id, compressed_buf.as_slice())),
+                    )?
+                }
+                (Some(CompressionJobResult::Incompressible), _) => {
+                    Self::write_compressed_block(
+                        table_file,
+                        &mut self.offset_in_file,
+                        uncompressed_block,
+                        None,
+                    )?
+                }
+                // Compress the block on this thread instead.
+                _ => {
+                    let compressed = Self::compress_data_block::<Codecs>(
+                        // exploded `self`
+                        &mut self.adaptive,
+                        &mut self.compression_buf,
+                        self.compression_goal,
+                        self.compressor,
+                        dictionary,
+                        // Actual args
+                        &mut_opts.buffer_pool,
+                        encoders,
+                        uncompressed_block,
+                    )?;
+                    let block_handle = Self::write_compressed_block(
+                        table_file,
+                        &mut self.offset_in_file,
+                        uncompressed_block,
+                        compressed
+                            .as_ref()
+                            .map(|(id, compressed_buf)| (*id, compressed_buf.as_slice())),
+                    );
+                    if let Some((_, compressed_buf)) = compressed {
+                        self.compression_buf.return_buffer(compressed_buf);
+                    }
+                    block_handle?
+                }
+            };
+
+            let mut encoded_handle = [0_u8; BlockHandle::MAX_ENCODED_LENGTH];
+            let encoded_handle = block_handle.encode_short(&mut encoded_handle);
+
+            // The index keys were buffered in the order that `self.write_data_block` would have
+            // added them, and `add_entry` checked that they can all be added.
+            #[expect(
+                clippy::expect_used,
+                reason = "hard to thoroughly verify, but should not panic",
+            )]
+            self.index_block
+                .add_entry(
+                    ShortSlice::new(index_key).expect("buffered index key is a `ShortSlice`"),
+                    encoded_handle,
+                )
+                .expect(
+                    "bug in `TableBuilder`; `AddTableEntryError` should be returned earlier to \
+                     prevent adding an index block entry from failing",
+                );
+
+            if let Some(filter_block) = &mut self.filter_block {
+                if let Err(_err) = filter_block.start_block(self.offset_in_file) {
+                    // TODO: log error
+                    self.filter_error = true;
+                }
+            }
+        }
+
+        // Any remaining filter keys are from the data block currently being built.
+        if let Some(filter_block) = &mut self.filter_block {
+            for filter_key in filter_keys {
+                if !self.filter_error {
+                    #[expect(clippy::expect_used, reason = "filter keys were user keys")]
+                    filter_block.add_key(
+                        UserKey::new(filter_key).expect("buffered filter key is a user key"),
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     #[expect(
         clippy::too_many_arguments,
-        reason = "morally, there are only 5 arguments (including `&mut self`)",
+        reason = "morally, there are only 6 arguments (including `&mut self`)",
     )]
     fn write_block<Codecs: CompressionCodecs>(
         // mfw no view types
@@ -615,75 +1132,241 @@ where
         uncompressed_block: &[u8],
         compressor:         Option<CompressorId>,
     ) -> Result<BlockHandle, WriteTableError<Codecs::CompressionError>> {
-        let compressed_buf = if let Some(compressor_id) = compressor {
-            let multiplied_len = u128::try_from(uncompressed_block.len())
-                .ok()
-                .and_then(|src_len| src_len.checked_mul(u128::from(compression_goal)))
-                .unwrap_or(0);
-
-            // Without overflow/wrapping,
-            // `multiplied_len <= 255 * block_contents.len() <= 255 * usize::MAX`
-            // so `multiplied_len / 256 <= block_contents.len() <= usize::MAX`.
-            #[expect(
-                clippy::as_conversions,
-                clippy::integer_division,
-                reason = "no wrapping/truncation occurs",
-            )]
-            let compression_goal_diff = (multiplied_len / 256) as usize;
-            // No underflow occurs; see above.
-            let compression_goal_len = uncompressed_block.len() - compression_goal_diff;
+        let compressed = Self::compress_block::<Codecs>(
+            compression_buf,
+            compression_goal,
+            buffer_pool,
+            encoders,
+            uncompressed_block,
+            compressor,
+            // The dictionary is only used for data blocks.
+            None,
+        )?;
+
+        let block_handle = Self::write_compressed_block(
+            table_file,
+            offset_in_file,
+            uncompressed_block,
+            compressed.as_ref().map(|(id, compressed_buf)| (*id, compressed_buf.as_slice())),
+        );
+
+        // We're done with the compressed data.
+        if let Some((_, compressed_buf)) = compressed {
+            compression_buf.return_buffer(compressed_buf);
+        }
+
+        block_handle
+    }
+
+    /// Compress a data block, choosing its compressor with `adaptive` if adaptive compression
+    /// is enabled for the current table.
+    ///
+    /// The `dictionary` (and the ID which blocks compressed with it are tagged with) is only used
+    /// with `compressor`, which it was trained for.
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "morally, there are only 4 arguments (including `&mut self`)",
+    )]
+    #[expect(clippy::type_complexity, reason = "still sufficiently readable")]
+    fn compress_data_block<Codecs: CompressionCodecs>(
+        // mfw no view types
+        adaptive:           &mut Option<AdaptiveCompression>,
+        compression_buf:    &mut Option<Pool::PooledBuffer>,
+        compression_goal:   u8,
+        compressor:         Option<CompressorId>,
+        dictionary:         Option<(CompressorId, &[u8])>,
+        // Actual arguments
+        buffer_pool:        &Pool,
+        encoders:           &mut Codecs::Encoders,
+        uncompressed_block: &[u8],
+    ) -> Result<
+        Option<(CompressorId, Pool::PooledBuffer)>,
+        WriteTableError<Codecs::CompressionError>,
+    > {
+        let dictionary_for = |candidate: Option<CompressorId>| {
+            dictionary.filter(|_| candidate == compressor)
+        };
+
+        let Some(adaptive) = adaptive else {
+            return Self::compress_block::<Codecs>(
+                compression_buf,
+                compression_goal,
+                buffer_pool,
+                encoders,
+                uncompressed_block,
+                compressor,
+                dictionary,
+            );
+        };
+
+        if !adaptive.sample_next_block() {
+            let chosen = adaptive.best_compressor();
+            return Self::compress_block::<Codecs>(
+                compression_buf,
+                compression_goal,
+                buffer_pool,
+                encoders,
+                uncompressed_block,
+                chosen,
+                dictionary_for(chosen),
+            );
+        }
+
+        // Try every candidate on this block, and keep whichever is best for this block.
+        // Storing the block uncompressed has a score of zero.
+        let mut best: Option<(i128, CompressorId, Pool::PooledBuffer)> = None;
+
+        for candidate_idx in 0..adaptive.candidates.len() {
+            let Some(candidate) = adaptive.candidates.get(candidate_idx) else {
+                break;
+            };
+            let candidate = candidate.compressor;
+
+            let start = Instant::now();
+            let compressed = Self::compress_block::<Codecs>(
+                compression_buf,
+                compression_goal,
+                buffer_pool,
+                encoders,
+                uncompressed_block,
+                Some(candidate),
+                dictionary_for(Some(candidate)),
+            )?;
+            let nanos = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+
+            let saved_bytes = compressed.as_ref().map_or(0, |(_, compressed_buf)| {
+                uncompressed_block.len().saturating_sub(compressed_buf.len())
+            });
+            let saved_bytes = u64::try_from(saved_bytes).unwrap_or(u64::MAX);
+
+            adaptive.record(candidate_idx, saved_bytes, nanos);
+
+            let Some((_, compressed_buf)) = compressed else {
+                continue;
+            };
+            let score = adaptive.score(saved_bytes, nanos);
+
+            if score > best.as_ref().map_or(0, |&(best_score, ..)| best_score) {
+                if let Some((_, _, prev_buf)) = best.replace((score, candidate, compressed_buf)) {
+                    compression_buf.return_buffer(prev_buf);
+                }
+            } else {
+                compression_buf.return_buffer(compressed_buf);
+            }
+        }
+
+        Ok(best.map(|(_, compressor_id, compressed_buf)| (compressor_id, compressed_buf)))
+    }
+
+    /// Compress `uncompressed_block` with `compressor` (if any), using the `dictionary` if one
+    /// is given.
+    ///
+    /// The returned compressor ID is the ID the block should be tagged with: `compressor`, or
+    /// the ID given with the `dictionary`.
+    ///
+    /// Returns `Ok(None)` if `compressor` is `None` or if the compression goal was not met,
+    /// in which case the block should be stored uncompressed.
+    #[expect(clippy::type_complexity, reason = "still sufficiently readable")]
+    fn compress_block<Codecs: CompressionCodecs>(
+        // mfw no view types
+        compression_buf:    &mut Option<Pool::PooledBuffer>,
+        compression_goal:   u8,
+        // Actual arguments
+        buffer_pool:        &Pool,
+        encoders:           &mut Codecs::Encoders,
+        uncompressed_block: &[u8],
+        compressor:         Option<CompressorId>,
+        dictionary:         Option<(CompressorId, &[u8])>,
+    ) -> Result<
+        Option<(CompressorId, Pool::PooledBuffer)>,
+        WriteTableError<Codecs::CompressionError>,
+    > {
+        let Some(compressor_id) = compressor else {
+            return Ok(None);
+        };
+
+        let compression_goal_len = compression_goal_len(
+            uncompressed_block.len(),
+            compression_goal,
+        );
 
-            match Codecs::encode(
+        let compression_result = if let Some((_, dictionary)) = dictionary {
+            Codecs::encode_with_dictionary(
                 encoders,
+                dictionary,
                 uncompressed_block,
                 compressor_id,
                 compression_goal_len,
                 buffer_pool,
                 compression_buf,
-            ) {
-                Ok(compressed_buf)                          => Some(compressed_buf),
-                Err(CodecsCompressionError::Unsupported)    => return Err(
-                    WriteTableError::UnsupportedCompressor(compressor_id),
-                ),
-                Err(CodecsCompressionError::BufferAlloc)    => return Err(
-                    WriteTableError::BufferAllocErr,
-                ),
-                Err(CodecsCompressionError::Incompressible) => None,
-                Err(CodecsCompressionError::Custom(err))    => return Err(
-                    WriteTableError::Compression(
-                        compressor_id,
-                        // This clone is expensive, but should not usually happen.
-                        uncompressed_block.to_vec(),
-                        err,
-                    ),
-                ),
-            }
+            )
         } else {
-            None
+            Codecs::encode(
+                encoders,
+                uncompressed_block,
+                compressor_id,
+                compression_goal_len,
+                buffer_pool,
+                compression_buf,
+            )
         };
+        // Blocks compressed with a dictionary are tagged with the ID given with the dictionary.
+        let block_compressor_id = dictionary.map_or(compressor_id, |(dictionary_id, _)| {
+            dictionary_id
+        });
+
+        match compression_result {
+            Ok(compressed_buf)                          => Ok(
+                Some((block_compressor_id, compressed_buf)),
+            ),
+            Err(CodecsCompressionError::Unsupported)    => Err(
+                WriteTableError::UnsupportedCompressor(compressor_id),
+            ),
+            Err(CodecsCompressionError::BufferAlloc)    => Err(
+                WriteTableError::BufferAllocErr,
+            ),
+            Err(CodecsCompressionError::Incompressible) => Ok(None),
+            Err(CodecsCompressionError::Custom(err))    => Err(
+                WriteTableError::Compression(
+                    compressor_id,
+                    // This clone is expensive, but should not usually happen.
+                    uncompressed_block.to_vec(),
+                    err,
+                ),
+            ),
+        }
+    }
 
-        let compressed_block = compressed_buf.as_ref()
-            .map(ByteBuffer::as_slice)
-            .unwrap_or(uncompressed_block);
-        let compressor = compressor.map(|id| id.0.get()).unwrap_or(0);
+    /// Write a block to the table file, followed by its trailer.
+    ///
+    /// If `compressed` is `None`, the block is stored uncompressed. Otherwise, it should be
+    /// `uncompressed_block` compressed with the given compressor.
+    fn write_compressed_block<Compression>(
+        // mfw no view types
+        table_file:         &mut File,
+        offset_in_file:     &mut FileOffset,
+        // Actual arguments
+        uncompressed_block: &[u8],
+        compressed:         Option<(CompressorId, &[u8])>,
+    ) -> Result<BlockHandle, WriteTableError<Compression>> {
+        let (compressor, compressed_block) = match compressed {
+            Some((compressor_id, compressed_block)) => (compressor_id.0.get(), compressed_block),
+            None                                    => (0, uncompressed_block),
+        };
 
         let mut digest = crc32c::crc32c(compressed_block);
         digest = crc32c::crc32c_append(digest, &[compressor]);
+        let masked_digest = mask_checksum(digest);
 
         // Write the block: the compressed contents, followed by the table block trailer.
         table_file.write_all(compressed_block).map_err(WriteTableError::WriteTable)?;
         table_file.write_all(&[compressor]).map_err(WriteTableError::WriteTable)?;
-        table_file.write_all(&digest.to_le_bytes()).map_err(WriteTableError::WriteTable)?;
+        table_file.write_all(&masked_digest.to_le_bytes()).map_err(WriteTableError::WriteTable)?;
 
         #[expect(clippy::expect_used, reason = "could theoretically panic, but won't")]
-        let block_size = u64::try_from(uncompressed_block.len())
+        let block_size = u64::try_from(compressed_block.len())
             .expect("A single slice should not be exabytes in length");
 
-        // We're done with the compressed data.
-        if let Some(new_buf) = compressed_buf {
-            compression_buf.return_buffer(new_buf);
-        }
-
         let block_handle = BlockHandle {
             offset: *offset_in_file,
             size:   TableBlockSize(block_size),
@@ -697,3 +1380,515 @@ where
         Ok(block_handle)
     }
 }
+
+/// Data blocks which are held in memory, rather than written to a table file, until enough of
+/// them have been collected to train a compression dictionary for the table or to compress a
+/// batch of them in parallel.
+///
+/// The index keys of the blocks and the keys which must be added to the table's filter are also
+/// held, since the filter depends on the offsets of the blocks in the table file.
+#[derive(Debug, Default)]
+struct BufferedBlocks {
+    /// The concatenated contents of the buffered (uncompressed) data blocks.
+    blocks:              Vec<u8>,
+    block_lens:          Vec<usize>,
+    /// The concatenated index keys of the buffered data blocks.
+    index_keys:          Vec<u8>,
+    index_key_lens:      Vec<usize>,
+    /// The concatenated user keys which must be added to the filter.
+    filter_keys:         Vec<u8>,
+    filter_key_lens:     Vec<usize>,
+    /// The number of filter keys of each buffered data block.
+    filter_key_counts:   Vec<usize>,
+    /// The number of filter keys of the data block currently being built.
+    pending_filter_keys: usize,
+}
+
+impl BufferedBlocks {
+    fn push_block(&mut self, block: &[u8], index_key: &[u8]) {
+        self.blocks.extend(block);
+        self.block_lens.push(block.len());
+        self.index_keys.extend(index_key);
+        self.index_key_lens.push(index_key.len());
+        self.filter_key_counts.push(mem::take(&mut self.pending_filter_keys));
+    }
+
+    fn add_filter_key(&mut self, user_key: UserKey<'_>) {
+        self.filter_keys.extend(user_key.inner());
+        self.filter_key_lens.push(user_key.inner().len());
+        self.pending_filter_keys += 1;
+    }
+
+    /// Pessimistically checks whether, if all the index entries buffered in `in_flight` (if any)
+    /// and `self` and an index entry whose key has length at most `key_len` were added to
+    /// `index_block`, another entry could then be added.
+    ///
+    /// See [`BlockBuilder::could_add_following_entry`].
+    #[must_use]
+    fn could_add_following_index_entry(
+        &self,
+        in_flight:   Option<&Self>,
+        index_block: &BlockBuilder,
+        key_len:     MinU32Usize,
+    ) -> bool {
+        // Each entry has at most 15 bytes of varint32 values and a handle, in addition to its key.
+        let max_entry_overhead = 15 + BlockHandle::MAX_ENCODED_LENGTH;
+
+        let index_keys_len = self.index_keys.len()
+            + in_flight.map_or(0, |in_flight| in_flight.index_keys.len());
+        let num_index_entries = self.index_key_lens.len()
+            + in_flight.map_or(0, |in_flight| in_flight.index_key_lens.len());
+
+        let worst_case_buf_len = index_block.finished_length()
+            .saturating_add(index_keys_len)
+            .saturating_add(usize::from(key_len))
+            .saturating_add(
+                num_index_entries
+                    .saturating_add(1)
+                    .saturating_mul(max_entry_overhead),
+            );
+
+        if u32::try_from(worst_case_buf_len).is_err() {
+            return false;
+        }
+        // Each entry could be a restart.
+        let worst_case_num_restarts = index_block.num_entries()
+            .saturating_add(num_index_entries)
+            .saturating_add(2);
+        u32::try_from(worst_case_num_restarts)
+            .is_ok_and(|num_restarts| num_restarts < u32::MAX)
+    }
+
+    fn clear(&mut self) {
+        self.blocks.clear();
+        self.block_lens.clear();
+        self.index_keys.clear();
+        self.index_key_lens.clear();
+        self.filter_keys.clear();
+        self.filter_key_lens.clear();
+        self.filter_key_counts.clear();
+        self.pending_filter_keys = 0;
+    }
+}
+
+/// A batch of buffered data blocks which is being compressed on worker threads.
+#[derive(Debug)]
+struct InFlightBatch {
+    /// The concatenated contents of the blocks. (`buffered.blocks` is empty.)
+    blocks:   Arc<Vec<u8>>,
+    buffered: BufferedBlocks,
+    pending:  PendingBatch,
+}
+
+/// The state of adaptive compression for a table: how well each candidate compressor has performed
+/// on the table's sampled data blocks so far.
+///
+/// See [`AdaptiveCompressionOptions`].
+#[derive(Debug)]
+struct AdaptiveCompression {
+    /// The first candidate is the table's usual compressor.
+    candidates:                  Vec<CandidateStats>,
+    /// The number of data blocks to compress before the next sampled block.
+    blocks_until_sample:         u32,
+    sample_interval:             NonZeroU32,
+    saved_bytes_per_microsecond: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CandidateStats {
+    compressor:  CompressorId,
+    /// The total number of bytes saved by this compressor on sampled blocks.
+    saved_bytes: u64,
+    /// The total time, in nanoseconds, taken by this compressor on sampled blocks.
+    nanos:       u64,
+}
+
+impl AdaptiveCompression {
+    #[must_use]
+    fn new(compressor: CompressorId, opts: AdaptiveCompressionOptions) -> Self {
+        let stats = |compressor| CandidateStats {
+            compressor,
+            saved_bytes: 0,
+            nanos:       0,
+        };
+
+        let mut candidates = vec![stats(compressor)];
+        for candidate in opts.candidates.into_iter().flatten() {
+            if candidates.iter().all(|existing| existing.compressor != candidate) {
+                candidates.push(stats(candidate));
+            }
+        }
+
+        Self {
+            candidates,
+            // The first block is sampled.
+            blocks_until_sample:         0,
+            sample_interval:             opts.sample_interval,
+            saved_bytes_per_microsecond: opts.saved_bytes_per_microsecond,
+        }
+    }
+
+    /// Returns whether the next data block should be sampled, and advances to the following
+    /// block.
+    fn sample_next_block(&mut self) -> bool {
+        if let Some(remaining) = self.blocks_until_sample.checked_sub(1) {
+            self.blocks_until_sample = remaining;
+            false
+        } else {
+            self.blocks_until_sample = self.sample_interval.get() - 1;
+            true
+        }
+    }
+
+    fn record(&mut self, candidate_idx: usize, saved_bytes: u64, nanos: u64) {
+        if let Some(stats) = self.candidates.get_mut(candidate_idx) {
+            stats.saved_bytes = stats.saved_bytes.saturating_add(saved_bytes);
+            stats.nanos = stats.nanos.saturating_add(nanos);
+        }
+    }
+
+    /// The net benefit, measured in bytes, of saving `saved_bytes` bytes with `nanos`
+    /// nanoseconds of compression time.
+    #[must_use]
+    fn score(&self, saved_bytes: u64, nanos: u64) -> i128 {
+        let cpu_cost = i128::from(nanos) * i128::from(self.saved_bytes_per_microsecond);
+
+        #[expect(clippy::integer_division, reason = "rounding down is fine")]
+        let cpu_cost = cpu_cost / 1000;
+
+        i128::from(saved_bytes) - cpu_cost
+    }
+
+    /// The compressor which has performed best on the sampled blocks so far, or `None` if no
+    /// compressor has been worth its compression time.
+    #[must_use]
+    fn best_compressor(&self) -> Option<CompressorId> {
+        self.candidates
+            .iter()
+            .map(|stats| (self.score(stats.saved_bytes, stats.nanos), stats.compressor))
+            .filter(|&(score, _)| score > 0)
+            .max_by_key(|&(score, _)| score)
+            .map(|(_, compressor)| compressor)
+    }
+}
+
+/// Split `data` into consecutive slices with the given lengths.
+///
+/// # Panics
+/// Panics if the sum of `lens` exceeds `data.len()`.
+fn split_by_lens<'a>(mut data: &'a [u8], lens: &'a [usize]) -> impl Iterator<Item = &'a [u8]> {
+    lens.iter().map(move |&len| {
+        let (first, rest) = data.split_at(len);
+        data = rest;
+        first
+    })
+}
+
+/// The maximum length that a block of length `uncompressed_len` may have after compression
+/// in order to meet the `compression_goal`.
+#[must_use]
+pub(super) fn compression_goal_len(uncompressed_len: usize, compression_goal: u8) -> usize {
+    let multiplied_len = u128::try_from(uncompressed_len)
+        .ok()
+        .and_then(|src_len| src_len.checked_mul(u128::from(compression_goal)))
+        .unwrap_or(0);
+
+    // Without overflow/wrapping,
+    // `multiplied_len <= 255 * uncompressed_len <= 255 * usize::MAX`
+    // so `multiplied_len / 256 <= uncompressed_len <= usize::MAX`.
+    #[expect(
+        clippy::as_conversions,
+        clippy::integer_division,
+        reason = "no wrapping/truncation occurs",
+    )]
+    let compression_goal_diff = (multiplied_len / 256) as usize;
+    // No underflow occurs; see above.
+    uncompressed_len - compression_goal_diff
+}
+
+/// The ID which data blocks compressed with a table's `dictionary` are tagged with, along with
+/// the dictionary, if the table has a dictionary (that is, if `dictionary` is nonempty).
+#[must_use]
+fn table_dictionary(
+    dictionary_opts: Option<CompressionDictionaryOptions>,
+    dictionary:      &[u8],
+) -> Option<(CompressorId, &[u8])> {
+    dictionary_opts
+        .filter(|_| !dictionary.is_empty())
+        .map(|dictionary_opts| (dictionary_opts.dictionary_compressor_id, dictionary))
+}
+
+#[cfg(test)]
+#[allow(
+    clippy::indexing_slicing,
+    clippy::missing_assert_message,
+    clippy::unwrap_used,
+    reason = "these are tests",
+)]
+mod tests {
+    use std::{iter, io::{Result as IoResult, Write}};
+    use std::num::{NonZeroU16, NonZeroU8};
+
+    use crate::{pub_traits::{cmp_and_policy::BloomPolicy, pool::BadPool}, utils::unmask_checksum};
+    use crate::pub_typed_bytes::{NUM_MIDDLE_LEVELS_USIZE, NUM_NONZERO_LEVELS_USIZE};
+    use super::*;
+    use self::none_codecs::{NoneCodecs, none_codecs};
+
+
+    #[allow(clippy::absolute_paths, dead_code, unused_qualifications, reason = "test codecs")]
+    mod none_codecs {
+        crate::codec_list! {
+            codecs[(None, crate::db_options::NoCompressionCodec)];
+
+            pub(super) struct NoneCodecs;
+            pub(super) struct NoneEncoders;
+            pub(super) struct NoneDecoders;
+            #[derive(Debug)]
+            pub(super) enum NoneCompressionError;
+            #[derive(Debug)]
+            pub(super) enum NoneDecompressionError;
+        }
+
+        pub(super) const fn none_codecs() -> NoneCodecs {
+            NoneCodecs((), crate::db_options::NoCompressionCodec)
+        }
+    }
+
+
+    #[derive(Debug, Default)]
+    struct MemoryFile(Vec<u8>);
+
+    impl Write for MemoryFile {
+        fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> IoResult<()> {
+            Ok(())
+        }
+    }
+
+    impl WritableFile for MemoryFile {
+        fn sync_data(&mut self) -> IoResult<()> {
+            Ok(())
+        }
+    }
+
+    type TestBuilder = TableBuilder<MemoryFile, BloomPolicy, BadPool>;
+
+    /// An inactive builder without a filter policy, dictionary compression, parallel compression,
+    /// or adaptive compression.
+    fn inactive_builder() -> TestBuilder {
+        let restart_interval = NonZeroU32::new(16).unwrap();
+
+        TableBuilder {
+            table_file:       None,
+            offset_in_file:   FileOffset(0),
+            num_entries:      0,
+            data_block:       BlockBuilder::new(restart_interval),
+            index_block:      BlockBuilder::new(restart_interval),
+            filter_block:     None,
+            filter_error:     false,
+            key_scratch:      Vec::new(),
+            compression_buf:  None,
+            block_size:       4 << 10_u8,
+            compressor:       None,
+            compression_goal: 0,
+            dictionary_opts:  None,
+            dictionary:       Arc::default(),
+            train_dictionary: false,
+            parallel:         false,
+            parallel_threads: None,
+            buffering:        false,
+            buffered:         BufferedBlocks::default(),
+            in_flight:        None,
+            spare_buffered:   BufferedBlocks::default(),
+            adaptive_opts:    None,
+            adaptive:         None,
+        }
+    }
+
+    fn dynamic_options(sstable_block_size: usize) -> DynamicOptions {
+        let trigger = NonZeroU16::new(8).unwrap();
+
+        DynamicOptions {
+            memtable_compressor:                       None,
+            table_compressors:                         [None; NUM_NONZERO_LEVELS_USIZE.get()],
+            memtable_compression_goal:                 0,
+            table_compression_goals:                   [0; NUM_NONZERO_LEVELS_USIZE.get()],
+            sstable_block_size,
+            sstable_block_restart_interval:            NonZeroU32::new(16).unwrap(),
+            level0_write_throttle_trigger:             trigger,
+            level0_write_halt_trigger:                 trigger,
+            pending_compaction_bytes_throttle_trigger: None,
+            pending_compaction_bytes_halt_trigger:     None,
+            max_level0_files:                          trigger,
+            max_level_sizes:                           [0; NUM_MIDDLE_LEVELS_USIZE.get()],
+        }
+    }
+
+    /// Check that the block at `handle` in `file` is `stored_block`, followed by a trailer with
+    /// the given compressor and the masked checksum that table readers expect.
+    fn check_block(file: &[u8], handle: BlockHandle, stored_block: &[u8], compressor: u8) {
+        let start = usize::try_from(handle.offset.0).unwrap();
+        let end = start + stored_block.len();
+
+        assert_eq!(handle.size, TableBlockSize(u64::try_from(stored_block.len()).unwrap()));
+        assert_eq!(&file[start..end], stored_block);
+        assert_eq!(file[end], compressor);
+
+        let masked_checksum = u32::from_le_bytes(file[end + 1..end + 5].try_into().unwrap());
+        let checksum = crc32c::crc32c_append(crc32c::crc32c(stored_block), &[compressor]);
+        assert_eq!(unmask_checksum(masked_checksum), checksum);
+    }
+
+    #[test]
+    fn uncompressed_block_trailer() {
+        let mut file = MemoryFile::default();
+        let mut offset = FileOffset(0);
+        let block = b"an uncompressed block";
+
+        let handle = TestBuilder::write_compressed_block::<()>(&mut file, &mut offset, block, None)
+            .unwrap();
+
+        assert_eq!(handle.offset, FileOffset(0));
+        assert_eq!(file.0.len(), block.len() + BLOCK_FOOTER_LEN);
+        assert_eq!(offset, FileOffset(u64::try_from(file.0.len()).unwrap()));
+        check_block(&file.0, handle, block, 0);
+    }
+
+    #[test]
+    fn compressed_block_handles() {
+        let mut file = MemoryFile::default();
+        let mut offset = FileOffset(0);
+        let compressor = CompressorId(NonZeroU8::new(1).unwrap());
+
+        let first_block = [b'a'; 100];
+        let first_compressed = b"100 a's";
+        let second_block = [b'b'; 50];
+        let second_compressed = b"50 b's";
+
+        let first_handle = TestBuilder::write_compressed_block::<()>(
+            &mut file,
+            &mut offset,
+            &first_block,
+            Some((compressor, first_compressed.as_slice())),
+        ).unwrap();
+        let second_handle = TestBuilder::write_compressed_block::<()>(
+            &mut file,
+            &mut offset,
+            &second_block,
+            Some((compressor, second_compressed.as_slice())),
+        ).unwrap();
+
+        // Handles and offsets are based on the stored (compressed) length of each block.
+        let first_len = u64::try_from(first_compressed.len() + BLOCK_FOOTER_LEN).unwrap();
+        assert_eq!(first_handle.offset, FileOffset(0));
+        assert_eq!(second_handle.offset, FileOffset(first_len));
+        assert_eq!(offset, FileOffset(u64::try_from(file.0.len()).unwrap()));
+
+        check_block(&file.0, first_handle, first_compressed, 1);
+        check_block(&file.0, second_handle, second_compressed, 1);
+    }
+
+    #[test]
+    fn incompressible_blocks_are_uncompressed() {
+        let mut encoders = none_codecs().init_encoders();
+        let compressor = CompressorId(NonZeroU8::new(1).unwrap());
+        let block = b"a block which cannot meet a compression goal without being compressed";
+
+        let compressed = TestBuilder::compress_block::<NoneCodecs>(
+            &mut None,
+            u8::MAX,
+            &BadPool::new(),
+            &mut encoders,
+            block,
+            Some(compressor),
+            None,
+        ).unwrap();
+        assert!(compressed.is_none());
+
+        // The block must be stored, and marked, as uncompressed.
+        let mut file = MemoryFile::default();
+        let mut offset = FileOffset(0);
+        let handle = TestBuilder::write_compressed_block::<()>(&mut file, &mut offset, block, None)
+            .unwrap();
+
+        check_block(&file.0, handle, block, 0);
+    }
+
+    #[test]
+    fn dictionary_blocks_are_tagged_with_dictionary_id() {
+        let mut encoders = none_codecs().init_encoders();
+        let compressor = CompressorId(NonZeroU8::new(1).unwrap());
+        let dictionary_id = CompressorId(NonZeroU8::MAX);
+        let block = b"a data block compressed with the table's dictionary";
+
+        let (with_dictionary, _) = TestBuilder::compress_block::<NoneCodecs>(
+            &mut None,
+            0,
+            &BadPool::new(),
+            &mut encoders,
+            block,
+            Some(compressor),
+            Some((dictionary_id, b"dictionary".as_slice())),
+        ).unwrap().unwrap();
+        assert_eq!(with_dictionary, dictionary_id);
+
+        let (without_dictionary, _) = TestBuilder::compress_block::<NoneCodecs>(
+            &mut None,
+            0,
+            &BadPool::new(),
+            &mut encoders,
+            block,
+            Some(compressor),
+            None,
+        ).unwrap().unwrap();
+        assert_eq!(without_dictionary, compressor);
+    }
+
+    #[test]
+    fn parallel_batches_overlap_and_stay_in_order() {
+        let parallel = ParallelCompression::new(&none_codecs(), NonZeroUsize::new(3).unwrap())
+            .unwrap();
+        let compressor = CompressorId(NonZeroU8::new(1).unwrap());
+        let dictionary = Arc::default();
+
+        let batches = [[1, 2, 3, 4, 5, 6, 7].as_slice(), [8, 9].as_slice()].map(|block_lens| {
+            let mut blocks = Vec::new();
+            for (block_idx, &block_len) in block_lens.iter().enumerate() {
+                blocks.extend(iter::repeat_n(u8::try_from(block_idx).unwrap(), block_len));
+            }
+            let blocks = Arc::new(blocks);
+            let pending = parallel
+                .compress_batch(Arc::clone(&blocks), block_lens, compressor, 0, &dictionary);
+            (blocks, block_lens, pending)
+        });
+
+        // Both batches were submitted before either was waited on.
+        for (blocks, block_lens, pending) in batches {
+            let results = pending.wait();
+            assert_eq!(results.len(), block_lens.len());
+
+            for (uncompressed_block, result) in split_by_lens(&blocks, block_lens).zip(results) {
+                let CompressionJobResult::Compressed(compressed_buf) = result else {
+                    panic!("`NoCompressionCodec` should meet a compression goal of 0");
+                };
+                assert_eq!(compressed_buf.as_slice(), uncompressed_block);
+            }
+        }
+
+        // The worker threads are joined.
+        drop(parallel);
+    }
+
+    #[test]
+    fn start_applies_block_size() {
+        let mut builder = inactive_builder();
+
+        for block_size in [256, 4 << 10_u8, 1 << 20_u8] {
+            builder.start(&dynamic_options(block_size), MemoryFile::default(), None);
+            assert_eq!(builder.block_size, block_size);
+        }
+    }
+}
@@ -18,6 +18,29 @@ pub(super) const BLOCK_FOOTER_LEN: usize = const {
 /// corresponding to filters).
 pub(super) const FILTER_META_PREFIX: &[u8] = b"filter.";
 
+/// The key of the metaindex block entry for the block listing the compressor IDs and names of
+/// the compression codecs which a table was written with (if any).
+///
+/// The contents of that block are encoded by `encode_codec_names`.
+pub(super) const COMPRESSION_CODECS_META_KEY: &[u8] = b"compression_codecs";
+
+/// The key of the metaindex block entry for a table's compression dictionary block (if any).
+///
+/// The contents of that block are the ID of the compressor which the dictionary is for, then the
+/// distinct ID which data blocks compressed with the dictionary are tagged with, followed by the
+/// dictionary itself.
+pub(super) const COMPRESSION_DICTIONARY_META_KEY: &[u8] = b"compression_dictionary";
+
+/// The key of the metaindex block entry for the block recording a table's
+/// [`TableProperties`] (if any).
+///
+/// The contents of that block are encoded by [`TableProperties::encode_to`]. Other LevelDB
+/// implementations ignore metaindex entries they do not recognize.
+///
+/// [`TableProperties`]: crate::file_tracking::TableProperties
+/// [`TableProperties::encode_to`]: crate::file_tracking::TableProperties::encode_to
+pub(super) const TABLE_PROPERTIES_META_KEY: &[u8] = b"table_properties";
+
 
 #[derive(Debug, Clone, Copy)]
 pub(super) struct TableFooter {
@@ -1,4 +1,4 @@
-use std::{cmp::Ordering, convert::Infallible};
+use std::{cmp::Ordering, convert::Infallible, iter};
 
 use crate::{pub_traits::cmp_and_policy::FilterPolicy, table_format::InternalFilterPolicy};
 use crate::{
@@ -6,6 +6,9 @@ use crate::{
     pub_typed_bytes::{BlockHandle, FileSize, TableBlockOffset},
 };
 use super::block_iter::BlockIter;
+use super::footer::{
+    COMPRESSION_CODECS_META_KEY, COMPRESSION_DICTIONARY_META_KEY, TABLE_PROPERTIES_META_KEY,
+};
 
 
 /// The hardcoded `filter.` prefix used before a filter's name (in metaindex block entries
@@ -28,14 +31,61 @@ impl<'a> MetaindexBlockIter<'a> {
     }
 
     /// Get the handle of the filter block corresponding to the given `policy` (if there is one).
+    ///
+    /// If there is no filter block named by the policy's [`FilterPolicy::name`], the names in
+    /// [`FilterPolicy::compatible_names`] are tried in order.
     pub fn get_filter_handle<Policy: FilterPolicy>(
         &mut self,
         policy: &InternalFilterPolicy<Policy>,
     ) -> Result<Option<BlockHandle>, MetaindexIterError> {
+        let policy_names = iter::once(policy.0.name())
+            .chain(policy.0.compatible_names().iter().copied());
+
+        for policy_name in policy_names {
+            let handle = self.get_handle(|key| {
+                MetaindexComparator::key_cmp_policy_name(key, policy_name)
+            })?;
+
+            if handle.is_some() {
+                return Ok(handle);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Get the handle of the compression codecs block (if there is one).
+    pub fn get_compression_codecs_handle(
+        &mut self,
+    ) -> Result<Option<BlockHandle>, MetaindexIterError> {
+        self.get_handle(|key| key.cmp(COMPRESSION_CODECS_META_KEY))
+    }
+
+    /// Get the handle of the compression dictionary block (if there is one).
+    pub fn get_compression_dictionary_handle(
+        &mut self,
+    ) -> Result<Option<BlockHandle>, MetaindexIterError> {
+        self.get_handle(|key| key.cmp(COMPRESSION_DICTIONARY_META_KEY))
+    }
+
+    /// Get the handle of the table properties block (if there is one).
+    pub fn get_table_properties_handle(
+        &mut self,
+    ) -> Result<Option<BlockHandle>, MetaindexIterError> {
+        self.get_handle(|key| key.cmp(TABLE_PROPERTIES_META_KEY))
+    }
+
+    /// Get the handle in the entry whose key compares equal to the target of `key_cmp_target`
+    /// (if there is such an entry).
+    fn get_handle<F>(
+        &mut self,
+        mut key_cmp_target: F,
+    ) -> Result<Option<BlockHandle>, MetaindexIterError>
+    where
+        F: FnMut(&[u8]) -> Ordering,
+    {
         self.1
-            .try_seek_by::<_, Infallible>(self.0, |key| {
-                Ok(MetaindexComparator::key_cmp_policy_name(key, policy))
-            })
+            .try_seek_by::<_, Infallible>(self.0, |key| Ok(key_cmp_target(key)))
             .map_err(|seek_err| {
                 match seek_err {
                     BlockSeekError::Block(block_err) => MetaindexIterError::Block(block_err),
@@ -43,20 +93,20 @@ impl<'a> MetaindexBlockIter<'a> {
                 }
             })?;
 
-        let Some(maybe_filter_entry) = self.1.current(self.0) else {
-            // There is no entry with a key greater than or equal to the policy's key.
+        let Some(maybe_entry) = self.1.current(self.0) else {
+            // There is no entry with a key greater than or equal to the target key.
             return Ok(None);
         };
-        if MetaindexComparator::key_cmp_policy_name(maybe_filter_entry.key, policy).is_ne() {
-            // There is an entry with a key greater than the policy's key, but not one equal to it.
+        if key_cmp_target(maybe_entry.key).is_ne() {
+            // There is an entry with a key greater than the target key, but not one equal to it.
             return Ok(None);
         }
 
-        // The filter entry exists, and we found it.
-        let (filter_block_handle, _) = BlockHandle::decode(maybe_filter_entry.value, self.2)
+        // The entry exists, and we found it.
+        let (block_handle, _) = BlockHandle::decode(maybe_entry.value, self.2)
             .map_err(MetaindexIterError::Handle)?;
 
-        Ok(Some(filter_block_handle))
+        Ok(Some(block_handle))
     }
 
     #[inline]
@@ -78,18 +128,15 @@ impl<'a> MetaindexBlockIter<'a> {
 struct MetaindexComparator;
 
 impl MetaindexComparator {
-    /// Compare a `key` against the name of the given `policy` (with an added
+    /// Compare a `key` against the name of a filter policy (with an added
     /// [`FILTER_META_PREFIX`]), with respect to the ordering used by the metaindex block of an
     /// SSTable (namely, the lexicographic ordering on byte slices).
     ///
     /// This should only be used for the metaindex block.
     #[must_use]
-    fn key_cmp_policy_name<Policy: FilterPolicy>(
-        key:    &[u8],
-        policy: &InternalFilterPolicy<Policy>,
-    ) -> Ordering {
+    fn key_cmp_policy_name(key: &[u8], policy_name: &[u8]) -> Ordering {
         if let Some(key_filter_name) = key.strip_prefix(FILTER_META_PREFIX) {
-            key_filter_name.cmp(policy.0.name())
+            key_filter_name.cmp(policy_name)
         } else {
             key.cmp(FILTER_META_PREFIX)
         }
@@ -9,13 +9,17 @@ mod index_block;
 mod metaindex_block;
 
 mod builder;
+mod parallel_compression;
 mod reader;
 mod iter;
+mod block_sampler;
 
 
 pub(crate) use self::{
+    block_sampler::{SAMPLED_PREFIX_LEN, sample_table_blocks},
     builder::TableBuilder,
     data_block::SSTableEntry,
     iter::TableIter,
+    parallel_compression::ParallelCompression,
     reader::TableReader,
 };
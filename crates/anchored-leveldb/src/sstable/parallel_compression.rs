@@ -0,0 +1,295 @@
+use std::{iter, num::NonZeroUsize, thread};
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        Arc, Mutex, PoisonError,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread::JoinHandle,
+};
+
+use crate::pub_traits::{
+    compression::{CodecsCompressionError, CompressionCodecs, CompressorId},
+    pool::{BadPool, BadPoolBuf},
+};
+use super::builder::compression_goal_len;
+
+
+/// Compresses batches of data blocks on a pool of worker threads, which live as long as this
+/// struct. Each worker thread keeps its own encoders from [`CompressionCodecs::init_encoders`]
+/// for its entire lifetime, so that their state (and allocations) are reused across batches.
+///
+/// Compressing on other threads requires `Codecs::Encoders: Send`, which is only known where the
+/// database is opened. The worker threads are therefore spawned when this struct is constructed,
+/// so that the table builder does not need that bound.
+pub(crate) struct ParallelCompression<Codecs> {
+    num_threads: NonZeroUsize,
+    /// Closed (set to `None`) when this struct is dropped, so that the worker threads exit.
+    jobs:        Option<Sender<CompressionJob>>,
+    workers:     Vec<JoinHandle<()>>,
+    _codecs:     PhantomData<fn() -> Codecs>,
+}
+
+#[expect(unreachable_pub, reason = "control visibility at type definition")]
+impl<Codecs: CompressionCodecs> ParallelCompression<Codecs> {
+    /// Spawn `num_threads` compression worker threads.
+    ///
+    /// Returns `None` if not a single worker thread could be spawned, in which case data blocks
+    /// should be compressed on the table builder's thread.
+    #[must_use]
+    pub fn new(codecs: &Codecs, num_threads: NonZeroUsize) -> Option<Self>
+    where
+        Codecs:           'static,
+        Codecs::Encoders: Send,
+    {
+        let (jobs, receiver) = mpsc::channel();
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        let workers = (0..num_threads.get())
+            .map_while(|thread_idx| {
+                let encoders = codecs.init_encoders();
+                let receiver = Arc::clone(&receiver);
+
+                thread::Builder::new()
+                    .name(format!("leveldb-compression-{thread_idx}"))
+                    .spawn(move || compression_worker::<Codecs>(encoders, &receiver))
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+
+        Some(Self {
+            num_threads: NonZeroUsize::new(workers.len())?,
+            jobs:        Some(jobs),
+            workers,
+            _codecs:     PhantomData,
+        })
+    }
+}
+
+#[expect(unreachable_pub, reason = "control visibility at type definition")]
+impl<Codecs> ParallelCompression<Codecs> {
+    #[inline]
+    #[must_use]
+    pub const fn num_threads(&self) -> NonZeroUsize {
+        self.num_threads
+    }
+
+    /// Begin compressing a batch of data blocks on the worker threads, and return immediately.
+    ///
+    /// The blocks are the consecutive slices of `blocks` with lengths `block_lens`. They're split
+    /// into one contiguous chunk per worker thread. If `dictionary` is nonempty, the blocks are
+    /// compressed with it.
+    ///
+    /// The table builder's thread may write out a previous batch while this batch is compressed.
+    #[must_use]
+    pub fn compress_batch(
+        &self,
+        blocks:           Arc<Vec<u8>>,
+        block_lens:       &[usize],
+        compressor:       CompressorId,
+        compression_goal: u8,
+        dictionary:       &Arc<[u8]>,
+    ) -> PendingBatch {
+        let (results, receiver) = mpsc::channel();
+
+        let chunk_len = block_lens.len().div_ceil(self.num_threads.get()).max(1);
+        let mut start = 0;
+
+        for (chunk_idx, chunk_lens) in block_lens.chunks(chunk_len).enumerate() {
+            let job = CompressionJob {
+                blocks:           Arc::clone(&blocks),
+                start,
+                block_lens:       chunk_lens.to_vec(),
+                first_block:      chunk_idx * chunk_len,
+                compressor,
+                compression_goal,
+                dictionary:       Arc::clone(dictionary),
+                results:          results.clone(),
+            };
+            start += chunk_lens.iter().sum::<usize>();
+
+            if let Some(jobs) = &self.jobs {
+                // If every worker thread has exited, the job is dropped, and its blocks are
+                // compressed on the table builder's thread instead.
+                let _unsent = jobs.send(job);
+            }
+        }
+
+        PendingBatch {
+            num_blocks: block_lens.len(),
+            results:    receiver,
+        }
+    }
+}
+
+impl<Codecs> Debug for ParallelCompression<Codecs> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("ParallelCompression")
+            .field("num_threads", &self.num_threads)
+            .field("jobs",        &self.jobs)
+            .field("workers",     &self.workers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<Codecs> Drop for ParallelCompression<Codecs> {
+    fn drop(&mut self) {
+        // Once the channel is closed, each worker thread exits after finishing its current job.
+        drop(self.jobs.take());
+        for worker in self.workers.drain(..) {
+            // Panics in codecs are caught by the worker threads, so this is always `Ok`.
+            let _result = worker.join();
+        }
+    }
+}
+
+/// A batch of data blocks being compressed by the worker threads of a [`ParallelCompression`].
+#[derive(Debug)]
+pub(crate) struct PendingBatch {
+    num_blocks: usize,
+    results:    Receiver<(usize, Vec<CompressionJobResult>)>,
+}
+
+#[expect(unreachable_pub, reason = "control visibility at type definition")]
+impl PendingBatch {
+    /// Wait until the worker threads have finished compressing the batch, and return the result
+    /// for each of its blocks, in order.
+    ///
+    /// Blocks which no worker thread compressed are [`CompressionJobResult::Pending`].
+    #[must_use]
+    pub fn wait(self) -> Vec<CompressionJobResult> {
+        let mut results = iter::repeat_with(|| CompressionJobResult::Pending)
+            .take(self.num_blocks)
+            .collect::<Vec<_>>();
+
+        // Iteration ends once every job of the batch has been dropped, whether or not it was run.
+        for (first_block, chunk_results) in self.results {
+            for (result, chunk_result) in results.iter_mut().skip(first_block).zip(chunk_results) {
+                *result = chunk_result;
+            }
+        }
+
+        results
+    }
+}
+
+/// A contiguous chunk of a batch of data blocks, to be compressed by a worker thread.
+#[derive(Debug)]
+struct CompressionJob {
+    /// The concatenated contents of every block in the batch.
+    blocks:           Arc<Vec<u8>>,
+    /// The offset in `blocks` of the first block of this chunk.
+    start:            usize,
+    block_lens:       Vec<usize>,
+    /// The index in the batch of the first block of this chunk.
+    first_block:      usize,
+    compressor:       CompressorId,
+    compression_goal: u8,
+    /// If nonempty, the dictionary to compress the blocks with.
+    dictionary:       Arc<[u8]>,
+    results:          Sender<(usize, Vec<CompressionJobResult>)>,
+}
+
+#[derive(Debug)]
+pub(crate) enum CompressionJobResult {
+    /// The block was not compressed by a worker thread, and should be compressed on the table
+    /// builder's thread.
+    Pending,
+    Compressed(BadPoolBuf),
+    /// The compression goal could not be met, so the block should be stored uncompressed.
+    Incompressible,
+    /// Compression failed. The block should be compressed again on the table builder's thread,
+    /// in order to report the error (if it reoccurs).
+    Failed,
+}
+
+/// Run jobs until the channel of jobs is closed.
+fn compression_worker<Codecs: CompressionCodecs>(
+    mut encoders: Codecs::Encoders,
+    jobs:         &Mutex<Receiver<CompressionJob>>,
+) {
+    // Compressed blocks are sent back to the table builder's thread, which does not
+    // have access to the user's buffer pool type in a thread-safe way.
+    let pool = BadPool::new();
+    let mut existing_buf = None;
+
+    loop {
+        // The lock is only held while waiting for the next job, so poison can be ignored.
+        let job = jobs.lock().unwrap_or_else(PoisonError::into_inner).recv();
+        let Ok(job) = job else {
+            return;
+        };
+
+        // If a codec panics, this job's blocks are compressed again on the table builder's
+        // thread, where the panic would presumably reoccur and be reported.
+        let results = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut block_start = job.start;
+            job.block_lens
+                .iter()
+                .map(|&block_len| {
+                    let block_end = block_start + block_len;
+                    let uncompressed_block = job.blocks
+                        .get(block_start..block_end)
+                        .unwrap_or_default();
+                    block_start = block_end;
+
+                    compress_block::<Codecs>(
+                        &mut encoders,
+                        &pool,
+                        &mut existing_buf,
+                        &job,
+                        uncompressed_block,
+                    )
+                })
+                .collect::<Vec<_>>()
+        }));
+
+        // Release the batch's blocks before the table builder's thread is notified, so that it
+        // can reuse their buffer.
+        drop(job.blocks);
+        if let Ok(results) = results {
+            // The table builder may have abandoned the table, which is fine.
+            let _unsent = job.results.send((job.first_block, results));
+        }
+    }
+}
+
+fn compress_block<Codecs: CompressionCodecs>(
+    encoders:           &mut Codecs::Encoders,
+    pool:               &BadPool,
+    existing_buf:       &mut Option<BadPoolBuf>,
+    job:                &CompressionJob,
+    uncompressed_block: &[u8],
+) -> CompressionJobResult {
+    let compression_goal_len = compression_goal_len(uncompressed_block.len(), job.compression_goal);
+
+    let compression_result = if job.dictionary.is_empty() {
+        Codecs::encode(
+            encoders,
+            uncompressed_block,
+            job.compressor,
+            compression_goal_len,
+            pool,
+            existing_buf,
+        )
+    } else {
+        Codecs::encode_with_dictionary(
+            encoders,
+            &job.dictionary,
+            uncompressed_block,
+            job.compressor,
+            compression_goal_len,
+            pool,
+            existing_buf,
+        )
+    };
+
+    match compression_result {
+        Ok(compressed_buf) => CompressionJobResult::Compressed(compressed_buf),
+        Err(CodecsCompressionError::Incompressible)
+            => CompressionJobResult::Incompressible,
+        Err(_) => CompressionJobResult::Failed,
+    }
+}
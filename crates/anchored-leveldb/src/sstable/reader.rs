@@ -8,9 +8,11 @@ use anchored_vfs::{LevelDBFilesystem, RandomAccess};
 use crate::table_caches::BlockCacheKey;
 use crate::{
     all_errors::types::{
-        CompressedBlockError, CorruptedTableError, MetaindexIterError,
+        BlockHandleCorruption, CompressedBlockError, CorruptedTableError, MetaindexIterError,
         NewTableReaderError, ReadTableBlockError, TableFooterCorruption,
     },
+    compression::{check_codec_names, decode_codec_names},
+    file_tracking::TableProperties,
     options::{
         InternallyMutableOptions, InternalOptions, InternalReadOptions, pub_options::CacheUsage,
     },
@@ -19,7 +21,9 @@ use crate::{
         compression::{CodecsDecompressionError, CompressionCodecs, CompressorId},
         pool::{BufferPool, ByteBuffer as _},
     },
-    pub_typed_bytes::{BlockHandle, BlockType, FileNumber, FileOffset, FileSize},
+    pub_typed_bytes::{
+        BlockHandle, BlockType, FileNumber, FileOffset, FileSize, TableBlockOffset,
+    },
     table_format::{InternalComparator, InternalFilterPolicy},
     typed_bytes::{InternalKey, LookupKey},
     utils::{get_buffer, unmask_checksum},
@@ -36,13 +40,18 @@ use super::{
 
 
 pub(crate) struct TableReader<File, Policy, Pool: BufferPool> {
-    file:             File,
-    file_number:      FileNumber,
-    file_size:        FileSize,
-    metaindex_offset: FileOffset,
-    index_handle:     BlockHandle,
-    index_block:      Pool::PooledBuffer,
-    filter_block:     Option<FilterBlockReader<Policy, Pool::PooledBuffer>>,
+    file:                   File,
+    file_number:            FileNumber,
+    file_size:              FileSize,
+    metaindex_offset:       FileOffset,
+    index_handle:           BlockHandle,
+    index_block:            Pool::PooledBuffer,
+    filter_block:           Option<FilterBlockReader<Policy, Pool::PooledBuffer>>,
+    /// The contents of the compression dictionary block (if any), which are parsed by
+    /// [`TableDictionary::parse`]. Validated on construction.
+    compression_dictionary: Option<Pool::PooledBuffer>,
+    /// Read from the table properties block, if the table has one.
+    properties:             Option<TableProperties>,
 }
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
@@ -70,8 +79,9 @@ where
         Codecs: CompressionCodecs,
     {
         // We need to read the footer and the index block, at the very least.
-        // Additionally, if a `Policy` was selected, then we need to read the metaindex block
-        // and filter block.
+        // Additionally, we need to read the metaindex block, the compression dictionary block
+        // and table properties block (if any), the compression codecs block (if any, and if the
+        // chosen codecs have names), and, if a `Policy` was selected, the filter block.
 
         let footer_offset = file_size.0
             .checked_sub(u64::from(TableFooter::ENCODED_LENGTH_U8))
@@ -109,21 +119,20 @@ where
             file_size,
             decoders,
             buffer_pool:      &mut_opts.buffer_pool,
+            // Only data blocks are compressed with the dictionary.
+            dictionary:       None,
         };
 
         let existing_buf = &mut None;
 
-        let filter_block = if let Some(policy) = &opts.policy {
-            block_reader.read_filter_block(
-                policy,
-                table_footer.metaindex,
-                file_size,
-                read_opts.verify_index_checksums,
-                existing_buf,
-            )?
-        } else {
-            None
-        };
+        let (filter_block, compression_dictionary, properties) = block_reader.read_meta_blocks(
+            opts.policy.as_ref(),
+            &opts.codec_names,
+            table_footer.metaindex,
+            file_size,
+            read_opts.verify_index_checksums,
+            existing_buf,
+        )?;
 
         let index_block = block_reader.read_table_block(
             BlockType::Index,
@@ -143,13 +152,15 @@ where
             })?;
 
         Ok(Self {
-            file:             sstable_file,
+            file:                   sstable_file,
             file_number,
             file_size,
-            metaindex_offset: table_footer.metaindex.offset,
-            index_handle:     table_footer.index,
+            metaindex_offset:       table_footer.metaindex.offset,
+            index_handle:           table_footer.index,
             index_block,
             filter_block,
+            compression_dictionary,
+            properties,
         })
     }
 
@@ -393,6 +404,18 @@ where
         self.index_handle
     }
 
+    /// Get this table's compression dictionary (if any).
+    fn compression_dictionary(&self) -> Option<TableDictionary<'_>> {
+        // Validated on construction.
+        TableDictionary::parse(self.compression_dictionary.as_ref()?.as_slice())
+    }
+
+    /// The properties recorded in this table's table properties block, if it has one.
+    #[must_use]
+    pub const fn properties(&self) -> Option<TableProperties> {
+        self.properties
+    }
+
     /// Used by [`DisjointLevelIter`] and [`IterToMerge`].
     ///
     /// [`DisjointLevelIter`]: crate::version::DisjointLevelIter
@@ -435,6 +458,7 @@ where
                 file_size:   self.file_size,
                 decoders,
                 buffer_pool: &mut_opts.buffer_pool,
+                dictionary:  self.compression_dictionary(),
             };
 
             let data_block = block_reader.read_table_block(
@@ -473,17 +497,57 @@ where
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         f.debug_struct("TableReader")
-            .field("file",             &self.file)
-            .field("file_number",      &self.file_number)
-            .field("file_size",        &self.file_size)
-            .field("metaindex_offset", &self.metaindex_offset)
-            .field("index_handle",     &self.index_handle)
-            .field("index_block",      &self.index_block)
-            .field("filter_block",     &self.filter_block)
+            .field("file",                   &self.file)
+            .field("file_number",            &self.file_number)
+            .field("file_size",              &self.file_size)
+            .field("metaindex_offset",       &self.metaindex_offset)
+            .field("index_handle",           &self.index_handle)
+            .field("index_block",            &self.index_block)
+            .field("filter_block",           &self.filter_block)
+            .field("compression_dictionary", &self.compression_dictionary)
+            .field("properties",             &self.properties)
             .finish()
     }
 }
 
+/// A table's compression dictionary, and the compressor IDs it's used with.
+#[derive(Debug, Clone, Copy)]
+struct TableDictionary<'a> {
+    /// The compressor which the dictionary was trained for.
+    compressor:       CompressorId,
+    /// The ID which data blocks compressed with the dictionary are tagged with, instead of
+    /// `compressor`.
+    block_compressor: CompressorId,
+    dictionary:       &'a [u8],
+}
+
+impl<'a> TableDictionary<'a> {
+    /// Parse the contents of a compression dictionary block: the ID of the dictionary's
+    /// compressor, the ID which blocks compressed with the dictionary are tagged with, and the
+    /// dictionary.
+    ///
+    /// Returns `None` if either ID is zero or if the two IDs are equal.
+    #[must_use]
+    fn parse(dictionary_block: &'a [u8]) -> Option<Self> {
+        let [compressor, block_compressor, dictionary @ ..] = dictionary_block else {
+            return None;
+        };
+
+        let compressor = CompressorId(NonZeroU8::new(*compressor)?);
+        let block_compressor = CompressorId(NonZeroU8::new(*block_compressor)?);
+
+        if compressor == block_compressor {
+            return None;
+        }
+
+        Some(Self {
+            compressor,
+            block_compressor,
+            dictionary,
+        })
+    }
+}
+
 /// A short-lived reader for any table block.
 #[derive(Debug)]
 struct TableBlockReader<'a, File, Codecs: CompressionCodecs, Pool> {
@@ -491,6 +555,9 @@ struct TableBlockReader<'a, File, Codecs: CompressionCodecs, Pool> {
     pub file_size:   FileSize,
     pub decoders:    &'a mut Codecs::Decoders,
     pub buffer_pool: &'a Pool,
+    /// The table's compression dictionary, which is used to decompress data blocks tagged with
+    /// its `block_compressor`.
+    pub dictionary:  Option<TableDictionary<'a>>,
 }
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
@@ -582,13 +649,33 @@ where
         }
 
         if let Some(compressor_id) = NonZeroU8::new(compressor_id).map(CompressorId) {
-            let decompression_result = Codecs::decode(
-                self.decoders,
-                compressed_block_data,
-                compressor_id,
-                self.buffer_pool,
-                &mut None,
-            );
+            let dictionary = self.dictionary.filter(|dictionary| {
+                matches!(block_type, BlockType::Data)
+                    && dictionary.block_compressor == compressor_id
+            });
+            // Errors refer to the compressor which actually decompressed the block.
+            let compressor_id = dictionary.map_or(compressor_id, |dictionary| {
+                dictionary.compressor
+            });
+
+            let decompression_result = if let Some(dictionary) = dictionary {
+                Codecs::decode_with_dictionary(
+                    self.decoders,
+                    dictionary.dictionary,
+                    compressed_block_data,
+                    compressor_id,
+                    self.buffer_pool,
+                    &mut None,
+                )
+            } else {
+                Codecs::decode(
+                    self.decoders,
+                    compressed_block_data,
+                    compressor_id,
+                    self.buffer_pool,
+                    &mut None,
+                )
+            };
 
             let decompression_result = decompression_result.map_err(|err| {
                 match err {
@@ -624,26 +711,37 @@ where
         }
     }
 
-    /// Attempts to read the filter block in `self.file` associated with the given `policy`.
+    /// Attempts to read the filter block in `self.file` associated with the given `policy`
+    /// (if any), the compression dictionary block in `self.file`, and the table properties block
+    /// in `self.file`.
+    ///
+    /// No block is mandatory; `None` is returned in place of any block which is not present
+    /// (or, for the filter block, if no `policy` is given).
     ///
-    /// Returns `Ok(None)` if there is no such filter block - which is valid -
-    /// and `Ok(Some(_))` if the filter block was found and successfully read.
+    /// If `codec_names` is nonempty and the table has a compression codecs block, the codec names
+    /// recorded in that block are also checked against `codec_names`.
     ///
     /// The `metaindex_handle` should be the handle of the metaindex block of this SSTable file.
     ///
-    /// Note that the checksum for the filter block is always validated, since there's otherwise
-    /// little chance of detecting corruption in the filter block.
+    /// Note that the checksums of the meta blocks are always validated, since there's otherwise
+    /// little chance of detecting corruption in the filter block, properties, or codec names, and
+    /// corruption in the dictionary would affect every data block compressed with it.
     #[expect(clippy::type_complexity, reason = "still sufficiently readable")]
-    pub fn read_filter_block<InvalidKey, Policy>(
+    pub fn read_meta_blocks<InvalidKey, Policy>(
         &mut self,
-        policy:                    &InternalFilterPolicy<Policy>,
+        policy:                    Option<&InternalFilterPolicy<Policy>>,
+        codec_names:               &[(CompressorId, &'static str)],
         metaindex_handle:          BlockHandle,
         table_size:                FileSize,
         verify_metaindex_checksum: bool,
         existing_buf:              &mut Option<Pool::PooledBuffer>,
     ) -> Result<
-        Option<FilterBlockReader<Policy, Pool::PooledBuffer>>,
-        ReadTableBlockError<InvalidKey, Codecs::DecompressionError>,
+        (
+            Option<FilterBlockReader<Policy, Pool::PooledBuffer>>,
+            Option<Pool::PooledBuffer>,
+            Option<TableProperties>,
+        ),
+        NewTableReaderError<InvalidKey, Codecs::DecompressionError>,
     >
     where
         Policy: FilterPolicy + FastMirroredClone,
@@ -652,10 +750,10 @@ where
             // If the metaindex block is contains only the `num_restarts` value (or less),
             // which is a `u32` (size: 4 bytes), then either that block is corrupt
             // (either it lacks the full four bytes or has a nonzero `num_restarts`,
-            // despite having no restarts), or it's empty and there's no filter.
+            // despite having no restarts), or it's empty and there are no meta blocks.
             // It isn't mandatory for us to report every possible corruption error, so we can
-            // just stop here and say there's no filter.
-            return Ok(None);
+            // just stop here and say there's none of them.
+            return Ok((None, None, None));
         }
         let metaindex_block = self.read_table_block(
             BlockType::Metaindex,
@@ -669,7 +767,7 @@ where
 
         let mut metaindex_iter = MetaindexBlockIter::new(metaindex_block, table_size)
             .map_err(|(offset, block_err)| {
-                ReadTableBlockError::TableCorruption(CorruptedTableError::CorruptedBlock(
+                NewTableReaderError::TableCorruption(CorruptedTableError::CorruptedBlock(
                     BlockType::Metaindex,
                     metaindex_handle,
                     offset,
@@ -677,49 +775,315 @@ where
                 ))
             })?;
 
-        let filter_block_handle = metaindex_iter.get_filter_handle(policy)
-            .map_err(|metaindex_err| {
-                match metaindex_err {
-                    MetaindexIterError::Block(block_err) => ReadTableBlockError::TableCorruption(
-                        CorruptedTableError::CorruptedBlock(
-                            BlockType::Metaindex,
-                            metaindex_handle,
-                            metaindex_iter.current_entry_offset(),
-                            block_err,
-                        ),
-                    ),
-                    MetaindexIterError::Handle(handle_err) => ReadTableBlockError::TableCorruption(
-                        CorruptedTableError::CorruptedFilterBlockHandle(
-                            metaindex_iter.current_value_offset(),
-                            handle_err,
+        if !codec_names.is_empty() {
+            let codecs_handle = metaindex_iter.get_compression_codecs_handle()
+                .map_err(|metaindex_err| map_metaindex_err(
+                    metaindex_handle,
+                    &metaindex_iter,
+                    metaindex_err,
+                    CorruptedTableError::CorruptedCompressionCodecsHandle,
+                ))?;
+
+            if let Some(codecs_handle) = codecs_handle {
+                let codecs_block = self.read_table_block(
+                    BlockType::CompressionCodecs,
+                    codecs_handle,
+                    // Always verify compression codecs checksums.
+                    true,
+                    // Like the metaindex block, this block is small and only read once.
+                    &mut None,
+                )?;
+
+                let recorded_names = decode_codec_names(codecs_block.as_slice())
+                    .ok_or(NewTableReaderError::TableCorruption(
+                        CorruptedTableError::InvalidCompressionCodecs(codecs_handle),
+                    ))?;
+
+                check_codec_names(codec_names, &recorded_names)
+                    .map_err(NewTableReaderError::Options)?;
+            }
+        }
+
+        let filter = if let Some(policy) = policy {
+            metaindex_iter.get_filter_handle(policy)
+                .map_err(|metaindex_err| map_metaindex_err(
+                    metaindex_handle,
+                    &metaindex_iter,
+                    metaindex_err,
+                    CorruptedTableError::CorruptedFilterBlockHandle,
+                ))?
+                .map(|filter_block_handle| (policy, filter_block_handle))
+        } else {
+            None
+        };
+
+        let dictionary_handle = metaindex_iter.get_compression_dictionary_handle()
+            .map_err(|metaindex_err| map_metaindex_err(
+                metaindex_handle,
+                &metaindex_iter,
+                metaindex_err,
+                CorruptedTableError::CorruptedCompressionDictionaryHandle,
+            ))?;
+
+        let properties_handle = metaindex_iter.get_table_properties_handle()
+            .map_err(|metaindex_err| map_metaindex_err(
+                metaindex_handle,
+                &metaindex_iter,
+                metaindex_err,
+                CorruptedTableError::CorruptedTablePropertiesHandle,
+            ))?;
+
+        let filter_reader = if let Some((policy, filter_block_handle)) = filter {
+            let filter_block = self.read_table_block(
+                BlockType::Filter,
+                filter_block_handle,
+                // Always verify filter checksums.
+                true,
+                existing_buf,
+            )?;
+            let policy = policy.fast_mirrored_clone();
+
+            let filter_reader = FilterBlockReader::new(policy, filter_block, filter_block_handle)
+                .map_err(|filter_err| {
+                    NewTableReaderError::TableCorruption(
+                        CorruptedTableError::CorruptedFilterBlock(
+                            filter_block_handle,
+                            filter_err,
                         ),
-                    ),
-                }
-            })?;
+                    )
+                })?;
 
-        let Some(filter_block_handle) = filter_block_handle else {
-            return Ok(None);
+            Some(filter_reader)
+        } else {
+            None
         };
 
-        let filter_block = self.read_table_block(
-            BlockType::Filter,
-            filter_block_handle,
-            // Always verify filter checksums.
-            true,
-            existing_buf,
-        )?;
-        let policy = policy.fast_mirrored_clone();
+        let dictionary_block = if let Some(dictionary_handle) = dictionary_handle {
+            let dictionary_block = self.read_table_block(
+                BlockType::CompressionDictionary,
+                dictionary_handle,
+                // Always verify dictionary checksums.
+                true,
+                // Like the metaindex block, the dictionary is read once and then kept around.
+                &mut None,
+            )?;
 
-        let filter_reader = FilterBlockReader::new(policy, filter_block, filter_block_handle)
-            .map_err(|filter_err| {
-                ReadTableBlockError::TableCorruption(
-                    CorruptedTableError::CorruptedFilterBlock(
-                        filter_block_handle,
-                        filter_err,
-                    ),
-                )
-            })?;
+            if TableDictionary::parse(dictionary_block.as_slice()).is_none() {
+                return Err(NewTableReaderError::TableCorruption(
+                    CorruptedTableError::InvalidCompressionDictionary(dictionary_handle),
+                ));
+            }
+
+            Some(dictionary_block)
+        } else {
+            None
+        };
+
+        let properties = if let Some(properties_handle) = properties_handle {
+            let properties_block = self.read_table_block(
+                BlockType::TableProperties,
+                properties_handle,
+                // Always verify table properties checksums.
+                true,
+                &mut None,
+            )?;
+
+            let properties = TableProperties::decode_from(properties_block.as_slice())
+                .ok_or(NewTableReaderError::TableCorruption(
+                    CorruptedTableError::InvalidTableProperties(properties_handle),
+                ))?;
+
+            Some(properties)
+        } else {
+            None
+        };
+
+        Ok((filter_reader, dictionary_block, properties))
+    }
+}
+
+/// Map an error from looking up an entry of a metaindex block into a table corruption error.
+///
+/// `corrupted_handle` is used if the block handle in the looked-up entry was corrupted.
+fn map_metaindex_err<InvalidKey, Decompression>(
+    metaindex_handle: BlockHandle,
+    metaindex_iter:   &MetaindexBlockIter<'_>,
+    metaindex_err:    MetaindexIterError,
+    corrupted_handle: fn(
+        TableBlockOffset,
+        BlockHandleCorruption,
+    ) -> CorruptedTableError<InvalidKey, Decompression>,
+) -> NewTableReaderError<InvalidKey, Decompression> {
+    match metaindex_err {
+        MetaindexIterError::Block(block_err) => NewTableReaderError::TableCorruption(
+            CorruptedTableError::CorruptedBlock(
+                BlockType::Metaindex,
+                metaindex_handle,
+                metaindex_iter.current_entry_offset(),
+                block_err,
+            ),
+        ),
+        MetaindexIterError::Handle(handle_err) => NewTableReaderError::TableCorruption(
+            corrupted_handle(metaindex_iter.current_value_offset(), handle_err),
+        ),
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, clippy::unwrap_used, reason = "these are tests")]
+mod tests {
+    use std::num::NonZeroU32;
+    use std::io::{Read as _, Result as IoResult};
+
+    use crate::{
+        all_errors::types::OptionsError,
+        compression::encode_codec_names,
+        pub_traits::{cmp_and_policy::BloomPolicy, pool::BadPool},
+        pub_typed_bytes::{ShortSlice, TableBlockSize},
+        utils::mask_checksum,
+    };
+    use super::super::{block_builder::BlockBuilder, footer::COMPRESSION_CODECS_META_KEY};
+    use super::*;
+    use self::none_codecs::{NoneCodecs, none_codecs};
+
+
+    #[allow(clippy::absolute_paths, dead_code, unused_qualifications, reason = "test codecs")]
+    mod none_codecs {
+        crate::codec_list! {
+            codecs[(None, crate::db_options::NoCompressionCodec)];
+
+            pub(super) struct NoneCodecs;
+            pub(super) struct NoneEncoders;
+            pub(super) struct NoneDecoders;
+            #[derive(Debug)]
+            pub(super) enum NoneCompressionError;
+            #[derive(Debug)]
+            pub(super) enum NoneDecompressionError;
+        }
+
+        pub(super) const fn none_codecs() -> NoneCodecs {
+            NoneCodecs((), crate::db_options::NoCompressionCodec)
+        }
+    }
+
+
+    #[derive(Debug, Default)]
+    struct MemoryFile(Vec<u8>);
+
+    impl MemoryFile {
+        /// Append `block` and an uncompressed block trailer, returning the block's handle.
+        fn push_block(&mut self, block: &[u8]) -> BlockHandle {
+            let offset = FileOffset(u64::try_from(self.0.len()).unwrap());
+
+            let checksum = crc32c::crc32c_append(crc32c::crc32c(block), &[0]);
+            self.0.extend(block);
+            self.0.push(0);
+            self.0.extend(mask_checksum(checksum).to_le_bytes());
+
+            BlockHandle {
+                offset,
+                size: TableBlockSize(u64::try_from(block.len()).unwrap()),
+            }
+        }
+    }
+
+    impl RandomAccess for MemoryFile {
+        fn read_at(&self, offset: u64, buf: &mut [u8]) -> IoResult<usize> {
+            let mut contents = self.0.get(usize::try_from(offset).unwrap()..).unwrap_or_default();
+            contents.read(buf)
+        }
+    }
+
+    fn id(compressor_id: u8) -> CompressorId {
+        CompressorId(NonZeroU8::new(compressor_id).unwrap())
+    }
+
+    /// A table file containing only a compression codecs block which records the given names,
+    /// and a metaindex block referring to it. Returns the file and the metaindex block's handle.
+    fn table_with_codec_names(names: &[(CompressorId, &[u8])]) -> (MemoryFile, BlockHandle) {
+        let mut file = MemoryFile::default();
+
+        let mut codecs_block = Vec::new();
+        encode_codec_names(
+            &mut codecs_block,
+            names.iter().map(|&(compressor_id, name)| {
+                (compressor_id, ShortSlice::new(name).unwrap())
+            }),
+        );
+        let codecs_handle = file.push_block(&codecs_block);
+
+        let mut encoded_handle = [0_u8; BlockHandle::MAX_ENCODED_LENGTH];
+        let mut metaindex_block = BlockBuilder::new(NonZeroU32::new(16).unwrap());
+        metaindex_block.add_first_entry(
+            ShortSlice::new(COMPRESSION_CODECS_META_KEY).unwrap(),
+            codecs_handle.encode_short(&mut encoded_handle),
+        );
+        let metaindex_handle = file.push_block(metaindex_block.finish_block_contents());
+
+        (file, metaindex_handle)
+    }
+
+    fn read_meta_blocks(
+        file:             &MemoryFile,
+        metaindex_handle: BlockHandle,
+        codec_names:      &[(CompressorId, &'static str)],
+    ) -> Result<(), NewTableReaderError<(), <NoneCodecs as CompressionCodecs>::DecompressionError>>
+    {
+        let file_size = FileSize(u64::try_from(file.0.len()).unwrap());
+        let mut decoders = none_codecs().init_decoders();
+        let buffer_pool = BadPool::new();
+
+        let mut block_reader: TableBlockReader<'_, MemoryFile, NoneCodecs, BadPool> =
+            TableBlockReader {
+                file,
+                file_size,
+                decoders:    &mut decoders,
+                buffer_pool: &buffer_pool,
+                dictionary:  None,
+            };
+
+        block_reader
+            .read_meta_blocks::<(), BloomPolicy>(
+                None,
+                codec_names,
+                metaindex_handle,
+                file_size,
+                true,
+                &mut None,
+            )
+            .map(|_meta_blocks| ())
+    }
+
+    #[test]
+    fn matching_table_codec_names() {
+        let (file, metaindex_handle) = table_with_codec_names(&[(id(1), b"snappy")]);
+
+        assert!(read_meta_blocks(&file, metaindex_handle, &[(id(1), "snappy")]).is_ok());
+        // Codecs which do not report names are not checked.
+        assert!(read_meta_blocks(&file, metaindex_handle, &[]).is_ok());
+    }
+
+    #[test]
+    fn mismatched_table_codec_names() {
+        let (file, metaindex_handle) = table_with_codec_names(&[(id(1), b"snappy")]);
+
+        let renamed = read_meta_blocks(&file, metaindex_handle, &[(id(1), "lz4")]);
+        assert!(matches!(
+            renamed,
+            Err(NewTableReaderError::Options(OptionsError::MismatchedCompressionCodec {
+                chosen: Some("lz4"),
+                ..
+            })),
+        ));
 
-        Ok(Some(filter_reader))
+        let unsupported = read_meta_blocks(&file, metaindex_handle, &[(id(2), "zlib")]);
+        assert!(matches!(
+            unsupported,
+            Err(NewTableReaderError::Options(OptionsError::MismatchedCompressionCodec {
+                chosen: None,
+                ..
+            })),
+        ));
     }
 }
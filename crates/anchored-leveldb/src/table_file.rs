@@ -7,7 +7,7 @@ use anchored_vfs::{CreateParentDir, LevelDBFilesystem, SyncParentDir, WritableFi
 
 use crate::{
     database_files::LevelDBFileName,
-    file_tracking::FileMetadata,
+    file_tracking::{FileMetadata, TableProperties},
     memtable::MemtableIter,
     table_caches::TableCacheKey,
 };
@@ -27,20 +27,42 @@ use crate::{
         compression::CompressionCodecs,
         pool::BufferPool,
     },
-    pub_typed_bytes::{FileNumber, FileSize, NonZeroLevel},
+    pub_typed_bytes::{EntryType, FileNumber, FileSize, NonZeroLevel, SequenceNumber},
     sstable::{TableBuilder, TableReader},
     typed_bytes::{EncodedInternalEntry, EncodedInternalKey, InternalKey, MaybeUserValue},
 };
 
 
 pub(crate) struct TableFileBuilder<File, Policy, Pool: BufferPool> {
-    builder:     TableBuilder<File, Policy, Pool>,
+    builder:       TableBuilder<File, Policy, Pool>,
     /// Value is irrelevant if `builder` is inactive.
-    file_number: FileNumber,
+    file_number:   FileNumber,
     /// Value is irrelevant if `builder` is inactive.
     ///
     /// `None` denotes that this table is being produces from a memtable.
-    level:       Option<NonZeroLevel>,
+    level:         Option<NonZeroLevel>,
+    /// The number of deletion entries added to the current table.
+    ///
+    /// Value is irrelevant if `builder` is inactive.
+    num_deletions: u64,
+    /// The smallest sequence number of any entry added to the current table.
+    ///
+    /// Value is irrelevant if `builder` is inactive.
+    smallest_seq:  SequenceNumber,
+    /// The largest sequence number of any entry added to the current table.
+    ///
+    /// Value is irrelevant if `builder` is inactive.
+    largest_seq:   SequenceNumber,
+    /// The number of bytes written to the current table which have been charged to the
+    /// compaction rate limiter.
+    ///
+    /// Value is irrelevant if `builder` is inactive.
+    bytes_charged: u64,
+    /// The number of bytes written to the current table which have been incrementally synced,
+    /// as per [`InternalOptions::sstable_bytes_per_sync`].
+    ///
+    /// Value is irrelevant if `builder` is inactive.
+    bytes_synced:  u64,
 }
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
@@ -64,9 +86,14 @@ where
         Policy: FastMirroredClone,
     {
         Self {
-            file_number:  FileNumber(0),
-            level:        None,
-            builder:      TableBuilder::new(opts),
+            file_number:   FileNumber(0),
+            level:         None,
+            builder:       TableBuilder::new(opts),
+            num_deletions: 0,
+            smallest_seq:  SequenceNumber::MAX_SEQUENCE_NUMBER,
+            largest_seq:   SequenceNumber::ZERO,
+            bytes_charged: 0,
+            bytes_synced:  0,
         }
     }
 
@@ -118,6 +145,11 @@ where
 
         self.file_number = file_number;
         self.level = level;
+        self.num_deletions = 0;
+        self.smallest_seq = SequenceNumber::MAX_SEQUENCE_NUMBER;
+        self.largest_seq = SequenceNumber::ZERO;
+        self.bytes_charged = 0;
+        self.bytes_synced = 0;
         self.builder.start(&mut_opts.dynamic.read(), table_file, level);
         Ok(())
     }
@@ -205,7 +237,9 @@ where
     /// or an invalid `Table` file may be produced by this builder.
     ///
     /// # Errors
-    /// On error, the current table file is abandoned and deleted.
+    /// On [`AddTableEntryError::AddEntryError`], the entry was not added, and the current table
+    /// should be finished with [`TableFileBuilder::finish`]. On any other error, the current
+    /// table file is abandoned and deleted.
     ///
     /// # Panics
     /// Panics if the builder is not currently [active].
@@ -228,16 +262,47 @@ where
         Codecs:     CompressionCodecs,
         Policy::Eq: CoarserThan<Cmp::Eq>,
     {
+        let key_tag = key.as_internal_key().1;
+        let is_deletion = matches!(key_tag.entry_type(), EntryType::Deletion);
+
         self.builder.add_entry(opts, mut_opts, encoders, key, value)
             .map_err(|add_entry_err| {
-                self.delete_table_file(opts, mut_opts);
                 match add_entry_err {
+                    // The table is still valid, and the caller should finish it.
                     AddTableEntryError::AddEntryError    => AddTableEntryError::AddEntryError,
-                    AddTableEntryError::Write(write_err) => AddTableEntryError::Write(
-                        write_err.into_rw_error(self.level, self.file_number),
-                    ),
+                    AddTableEntryError::Write(write_err) => {
+                        self.delete_table_file(opts, mut_opts);
+                        AddTableEntryError::Write(
+                            write_err.into_rw_error(self.level, self.file_number),
+                        )
+                    }
                 }
-            })
+            })?;
+
+        if is_deletion {
+            self.num_deletions = self.num_deletions.saturating_add(1);
+        }
+        self.smallest_seq = self.smallest_seq.min(key_tag.sequence_number());
+        self.largest_seq = self.largest_seq.max(key_tag.sequence_number());
+        self.charge_rate_limiter(mut_opts, self.builder.bytes_written());
+
+        if let Some(bytes_per_sync) = opts.sstable_bytes_per_sync {
+            let bytes_written = self.builder.bytes_written();
+            let unsynced = bytes_written.saturating_sub(self.bytes_synced);
+
+            if unsynced >= bytes_per_sync.get() {
+                self.builder.sync_data_range(self.bytes_synced, unsynced)
+                    .map_err(|write_err| {
+                        self.delete_table_file(opts, mut_opts);
+                        AddTableEntryError::Write(
+                            write_err.into_rw_error(self.level, self.file_number),
+                        )
+                    })?;
+                self.bytes_synced = bytes_written;
+            }
+        }
+
+        Ok(())
     }
 
     /// Finish writing the entire table to the table file and sync the file to persistent storage.
@@ -270,11 +335,20 @@ where
         Codecs:     CompressionCodecs,
         Policy::Eq: CoarserThan<Cmp::Eq>,
     {
-        let file_size = self.builder.finish(opts, mut_opts, encoders)
+        let properties = TableProperties {
+            num_entries:   u64::try_from(self.builder.num_entries()).unwrap_or(u64::MAX),
+            num_deletions: self.num_deletions,
+            smallest_seq:  self.smallest_seq,
+            largest_seq:   self.largest_seq,
+            creation_time: TableProperties::current_time(),
+        };
+
+        let file_size = self.builder.finish(opts, mut_opts, encoders, properties)
             .map_err(|write_err| {
                 self.delete_table_file(opts, mut_opts);
                 write_err.into_rw_error(self.level, self.file_number)
             })?;
+        self.charge_rate_limiter(mut_opts, file_size.0);
 
         // Confirm that the produced table is actually usable
         let read_opts = InternalReadOptions {
@@ -302,6 +376,7 @@ where
             file_size,
             smallest_key,
             largest_key,
+            Some(properties),
             opts.compaction.seek_compactions,
         ))
     }
@@ -394,6 +469,22 @@ where
         // TODO: would be good to log the error.
         let _: Result<_, _> = self.deactivate(opts, mut_opts);
     }
+
+    /// Charge the compaction rate limiter for any bytes written to the current table since it
+    /// was last charged, possibly blocking for some time.
+    ///
+    /// `bytes_written` should be the total number of bytes written to the current table.
+    fn charge_rate_limiter<FS: LevelDBFilesystem>(
+        &mut self,
+        mut_opts:      &InternallyMutableOptions<FS, Policy, Pool>,
+        bytes_written: u64,
+    ) {
+        let uncharged = bytes_written.saturating_sub(self.bytes_charged);
+        if uncharged > 0 {
+            mut_opts.rate_limiter.request(uncharged);
+            self.bytes_charged = bytes_written;
+        }
+    }
 }
 
 impl<File, Policy, Pool> Debug for TableFileBuilder<File, Policy, Pool>
@@ -404,22 +495,31 @@ where
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         f.debug_struct("TableFileBuilder")
-            .field("builder",     &self.builder)
-            .field("file_number", &self.file_number)
-            .field("level",       &self.level)
+            .field("builder",       &self.builder)
+            .field("file_number",   &self.file_number)
+            .field("level",         &self.level)
+            .field("num_deletions", &self.num_deletions)
+            .field("smallest_seq",  &self.smallest_seq)
+            .field("largest_seq",   &self.largest_seq)
+            .field("bytes_charged", &self.bytes_charged)
+            .field("bytes_synced",  &self.bytes_synced)
             .finish()
     }
 }
 
+/// Open the table file described by `table_file`, through the table cache if `read_opts`
+/// permits it.
+///
+/// The [`TableProperties`] of table files which already existed when the database was opened
+/// are not known until the table file is read, so they are recorded in `table_file` here.
 #[expect(clippy::type_complexity, reason = "the result is still fairly readable")]
 pub(crate) fn read_sstable<FS, Cmp, Policy, Codecs, Pool>(
-    opts:                &InternalOptions<Cmp, Policy, Codecs>,
-    mut_opts:            &InternallyMutableOptions<FS, Policy, Pool>,
-    read_opts:           InternalReadOptions,
-    decoders:            &mut Codecs::Decoders,
-    manifest_number:     FileNumber,
-    sstable_file_number: FileNumber,
-    sstable_file_size:   FileSize,
+    opts:            &InternalOptions<Cmp, Policy, Codecs>,
+    mut_opts:        &InternallyMutableOptions<FS, Policy, Pool>,
+    read_opts:       InternalReadOptions,
+    decoders:        &mut Codecs::Decoders,
+    manifest_number: FileNumber,
+    table_file:      &FileMetadata,
 ) -> Result<
     Arc<TableReader<FS::RandomAccessFile, Policy, Pool>>,
     RwErrorKindAlias<FS, Cmp, Codecs>,
@@ -431,10 +531,10 @@ where
     Codecs: CompressionCodecs,
     Pool:   BufferPool,
 {
-    let table_key = TableCacheKey { table_number: sstable_file_number };
+    let table_key = TableCacheKey { table_number: table_file.file_number() };
 
     let mut read_table = || {
-        let file_number = sstable_file_number;
+        let file_number = table_file.file_number();
         let table_path = LevelDBFileName::Table { file_number }
             .file_path(&opts.db_directory);
 
@@ -458,7 +558,7 @@ where
         let table = TableReader::new(
             sstable_file,
             file_number,
-            sstable_file_size,
+            table_file.file_size(),
             opts,
             mut_opts,
             read_opts,
@@ -474,6 +574,8 @@ where
                         manifest_number,
                         CorruptedManifestError::FileSizeTooSmall(file_number),
                     )),
+                NewTableReaderError::Options(options_err)
+                    => RwErrorKind::Options(options_err),
                 NewTableReaderError::TableCorruption(corruption)
                     => RwErrorKind::Corruption(CorruptionError::CorruptedTable(
                         file_number,
@@ -490,15 +592,23 @@ where
         Ok(Arc::new(table))
     };
 
-    match read_opts.table_cache_usage {
-        CacheUsage::ReadAndFill => mut_opts.table_cache.get_or_insert_with(table_key, read_table),
+    let table = match read_opts.table_cache_usage {
+        CacheUsage::ReadAndFill => mut_opts.table_cache.get_or_insert_with(table_key, read_table)?,
         CacheUsage::Read => {
             if let Some(cached_table) = mut_opts.table_cache.get(table_key) {
-                Ok(cached_table)
+                cached_table
             } else {
-                read_table()
+                read_table()?
             }
         }
-        CacheUsage::Ignore => read_table(),
+        CacheUsage::Ignore => read_table()?,
+    };
+
+    if table_file.properties().is_none() {
+        if let Some(properties) = table.properties() {
+            table_file.set_properties(properties);
+        }
     }
+
+    Ok(table)
 }
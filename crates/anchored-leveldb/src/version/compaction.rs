@@ -1,20 +1,21 @@
 #![expect(clippy::indexing_slicing, reason = "TODO: justify each case.")]
 
-use std::{ops::Range, sync::Arc};
+use std::{collections::HashSet, ops::Range, sync::Arc};
 use std::slice::Iter as SliceIter;
 
 use anchored_skiplist::Comparator as _;
 
 use crate::{
+    all_errors::types::CompactFilesError,
     options::InternalOptions,
-    pub_traits::cmp_and_policy::LevelDBComparator,
+    pub_traits::{cmp_and_policy::LevelDBComparator, partitioner::CompactionPartitioner},
     table_format::InternalComparator,
     typed_bytes::InternalKey,
 };
 use crate::{
     file_tracking::{FileMetadata, StartSeekCompaction},
     pub_typed_bytes::{
-        IndexMiddleLevel as _, IndexNonZeroLevel as _, Level, MiddleLevel, NonZeroLevel,
+        FileNumber, IndexMiddleLevel as _, IndexNonZeroLevel as _, Level, NonZeroLevel,
     },
 };
 use super::{
@@ -41,15 +42,89 @@ impl CompactionInputsCow<'_> {
             Self::Borrowed(this) => this.len(),
         }
     }
+
+    #[must_use]
+    pub fn total_file_size(&self) -> u64 {
+        match self {
+            Self::Owned(this)    => FileMetadata::total_file_size_ref(this),
+            Self::Borrowed(this) => FileMetadata::total_file_size(this),
+        }
+    }
+}
+
+/// A [`CompactionStyle::Tiered`] compaction, which merges files in `base_level`, together with
+/// the overlapping files of every later level up to `parent_level`, into `parent_level`, up to
+/// the `max_compaction_inputs` limit of `parent_level`.
+///
+/// Every level strictly between `base_level` and `parent_level` is empty, unless the
+/// compaction was chosen because the space amplification limit was exceeded, in which case
+/// those levels are sorted runs which are merged as well.
+///
+/// [`CompactionStyle::Tiered`]: crate::options::pub_options::CompactionStyle::Tiered
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TieredCompaction {
+    pub base_level:   Level,
+    pub parent_level: NonZeroLevel,
+}
+
+/// The inputs of a [`TieredCompaction`], as chosen by `StartCompaction::get_tiered_inputs`.
+#[derive(Debug)]
+struct TieredInputs<'a> {
+    base_inputs:   CompactionInputsCow<'a>,
+    middle_inputs: Vec<(NonZeroLevel, &'a [Arc<FileMetadata>])>,
+    parent_inputs: &'a [Arc<FileMetadata>],
+    /// The last key of the base inputs.
+    base_last:     InternalKey<'a>,
+    /// The smallest key among all inputs.
+    smallest_key:  InternalKey<'a>,
+    /// The largest key among all inputs.
+    largest_key:   InternalKey<'a>,
+}
+
+impl TieredInputs<'_> {
+    #[must_use]
+    fn total_file_size(&self) -> u64 {
+        let middle_size = self.middle_inputs
+            .iter()
+            .map(|&(_, inputs)| FileMetadata::total_file_size(inputs))
+            .fold(0, u64::saturating_add);
+
+        self.base_inputs.total_file_size()
+            .saturating_add(middle_size)
+            .saturating_add(FileMetadata::total_file_size(self.parent_inputs))
+    }
+}
+
+/// A size compaction, which compacts a file in `base_level` (together with overlapping files)
+/// into `parent_level`.
+///
+/// Usually, `base_level` is `parent_level.prev_level()`; however, if
+/// [`SizeCompactionOptions::dynamic_level_sizes`] is enabled, level-0 files may be compacted
+/// directly into a higher level, in which case every level strictly between them is empty.
+///
+/// [`SizeCompactionOptions::dynamic_level_sizes`]:
+/// crate::options::pub_options::SizeCompactionOptions::dynamic_level_sizes
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SizeCompaction {
+    pub base_level:   Level,
+    pub parent_level: NonZeroLevel,
 }
 
 #[derive(Debug)]
 pub(crate) struct StartCompaction<'a> {
+    /// Usually `parent_level.prev_level()`, but tiered compactions and some level-0 size
+    /// compactions may skip past empty levels (or, for tiered compactions, past the levels
+    /// of `middle_inputs`).
+    pub base_level:              Level,
     pub parent_level:            NonZeroLevel,
     // max_output_size:         FileSize,
     // input_version:           &'a Arc<Version>,
     // edit:                    VersionEdit,
     pub base_inputs:             CompactionInputsCow<'a>,
+    /// The inputs from each nonempty level strictly between `base_level` and `parent_level`.
+    ///
+    /// Only tiered compactions which merge several sorted runs have middle inputs.
+    pub middle_inputs:           Vec<(NonZeroLevel, &'a [Arc<FileMetadata>])>,
     pub parent_inputs:           &'a [Arc<FileMetadata>],
     pub grandparents:            &'a [Arc<FileMetadata>],
     // ancestor_iters:          [DisjointLevelFileIter; NUM_MIDDLE_LEVELS_USIZE.get()],
@@ -143,6 +218,7 @@ impl<'a> StartCompaction<'a> {
                     version_set,
                     version_edit,
                     version,
+                    parent_level,
                     base_inputs,
                     true,
                 ))
@@ -155,9 +231,9 @@ impl<'a> StartCompaction<'a> {
         version_set:  &mut VersionSet<File>,
         version_edit: &mut VersionEdit,
         version:      &'a Version,
-        parent_level: NonZeroLevel,
+        size:         SizeCompaction,
     ) -> Option<Self> {
-        let base_level = parent_level.prev_level();
+        let base_level = size.base_level;
         let base_files = version.level_files(base_level);
 
         // For below code to be correct, we need to confirm that index `0` is in-bounds.
@@ -193,7 +269,8 @@ impl<'a> StartCompaction<'a> {
             version_set,
             version_edit,
             version,
-            parent_level,
+            base_level,
+            size.parent_level,
             base_input_index,
         ))
     }
@@ -210,27 +287,382 @@ impl<'a> StartCompaction<'a> {
             version_set,
             version_edit,
             version,
+            seek_compaction.level.prev_level(),
             seek_compaction.level,
             seek_compaction.file,
         )
     }
 
+    /// Set up a compaction of files in `tiered.base_level` together with the overlapping files
+    /// in every later level up to `tiered.parent_level`.
+    ///
+    /// Base inputs are chosen in key order, starting from the first file of the base level,
+    /// for as long as the total size of the inputs stays within the `max_compaction_inputs`
+    /// limit of the parent level. At least one base file (together with any level-0 files it
+    /// transitively overlaps) is always chosen.
+    ///
+    /// Compaction pointers are neither used nor updated.
+    ///
+    /// Returns `None` if the base level is empty.
+    pub fn new_tiered_compaction<Cmp: LevelDBComparator, Policy, Codecs>(
+        opts:    &InternalOptions<Cmp, Policy, Codecs>,
+        version: &'a Version,
+        tiered:  TieredCompaction,
+    ) -> Option<Self> {
+        let base_files = version.level_files(tiered.base_level).inner();
+        let first_file = base_files.first()?;
+
+        let max_input_size = *opts.compaction
+            .max_compaction_inputs
+            .infallible_index_nonzero(tiered.parent_level);
+
+        let mut inputs = Self::get_tiered_inputs(opts, version, tiered, first_file, first_file);
+
+        // The files are sorted by their smallest keys, so each file whose largest key is past
+        // the chosen range extends the range. (A file whose largest key is not past the range
+        // has already been chosen.)
+        for file in base_files {
+            if opts.cmp.cmp(file.largest_key(), inputs.base_last).is_le() {
+                continue;
+            }
+
+            let expanded = Self::get_tiered_inputs(opts, version, tiered, first_file, file);
+            if expanded.total_file_size() > max_input_size {
+                break;
+            }
+
+            inputs = expanded;
+        }
+
+        let (
+            grandparents,
+            max_grandparent_overlap,
+        ) = if let Some(parent_level) = tiered.parent_level.try_as_middle_level() {
+            let max_grandparent_overlap = *opts.compaction
+                .max_grandparent_overlap
+                .infallible_index_middle(parent_level);
+
+            let grandparent_files = version.level_files(parent_level.next_level().as_level());
+            let grandparents = grandparent_files.get_overlapping_files_disjoint(
+                &opts.cmp,
+                Some(inputs.smallest_key),
+                Some(inputs.largest_key),
+            );
+            (&grandparent_files.inner()[grandparents], max_grandparent_overlap)
+        } else {
+            ([].as_slice(), u64::MAX)
+        };
+
+        Some(Self {
+            base_level:    tiered.base_level,
+            parent_level:  tiered.parent_level,
+            base_inputs:   inputs.base_inputs,
+            middle_inputs: inputs.middle_inputs,
+            parent_inputs: inputs.parent_inputs,
+            grandparents,
+            max_grandparent_overlap,
+            smallest_key:  inputs.smallest_key,
+            largest_key:   inputs.largest_key,
+            is_manual:     false,
+        })
+    }
+
+    /// Get the inputs of a tiered compaction whose base inputs cover the key range from the
+    /// smallest key of `first_file` to the largest key of `last_file`.
+    ///
+    /// Level-0 base inputs are expanded to every level-0 file which they transitively overlap,
+    /// so that no newer level-0 file is moved below an older, overlapping level-0 file. Nonzero
+    /// base inputs include boundary inputs.
+    ///
+    /// Each later level up to the parent level contributes every file (including boundary
+    /// inputs) which overlaps the inputs of the levels before it, so that no entry is moved below
+    /// an older entry which is not compacted.
+    ///
+    /// # Correctness
+    /// Requires that `first_file` and `last_file` are files in `tiered.base_level` of `version`,
+    /// and that `first_file` is the first file of that level.
+    fn get_tiered_inputs<Cmp: LevelDBComparator, Policy, Codecs>(
+        opts:       &InternalOptions<Cmp, Policy, Codecs>,
+        version:    &'a Version,
+        tiered:     TieredCompaction,
+        first_file: &'a FileMetadata,
+        last_file:  &'a FileMetadata,
+    ) -> TieredInputs<'a> {
+        let base_files = version.level_files(tiered.base_level);
+
+        let (base_inputs, base_first, base_last) = if tiered.base_level == Level::ZERO {
+            let mut base_inputs = Vec::new();
+            base_files.get_overlapping_files(
+                &opts.cmp,
+                Some(first_file.smallest_user_key()),
+                Some(last_file.largest_user_key()),
+                &mut base_inputs,
+            );
+
+            // `first_file` itself overlaps the range, so `base_inputs` is nonempty.
+            let (base_first, base_last) = Self::get_zero_key_range(&opts.cmp, &base_inputs);
+
+            (CompactionInputsCow::Owned(base_inputs), base_first, base_last)
+        } else {
+            // The files of a nonzero level are sorted and disjoint.
+            let base_inputs = base_files.get_overlapping_inputs_disjoint(
+                &opts.cmp,
+                first_file.smallest_key(),
+                last_file.largest_key(),
+            );
+
+            let (base_first, base_last) = match (base_inputs.first(), base_inputs.last()) {
+                (Some(first), Some(last)) => (first.smallest_key(), last.largest_key()),
+                // `first_file` itself overlaps the range, so this is unreachable; fall back to
+                // the requested range regardless.
+                _ => (first_file.smallest_key(), last_file.largest_key()),
+            };
+
+            (CompactionInputsCow::Borrowed(base_inputs), base_first, base_last)
+        };
+
+        let mut smallest_key = base_first;
+        let mut largest_key = base_last;
+        let mut middle_inputs = Vec::new();
+        let mut next_level = tiered.base_level.next_level();
+
+        while let Some(level) = next_level {
+            let inputs = version
+                .level_files(level.as_level())
+                .get_overlapping_inputs_disjoint(&opts.cmp, smallest_key, largest_key);
+
+            if let ([first, ..], [.., last]) = (inputs, inputs) {
+                if opts.cmp.cmp(first.smallest_key(), smallest_key).is_lt() {
+                    smallest_key = first.smallest_key();
+                }
+                if opts.cmp.cmp(largest_key, last.largest_key()).is_lt() {
+                    largest_key = last.largest_key();
+                }
+            }
+
+            if level == tiered.parent_level {
+                return TieredInputs {
+                    base_inputs,
+                    middle_inputs,
+                    parent_inputs: inputs,
+                    base_last,
+                    smallest_key,
+                    largest_key,
+                };
+            }
+
+            if !inputs.is_empty() {
+                middle_inputs.push((level, inputs));
+            }
+            next_level = level.as_level().next_level();
+        }
+
+        // `tiered.parent_level` is after `tiered.base_level`, so this is unreachable; fall back
+        // to a compaction without parent inputs regardless.
+        TieredInputs {
+            base_inputs,
+            middle_inputs,
+            parent_inputs: &[],
+            base_last,
+            smallest_key,
+            largest_key,
+        }
+    }
+
+    /// Set up a compaction of exactly the indicated files, together with any overlapping files in
+    /// `output_level`, into `output_level`.
+    ///
+    /// Input files in `output_level` are treated as parent inputs, and every other input file
+    /// must be in the same level. Compaction pointers are neither used nor updated.
+    ///
+    /// # Errors
+    /// Returns an error if some input file is not live, or if the compaction could not be
+    /// performed without including files which were not provided as inputs.
+    pub fn new_files_compaction<Cmp: LevelDBComparator, Policy, Codecs>(
+        opts:         &InternalOptions<Cmp, Policy, Codecs>,
+        version:      &'a Version,
+        file_numbers: &[FileNumber],
+        output_level: NonZeroLevel,
+    ) -> Result<Self, CompactFilesError> {
+        let selected = file_numbers.iter().copied().collect::<HashSet<_>>();
+        if selected.is_empty() {
+            return Err(CompactFilesError::NoInputFiles);
+        }
+
+        // The inputs are sorted by level, since `Level::ALL_LEVELS` is in increasing order.
+        let mut inputs = Vec::with_capacity(selected.len());
+        for level in Level::ALL_LEVELS {
+            inputs.extend(
+                version.level_files(level).inner().iter()
+                    .filter(|file| selected.contains(&file.file_number()))
+                    .map(|file| (level, file)),
+            );
+        }
+
+        if let Some(&missing) = file_numbers.iter().find(|&&file_number| {
+            !inputs.iter().any(|(_, file)| file.file_number() == file_number)
+        }) {
+            return Err(CompactFilesError::FileNotLive(missing));
+        }
+
+        let output = output_level.as_level();
+        if inputs.iter().any(|&(level, _)| level > output) {
+            return Err(CompactFilesError::OutputLevelTooLow(output_level));
+        }
+        let Some(&(base_level, first_input)) = inputs.iter().find(|&&(level, _)| level != output)
+        else {
+            return Err(CompactFilesError::OutputLevelTooLow(output_level));
+        };
+        if inputs.iter().any(|&(level, _)| level != base_level && level != output) {
+            return Err(CompactFilesError::InputsInMultipleLevels);
+        }
+
+        let base_inputs = inputs.iter()
+            .filter(|&&(level, _)| level == base_level)
+            .map(|&(_, file)| file)
+            .collect::<Vec<_>>();
+
+        let mut base_first = first_input.smallest_key();
+        let mut base_last = first_input.largest_key();
+
+        for file in &base_inputs {
+            if opts.cmp.cmp(file.smallest_key(), base_first).is_lt() {
+                base_first = file.smallest_key();
+            }
+            if opts.cmp.cmp(base_last, file.largest_key()).is_lt() {
+                base_last = file.largest_key();
+            }
+        }
+
+        let base_files = version.level_files(base_level);
+        if base_level == Level::ZERO {
+            // Every level-0 file overlapping the inputs must be moved down along with them.
+            let mut overlapping = Vec::new();
+            base_files.get_overlapping_files(
+                &opts.cmp,
+                Some(base_first.0),
+                Some(base_last.0),
+                &mut overlapping,
+            );
+
+            if let Some(other) = overlapping.iter()
+                .find(|file| !selected.contains(&file.file_number()))
+            {
+                return Err(CompactFilesError::OverlapsOtherFile(other.file_number()));
+            }
+        } else {
+            // Entries for a user key must not be split between levels such that older entries
+            // stay in a higher level; see `add_boundary_inputs_disjoint`.
+            for (index, file) in base_files.inner().iter().enumerate() {
+                if selected.contains(&file.file_number()) {
+                    let end_index = base_files.add_boundary_inputs_disjoint(&opts.cmp, index + 1);
+
+                    if let Some(other) = base_files.inner()[index + 1..end_index].iter()
+                        .find(|other| !selected.contains(&other.file_number()))
+                    {
+                        return Err(CompactFilesError::OverlapsOtherFile(other.file_number()));
+                    }
+                }
+            }
+        }
+
+        // The compacted entries must not be moved beneath older entries of intermediate levels.
+        if let Some(first_middle_level) = base_level.next_level() {
+            for level in first_middle_level.inclusive_range(output_level) {
+                if level == output_level {
+                    break;
+                }
+
+                let level_files = version.level_files(level.as_level());
+                let overlapping = level_files.get_overlapping_files_disjoint(
+                    &opts.cmp,
+                    Some(base_first),
+                    Some(base_last),
+                );
+
+                if let Some(other) = level_files.inner()[overlapping].first() {
+                    return Err(CompactFilesError::OverlapsOtherFile(other.file_number()));
+                }
+            }
+        }
+
+        let mut compaction_smallest = base_first;
+        let mut compaction_largest = base_last;
+
+        for &(_, file) in inputs.iter().filter(|&&(level, _)| level == output) {
+            if opts.cmp.cmp(file.smallest_key(), compaction_smallest).is_lt() {
+                compaction_smallest = file.smallest_key();
+            }
+            if opts.cmp.cmp(compaction_largest, file.largest_key()).is_lt() {
+                compaction_largest = file.largest_key();
+            }
+        }
+
+        let parent_inputs = version.level_files(output).get_overlapping_inputs_disjoint(
+            &opts.cmp,
+            compaction_smallest,
+            compaction_largest,
+        );
+
+        if let ([parent_first, ..], [.., parent_last]) = (parent_inputs, parent_inputs) {
+            if opts.cmp.cmp(parent_first.smallest_key(), compaction_smallest).is_lt() {
+                compaction_smallest = parent_first.smallest_key();
+            }
+            if opts.cmp.cmp(compaction_largest, parent_last.largest_key()).is_lt() {
+                compaction_largest = parent_last.largest_key();
+            }
+        }
+
+        let (
+            grandparents,
+            max_grandparent_overlap,
+        ) = if let Some(parent_level) = output_level.try_as_middle_level() {
+            let max_grandparent_overlap = *opts.compaction
+                .max_grandparent_overlap
+                .infallible_index_middle(parent_level);
+
+            let grandparent_files = version.level_files(parent_level.next_level().as_level());
+            let grandparents = grandparent_files.get_overlapping_files_disjoint(
+                &opts.cmp,
+                Some(compaction_smallest),
+                Some(compaction_largest),
+            );
+            (&grandparent_files.inner()[grandparents], max_grandparent_overlap)
+        } else {
+            ([].as_slice(), u64::MAX)
+        };
+
+        Ok(Self {
+            base_level,
+            parent_level:  output_level,
+            base_inputs:   CompactionInputsCow::Owned(base_inputs),
+            middle_inputs: Vec::new(),
+            parent_inputs,
+            grandparents,
+            max_grandparent_overlap,
+            smallest_key:  compaction_smallest,
+            largest_key:   compaction_largest,
+            is_manual:     true,
+        })
+    }
+
     /// Finish setting up a compaction beginning with a single base input file.
     ///
     /// Used only for size and seek compactions, not manual compactions.
     ///
     /// # Correctness
     /// It is required that `base_input_index` is an in-bounds index of a file on level
-    /// `parent_level.prev_level()`.
+    /// `base_level`. If `base_level` is nonzero, it must be `parent_level.prev_level()`;
+    /// otherwise, every level strictly between `base_level` and `parent_level` must be empty.
     fn new_single_compaction<File, Cmp: LevelDBComparator, Policy, Codecs>(
         opts:             &InternalOptions<Cmp, Policy, Codecs>,
         version_set:      &mut VersionSet<File>,
         version_edit:     &mut VersionEdit,
         version:          &'a Version,
+        base_level:       Level,
         parent_level:     NonZeroLevel,
         base_input_index: usize,
     ) -> Self {
-        let base_level = parent_level.prev_level();
         let base_files = version.level_files(base_level);
 
         if let Some(base_level) = base_level.try_as_nonzero_level() {
@@ -266,13 +698,14 @@ impl<'a> StartCompaction<'a> {
                 version_set,
                 version_edit,
                 version,
+                parent_level,
                 base_inputs,
                 false,
             )
         }
     }
 
-    /// Finish setting up a compaction from level `0` to level `1`, given a `Vec` of initial
+    /// Finish setting up a compaction from level `0` to `parent_level`, given a `Vec` of initial
     /// inputs.
     ///
     /// The user key ranges of the provided level-0 inputs must be disjoint from the user key
@@ -280,12 +713,14 @@ impl<'a> StartCompaction<'a> {
     /// entirely compacted or entirely untouched.
     ///
     /// # Correctness
-    /// It is required that `base_inputs` is a nonempty `Vec` of level-0 file metadata.
+    /// It is required that `base_inputs` is a nonempty `Vec` of level-0 file metadata, and
+    /// that every level strictly between level `0` and `parent_level` is empty.
     fn new_zero_compaction<File, Cmp: LevelDBComparator, Policy, Codecs>(
         opts:         &InternalOptions<Cmp, Policy, Codecs>,
         version_set:  &mut VersionSet<File>,
         version_edit: &mut VersionEdit,
         version:      &'a Version,
+        parent_level: NonZeroLevel,
         base_inputs:  Vec<&'a Arc<FileMetadata>>,
         is_manual:    bool,
     ) -> Self {
@@ -296,6 +731,7 @@ impl<'a> StartCompaction<'a> {
             version,
             &opts.cmp,
             &base_inputs,
+            parent_level,
         );
 
         let mut compaction_base_inputs = base_inputs;
@@ -330,7 +766,7 @@ impl<'a> StartCompaction<'a> {
             let total_expanded_size = expanded_base_size.saturating_add(parent_size);
             let max_input_size = *opts.compaction
                 .max_compaction_inputs
-                .infallible_index_nonzero(NonZeroLevel::ONE);
+                .infallible_index_nonzero(parent_level);
 
             if expanded_base_size > base_size && total_expanded_size < max_input_size {
                 let (
@@ -341,6 +777,7 @@ impl<'a> StartCompaction<'a> {
                     version,
                     &opts.cmp,
                     &expanded_inputs,
+                    parent_level,
                 );
 
                 if parent_inputs.len() == expanded_parents.len() {
@@ -359,19 +796,24 @@ impl<'a> StartCompaction<'a> {
             }
         }
 
-        let grandparent_level = MiddleLevel::ONE.next_level();
-
-        let max_grandparent_overlap = *opts.compaction
-            .max_grandparent_overlap
-            .infallible_index_middle(MiddleLevel::ONE);
+        let (
+            grandparents,
+            max_grandparent_overlap,
+        ) = if let Some(parent_level) = parent_level.try_as_middle_level() {
+            let max_grandparent_overlap = *opts.compaction
+                .max_grandparent_overlap
+                .infallible_index_middle(parent_level);
 
-        let grandparent_files = version.level_files(grandparent_level.as_level());
-        let grandparents = grandparent_files.get_overlapping_files_disjoint(
-            &opts.cmp,
-            Some(compaction_smallest),
-            Some(compaction_largest),
-        );
-        let grandparents = &grandparent_files.inner()[grandparents];
+            let grandparent_files = version.level_files(parent_level.next_level().as_level());
+            let grandparents = grandparent_files.get_overlapping_files_disjoint(
+                &opts.cmp,
+                Some(compaction_smallest),
+                Some(compaction_largest),
+            );
+            (&grandparent_files.inner()[grandparents], max_grandparent_overlap)
+        } else {
+            ([].as_slice(), u64::MAX)
+        };
 
         // Update the compaction pointer for the next size compaction. We match LevelDB's behavior
         // in always updating the pointer even for non-size compactions.
@@ -380,13 +822,15 @@ impl<'a> StartCompaction<'a> {
         version_edit.compaction_pointers.push((Level::ZERO, compaction_largest.to_owned()));
 
         Self {
-            parent_level: NonZeroLevel::ONE,
-            base_inputs:  CompactionInputsCow::Owned(compaction_base_inputs),
+            base_level:    Level::ZERO,
+            parent_level,
+            base_inputs:   CompactionInputsCow::Owned(compaction_base_inputs),
+            middle_inputs: Vec::new(),
             parent_inputs,
             grandparents,
             max_grandparent_overlap,
-            smallest_key: compaction_smallest,
-            largest_key:  compaction_largest,
+            smallest_key:  compaction_smallest,
+            largest_key:   compaction_largest,
             is_manual,
         }
     }
@@ -405,10 +849,30 @@ impl<'a> StartCompaction<'a> {
     /// # Panics
     /// Panics if `base_inputs` is empty.
     fn get_zero_parent_inputs<Cmp: LevelDBComparator>(
-        version:     &'a Version,
+        version:      &'a Version,
+        cmp:          &InternalComparator<Cmp>,
+        base_inputs:  &[&'a Arc<FileMetadata>],
+        parent_level: NonZeroLevel,
+    ) -> (&'a [Arc<FileMetadata>], InternalKey<'a>, InternalKey<'a>) {
+        let (first, last) = Self::get_zero_key_range(cmp, base_inputs);
+
+        // Get parent inputs (including boundary inputs).
+        // NOTE: Adding boundary inputs for parents isn't necessary for correctness, but it is
+        // good for performance (reduce the fragmentation of keys across multiple files).
+        let parent_files = version.level_files(parent_level.as_level());
+        let parent_inputs = parent_files.get_overlapping_inputs_disjoint(cmp, first, last);
+        (parent_inputs, first, last)
+    }
+
+    /// Get the first and last key of the given level-0 base inputs, which need not be sorted
+    /// or disjoint.
+    ///
+    /// # Panics
+    /// Panics if `base_inputs` is empty.
+    fn get_zero_key_range<Cmp: LevelDBComparator>(
         cmp:         &InternalComparator<Cmp>,
         base_inputs: &[&'a Arc<FileMetadata>],
-    ) -> (&'a [Arc<FileMetadata>], InternalKey<'a>, InternalKey<'a>) {
+    ) -> (InternalKey<'a>, InternalKey<'a>) {
         let mut base_input_iter = base_inputs.iter();
 
         #[expect(clippy::expect_used, reason = "panic is documented, and easily avoided by caller")]
@@ -431,12 +895,7 @@ impl<'a> StartCompaction<'a> {
             }
         }
 
-        // Get parent inputs (including boundary inputs).
-        // NOTE: Adding boundary inputs for parents isn't necessary for correctness, but it is
-        // good for performance (reduce the fragmentation of keys across multiple files).
-        let parent_files = version.level_files(NonZeroLevel::ONE.as_level());
-        let parent_inputs = parent_files.get_overlapping_inputs_disjoint(cmp, first, last);
-        (parent_inputs, first, last)
+        (first, last)
     }
 
     /// Finish setting up a compaction between two nonzero levels, given the range of initial
@@ -566,13 +1025,15 @@ impl<'a> StartCompaction<'a> {
         // We can use the `Borrowed` variant because the base inputs are a subslice of
         // a nonzero level's `SortedFiles`, and they are therefore sorted and disjoint.
         Self {
+            base_level:    base_level.as_level(),
             parent_level,
-            base_inputs:  CompactionInputsCow::Borrowed(compaction_base_inputs),
+            base_inputs:   CompactionInputsCow::Borrowed(compaction_base_inputs),
+            middle_inputs: Vec::new(),
             parent_inputs,
             grandparents,
             max_grandparent_overlap,
-            smallest_key: compaction_smallest,
-            largest_key:  compaction_largest,
+            smallest_key:  compaction_smallest,
+            largest_key:   compaction_largest,
             is_manual,
         }
     }
@@ -613,7 +1074,7 @@ impl<'a> StartCompaction<'a> {
     }
 
     pub fn trivial_move(&self) -> Option<&'a Arc<FileMetadata>> {
-        if !self.parent_inputs.is_empty() {
+        if !self.parent_inputs.is_empty() || !self.middle_inputs.is_empty() {
             return None;
         }
 
@@ -644,7 +1105,7 @@ impl<'a> StartCompaction<'a> {
 
     /// Add all inputs to this compaction as delete operations to `edit`.
     pub fn add_input_deletions(&self, edit: &mut VersionEdit) {
-        let base_level = self.parent_level.prev_level();
+        let base_level = self.base_level;
 
         match &self.base_inputs {
             CompactionInputsCow::Owned(base_inputs) => {
@@ -659,6 +1120,13 @@ impl<'a> StartCompaction<'a> {
             }
         }
 
+        for &(middle_level, middle_inputs) in &self.middle_inputs {
+            let middle_level = middle_level.as_level();
+            edit.deleted_files.extend(middle_inputs.iter().map(|meta| {
+                (middle_level, meta.file_number())
+            }));
+        }
+
         let parent_level = self.parent_level.as_level();
         let parent_inputs = self.parent_inputs.iter().map(|meta| {
             (parent_level, meta.file_number())
@@ -677,6 +1145,7 @@ impl<'a> StartCompaction<'a> {
             has_nonempty_output:     false,
             current_overlap:         0,
             max_grandparent_overlap: self.max_grandparent_overlap,
+            prev_user_key:           Vec::new(),
         }
     }
 }
@@ -709,15 +1178,32 @@ pub(crate) struct ActiveCompaction<'a> {
     /// The size (in bytes) of the overlap between the current output file and grandparent files.
     current_overlap:         u64,
     max_grandparent_overlap: u64,
+    /// The user key of the previous entry passed to `should_stop_before`, if a
+    /// [`CompactionPartitioner`] is in use.
+    prev_user_key:           Vec<u8>,
 }
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
 impl ActiveCompaction<'_> {
     pub fn should_stop_before<Cmp: LevelDBComparator>(
         &mut self,
-        cmp: &InternalComparator<Cmp>,
-        key: InternalKey<'_>,
+        cmp:         &InternalComparator<Cmp>,
+        partitioner: Option<&(dyn CompactionPartitioner + Send + Sync)>,
+        key:         InternalKey<'_>,
     ) -> bool {
+        // The partitioner is only consulted between distinct user keys of a nonempty output file.
+        let partition = if let Some(partitioner) = partitioner {
+            let partition = self.has_nonempty_output
+                && cmp.0.cmp(&self.prev_user_key, key.0.inner()).is_lt()
+                && partitioner.should_partition(&self.prev_user_key, key.0.inner());
+
+            self.prev_user_key.clear();
+            self.prev_user_key.extend(key.0.inner());
+            partition
+        } else {
+            false
+        };
+
         while let Some(grandparent) = self.grandparents.as_slice().first() {
             if cmp.cmp(grandparent.largest_key(), key).is_lt() {
                 if self.has_nonempty_output {
@@ -732,8 +1218,9 @@ impl ActiveCompaction<'_> {
         }
         self.has_nonempty_output = true;
 
-        if self.current_overlap > self.max_grandparent_overlap {
-            // Too much grandparent overlap for the current output file; start a new one.
+        if self.current_overlap > self.max_grandparent_overlap || partition {
+            // Too much grandparent overlap for the current output file, or the partitioner chose
+            // a boundary; start a new one.
             self.current_overlap = 0;
             true
         } else {
@@ -762,3 +1249,80 @@ impl ActiveCompaction<'_> {
     //     false
     // }
 }
+
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, clippy::unwrap_used, reason = "these are tests")]
+mod tests {
+    use crate::pub_traits::{cmp_and_policy::BytewiseComparator, partitioner::PrefixPartitioner};
+    use crate::pub_typed_bytes::{EntryType, SequenceNumber};
+    use crate::typed_bytes::{InternalKeyTag, UserKey};
+    use super::*;
+
+
+    const NO_FILES: &[Arc<FileMetadata>] = &[];
+
+    fn key(user_key: &[u8], sequence_number: u64) -> InternalKey<'_> {
+        let sequence_number = SequenceNumber::new(sequence_number).unwrap();
+        InternalKey(
+            UserKey::new(user_key).unwrap(),
+            InternalKeyTag::new(sequence_number, EntryType::Value),
+        )
+    }
+
+    /// An active compaction without grandparent files, so that only a partitioner can choose
+    /// to stop before a key.
+    fn active_compaction<'a>(
+        smallest_key: InternalKey<'a>,
+        largest_key:  InternalKey<'a>,
+    ) -> ActiveCompaction<'a> {
+        ActiveCompaction {
+            parent_level:            NonZeroLevel::NONZERO_LEVELS[0],
+            base_inputs:             CompactionInputsCow::Borrowed(NO_FILES),
+            parent_inputs:           NO_FILES,
+            grandparents:            NO_FILES.iter(),
+            smallest_key,
+            largest_key,
+            has_nonempty_output:     false,
+            current_overlap:         0,
+            max_grandparent_overlap: u64::MAX,
+            prev_user_key:           Vec::new(),
+        }
+    }
+
+    #[test]
+    fn prefix_partitioner_splits_at_prefix_boundaries() {
+        let cmp = InternalComparator(BytewiseComparator);
+        let partitioner = PrefixPartitioner { prefix_len: 2 };
+        let keys = [
+            (key(b"aa1", 9), false),
+            (key(b"aa2", 8), false),
+            (key(b"ab1", 7), true),
+            // Entries with the same user key are never split into separate files.
+            (key(b"ab1", 6), false),
+            (key(b"ab2", 5), false),
+            // A key shorter than the prefix length is its own prefix.
+            (key(b"b", 4), true),
+            (key(b"bc1", 3), true),
+            (key(b"bc2", 2), false),
+        ];
+
+        let smallest_key = keys.first().unwrap().0;
+        let largest_key = keys.last().unwrap().0;
+
+        let mut compaction = active_compaction(smallest_key, largest_key);
+        for (internal_key, should_stop) in keys {
+            assert_eq!(
+                compaction.should_stop_before(&cmp, Some(&partitioner), internal_key),
+                should_stop,
+                "{internal_key:?}",
+            );
+        }
+
+        // Without a partitioner (or grandparents), outputs are not split.
+        let mut compaction = active_compaction(smallest_key, largest_key);
+        for (internal_key, _) in keys {
+            assert!(!compaction.should_stop_before(&cmp, None, internal_key));
+        }
+    }
+}
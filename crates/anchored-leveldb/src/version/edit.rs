@@ -126,6 +126,7 @@ impl VersionEdit {
                         file_size,
                         smallest_key,
                         largest_key,
+                        None,
                         opts,
                     ));
 
@@ -9,7 +9,7 @@ mod compaction;
 
 pub(crate) use self::{edit::VersionEdit, version_struct::Version};
 pub(crate) use self::{
-    compaction::{CompactionInputsCow, StartCompaction},
+    compaction::{CompactionInputsCow, SizeCompaction, StartCompaction, TieredCompaction},
     set::{InstallToken, LogToken, VersionSet},
     set_builder::{BeginVersionSetRecovery, VersionSetBuilder},
     version_tracking::{CurrentVersion, NeedsSeekCompaction, OldVersions},
@@ -8,7 +8,7 @@ use anchored_vfs::WritableFile;
 
 use crate::{
     file_tracking::StartSeekCompaction,
-    options::pub_options::SizeCompactionOptions,
+    options::InternalCompactionOptions,
     pub_traits::cmp_and_policy::LevelDBComparator,
     table_format::InternalComparator,
 };
@@ -217,11 +217,15 @@ impl<File: WritableFile> VersionSet<File> {
         })
     }
 
-    pub fn install(&mut self, token: InstallToken<'_, File>, size_opts: SizeCompactionOptions) {
+    pub fn install(
+        &mut self,
+        token:           InstallToken<'_, File>,
+        compaction_opts: &InternalCompactionOptions,
+    ) {
         self.manifest_writer    = Some(token.manifest_writer);
         self.edit_record_buffer = token.edit_record_buffer;
 
-        let old_version = self.current_version.set(token.new_version, size_opts);
+        let old_version = self.current_version.set(token.new_version, compaction_opts);
         self.old_versions.add_old_version(old_version);
 
         // See `Self::apply`. These fields are guaranteed to be in the version edit.
@@ -293,6 +297,11 @@ impl<File> VersionSet<File> {
         &self.current_version
     }
 
+    /// See [`CurrentVersion::refresh_compactions`].
+    pub fn refresh_compactions(&mut self, compaction_opts: &InternalCompactionOptions) {
+        self.current_version.refresh_compactions(compaction_opts);
+    }
+
     /// Get a reference-counted clone to the current version.
     #[must_use]
     pub fn cloned_current_version(&self) -> Arc<Version> {
@@ -1,5 +1,6 @@
 use std::{mem, str};
-use std::{borrow::Cow, collections::HashSet, sync::Arc};
+use std::{borrow::Cow, sync::Arc};
+use std::collections::{BTreeSet, HashSet};
 use std::{
     io::{Error as IoError, Read},
     fmt::{Debug, Formatter, Result as FmtResult},
@@ -264,7 +265,7 @@ impl<File: WritableFile> VersionSetBuilder<File, false> {
             })?;
         let current_version = CurrentVersion::new(
             recovered_version,
-            opts.compaction.size_compactions,
+            &opts.compaction,
         );
 
         let reused_manifest = try_reuse_manifest(
@@ -459,6 +460,23 @@ impl<File> VersionSetBuilder<File, true> {
         self.new_file_number()
     }
 
+    /// Get every table file in the recovered version, along with its level.
+    ///
+    /// Table files added with [`Self::add_new_table_files`] are not included.
+    #[must_use]
+    pub fn live_table_files(&self) -> Vec<(Level, Arc<FileMetadata>)> {
+        let mut live_files = Vec::new();
+
+        for level in Level::ALL_LEVELS {
+            live_files.extend(
+                self.current_version.level_files(level).inner()
+                    .iter().map(|file_metadata| (level, Arc::clone(file_metadata))),
+            );
+        }
+
+        live_files
+    }
+
     // `VersionSetBuilder` does not expose anything which depends on `mark_sequence_used`
     // other than `finish`, so unlike `mark_file_used`, this method can be called at any point
     // during recovery without issue.
@@ -519,43 +537,110 @@ impl<File> VersionSetBuilder<File, true> {
                     manifest_writer,
                     manifest_file_number,
                     None,
+                    BTreeSet::new(),
                 )
             }
         } else {
             // We need to issue a `MANIFEST` write, including writing the base version.
-            let file_number = self.new_file_number()
-                .map_err(OutOfFileNumbers::into_recovery_err)?;
-            let manifest_name = LevelDBFileName::Manifest { file_number }.file_name();
-            let manifest_path = opts.db_directory.join(&manifest_name);
-            let manifest_file = mut_opts.filesystem
-                .open_writable(&manifest_path, CreateParentDir::False, SyncParentDir::False)
-                .map_err(|fs_err| RecoveryErrorKind::Write(WriteError::Filesystem(
-                    FilesystemError::FsError(fs_err),
-                    file_number,
-                    WriteFsError::OpenWritableManifest,
-                )))?;
-
-            self.finish_with_manifest_write(
+            self.finish_with_new_manifest(
                 opts,
                 mut_opts,
                 verify_new_version,
-                WriteLogWriter::new_empty(manifest_file, opts.binary_log_block_size),
-                file_number,
-                Some(&manifest_name),
-            ).inspect_err(|_error| {
-                // Try to clean up the now-pointless manifest file. No worries if that fails,
-                // the next time that file is opened, it'll be with `open_writable` not
-                // `open_appendable`, so no corruption can occur.
-                // Also, any leftover file will eventually be garbage-collected.
-                let _err = mut_opts.filesystem.remove_file(&manifest_path);
-            })
+                BTreeSet::new(),
+            )
         }
     }
 
+    /// Replace table files in the recovered version with new table files, and write a new
+    /// `MANIFEST` file to persist the change.
+    ///
+    /// Each replaced table file is given by its level and file number, along with the new table
+    /// files (placed into the same level) which hold its entries.
+    ///
+    /// Unlike [`Self::finish`], `min_log_number` and `prev_log_number` are left unchanged, so
+    /// any `.log` files are left for the next database invocation to recover. No `.log` files
+    /// should have been recovered, and no table files should have been added with
+    /// [`Self::add_new_table_files`].
+    pub fn finish_replacing_tables<FS, Cmp, Policy, Codecs, Pool>(
+        mut self,
+        opts:               &InternalOptions<Cmp, Policy, Codecs>,
+        mut_opts:           &InternallyMutableOptions<FS, Policy, Pool>,
+        verify_new_version: bool,
+        replaced_tables:    Vec<(Level, FileNumber, Vec<FileMetadata>)>,
+    ) -> Result<VersionSet<File>, RecoveryErrorKindAlias<FS, Cmp, Codecs>>
+    where
+        FS:     LevelDBFilesystem<WriteFile = File>,
+        Cmp:    LevelDBComparator,
+        Codecs: CompressionCodecs,
+        Pool:   BufferPool,
+        File:   WritableFile,
+    {
+        // Even if the old `MANIFEST` could have been reused, it still refers to the replaced
+        // table files. A new `MANIFEST` only refers to the live ones.
+        self.reused_manifest = None;
+
+        let mut deleted_files = BTreeSet::new();
+        for (level, old_file_number, new_files) in replaced_tables {
+            deleted_files.insert((level, old_file_number));
+            self.added_table_files.extend(
+                new_files.into_iter().map(|file_metadata| (level, Arc::new(file_metadata))),
+            );
+        }
+
+        self.finish_with_new_manifest(opts, mut_opts, verify_new_version, deleted_files)
+    }
+
+    /// Create a new `MANIFEST` file, and write the base version and a `VersionEdit` to it.
+    ///
+    /// This function should only be called from [`Self::finish`] or
+    /// [`Self::finish_replacing_tables`].
+    fn finish_with_new_manifest<FS, Cmp, Policy, Codecs, Pool>(
+        mut self,
+        opts:               &InternalOptions<Cmp, Policy, Codecs>,
+        mut_opts:           &InternallyMutableOptions<FS, Policy, Pool>,
+        verify_new_version: bool,
+        deleted_files:      BTreeSet<(Level, FileNumber)>,
+    ) -> Result<VersionSet<File>, RecoveryErrorKindAlias<FS, Cmp, Codecs>>
+    where
+        FS:     LevelDBFilesystem<WriteFile = File>,
+        Cmp:    LevelDBComparator,
+        Codecs: CompressionCodecs,
+        Pool:   BufferPool,
+        File:   WritableFile,
+    {
+        let file_number = self.new_file_number()
+            .map_err(OutOfFileNumbers::into_recovery_err)?;
+        let manifest_name = LevelDBFileName::Manifest { file_number }.file_name();
+        let manifest_path = opts.db_directory.join(&manifest_name);
+        let manifest_file = mut_opts.filesystem
+            .open_writable(&manifest_path, CreateParentDir::False, SyncParentDir::False)
+            .map_err(|fs_err| RecoveryErrorKind::Write(WriteError::Filesystem(
+                FilesystemError::FsError(fs_err),
+                file_number,
+                WriteFsError::OpenWritableManifest,
+            )))?;
+
+        self.finish_with_manifest_write(
+            opts,
+            mut_opts,
+            verify_new_version,
+            WriteLogWriter::new_empty(manifest_file, opts.binary_log_block_size),
+            file_number,
+            Some(&manifest_name),
+            deleted_files,
+        ).inspect_err(|_error| {
+            // Try to clean up the now-pointless manifest file. No worries if that fails,
+            // the next time that file is opened, it'll be with `open_writable` not
+            // `open_appendable`, so no corruption can occur.
+            // Also, any leftover file will eventually be garbage-collected.
+            let _err = mut_opts.filesystem.remove_file(&manifest_path);
+        })
+    }
+
     /// We created a new manifest file iff `new_manifest_name` is `Some`.
     ///
-    /// This function should only be called from [`Self::finish`], after
-    /// `self.min_log_number` and `self.prev_log_number` have been updated.
+    /// This function should only be called from [`Self::finish`] (after `self.min_log_number`
+    /// and `self.prev_log_number` have been updated) or [`Self::finish_with_new_manifest`].
     fn finish_with_manifest_write<FS, Cmp, Policy, Codecs, Pool>(
         mut self,
         opts:                 &InternalOptions<Cmp, Policy, Codecs>,
@@ -564,6 +649,7 @@ impl<File> VersionSetBuilder<File, true> {
         mut manifest_writer:  WriteLogWriter<File>,
         manifest_file_number: FileNumber,
         new_manifest_name:    Option<&str>,
+        deleted_files:        BTreeSet<(Level, FileNumber)>,
     ) -> Result<VersionSet<File>, RecoveryErrorKindAlias<FS, Cmp, Codecs>>
     where
         FS:     LevelDBFilesystem<WriteFile = File>,
@@ -572,12 +658,14 @@ impl<File> VersionSetBuilder<File, true> {
         Pool:   BufferPool,
         File:   WritableFile,
     {
-        // The `VersionEdit` has at least the minimum four fields, plus `added_files`.
+        // The `VersionEdit` has at least the minimum four fields, plus `deleted_files` and
+        // `added_files`.
         let edit = VersionEdit {
             log_number:       Some(self.min_log_number),
             prev_log_number:  Some(self.prev_log_number),
             next_file_number: Some(self.next_file_number),
             last_sequence:    Some(self.last_sequence),
+            deleted_files,
             added_files:      self.added_table_files,
             ..VersionEdit::new_empty()
         };
@@ -602,7 +690,7 @@ impl<File> VersionSetBuilder<File, true> {
             })?;
         let built_version = CurrentVersion::new(
             built_version,
-            opts.compaction.size_compactions,
+            &opts.compaction,
         );
 
         let mut edit_record_buffer = Vec::new();
@@ -642,6 +730,15 @@ impl<File> VersionSetBuilder<File, true> {
         edit_record_buffer.clear();
 
         if let Some(manifest_name) = new_manifest_name {
+            // The new `MANIFEST` must be persisted before `CURRENT` refers to it.
+            manifest_writer.sync_log_data().map_err(|io_err| {
+                RecoveryErrorKind::Write(WriteError::Filesystem(
+                    FilesystemError::Io(io_err),
+                    manifest_file_number,
+                    WriteFsError::SyncManifest,
+                ))
+            })?;
+
             set_current(
                 &mut_opts.filesystem,
                 &opts.db_directory,
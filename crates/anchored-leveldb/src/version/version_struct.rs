@@ -1,4 +1,5 @@
-use std::{cmp::Reverse as ReverseOrder, sync::Arc};
+use std::{cmp::Reverse as ReverseOrder, num::NonZeroU64, sync::Arc};
+use std::time::Duration;
 
 use clone_behavior::FastMirroredClone;
 
@@ -15,7 +16,7 @@ use crate::{
     internal_iters::{DisjointLevelIter, IterToMerge},
     options::{
         InternallyMutableOptions, InternalOptions, InternalReadOptions,
-        pub_options::SizeCompactionOptions,
+        pub_options::{FifoCompactionOptions, SizeCompactionOptions, TieredCompactionOptions},
     },
     pub_traits::{
         cmp_and_policy::{CoarserThan, FilterPolicy, LevelDBComparator},
@@ -23,12 +24,14 @@ use crate::{
         pool::BufferPool,
     },
     pub_typed_bytes::{
-        FileNumber, FileOffset, IndexLevel as _, IndexMiddleLevel as _, Level, MiddleLevel,
-        NonZeroLevel, NUM_LEVELS_USIZE,
+        FileNumber, FileOffset, IndexLevel as _, IndexMiddleLevel as _, IndexNonZeroLevel as _,
+        Level, MiddleLevel, NonZeroLevel, NUM_LEVELS_USIZE, NUM_MIDDLE_LEVELS_USIZE,
+        NUM_NONZERO_LEVELS_USIZE,
     },
     sstable::{SSTableEntry, TableIter},
     typed_bytes::{InternalKey, LookupKey},
 };
+use super::compaction::{SizeCompaction, TieredCompaction};
 
 
 /// A collection of table files (`.ldb` and `.sst` files).
@@ -75,7 +78,7 @@ impl Version {
     pub(super) fn compute_size_compaction(
         &self,
         size_opts: SizeCompactionOptions,
-    ) -> Option<NonZeroLevel> {
+    ) -> Option<SizeCompaction> {
         #![expect(
             clippy::as_conversions,
             clippy::cast_precision_loss,
@@ -84,31 +87,342 @@ impl Version {
         )]
 
         let num_l0_files = self.level_files(Level::ZERO).inner().len();
+        let (level0_parent, max_level_sizes) = self.level_size_targets(size_opts);
 
-        let mut best_level_parent = NonZeroLevel::ONE;
+        let mut best_compaction = SizeCompaction {
+            base_level:   Level::ZERO,
+            parent_level: level0_parent,
+        };
         // Level 0 is bounded by number of files instead of size in bytes.
         let mut best_score = (num_l0_files as f64) / f64::from(size_opts.max_level0_files.get());
 
         for level in MiddleLevel::MIDDLE_LEVELS {
+            let Some(max_level_size) = *max_level_sizes.infallible_index_middle(level) else {
+                continue;
+            };
+
             let level_files = self.files.infallible_index(level.as_level()).borrowed();
             let level_size = level_files.total_file_size();
 
-            let max_level_size = *size_opts.max_level_sizes.infallible_index_middle(level);
-            let score = (level_size as f64) / max_level_size as f64;
+            let score = if max_level_size == 0 {
+                // Only nonempty levels need to be compacted.
+                if level_size == 0 { 0_f64 } else { f64::INFINITY }
+            } else {
+                (level_size as f64) / max_level_size as f64
+            };
 
             if score > best_score {
-                best_level_parent = level.next_level();
+                best_compaction = SizeCompaction {
+                    base_level:   level.as_level(),
+                    parent_level: level.next_level(),
+                };
                 best_score = score;
             }
         }
 
         if best_score >= 1_f64 {
-            Some(best_level_parent)
+            Some(best_compaction)
         } else {
             None
         }
     }
 
+    /// Estimate the number of bytes which size compactions would need to rewrite in order to
+    /// bring every level within its size limit.
+    ///
+    /// Each byte by which a middle level exceeds its limit is counted once for itself, and
+    /// `level_size_multiplier` more times for the data which it overlaps in the next level. If
+    /// level 0 has enough files to need a compaction, all of level 0 is counted in the same way.
+    #[must_use]
+    pub(super) fn estimated_pending_compaction_bytes(
+        &self,
+        size_opts: SizeCompactionOptions,
+    ) -> u64 {
+        let (_, max_level_sizes) = self.level_size_targets(size_opts);
+        let rewrite_factor = u64::from(size_opts.level_size_multiplier.get()) + 1;
+
+        let level0_files = self.level_files(Level::ZERO);
+        let mut pending_bytes = if level0_files.inner().len()
+            >= usize::from(size_opts.max_level0_files.get())
+        {
+            level0_files.total_file_size().saturating_mul(rewrite_factor)
+        } else {
+            0
+        };
+
+        for level in MiddleLevel::MIDDLE_LEVELS {
+            let Some(max_level_size) = *max_level_sizes.infallible_index_middle(level) else {
+                continue;
+            };
+
+            let level_size = self.files.infallible_index(level.as_level())
+                .borrowed()
+                .total_file_size();
+            let excess = level_size.saturating_sub(max_level_size);
+
+            pending_bytes = pending_bytes.saturating_add(excess.saturating_mul(rewrite_factor));
+        }
+
+        pending_bytes
+    }
+
+    /// Get the level into which level-0 files should be compacted, and the size limit of each
+    /// middle level (or `None` for levels without a limit).
+    ///
+    /// See [`SizeCompactionOptions::dynamic_level_sizes`].
+    #[must_use]
+    fn level_size_targets(
+        &self,
+        size_opts: SizeCompactionOptions,
+    ) -> (NonZeroLevel, [Option<u64>; NUM_MIDDLE_LEVELS_USIZE.get()]) {
+        if !size_opts.dynamic_level_sizes {
+            return (NonZeroLevel::ONE, size_opts.max_level_sizes.map(Some));
+        }
+
+        let level_size = |level: NonZeroLevel| {
+            self.files.infallible_index(level.as_level()).borrowed().total_file_size()
+        };
+
+        let mut max_level_sizes = [None; NUM_MIDDLE_LEVELS_USIZE.get()];
+
+        let Some(last_nonempty) = NonZeroLevel::NONZERO_LEVELS
+            .into_iter()
+            .rev()
+            .find(|&level| level_size(level) > 0)
+        else {
+            // Move level-0 files straight to the last level, where they will end up anyway.
+            return (NonZeroLevel::LAST, max_level_sizes);
+        };
+
+        let min_base_level_size = *size_opts.max_level_sizes
+            .infallible_index_middle(MiddleLevel::ONE);
+        let multiplier = NonZeroU64::from(size_opts.level_size_multiplier);
+
+        let mut base_level = last_nonempty;
+        let mut max_level_size = level_size(last_nonempty);
+
+        if let Some(prev_level) = last_nonempty.prev_level().try_as_nonzero_level() {
+            for level in NonZeroLevel::ONE.inclusive_range(prev_level).rev() {
+                max_level_size /= multiplier;
+                if max_level_size < min_base_level_size {
+                    break;
+                }
+
+                // Every level before `last_nonempty` is a middle level.
+                if let Some(level) = level.try_as_middle_level() {
+                    *max_level_sizes.infallible_index_middle_mut(level) = Some(max_level_size);
+                }
+                base_level = level;
+            }
+        }
+
+        // Levels before the base level should be empty, and any files in them should be pushed
+        // down towards the base level.
+        for level in MiddleLevel::MIDDLE_LEVELS {
+            if level.as_nonzero_level() >= base_level {
+                break;
+            }
+            *max_level_sizes.infallible_index_middle_mut(level) = Some(0);
+        }
+
+        // Level-0 files must not be compacted past a nonempty level, or else newer entries could
+        // end up in a higher level than older entries.
+        let first_nonempty = NonZeroLevel::NONZERO_LEVELS
+            .into_iter()
+            .find(|&level| level_size(level) > 0)
+            .unwrap_or(base_level);
+
+        (base_level.min(first_nonempty), max_level_sizes)
+    }
+
+    /// Choose the level-0 files which should be deleted by a FIFO compaction, as documented on
+    /// [`FifoCompactionOptions`].
+    ///
+    /// The chosen files are always the oldest level-0 files; deleting a newer file while keeping
+    /// an older one could resurrect overwritten or deleted entries.
+    #[must_use]
+    pub(super) fn compute_fifo_deletions(
+        &self,
+        fifo_opts: FifoCompactionOptions,
+    ) -> Vec<FileNumber> {
+        let mut level0_files = self.level_files(Level::ZERO).inner().iter().collect::<Vec<_>>();
+        level0_files.sort_unstable_by_key(|file| file.file_number());
+
+        let mut total_size = FileMetadata::total_file_size_ref(&level0_files);
+        let mut deletions = Vec::new();
+
+        for file in level0_files {
+            let too_old = fifo_opts.max_file_age.is_some_and(|max_age| file.age() >= max_age);
+
+            if total_size <= fifo_opts.max_table_files_size && !too_old {
+                break;
+            }
+
+            total_size = total_size.saturating_sub(file.file_size().0);
+            deletions.push(file.file_number());
+        }
+
+        deletions
+    }
+
+    /// Choose the table file (outside the last level) with the greatest fraction of deletions,
+    /// if that fraction is at least `max_deletion_ratio`.
+    #[must_use]
+    pub(super) fn compute_deletion_compaction(
+        &self,
+        max_deletion_ratio: f64,
+    ) -> Option<StartSeekCompaction> {
+        let mut best: Option<(StartSeekCompaction, f64)> = None;
+
+        for parent_level in NonZeroLevel::NONZERO_LEVELS {
+            let base_files = self.level_files(parent_level.prev_level()).inner();
+
+            for (file_idx, file) in base_files.iter().enumerate() {
+                let Some(ratio) = file.properties().and_then(|props| props.deletion_ratio()) else {
+                    continue;
+                };
+
+                if ratio >= max_deletion_ratio
+                    && best.is_none_or(|(_, best_ratio)| ratio > best_ratio)
+                {
+                    let start = StartSeekCompaction {
+                        level: parent_level,
+                        file:  file_idx,
+                    };
+                    best = Some((start, ratio));
+                }
+            }
+        }
+
+        best.map(|(start, _)| start)
+    }
+
+    /// Choose the oldest table file (outside the last level), if it is at least `max_age` old.
+    #[must_use]
+    pub(super) fn compute_periodic_compaction(
+        &self,
+        max_age: Duration,
+    ) -> Option<StartSeekCompaction> {
+        let mut best: Option<(StartSeekCompaction, Duration)> = None;
+
+        for parent_level in NonZeroLevel::NONZERO_LEVELS {
+            let base_files = self.level_files(parent_level.prev_level()).inner();
+
+            for (file_idx, file) in base_files.iter().enumerate() {
+                let age = file.age();
+
+                if age >= max_age && best.is_none_or(|(_, best_age)| age > best_age) {
+                    let start = StartSeekCompaction {
+                        level: parent_level,
+                        file:  file_idx,
+                    };
+                    best = Some((start, age));
+                }
+            }
+        }
+
+        best.map(|(start, _)| start)
+    }
+
+    /// Choose a tiered compaction, as documented on [`TieredCompactionOptions`].
+    #[must_use]
+    pub(super) fn compute_tiered_compaction(
+        &self,
+        tiered_opts:           TieredCompactionOptions,
+        max_compaction_inputs: &[u64; NUM_NONZERO_LEVELS_USIZE.get()],
+    ) -> Option<TieredCompaction> {
+        let num_level0_files = self.level_files(Level::ZERO).inner().len();
+
+        // Each nonempty level and its total size, from newest to oldest. Level 0 is treated as
+        // a single run here.
+        let runs: Vec<(Level, u64)> = Level::ALL_LEVELS
+            .into_iter()
+            .filter_map(|level| {
+                let files = self.level_files(level);
+                (!files.inner().is_empty()).then(|| (level, files.total_file_size()))
+            })
+            .collect();
+
+        let num_nonzero_runs = runs.len() - usize::from(num_level0_files > 0);
+        let num_sorted_runs = num_level0_files + num_nonzero_runs;
+
+        if num_sorted_runs < usize::from(tiered_opts.sorted_run_trigger.get()) {
+            return None;
+        }
+
+        // Only `Level::ZERO` can be the first run, so the oldest run is in a nonzero level
+        // if there are at least two runs.
+        if let Some((&(oldest_level, oldest_size), newer_runs)) = runs.split_last() {
+            if let Some(&(second_oldest_level, second_oldest_size)) = newer_runs.last() {
+                let newer_size = newer_runs.iter()
+                    .fold(0_u64, |sum, &(_, size)| sum.saturating_add(size));
+
+                if u128::from(newer_size) * 100
+                    >= u128::from(oldest_size) * u128::from(tiered_opts.max_space_amplification)
+                {
+                    let parent_level = oldest_level.try_as_nonzero_level()?;
+                    let max_input_size =
+                        *max_compaction_inputs.infallible_index_nonzero(parent_level);
+
+                    // Merge runs from oldest to newest for as long as their total size stays
+                    // within the limit. The two oldest runs are always merged.
+                    let mut base_level = second_oldest_level;
+                    let mut input_size = oldest_size.saturating_add(second_oldest_size);
+
+                    for &(level, size) in newer_runs.iter().rev().skip(1) {
+                        input_size = input_size.saturating_add(size);
+                        if input_size > max_input_size {
+                            break;
+                        }
+                        base_level = level;
+                    }
+
+                    return Some(TieredCompaction {
+                        base_level,
+                        parent_level,
+                    });
+                }
+            }
+        }
+
+        for window in runs.windows(2) {
+            if let &[(level, size), (next_level, next_size)] = window {
+                if u128::from(next_size) * 100
+                    <= u128::from(size) * (100 + u128::from(tiered_opts.size_ratio))
+                {
+                    return Some(TieredCompaction {
+                        base_level:   level,
+                        parent_level: next_level.try_as_nonzero_level()?,
+                    });
+                }
+            }
+        }
+
+        let &(newest_level, _) = runs.first()?;
+        let next_run_level = runs.get(1).map(|&(level, _)| level);
+
+        let parent_level = if newest_level == Level::ZERO {
+            // Place the level-0 files into the empty level just before the next run, if there is
+            // one; otherwise, merge them into the next run. If there is no next run, use the
+            // last level.
+            match next_run_level {
+                Some(next_run_level) => {
+                    let before_next_run = next_run_level.try_as_nonzero_level()?.prev_level();
+                    before_next_run.try_as_nonzero_level()
+                        .or_else(|| next_run_level.try_as_nonzero_level())?
+                }
+                None => *NonZeroLevel::NONZERO_LEVELS.last()?,
+            }
+        } else {
+            next_run_level?.try_as_nonzero_level()?
+        };
+
+        Some(TieredCompaction {
+            base_level: newest_level,
+            parent_level,
+        })
+    }
+
     #[expect(clippy::type_complexity, reason = "the individual types have clear semantic meaning")]
     pub fn get<FS, Cmp, Policy, Codecs, Pool>(
         &self,
@@ -153,8 +467,7 @@ impl Version {
                 last_file_read = Some(($level, $file_index, $file));
                 {
                     let sstable = read_sstable(
-                        opts, mut_opts, read_opts, decoders, manifest_number,
-                        $file.file_number(), $file.file_size(),
+                        opts, mut_opts, read_opts, decoders, manifest_number, $file,
                     )?;
 
                     let table_entry: Option<SSTableEntry<_>> = sstable.get(
@@ -283,8 +596,7 @@ impl Version {
                         read_opts,
                         decoders,
                         manifest_number,
-                        file.file_number(),
-                        file.file_size(),
+                        file,
                     ) {
                         approx_offset += table.approximate_offset_of_key(&opts.cmp, key).0;
                     }
@@ -493,8 +805,7 @@ impl Version {
                 read_opts,
                 decoders,
                 manifest_number,
-                table_file.file_number(),
-                table_file.file_size(),
+                table_file,
             )?;
 
             let sstable_iter = TableIter::new(&sstable);
@@ -519,3 +830,183 @@ impl Version {
     // file_summary_with_numeric_keys(&self, f) -> FmtResult
     // file_summary_with<K>(&self, f, display_key: K) -> FmtResult
 }
+
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, clippy::unwrap_used, reason = "these are tests")]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::pub_traits::cmp_and_policy::BytewiseComparator;
+    use crate::{
+        file_tracking::TableProperties,
+        options::pub_options::SeekCompactionOptions,
+        pub_typed_bytes::{EntryType, FileSize, SequenceNumber},
+        typed_bytes::{InternalKeyTag, UserKey},
+    };
+    use super::*;
+
+
+    /// A table file whose only user key is `file_number`, written `age_secs` seconds ago.
+    fn file(file_number: u64, file_size: u64, age_secs: u64) -> Arc<FileMetadata> {
+        let user_key = file_number.to_be_bytes();
+        let sequence_number = SequenceNumber::new(file_number).unwrap();
+        let key = InternalKey(
+            UserKey::new(&user_key).unwrap(),
+            InternalKeyTag::new(sequence_number, EntryType::Value),
+        );
+        let properties = TableProperties {
+            num_entries:   1,
+            num_deletions: 0,
+            smallest_seq:  sequence_number,
+            largest_seq:   sequence_number,
+            creation_time: TableProperties::current_time() - age_secs,
+        };
+
+        Arc::new(FileMetadata::new(
+            FileNumber(file_number),
+            FileSize(file_size),
+            key,
+            key,
+            Some(properties),
+            SeekCompactionOptions::enabled(),
+        ))
+    }
+
+    fn version(levels: Vec<(Level, Vec<Arc<FileMetadata>>)>) -> Version {
+        let cmp = InternalComparator(BytewiseComparator);
+        let mut files: [OwnedSortedFiles; NUM_LEVELS_USIZE.get()] = Default::default();
+
+        for (level, mut level_files) in levels {
+            *files.infallible_index_mut(level) = OwnedSortedFiles::merge(
+                OwnedSortedFiles::new_empty().borrowed(),
+                &mut level_files,
+                &HashSet::new(),
+                &cmp,
+            );
+        }
+
+        Version::new(files)
+    }
+
+    #[test]
+    fn fifo_deletes_oldest_files_over_size_budget() {
+        let version = version(vec![(Level::ZERO, vec![
+            file(3, 100, 0),
+            file(1, 100, 0),
+            file(4, 100, 0),
+            file(2, 100, 0),
+        ])]);
+
+        let fifo_opts = FifoCompactionOptions {
+            max_table_files_size: 250,
+            max_file_age:         None,
+        };
+        assert_eq!(version.compute_fifo_deletions(fifo_opts), [FileNumber(1), FileNumber(2)]);
+
+        let fifo_opts = FifoCompactionOptions {
+            max_table_files_size: 400,
+            max_file_age:         None,
+        };
+        assert!(version.compute_fifo_deletions(fifo_opts).is_empty());
+    }
+
+    #[test]
+    fn fifo_deletes_files_over_max_age() {
+        let version = version(vec![(Level::ZERO, vec![
+            file(1, 100, 1000),
+            file(2, 100, 500),
+            file(3, 100, 10),
+            file(4, 100, 0),
+        ])]);
+
+        let fifo_opts = FifoCompactionOptions {
+            max_table_files_size: u64::MAX,
+            max_file_age:         Some(Duration::from_secs(100)),
+        };
+        assert_eq!(version.compute_fifo_deletions(fifo_opts), [FileNumber(1), FileNumber(2)]);
+
+        // Both limits apply at once.
+        let fifo_opts = FifoCompactionOptions {
+            max_table_files_size: 150,
+            max_file_age:         Some(Duration::from_secs(100)),
+        };
+        assert_eq!(
+            version.compute_fifo_deletions(fifo_opts),
+            [FileNumber(1), FileNumber(2), FileNumber(3)],
+        );
+    }
+
+    /// Compute a tiered compaction with the default options, and return its base and parent
+    /// levels.
+    fn tiered_levels(version: &Version, max_compaction_inputs: u64) -> Option<(Level, Level)> {
+        let max_compaction_inputs = [max_compaction_inputs; NUM_NONZERO_LEVELS_USIZE.get()];
+        version
+            .compute_tiered_compaction(TieredCompactionOptions::default(), &max_compaction_inputs)
+            .map(|compaction| (compaction.base_level, compaction.parent_level.as_level()))
+    }
+
+    #[test]
+    fn tiered_below_sorted_run_trigger() {
+        let version = version(vec![
+            (Level::ZERO,            vec![file(1, 100, 0)]),
+            (Level::new(1).unwrap(), vec![file(2, 100, 0)]),
+        ]);
+
+        assert!(tiered_levels(&version, u64::MAX).is_none());
+    }
+
+    #[test]
+    fn tiered_space_amplification_merges_every_run() {
+        // Every run except the oldest has a total size of 400, which is over 200% of the size
+        // of the oldest run.
+        let version = version(vec![
+            (Level::ZERO,            vec![file(1, 100, 0), file(2, 100, 0)]),
+            (Level::new(1).unwrap(), vec![file(3, 100, 0)]),
+            (Level::new(2).unwrap(), vec![file(4, 100, 0)]),
+            (Level::new(4).unwrap(), vec![file(5, 100, 0)]),
+        ]);
+
+        assert_eq!(
+            tiered_levels(&version, u64::MAX),
+            Some((Level::ZERO, Level::new(4).unwrap())),
+        );
+    }
+
+    #[test]
+    fn tiered_space_amplification_is_capped() {
+        let version = version(vec![
+            (Level::ZERO,            vec![file(1, 100, 0), file(2, 100, 0)]),
+            (Level::new(1).unwrap(), vec![file(3, 100, 0)]),
+            (Level::new(2).unwrap(), vec![file(4, 100, 0)]),
+            (Level::new(4).unwrap(), vec![file(5, 100, 0)]),
+        ]);
+
+        // Levels 4, 2 and 1 have a total size of 300, and adding level 0 would exceed the cap.
+        assert_eq!(
+            tiered_levels(&version, 350),
+            Some((Level::new(1).unwrap(), Level::new(4).unwrap())),
+        );
+        // The two oldest runs are always merged.
+        assert_eq!(
+            tiered_levels(&version, 150),
+            Some((Level::new(2).unwrap(), Level::new(4).unwrap())),
+        );
+    }
+
+    #[test]
+    fn tiered_merges_runs_within_size_ratio() {
+        // Level 2 is at most 1% larger than level 1, while level 1 is much larger than level 0.
+        let version = version(vec![
+            (Level::ZERO,            vec![file(1, 10, 0)]),
+            (Level::new(1).unwrap(), vec![file(2, 100, 0)]),
+            (Level::new(2).unwrap(), vec![file(3, 101, 0)]),
+            (Level::new(3).unwrap(), vec![file(4, 10_000, 0)]),
+        ]);
+
+        assert_eq!(
+            tiered_levels(&version, u64::MAX),
+            Some((Level::new(1).unwrap(), Level::new(2).unwrap())),
+        );
+    }
+}
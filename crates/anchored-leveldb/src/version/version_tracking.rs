@@ -7,10 +7,13 @@ use std::{
 
 use crate::{
     file_tracking::StartSeekCompaction,
-    options::{InternalCompactionOptions, pub_options::SizeCompactionOptions},
-    pub_typed_bytes::NonZeroLevel,
+    options::{InternalCompactionOptions, pub_options::CompactionStyle},
+    pub_typed_bytes::{FileNumber, Level},
+};
+use super::{
+    compaction::{SizeCompaction, TieredCompaction},
+    version_struct::Version,
 };
-use super::version_struct::Version;
 
 
 #[derive(Debug, Clone, Copy)]
@@ -21,17 +24,31 @@ pub(crate) struct NeedsSeekCompaction {
 
 #[derive(Debug)]
 pub(crate) struct CurrentVersion {
-    version:        Arc<Version>,
+    version:                  Arc<Version>,
     /// If a certain level in the database is too large (that is, the total size in bytes of
     /// all files associated with a certain `Level` is too large), a "size compaction" needs to
     /// be performed in order to move data to a higher and larger level.
     ///
-    /// This field indicates the parent level of the desired compaction.
+    /// This field indicates the base and parent levels of the desired compaction.
     /// No compactions can start in the maximum level.
-    size_compaction: Option<NonZeroLevel>,
+    ///
+    /// Always `None` unless the compaction style is [`CompactionStyle::Leveled`].
+    size_compaction:          Option<SizeCompaction>,
+    /// Always `None` unless the compaction style is [`CompactionStyle::Tiered`].
+    tiered_compaction:        Option<TieredCompaction>,
+    /// A file whose fraction of deletions is too high, if
+    /// [`InternalCompactionOptions::max_deletion_ratio`] is set.
+    ///
+    /// File ages change over time, so age-triggered compactions are not cached.
+    deletion_compaction:      Option<StartSeekCompaction>,
     /// # Correctness
     /// Must indicate a file in `self.version`; otherwise, downstream panics may occur.
-    seek_compaction: Option<StartSeekCompaction>,
+    seek_compaction:          Option<StartSeekCompaction>,
+    /// An estimate of the number of bytes which size compactions need to rewrite, used to
+    /// throttle writes.
+    ///
+    /// Always `0` unless the compaction style is [`CompactionStyle::Leveled`].
+    pending_compaction_bytes: u64,
 }
 
 #[expect(unreachable_pub, reason = "control visibility at type definition")]
@@ -40,19 +57,31 @@ impl CurrentVersion {
     #[must_use]
     pub fn new_empty() -> Self {
         Self {
-            version:         Arc::new(Version::new_empty()),
-            size_compaction: None,
-            seek_compaction: None,
+            version:                  Arc::new(Version::new_empty()),
+            size_compaction:          None,
+            tiered_compaction:        None,
+            deletion_compaction:      None,
+            seek_compaction:          None,
+            pending_compaction_bytes: 0,
         }
     }
 
     #[must_use]
-    pub fn new(version: Version, size_opts: SizeCompactionOptions) -> Self {
-        let size_compaction = version.compute_size_compaction(size_opts);
+    pub fn new(version: Version, opts: &InternalCompactionOptions) -> Self {
+        let (
+            size_compaction,
+            tiered_compaction,
+            deletion_compaction,
+        ) = Self::compute_compactions(&version, opts);
+        let pending_compaction_bytes = Self::compute_pending_compaction_bytes(&version, opts);
+
         Self {
             version:         Arc::new(version),
             size_compaction,
+            tiered_compaction,
+            deletion_compaction,
             seek_compaction: None,
+            pending_compaction_bytes,
         }
     }
 
@@ -61,24 +90,82 @@ impl CurrentVersion {
     pub fn set(
         &mut self,
         new_version: Version,
-        size_opts:   SizeCompactionOptions
+        opts:        &InternalCompactionOptions,
     ) -> Arc<Version> {
-        self.size_compaction = new_version.compute_size_compaction(size_opts);
+        (self.size_compaction, self.tiered_compaction, self.deletion_compaction)
+            = Self::compute_compactions(&new_version, opts);
         self.seek_compaction = None;
+        self.pending_compaction_bytes = Self::compute_pending_compaction_bytes(&new_version, opts);
 
         mem::replace(&mut self.version, Arc::new(new_version))
     }
 
+    /// Recompute the desired compactions of the current version and the estimate of pending
+    /// compaction bytes, for instance after the size compaction targets in `opts` changed.
+    pub fn refresh_compactions(&mut self, opts: &InternalCompactionOptions) {
+        (self.size_compaction, self.tiered_compaction, self.deletion_compaction)
+            = Self::compute_compactions(&self.version, opts);
+        self.pending_compaction_bytes = Self::compute_pending_compaction_bytes(&self.version, opts);
+    }
+
+    #[must_use]
+    fn compute_compactions(
+        version: &Version,
+        opts:    &InternalCompactionOptions,
+    ) -> (Option<SizeCompaction>, Option<TieredCompaction>, Option<StartSeekCompaction>) {
+        let deletion_compaction = if Self::file_compactions_enabled(opts) {
+            opts.max_deletion_ratio
+                .and_then(|max_ratio| version.compute_deletion_compaction(max_ratio))
+        } else {
+            None
+        };
+
+        match opts.style {
+            CompactionStyle::Leveled => (
+                version.compute_size_compaction(opts.size_compactions),
+                None,
+                deletion_compaction,
+            ),
+            CompactionStyle::Tiered(tiered_opts) => (
+                None,
+                version.compute_tiered_compaction(tiered_opts, &opts.max_compaction_inputs),
+                deletion_compaction,
+            ),
+            CompactionStyle::Fifo(_) => (None, None, None),
+        }
+    }
+
+    #[must_use]
+    fn compute_pending_compaction_bytes(
+        version: &Version,
+        opts:    &InternalCompactionOptions,
+    ) -> u64 {
+        if matches!(opts.style, CompactionStyle::Leveled) {
+            version.estimated_pending_compaction_bytes(opts.size_compactions)
+        } else {
+            0
+        }
+    }
+
     #[must_use]
     pub const fn version(&self) -> &Arc<Version> {
         &self.version
     }
 
-    /// Returns `true` if a size or seek compaction is requested.
+    /// An estimate of the number of bytes which size compactions need to rewrite in order to
+    /// bring every level within its size limit.
+    ///
+    /// Always `0` unless the compaction style is [`CompactionStyle::Leveled`].
+    #[must_use]
+    pub const fn pending_compaction_bytes(&self) -> u64 {
+        self.pending_compaction_bytes
+    }
+
+    /// Returns `true` if a size, tiered, FIFO, seek, or file compaction is requested.
     #[must_use]
-    pub const fn wants_compaction(&self, opts: &InternalCompactionOptions) -> bool {
-        if let Some(parent_level) = self.size_compaction {
-            let is_for_level0 = matches!(parent_level, NonZeroLevel::ONE);
+    pub fn wants_compaction(&self, opts: &InternalCompactionOptions) -> bool {
+        if let Some(size_compaction) = self.size_compaction {
+            let is_for_level0 = size_compaction.base_level == Level::ZERO;
 
             if (is_for_level0 && opts.size_compactions.autocompact_level_zero)
                 || (!is_for_level0 && opts.size_compactions.autocompact_nonzero_levels)
@@ -87,7 +174,15 @@ impl CurrentVersion {
             }
         }
 
-        self.seek_compaction.is_some() && opts.seek_compactions.seek_autocompactions
+        if self.tiered_compaction(opts).is_some() || !self.fifo_deletions(opts).is_empty() {
+            return true;
+        }
+
+        if self.seek_compaction.is_some() && Self::seek_compactions_enabled(opts) {
+            return true;
+        }
+
+        self.file_compaction(opts).is_some()
     }
 
     /// Returns the desired size compaction and seek compaction (in that order), if any are
@@ -96,22 +191,22 @@ impl CurrentVersion {
     pub fn compactions(
         &self,
         opts: &InternalCompactionOptions,
-    ) -> (Option<NonZeroLevel>, Option<StartSeekCompaction>) {
+    ) -> (Option<SizeCompaction>, Option<StartSeekCompaction>) {
         let mut size_compaction = None;
         let mut seek_compaction = None;
 
-        if let Some(parent_level) = self.size_compaction {
-            let is_for_level0 = parent_level == NonZeroLevel::ONE;
+        if let Some(start_size_compaction) = self.size_compaction {
+            let is_for_level0 = start_size_compaction.base_level == Level::ZERO;
 
             if (is_for_level0 && opts.size_compactions.autocompact_level_zero)
                 || (!is_for_level0 && opts.size_compactions.autocompact_nonzero_levels)
             {
-                size_compaction = Some(parent_level);
+                size_compaction = Some(start_size_compaction);
             }
         }
 
         if let Some(start_seek_compaction) = self.seek_compaction {
-            if opts.seek_compactions.seek_autocompactions {
+            if Self::seek_compactions_enabled(opts) {
                 seek_compaction = Some(start_seek_compaction);
             }
         }
@@ -119,6 +214,48 @@ impl CurrentVersion {
         (size_compaction, seek_compaction)
     }
 
+    /// Returns the desired tiered compaction, if one is needed and enabled.
+    #[must_use]
+    pub const fn tiered_compaction(
+        &self,
+        opts: &InternalCompactionOptions,
+    ) -> Option<TieredCompaction> {
+        if let Some(tiered_compaction) = self.tiered_compaction {
+            let is_for_level0 = matches!(tiered_compaction.base_level, Level::ZERO);
+
+            if (is_for_level0 && opts.size_compactions.autocompact_level_zero)
+                || (!is_for_level0 && opts.size_compactions.autocompact_nonzero_levels)
+            {
+                return Some(tiered_compaction);
+            }
+        }
+
+        None
+    }
+
+    /// Returns the level-0 files which a FIFO compaction should delete, if FIFO compactions are
+    /// enabled.
+    ///
+    /// Since file ages change over time, this is computed on each call.
+    #[must_use]
+    pub fn fifo_deletions(&self, opts: &InternalCompactionOptions) -> Vec<FileNumber> {
+        if let CompactionStyle::Fifo(fifo_opts) = opts.style {
+            if opts.size_compactions.autocompact_level_zero {
+                return self.version.compute_fifo_deletions(fifo_opts);
+            }
+        }
+
+        Vec::new()
+    }
+
+    /// Seek compactions would merge level-0 files into level 1, so they are disabled for
+    /// [`CompactionStyle::Fifo`].
+    #[must_use]
+    const fn seek_compactions_enabled(opts: &InternalCompactionOptions) -> bool {
+        opts.seek_compactions.seek_autocompactions
+            && !matches!(opts.style, CompactionStyle::Fifo(_))
+    }
+
     /// # Correctness
     /// `start_seek_compaction` must be associated with `maybe_current_version`.
     ///
@@ -149,6 +286,33 @@ impl CurrentVersion {
             }
         }
     }
+
+    /// Returns a table file which should be compacted into the next level because too many of
+    /// its entries are deletions or because it is too old, if any.
+    ///
+    /// Like a [`StartSeekCompaction`] from [`Self::needs_seek_compaction`], the returned value
+    /// indicates a file in the current version and the parent level of the compaction.
+    #[must_use]
+    pub fn file_compaction(&self, opts: &InternalCompactionOptions) -> Option<StartSeekCompaction> {
+        if let Some(deletion_compaction) = self.deletion_compaction {
+            return Some(deletion_compaction);
+        }
+
+        if Self::file_compactions_enabled(opts) {
+            if let Some(max_age) = opts.periodic_compaction_age {
+                return self.version.compute_periodic_compaction(max_age);
+            }
+        }
+
+        None
+    }
+
+    /// File compactions would merge level-0 files into level 1, so they are disabled for
+    /// [`CompactionStyle::Fifo`].
+    #[must_use]
+    const fn file_compactions_enabled(opts: &InternalCompactionOptions) -> bool {
+        !matches!(opts.style, CompactionStyle::Fifo(_))
+    }
 }
 
 impl Deref for CurrentVersion {
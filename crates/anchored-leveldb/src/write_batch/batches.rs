@@ -3,8 +3,8 @@ use std::cmp::Ordering;
 use crate::utils::WriteVarint as _;
 use crate::{
     all_errors::types::{
-        OutOfSequenceNumbers, PushBatchError, WriteBatchDeleteError, WriteBatchPutError,
-        WriteBatchValidationError,
+        NoSavepointError, OutOfSequenceNumbers, PushBatchError, WriteBatchDeleteError,
+        WriteBatchPutError, WriteBatchValidationError,
     },
     pub_typed_bytes::{EntryType, ReadPrefixedBytes as _, SequenceNumber},
     typed_bytes::{UserKey, UserValue},
@@ -35,6 +35,18 @@ use super::iter::{ChainedWriteBatchIter, WriteBatchIter, WriteEntry};
 pub struct WriteBatch {
     num_entries: u32,
     entries:     Vec<u8>,
+    /// The stack of savepoints set by [`WriteBatch::set_savepoint`], the most recent last.
+    savepoints:  Vec<Savepoint>,
+}
+
+/// The state of a [`WriteBatch`] when a savepoint was set.
+///
+/// Entries are only ever appended to a write batch (aside from being cleared or rolled back),
+/// so truncating the batch to this state removes exactly the entries added since then.
+#[derive(Debug, Clone, Copy)]
+struct Savepoint {
+    num_entries: u32,
+    entries_len: usize,
 }
 
 impl WriteBatch {
@@ -44,6 +56,7 @@ impl WriteBatch {
         Self {
             num_entries: 0,
             entries:     Vec::new(),
+            savepoints:  Vec::new(),
         }
     }
 
@@ -55,13 +68,17 @@ impl WriteBatch {
         Self {
             num_entries: 0,
             entries:     buffer,
+            savepoints:  Vec::new(),
         }
     }
 
     /// Reset the write batch to its initial empty state, keeping only buffer capacity.
+    ///
+    /// Any savepoints are discarded.
     pub fn clear(&mut self) {
         self.num_entries = 0;
         self.entries.clear();
+        self.savepoints.clear();
     }
 
     #[inline]
@@ -71,7 +88,11 @@ impl WriteBatch {
     ) -> Result<Self, WriteBatchValidationError> {
         BorrowedWriteBatch::validate(num_entries, &mut &*entries)?;
 
-        Ok(Self { num_entries, entries })
+        Ok(Self {
+            num_entries,
+            entries,
+            savepoints: Vec::new(),
+        })
     }
 
     /// # Errors
@@ -168,6 +189,48 @@ impl WriteBatch {
         self.entries.extend(other.entries);
         Ok(())
     }
+
+    /// Record the current contents of the write batch, so that any entries added afterwards can
+    /// be removed with [`rollback_to_savepoint`].
+    ///
+    /// Savepoints form a stack; nested savepoints may be set.
+    ///
+    /// [`rollback_to_savepoint`]: WriteBatch::rollback_to_savepoint
+    #[inline]
+    pub fn set_savepoint(&mut self) {
+        self.savepoints.push(Savepoint {
+            num_entries: self.num_entries,
+            entries_len: self.entries.len(),
+        });
+    }
+
+    /// Remove every entry added since the most recent savepoint, and remove that savepoint.
+    ///
+    /// # Errors
+    /// Returns an error, and leaves the write batch unchanged, if there is no savepoint.
+    pub fn rollback_to_savepoint(&mut self) -> Result<(), NoSavepointError> {
+        let savepoint = self.savepoints.pop().ok_or(NoSavepointError)?;
+
+        self.num_entries = savepoint.num_entries;
+        self.entries.truncate(savepoint.entries_len);
+        Ok(())
+    }
+
+    /// Remove the most recent savepoint, without removing any entries.
+    ///
+    /// # Errors
+    /// Returns an error if there is no savepoint.
+    #[inline]
+    pub fn pop_savepoint(&mut self) -> Result<(), NoSavepointError> {
+        self.savepoints.pop().map(|_savepoint| ()).ok_or(NoSavepointError)
+    }
+
+    /// The number of savepoints which have been set and not yet rolled back to or popped.
+    #[inline]
+    #[must_use]
+    pub const fn num_savepoints(&self) -> usize {
+        self.savepoints.len()
+    }
 }
 
 impl Default for WriteBatch {
@@ -255,6 +318,7 @@ impl<'a> BorrowedWriteBatch<'a> {
         WriteBatch {
             num_entries: self.num_entries,
             entries:     self.entries.to_owned(),
+            savepoints:  Vec::new(),
         }
     }
 
@@ -353,3 +417,126 @@ impl Default for ChainedWriteBatches<'_> {
         Self::new()
     }
 }
+
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, clippy::unwrap_used, reason = "these are tests")]
+mod tests {
+    use super::*;
+
+
+    fn contents(batch: BorrowedWriteBatch<'_>) -> Vec<(&[u8], Option<&[u8]>)> {
+        batch.iter().map(|entry| (entry.key(), entry.value())).collect()
+    }
+
+    #[test]
+    fn rollback_removes_entries_since_savepoint() {
+        let mut batch = WriteBatch::new();
+        batch.put(b"a", b"1").unwrap();
+
+        batch.set_savepoint();
+        batch.put(b"b", b"2").unwrap();
+        batch.delete(b"a").unwrap();
+        assert_eq!(batch.num_entries(), 3);
+
+        batch.rollback_to_savepoint().unwrap();
+        assert_eq!(batch.num_entries(), 1);
+        assert_eq!(batch.num_savepoints(), 0);
+        assert_eq!(contents(batch.borrow()), [(b"a".as_slice(), Some(b"1".as_slice()))]);
+
+        // The batch can still be edited after a rollback.
+        batch.delete(b"c").unwrap();
+        assert_eq!(
+            contents(batch.borrow()),
+            [(b"a".as_slice(), Some(b"1".as_slice())), (b"c".as_slice(), None)],
+        );
+    }
+
+    #[test]
+    fn nested_savepoints() {
+        let mut batch = WriteBatch::new();
+        batch.set_savepoint();
+        batch.put(b"outer", b"1").unwrap();
+
+        batch.set_savepoint();
+        batch.put(b"inner", b"2").unwrap();
+        assert_eq!(batch.num_savepoints(), 2);
+
+        // Rolling back the inner savepoint keeps the outer savepoint and its entries.
+        batch.rollback_to_savepoint().unwrap();
+        assert_eq!(batch.num_savepoints(), 1);
+        assert_eq!(contents(batch.borrow()), [(b"outer".as_slice(), Some(b"1".as_slice()))]);
+
+        batch.set_savepoint();
+        batch.put(b"kept", b"3").unwrap();
+
+        // Popping the inner savepoint keeps its entries, which the outer savepoint now covers.
+        batch.pop_savepoint().unwrap();
+        assert_eq!(batch.num_savepoints(), 1);
+        assert_eq!(batch.num_entries(), 2);
+
+        batch.rollback_to_savepoint().unwrap();
+        assert_eq!(batch.num_entries(), 0);
+        assert!(batch.entries().is_empty());
+        assert_eq!(batch.num_savepoints(), 0);
+    }
+
+    #[test]
+    fn no_savepoint_leaves_batch_unchanged() {
+        let mut batch = WriteBatch::new();
+        batch.put(b"a", b"1").unwrap();
+
+        assert!(batch.rollback_to_savepoint().is_err());
+        assert!(batch.pop_savepoint().is_err());
+        assert_eq!(contents(batch.borrow()), [(b"a".as_slice(), Some(b"1".as_slice()))]);
+
+        batch.set_savepoint();
+        batch.clear();
+        assert_eq!(batch.num_savepoints(), 0);
+        assert!(batch.rollback_to_savepoint().is_err());
+    }
+
+    #[test]
+    fn rollback_removes_pushed_batches() {
+        let mut other = WriteBatch::new();
+        other.put(b"x", b"1").unwrap();
+        other.delete(b"y").unwrap();
+
+        let mut batch = WriteBatch::new();
+        batch.put(b"a", b"1").unwrap();
+        batch.set_savepoint();
+        batch.push_batch(other.borrow()).unwrap();
+        assert_eq!(batch.num_entries(), 3);
+
+        batch.rollback_to_savepoint().unwrap();
+        assert_eq!(contents(batch.borrow()), [(b"a".as_slice(), Some(b"1".as_slice()))]);
+    }
+
+    /// Only the entries which survive a rollback are committed, and each is assigned exactly one
+    /// sequence number.
+    #[test]
+    fn committed_batch_excludes_rolled_back_entries() {
+        let mut batch = WriteBatch::new();
+        batch.put(b"a", b"1").unwrap();
+        batch.set_savepoint();
+        batch.put(b"b", b"2").unwrap();
+        batch.put(b"c", b"3").unwrap();
+        batch.rollback_to_savepoint().unwrap();
+        batch.delete(b"d").unwrap();
+
+        // The rolled-back batch is still a valid batch.
+        let borrowed = batch.borrow();
+        let mut entries = borrowed.entries();
+        let validated = BorrowedWriteBatch::validate(borrowed.num_entries(), &mut entries).unwrap();
+        assert_eq!(validated.num_entries(), 2);
+        assert_eq!(borrowed.to_owned().num_savepoints(), 0);
+
+        let mut chained = ChainedWriteBatches::new();
+        chained.push_batch(batch.borrow()).unwrap();
+        assert_eq!(chained.num_entries(), 2);
+
+        let (iter, last_sequence) = chained.try_get_iter(SequenceNumber::ZERO).unwrap();
+        assert_eq!(iter.count(), 2);
+        assert_eq!(last_sequence.inner(), 2);
+    }
+}
@@ -0,0 +1,360 @@
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+use crate::{
+    all_errors::types::{WriteBatchDeleteError, WriteBatchPutError},
+    memtable::{MemtableIter, UniqueMemtable},
+    pub_traits::cmp_and_policy::LevelDBComparator,
+    pub_typed_bytes::{EntryType, SequenceNumber, ShortSlice},
+    table_format::InternalComparator,
+};
+use crate::typed_bytes::{
+    EncodedInternalEntry, InternalEntry, InternalKey, InternalKeyTag, MaybeUserValue, UserKey,
+};
+use super::{
+    batches::{BorrowedWriteBatch, WriteBatch},
+    iter::WriteEntry,
+};
+
+
+/// The initial capacity, in bytes, of the skiplist which indexes an [`IndexedWriteBatch`].
+const INDEX_INIT_CAPACITY: usize = 4 << 10;
+#[expect(clippy::unusual_byte_groupings, reason = "random fun number")]
+const INDEX_SEED: u128 = 0x_1d_deadbeef_28;
+
+/// A [`WriteBatch`] which additionally maintains an in-memory index of its keys, sorted by a
+/// comparator.
+///
+/// The newest entry of each key in the batch can be looked up or iterated over in sorted order,
+/// so that a multi-step edit can observe its own writes before the batch is written to the
+/// database.
+#[derive(Debug)]
+pub struct IndexedWriteBatch<Cmp> {
+    batch:    WriteBatch,
+    /// Every entry of `batch`, sorted by key and then from newest to oldest.
+    ///
+    /// The sequence number of each entry is its (one-indexed) position in `batch`, so that the
+    /// first entry of each key in the index is the newest entry of that key in `batch`.
+    index:    UniqueMemtable<Cmp>,
+    num_keys: usize,
+}
+
+impl<Cmp> IndexedWriteBatch<Cmp> {
+    /// The underlying [`WriteBatch`], including every entry (rather than only the newest entry
+    /// of each key).
+    #[inline]
+    #[must_use]
+    pub const fn write_batch(&self) -> &WriteBatch {
+        &self.batch
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn borrow(&self) -> BorrowedWriteBatch<'_> {
+        self.batch.borrow()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn into_write_batch(self) -> WriteBatch {
+        self.batch
+    }
+
+    /// The number of distinct keys in the batch.
+    #[inline]
+    #[must_use]
+    pub const fn num_keys(&self) -> usize {
+        self.num_keys
+    }
+}
+
+impl<Cmp: LevelDBComparator> IndexedWriteBatch<Cmp> {
+    /// Create an empty batch whose keys are indexed according to `cmp`, which should be the
+    /// comparator of the database that the batch will be written to.
+    #[must_use]
+    pub fn new(cmp: Cmp) -> Self {
+        Self {
+            batch:    WriteBatch::new(),
+            index:    UniqueMemtable::new(INDEX_INIT_CAPACITY, INDEX_SEED, InternalComparator(cmp)),
+            num_keys: 0,
+        }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.batch.clear();
+        self.index.reset();
+        self.num_keys = 0;
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn cmp(&self) -> &Cmp {
+        &self.index.cmp().0
+    }
+
+    /// Add a write of `value` to `key` to the batch.
+    ///
+    /// # Errors
+    /// See [`WriteBatch::put`].
+    pub fn put(&mut self, key: &[u8], value: &[u8]) -> Result<(), WriteBatchPutError> {
+        self.batch.put(key, value)?;
+        self.index_newest_entry(key, Some(value));
+        Ok(())
+    }
+
+    /// Add a deletion of `key` to the batch.
+    ///
+    /// # Errors
+    /// See [`WriteBatch::delete`].
+    pub fn delete(&mut self, key: &[u8]) -> Result<(), WriteBatchDeleteError> {
+        self.batch.delete(key)?;
+        self.index_newest_entry(key, None);
+        Ok(())
+    }
+
+    /// Get the newest entry for `key` in the batch.
+    ///
+    /// The outer `Option` is `None` if the batch does not contain `key`, and the inner `Option`
+    /// is `None` if the newest entry for `key` is a deletion.
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<Option<&[u8]>> {
+        let user_key = UserKey::new(key)?;
+
+        let mut iter = self.iter();
+        iter.seek(user_key);
+        iter.current()
+            .filter(|entry| self.cmp().cmp(entry.key(), key).is_eq())
+            .map(WriteEntry::value)
+    }
+
+    /// Iterate over the newest entry of each distinct key in the batch, in sorted order.
+    #[inline]
+    #[must_use]
+    pub const fn iter(&self) -> IndexedWriteBatchIter<'_, Cmp> {
+        IndexedWriteBatchIter {
+            iter: self.index.iter(),
+        }
+    }
+
+    /// Index the entry which was just pushed to `self.batch`. `value` is `None` for a deletion.
+    #[expect(clippy::expect_used, reason = "the entry was validated by `self.batch`")]
+    fn index_newest_entry(&mut self, key: &[u8], value: Option<&[u8]>) {
+        if self.get(key).is_none() {
+            self.num_keys += 1;
+        }
+
+        let user_key = UserKey::new(key)
+            .expect("`WriteBatch` validates the lengths of its keys");
+        let sequence_number = SequenceNumber::new(u64::from(self.batch.num_entries()))
+            .expect("every `u32` is a valid sequence number");
+
+        let (entry_type, value) = match value {
+            Some(value) => {
+                let value = ShortSlice::new(value)
+                    .expect("`WriteBatch` validates the lengths of its values");
+                (EntryType::Value, value)
+            }
+            None => (EntryType::Deletion, ShortSlice::EMPTY),
+        };
+
+        self.index.insert_entry(InternalEntry(
+            InternalKey(user_key, InternalKeyTag::new(sequence_number, entry_type)),
+            MaybeUserValue(value),
+        ));
+    }
+}
+
+/// An iterator over the newest entry of each distinct key in an [`IndexedWriteBatch`], in sorted
+/// order.
+pub struct IndexedWriteBatchIter<'a, Cmp> {
+    /// Invariant: if `iter` is valid, it is at the newest entry of its key.
+    iter: MemtableIter<'a, Cmp>,
+}
+
+impl<'a, Cmp: LevelDBComparator> IndexedWriteBatchIter<'a, Cmp> {
+    #[inline]
+    #[must_use]
+    pub(crate) fn current(&self) -> Option<WriteEntry<'a>> {
+        self.iter.current().map(write_entry)
+    }
+
+    /// Move the iterator to the newest entry of the previous key, and return that entry.
+    ///
+    /// If the iterator was `!valid()`, it moves to the last key. If it was at the first key,
+    /// it becomes `!valid()`.
+    pub(crate) fn prev(&mut self) -> Option<WriteEntry<'a>> {
+        // Since `self.iter` is at the newest entry of its key, the previous entry in the index
+        // is the oldest entry of the previous key.
+        if self.iter.prev().is_some() {
+            self.seek_newest_of_key();
+        }
+        self.current()
+    }
+
+    pub(crate) const fn reset(&mut self) {
+        self.iter.reset();
+    }
+
+    /// Move the iterator to the first key which is greater than or equal to `lower_bound`.
+    pub(crate) fn seek(&mut self, lower_bound: UserKey<'_>) {
+        // Entries with greater sequence numbers are sorted first, so this finds the newest
+        // entry of the key.
+        self.iter.seek(InternalKey(lower_bound, InternalKeyTag::MAX_KEY_TAG));
+    }
+
+    /// Move the iterator to the last key which is strictly less than `strict_upper_bound`.
+    pub(crate) fn seek_before(&mut self, strict_upper_bound: UserKey<'_>) {
+        self.iter.seek_before(InternalKey(strict_upper_bound, InternalKeyTag::MAX_KEY_TAG));
+        self.seek_newest_of_key();
+    }
+
+    pub(crate) fn seek_to_first(&mut self) {
+        self.iter.seek_to_first();
+    }
+
+    pub(crate) fn seek_to_last(&mut self) {
+        self.iter.seek_to_last();
+        self.seek_newest_of_key();
+    }
+
+    /// If `self.iter` is at some entry of a key, move it to the newest entry of that key.
+    fn seek_newest_of_key(&mut self) {
+        if let Some(entry) = self.iter.current() {
+            self.iter.seek(InternalKey(entry.user_key(), InternalKeyTag::MAX_KEY_TAG));
+        }
+    }
+}
+
+impl<'a, Cmp: LevelDBComparator> Iterator for IndexedWriteBatchIter<'a, Cmp> {
+    type Item = WriteEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(entry) = self.iter.current() {
+            // Every entry has a nonzero sequence number, so this skips past every older entry
+            // of the current key.
+            self.iter.seek(InternalKey(entry.user_key(), InternalKeyTag::MIN_KEY_TAG));
+        } else {
+            self.iter.next();
+        }
+        self.current()
+    }
+}
+
+impl<Cmp: LevelDBComparator> Debug for IndexedWriteBatchIter<'_, Cmp> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("IndexedWriteBatchIter")
+            .field("current", &self.current())
+            .finish()
+    }
+}
+
+fn write_entry(entry: EncodedInternalEntry<'_>) -> WriteEntry<'_> {
+    let key = entry.user_key().inner();
+
+    match entry.0.as_internal_key().1.entry_type() {
+        EntryType::Deletion => WriteEntry::Deletion { key },
+        EntryType::Value    => WriteEntry::Value { key, value: entry.1.0.inner() },
+    }
+}
+
+
+#[cfg(test)]
+#[allow(clippy::missing_assert_message, clippy::unwrap_used, reason = "these are tests")]
+mod tests {
+    use crate::pub_traits::cmp_and_policy::BytewiseComparator;
+    use super::*;
+
+
+    fn contents(batch: &IndexedWriteBatch<BytewiseComparator>) -> Vec<(&[u8], Option<&[u8]>)> {
+        batch.iter().map(|entry| (entry.key(), entry.value())).collect()
+    }
+
+    fn key_and_value(entry: Option<WriteEntry<'_>>) -> Option<(&[u8], Option<&[u8]>)> {
+        entry.map(|entry| (entry.key(), entry.value()))
+    }
+
+    #[test]
+    fn overwrites_keep_newest_entry() {
+        let mut batch = IndexedWriteBatch::new(BytewiseComparator);
+        batch.put(b"a", b"1").unwrap();
+        batch.put(b"b", b"2").unwrap();
+        batch.put(b"a", b"3").unwrap();
+        batch.delete(b"b").unwrap();
+        batch.put(b"b", b"4").unwrap();
+
+        assert_eq!(batch.num_keys(), 2);
+        assert_eq!(batch.write_batch().num_entries(), 5);
+        assert_eq!(batch.get(b"a"), Some(Some(b"3".as_slice())));
+        assert_eq!(batch.get(b"b"), Some(Some(b"4".as_slice())));
+        assert_eq!(batch.get(b"c"), None);
+        assert_eq!(
+            contents(&batch),
+            [(b"a".as_slice(), Some(b"3".as_slice())), (b"b".as_slice(), Some(b"4".as_slice()))],
+        );
+    }
+
+    #[test]
+    fn deletions_are_indexed() {
+        let mut batch = IndexedWriteBatch::new(BytewiseComparator);
+        batch.put(b"a", b"1").unwrap();
+        batch.delete(b"a").unwrap();
+        batch.delete(b"c").unwrap();
+
+        assert_eq!(batch.num_keys(), 2);
+        assert_eq!(batch.get(b"a"), Some(None));
+        assert_eq!(batch.get(b"c"), Some(None));
+        assert_eq!(batch.get(b"b"), None);
+        assert_eq!(contents(&batch), [(b"a".as_slice(), None), (b"c".as_slice(), None)]);
+    }
+
+    #[test]
+    fn iteration_in_both_directions() {
+        let mut batch = IndexedWriteBatch::new(BytewiseComparator);
+        batch.put(b"c", b"1").unwrap();
+        batch.put(b"a", b"2").unwrap();
+        batch.put(b"b", b"3").unwrap();
+        batch.put(b"b", b"4").unwrap();
+
+        assert_eq!(
+            contents(&batch),
+            [
+                (b"a".as_slice(), Some(b"2".as_slice())),
+                (b"b".as_slice(), Some(b"4".as_slice())),
+                (b"c".as_slice(), Some(b"1".as_slice())),
+            ],
+        );
+
+        let mut iter = batch.iter();
+        iter.seek_to_last();
+        assert_eq!(key_and_value(iter.current()), Some((b"c".as_slice(), Some(b"1".as_slice()))));
+        // Moving backwards lands on the newest entry of `b`, not the oldest.
+        assert_eq!(key_and_value(iter.prev()), Some((b"b".as_slice(), Some(b"4".as_slice()))));
+        assert_eq!(key_and_value(iter.prev()), Some((b"a".as_slice(), Some(b"2".as_slice()))));
+        assert!(iter.prev().is_none());
+        // The iterator is circular.
+        assert_eq!(key_and_value(iter.prev()), Some((b"c".as_slice(), Some(b"1".as_slice()))));
+
+        iter.seek(UserKey::new(b"b").unwrap());
+        assert_eq!(key_and_value(iter.current()), Some((b"b".as_slice(), Some(b"4".as_slice()))));
+        iter.seek(UserKey::new(b"bb").unwrap());
+        assert_eq!(key_and_value(iter.current()), Some((b"c".as_slice(), Some(b"1".as_slice()))));
+        iter.seek_before(UserKey::new(b"c").unwrap());
+        assert_eq!(key_and_value(iter.current()), Some((b"b".as_slice(), Some(b"4".as_slice()))));
+        iter.seek_before(UserKey::new(b"a").unwrap());
+        assert!(iter.current().is_none());
+    }
+
+    #[test]
+    fn clear_empties_index() {
+        let mut batch = IndexedWriteBatch::new(BytewiseComparator);
+        batch.put(b"a", b"1").unwrap();
+        batch.clear();
+
+        assert_eq!(batch.num_keys(), 0);
+        assert_eq!(batch.get(b"a"), None);
+        assert!(contents(&batch).is_empty());
+
+        batch.put(b"b", b"2").unwrap();
+        assert_eq!(contents(&batch), [(b"b".as_slice(), Some(b"2".as_slice()))]);
+    }
+}
@@ -17,6 +17,26 @@ pub enum WriteEntry<'a> {
     },
 }
 
+impl<'a> WriteEntry<'a> {
+    #[inline]
+    #[must_use]
+    pub const fn key(self) -> &'a [u8] {
+        match self {
+            Self::Value { key, .. } | Self::Deletion { key } => key,
+        }
+    }
+
+    /// Returns `None` for a [`WriteEntry::Deletion`] entry.
+    #[inline]
+    #[must_use]
+    pub const fn value(self) -> Option<&'a [u8]> {
+        match self {
+            Self::Value { value, .. } => Some(value),
+            Self::Deletion { .. }     => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct WriteBatchIter<'a> {
     /// The remaining entries to iterate over.
@@ -32,6 +52,15 @@ impl<'a> WriteBatchIter<'a> {
         }
     }
 
+    /// # Correctness
+    /// `entries` must be a suffix of the entries of a valid write batch, beginning at the start
+    /// of an entry. Otherwise, iteration may panic.
+    #[inline]
+    #[must_use]
+    pub(super) const fn from_entries(entries: &'a [u8]) -> Self {
+        Self { entries }
+    }
+
     #[inline]
     #[must_use]
     pub const fn remaining_entries(&self) -> &'a [u8] {
@@ -2,10 +2,13 @@
 mod batches;
 /// `WriteEntry`, `WriteBatchIter`, `ChainedWriteBatchIter`.
 mod iter;
+/// `IndexedWriteBatch`, `IndexedWriteBatchIter`.
+mod indexed;
 
 
 pub use self::{
     batches::{BorrowedWriteBatch, ChainedWriteBatches, WriteBatch},
+    indexed::{IndexedWriteBatch, IndexedWriteBatchIter},
     iter::{WriteBatchIter, WriteEntry},
 };
 pub(crate) use self::iter::ChainedWriteBatchIter;
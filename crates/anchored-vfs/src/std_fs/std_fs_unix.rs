@@ -1,5 +1,15 @@
+#![cfg_attr(
+    target_os = "linux",
+    expect(unsafe_code, reason = "call `sync_file_range`, which `std` does not expose"),
+)]
+
 use std::fs;
 use std::{fs::File, io::Result as IoResult, os::unix::fs::FileExt, path::Path};
+#[cfg(target_os = "linux")]
+use std::{
+    io::{Error as IoError, ErrorKind},
+    os::fd::AsRawFd as _,
+};
 
 
 /// See [`fs::create_dir_all`]. Additionally, this function optionally syncs the directory
@@ -61,3 +71,49 @@ pub(super) fn read_at(file: &File, offset: u64, buf: &mut [u8]) -> IoResult<usiz
     // making it threadsafe.
     FileExt::read_at(file, buf, offset)
 }
+
+/// Begin writing back the indicated range of the file's data.
+///
+/// This calls `sync_file_range` with `SYNC_FILE_RANGE_WRITE`, which starts writeback of the
+/// range's dirty pages without waiting for it to complete, and without syncing metadata. If the
+/// filesystem does not support `sync_file_range`, the file's data is synced with
+/// [`File::sync_data`] instead.
+#[cfg(target_os = "linux")]
+pub(super) fn sync_data_range(file: &File, offset: u64, len: u64) -> IoResult<()> {
+    // A length of zero would instead request writeback through the end of the file.
+    if len == 0 {
+        return Ok(());
+    }
+
+    let (Ok(offset), Ok(len)) = (libc::off64_t::try_from(offset), libc::off64_t::try_from(len))
+    else {
+        return Err(IoError::new(
+            ErrorKind::InvalidInput,
+            "sync_data_range: range exceeds the maximum file offset",
+        ));
+    };
+
+    // SAFETY: `file` keeps its file descriptor open for the duration of this call, and
+    // `sync_file_range` does not access any memory of this process.
+    let result = unsafe {
+        libc::sync_file_range(file.as_raw_fd(), offset, len, libc::SYNC_FILE_RANGE_WRITE)
+    };
+
+    if result == 0 {
+        Ok(())
+    } else {
+        let err = IoError::last_os_error();
+        if err.raw_os_error() == Some(libc::ENOSYS) {
+            file.sync_data()
+        } else {
+            Err(err)
+        }
+    }
+}
+
+/// Other Unix systems have no equivalent of Linux's `sync_file_range`, so the whole file's data
+/// is synced with [`File::sync_data`], which waits for the data to reach persistent storage.
+#[cfg(not(target_os = "linux"))]
+pub(super) fn sync_data_range(file: &File, _offset: u64, _len: u64) -> IoResult<()> {
+    file.sync_data()
+}
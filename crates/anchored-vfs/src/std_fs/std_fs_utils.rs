@@ -143,4 +143,10 @@ impl WritableFile for BufWriter<File> {
         self.get_ref().sync_data()?;
         Ok(())
     }
+
+    #[inline]
+    fn sync_data_range(&mut self, offset: u64, len: u64) -> IoResult<()> {
+        self.flush()?;
+        std_fs_sys::sync_data_range(self.get_ref(), offset, len)
+    }
 }
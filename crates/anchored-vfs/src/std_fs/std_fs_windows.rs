@@ -24,3 +24,8 @@ pub(super) fn read_at(file: &File, offset: u64, buf: &mut [u8]) -> IoResult<usiz
     // depend on the value of the cursor.
     FileExt::seek_read(file, buf, offset)
 }
+
+/// Windows has no equivalent of `sync_file_range`, so the whole file is synced.
+pub(super) fn sync_data_range(file: &File, _offset: u64, _len: u64) -> IoResult<()> {
+    file.sync_data()
+}
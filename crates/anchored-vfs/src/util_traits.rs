@@ -124,6 +124,22 @@ pub trait WritableFile: Write {
     /// [`BufWriter`]: std::io::BufWriter
     /// [`File::sync_data`]: std::fs::File::sync_data
     fn sync_data(&mut self) -> IoResult<()>;
+
+    /// Begins flushing the indicated range of the file's data to persistent storage, to spread
+    /// the cost of a later [`sync_data`] call over time.
+    ///
+    /// Unlike [`sync_data`], this method need not wait for the data to reach persistent storage,
+    /// and need not make the file size durable; it is purely a performance hint. Callers must
+    /// still call [`sync_data`] before relying on the data being durable.
+    ///
+    /// The default implementation calls [`sync_data`]. On Linux, implementations backed by a
+    /// real file may wish to use `sync_file_range`.
+    ///
+    /// [`sync_data`]: WritableFile::sync_data
+    #[inline]
+    fn sync_data_range(&mut self, _offset: u64, _len: u64) -> IoResult<()> {
+        self.sync_data()
+    }
 }
 
 /// Provides an iterator over the immediate children of a directory, for